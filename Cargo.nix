@@ -11082,6 +11082,11 @@ rec {
             name = "tree-sitter-typescript";
             packageId = "tree-sitter-typescript";
           }
+          {
+            name = "tree-sitter-zig";
+            packageId = "tree-sitter-zig";
+            optional = true;
+          }
           {
             name = "uuid";
             packageId = "uuid";
@@ -11108,7 +11113,9 @@ rec {
           }
         ];
         features = {
+          "coreml" = [ "ort/coreml" ];
           "cuda" = [ "ort/cuda" ];
+          "lang-zig" = [ "dep:tree-sitter-zig" ];
           "tensorrt" = [ "ort/tensorrt" ];
         };
         resolvedDefaultFeatures = [ "cuda" "default" "tensorrt" ];
@@ -16753,6 +16760,28 @@ rec {
           }
         ];
 
+      };
+      "tree-sitter-zig" = rec {
+        crateName = "tree-sitter-zig";
+        version = "1.1.2";
+        edition = "2021";
+        sha256 = "0a1yspjia5sj8k6rsf1q6blr44b3v6xq6ng5sm6xpc2i909gq4db";
+        build = "bindings/rust/build.rs";
+        libName = "tree_sitter_zig";
+        libPath = "bindings/rust/lib.rs";
+        dependencies = [
+          {
+            name = "tree-sitter-language";
+            packageId = "tree-sitter-language";
+          }
+        ];
+        buildDependencies = [
+          {
+            name = "cc";
+            packageId = "cc";
+          }
+        ];
+
       };
       "try-lock" = rec {
         crateName = "try-lock";
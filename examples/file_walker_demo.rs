@@ -1,18 +1,50 @@
 use codesearch::FileWalker;
 use std::env;
+use std::time::Instant;
 
+// Usage: file_walker_demo [PATH] [--compare]
+//
+// --compare also walks with every indexable file read for binary data (the
+// behavior before extension-based sniffing) and reports both walk times.
+// Run it on a large tree twice and take the second numbers, once the OS
+// file cache is warm.
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let compare = args.iter().any(|arg| arg == "--compare");
     // Get directory from command line or use current directory
-    let path = env::args().nth(1).unwrap_or_else(|| ".".to_string());
+    let path = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .cloned()
+        .unwrap_or_else(|| ".".to_string());
 
     println!("Walking directory: {}", path);
     println!();
 
     let walker = FileWalker::new(&path);
 
-    match walker.walk() {
+    let start = Instant::now();
+    let walked = walker.walk();
+    let walk_time = start.elapsed();
+
+    match walked {
         Ok((files, stats)) => {
             println!("\n=== File Discovery Results ===\n");
+            println!(
+                "Walk time: {:?} ({} files read for binary data)",
+                walk_time, stats.content_sniffed
+            );
+            if compare {
+                let start = Instant::now();
+                match FileWalker::new(&path).with_sniff_all(true).walk() {
+                    Ok((_, all)) => println!(
+                        "Walk time reading every file: {:?} ({} files read)",
+                        start.elapsed(),
+                        all.content_sniffed
+                    ),
+                    Err(e) => eprintln!("Comparison walk failed: {}", e),
+                }
+            }
             println!("Total files discovered: {}", stats.total_files);
             println!("Indexable files: {}", stats.indexable_files);
             println!("Skipped (binary/ignored): {}", stats.skipped_binary);
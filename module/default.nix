@@ -24,6 +24,7 @@ with lib; let
   codesearchDaemonConfig = pkgs.writeText "codesearch-daemon.yaml"
     (builtins.toJSON ({
       port = daemonCfg.port;
+      bind = daemonCfg.bind;
       index_interval = daemonCfg.indexInterval;
       lmdb_map_size_mb = daemonCfg.lmdbMapSizeMB;
       repos = daemonCfg.repos;
@@ -78,6 +79,12 @@ in {
         description = "Codesearch serve port (HTTP API for search)";
       };
 
+      bind = mkOption {
+        type = types.str;
+        default = "127.0.0.1";
+        description = "Listen address for the HTTP API. Per-IP rate limits only tell clients apart on a non-loopback address.";
+      };
+
       lmdbMapSizeMB = mkOption {
         type = types.int;
         default = 2048;
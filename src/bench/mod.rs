@@ -1 +1,491 @@
-// Benchmarking framework and utilities
+//! Benchmarking framework and utilities
+//!
+//! `codesearch bench` builds a throwaway index of a project with the chosen model,
+//! runs a fixed set of queries through the same [`Searcher`] the CLI uses, and
+//! reports indexing time, per-stage query latencies (embed, search, rerank) and
+//! recall against known-relevant files. Queries come from a JSON file (`--queries`;
+//! `benchmarks/queries/codesearch.json` covers codesearch's own sources) or from
+//! `--query` flags, which are timed but not scored. Pass `--strip-comments` to
+//! measure embedding code without comments and strings, or
+//! `--compare-strip-comments` to run with and without it and print the difference.
+
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::embed::ModelType;
+use crate::index::{index_into, IndexOptions};
+use crate::search::{IndexMetadata, SearchOptions, Searcher};
+
+/// One benchmark query and the files a good answer includes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchQuery {
+    pub query: String,
+    /// Path fragments of relevant files; a result is relevant if its path contains one
+    #[serde(default)]
+    pub relevant: Vec<String>,
+}
+
+/// Time spent building the throwaway index
+#[derive(Debug, Serialize)]
+pub struct IndexTiming {
+    pub files: usize,
+    pub chunks: usize,
+    pub walk_ms: u64,
+    pub model_load_ms: u64,
+    /// Chunking, embedding and storing, which are streamed a file at a time
+    pub embed_ms: u64,
+    /// Building the vector index over the stored chunks
+    pub build_ms: u64,
+}
+
+/// Outcome of one query
+#[derive(Debug, Serialize)]
+pub struct QueryReport {
+    pub query: String,
+    /// 1-based rank of the first relevant result within the top k
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_relevant_rank: Option<usize>,
+    /// Fraction of the relevant files found in the top k (absent without relevance data)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recall: Option<f64>,
+    pub embed_ms: f64,
+    pub search_ms: f64,
+    pub rerank_ms: f64,
+}
+
+/// Mean and percentiles of one latency, in milliseconds
+#[derive(Debug, Serialize)]
+pub struct LatencySummary {
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Full benchmark report (printed, or serialized with `--json`)
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub model: String,
+    pub dimensions: usize,
+    pub top_k: usize,
+    pub rerank: bool,
+    /// Whether chunks were embedded with comments and string literals stripped
+    pub strip_comments: bool,
+    pub index: IndexTiming,
+    pub queries: Vec<QueryReport>,
+    /// Share of judged queries whose best result is relevant
+    pub hit_at_1: f64,
+    /// Mean recall@k over judged queries
+    pub recall_at_k: f64,
+    /// Mean reciprocal rank over judged queries
+    pub mrr: f64,
+    pub embed: LatencySummary,
+    pub search: LatencySummary,
+    pub rerank_latency: LatencySummary,
+}
+
+/// `--compare-strip-comments` report: the same queries against raw and stripped embeddings
+#[derive(Debug, Serialize)]
+pub struct StripComparison {
+    pub raw: BenchReport,
+    pub stripped: BenchReport,
+}
+
+/// `codesearch bench`: index `path` into a scratch database and report query latency and recall
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    path: Option<PathBuf>,
+    model: Option<ModelType>,
+    queries_file: Option<PathBuf>,
+    extra_queries: Vec<String>,
+    top_k: usize,
+    rerank: bool,
+    strip_comments: bool,
+    compare_strip_comments: bool,
+    json: bool,
+) -> Result<()> {
+    let project_path = path
+        .unwrap_or_else(|| PathBuf::from("."))
+        .canonicalize()
+        .context("Project path not found")?;
+    let model_type = model.unwrap_or_default();
+    let mut queries = match &queries_file {
+        Some(file) => load_queries(file)?,
+        None => Vec::new(),
+    };
+    queries.extend(extra_queries.into_iter().map(|query| BenchQuery {
+        query,
+        relevant: Vec::new(),
+    }));
+    if queries.is_empty() {
+        return Err(anyhow!(
+            "No benchmark queries: pass --queries <FILE> or --query <TEXT> \
+             (benchmarks/queries/codesearch.json covers codesearch's own sources)"
+        ));
+    }
+
+    if !json {
+        println!("{}", "⏱️  Codesearch Bench".bright_cyan().bold());
+        println!("{}", "=".repeat(60));
+        println!("📂 Project: {}", project_path.display());
+        println!(
+            "🧠 Model: {} ({} dims)",
+            model_type.name(),
+            model_type.dimensions()
+        );
+        println!(
+            "🔎 Queries: {} ({}), top {}{}",
+            queries.len(),
+            queries_file
+                .as_deref()
+                .map_or("--query".to_string(), |f| f.display().to_string()),
+            top_k,
+            if rerank { ", reranked" } else { "" }
+        );
+        if compare_strip_comments {
+            println!("✂️  Comparing raw embeddings against comments and strings stripped");
+        } else if strip_comments {
+            println!("✂️  Embedding code with comments and strings stripped");
+        }
+        println!("\n📦 Building a scratch index (your own index is not touched)...");
+    }
+    let bench = |strip_comments: bool| {
+        let scratch =
+            std::env::temp_dir().join(format!("codesearch-bench-{}", uuid::Uuid::new_v4()));
+        let result = run_in(
+            &scratch,
+            &project_path,
+            model_type,
+            &queries,
+            top_k,
+            rerank,
+            strip_comments,
+        );
+        let _ = std::fs::remove_dir_all(&scratch);
+        result
+    };
+
+    if compare_strip_comments {
+        let comparison = StripComparison {
+            raw: bench(false)?,
+            stripped: bench(true)?,
+        };
+        if json {
+            println!("{}", serde_json::to_string_pretty(&comparison)?);
+        } else {
+            println!("\n{}", "── Raw ──".bright_cyan().bold());
+            print_report(&comparison.raw);
+            println!("\n{}", "── Comments stripped ──".bright_cyan().bold());
+            print_report(&comparison.stripped);
+            print_comparison(&comparison.raw, &comparison.stripped);
+        }
+        return Ok(());
+    }
+
+    let report = bench(strip_comments)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    Ok(())
+}
+
+fn run_in(
+    db_path: &Path,
+    project_path: &Path,
+    model_type: ModelType,
+    queries: &[BenchQuery],
+    top_k: usize,
+    rerank: bool,
+    strip_comments: bool,
+) -> Result<BenchReport> {
+    let index = build_index(db_path, project_path, model_type, strip_comments)?;
+
+    let searcher = Searcher::open_with_model(db_path, Some(model_type.short_name()))?;
+    let options = SearchOptions {
+        max_results: top_k,
+        rerank,
+        ..Default::default()
+    };
+
+    let mut reports = Vec::with_capacity(queries.len());
+    for query in queries {
+        let (results, timing) = searcher.search_timed(&query.query, &options)?;
+        let paths: Vec<String> = results.iter().map(|r| r.path.replace('\\', "/")).collect();
+        let (first_relevant_rank, recall) = score_query(&paths, &query.relevant);
+        reports.push(QueryReport {
+            query: query.query.clone(),
+            first_relevant_rank,
+            recall,
+            embed_ms: as_ms(timing.embed),
+            search_ms: as_ms(timing.search),
+            rerank_ms: as_ms(timing.rerank),
+        });
+    }
+
+    let judged: Vec<&QueryReport> = reports.iter().filter(|r| r.recall.is_some()).collect();
+    let mean = |f: &dyn Fn(&QueryReport) -> f64| {
+        if judged.is_empty() {
+            0.0
+        } else {
+            judged.iter().map(|&r| f(r)).sum::<f64>() / judged.len() as f64
+        }
+    };
+    let hit_at_1 = mean(&|r| {
+        if r.first_relevant_rank == Some(1) {
+            1.0
+        } else {
+            0.0
+        }
+    });
+    let recall_at_k = mean(&|r| r.recall.unwrap_or(0.0));
+    let mrr = mean(&|r| r.first_relevant_rank.map_or(0.0, |rank| 1.0 / rank as f64));
+
+    Ok(BenchReport {
+        model: model_type.short_name().to_string(),
+        dimensions: model_type.dimensions(),
+        top_k,
+        rerank,
+        strip_comments,
+        index,
+        hit_at_1,
+        recall_at_k,
+        mrr,
+        embed: summarize(reports.iter().map(|r| r.embed_ms).collect()),
+        search: summarize(reports.iter().map(|r| r.search_ms).collect()),
+        rerank_latency: summarize(reports.iter().map(|r| r.rerank_ms).collect()),
+        queries: reports,
+    })
+}
+
+/// Index `project_path` into a fresh database at `db_path` with the same pipeline as `codesearch index`
+fn build_index(
+    db_path: &Path,
+    project_path: &Path,
+    model_type: ModelType,
+    strip_comments: bool,
+) -> Result<IndexTiming> {
+    let options = IndexOptions {
+        force: true,
+        model: Some(model_type),
+        strip_comments: Some(strip_comments),
+        quiet: true,
+        ..Default::default()
+    };
+    let summary = index_into(
+        db_path,
+        project_path.to_path_buf(),
+        IndexMetadata::default(),
+        options,
+        CancellationToken::new(),
+    )?;
+    if summary.chunks == 0 {
+        return Err(anyhow!("No indexable files in {}", project_path.display()));
+    }
+
+    Ok(IndexTiming {
+        files: summary.files,
+        chunks: summary.chunks,
+        walk_ms: summary.discovery.as_millis() as u64,
+        model_load_ms: summary.model_load.as_millis() as u64,
+        embed_ms: summary
+            .chunking
+            .saturating_sub(summary.model_load)
+            .as_millis() as u64,
+        build_ms: summary.build.as_millis() as u64,
+    })
+}
+
+/// Read a JSON array of `{"query": ..., "relevant": [...]}` objects
+fn load_queries(file: &Path) -> Result<Vec<BenchQuery>> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read queries file {}", file.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Invalid queries file {}", file.display()))
+}
+
+/// Rank of the first relevant path and recall over `relevant` for one query's results
+///
+/// Both are `None` when the query has no relevance judgements.
+fn score_query(paths: &[String], relevant: &[String]) -> (Option<usize>, Option<f64>) {
+    if relevant.is_empty() {
+        return (None, None);
+    }
+    let is_relevant = |path: &str| relevant.iter().any(|r| path.contains(r.as_str()));
+    let first_rank = paths.iter().position(|p| is_relevant(p)).map(|i| i + 1);
+    let found = relevant
+        .iter()
+        .filter(|r| paths.iter().any(|p| p.contains(r.as_str())))
+        .count();
+    (first_rank, Some(found as f64 / relevant.len() as f64))
+}
+
+fn summarize(mut samples: Vec<f64>) -> LatencySummary {
+    if samples.is_empty() {
+        return LatencySummary {
+            mean_ms: 0.0,
+            p50_ms: 0.0,
+            p95_ms: 0.0,
+        };
+    }
+    samples.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |p: f64| samples[((samples.len() - 1) as f64 * p).round() as usize];
+    LatencySummary {
+        mean_ms: samples.iter().sum::<f64>() / samples.len() as f64,
+        p50_ms: percentile(0.50),
+        p95_ms: percentile(0.95),
+    }
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn print_report(report: &BenchReport) {
+    let index = &report.index;
+    println!("\n{}", "Index:".bright_green());
+    println!("   {} files, {} chunks", index.files, index.chunks);
+    println!(
+        "   walk {} ms, model load {} ms, chunk/embed/store {} ms, build {} ms",
+        index.walk_ms, index.model_load_ms, index.embed_ms, index.build_ms
+    );
+
+    println!("\n{}", "Queries:".bright_green());
+    for q in &report.queries {
+        let verdict = match (q.first_relevant_rank, q.recall) {
+            (_, None) => "·".dimmed().to_string(),
+            (Some(1), _) => "✅".to_string(),
+            (Some(_), _) => "🟡".to_string(),
+            (None, _) => "❌".to_string(),
+        };
+        let rank = q
+            .first_relevant_rank
+            .map_or("-".to_string(), |rank| format!("#{}", rank));
+        println!(
+            "   {} {:<48} {:>4}  {:>7.1} ms",
+            verdict,
+            crate::output::truncate_with_ellipsis(&q.query, 45),
+            rank,
+            q.embed_ms + q.search_ms + q.rerank_ms
+        );
+    }
+
+    println!("\n{}", "Quality:".bright_green());
+    println!("   hit@1: {:.2}", report.hit_at_1);
+    println!("   recall@{}: {:.2}", report.top_k, report.recall_at_k);
+    println!("   MRR: {:.3}", report.mrr);
+
+    println!("\n{}", "Latency (mean / p50 / p95):".bright_green());
+    for (stage, summary) in [
+        ("embed", &report.embed),
+        ("search", &report.search),
+        ("rerank", &report.rerank_latency),
+    ] {
+        if stage == "rerank" && !report.rerank {
+            continue;
+        }
+        println!(
+            "   {:<7} {:>7.1} / {:>7.1} / {:>7.1} ms",
+            stage, summary.mean_ms, summary.p50_ms, summary.p95_ms
+        );
+    }
+}
+
+fn print_comparison(raw: &BenchReport, stripped: &BenchReport) {
+    println!(
+        "\n{}",
+        "Raw → comments stripped (before / after / change):".bright_green()
+    );
+    for (metric, before, after) in [
+        ("hit@1", raw.hit_at_1, stripped.hit_at_1),
+        ("recall", raw.recall_at_k, stripped.recall_at_k),
+        ("MRR", raw.mrr, stripped.mrr),
+    ] {
+        println!(
+            "   {:<7} {:>6.3} / {:>6.3} / {:>+7.3}",
+            metric,
+            before,
+            after,
+            after - before
+        );
+    }
+    println!(
+        "   {:<7} {:>6} / {:>6} / {:>+7} ms",
+        "embed",
+        raw.index.embed_ms,
+        stripped.index.embed_ms,
+        stripped.index.embed_ms as i64 - raw.index.embed_ms as i64
+    );
+    println!(
+        "   {:<7} {:>6} / {:>6} / {:>+7}",
+        "chunks",
+        raw.index.chunks,
+        stripped.index.chunks,
+        stripped.index.chunks as i64 - raw.index.chunks as i64
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_score_query() {
+        let results = paths(&["/repo/src/a.rs", "/repo/src/b.rs", "/repo/src/c.rs"]);
+
+        let (rank, recall) = score_query(&results, &paths(&["src/b.rs", "src/z.rs"]));
+        assert_eq!(rank, Some(2));
+        assert_eq!(recall, Some(0.5));
+
+        let (rank, recall) = score_query(&results, &paths(&["src/z.rs"]));
+        assert_eq!(rank, None);
+        assert_eq!(recall, Some(0.0));
+
+        // Queries without judgements don't count towards quality
+        assert_eq!(score_query(&results, &[]), (None, None));
+    }
+
+    #[test]
+    fn test_summarize() {
+        let summary = summarize(vec![4.0, 1.0, 3.0, 2.0, 10.0]);
+        assert_eq!(summary.mean_ms, 4.0);
+        assert_eq!(summary.p50_ms, 3.0);
+        assert_eq!(summary.p95_ms, 10.0);
+        assert_eq!(summarize(Vec::new()).mean_ms, 0.0);
+    }
+
+    #[test]
+    fn test_load_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("queries.json");
+        std::fs::write(
+            &file,
+            r#"[{"query": "retry logic", "relevant": ["src/net/retry.rs"]}, {"query": "unjudged"}]"#,
+        )
+        .unwrap();
+        let queries = load_queries(&file).unwrap();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].relevant, vec!["src/net/retry.rs"]);
+        assert!(queries[1].relevant.is_empty());
+
+        std::fs::write(&file, "not json").unwrap();
+        assert!(load_queries(&file).is_err());
+    }
+
+    #[test]
+    fn test_shipped_queries_are_judged() {
+        let file = Path::new(env!("CARGO_MANIFEST_DIR")).join("benchmarks/queries/codesearch.json");
+        let queries = load_queries(&file).unwrap();
+        assert!(!queries.is_empty());
+        assert!(queries.iter().all(|q| !q.relevant.is_empty()));
+    }
+}
@@ -1,9 +1,9 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use crate::constants::FILE_META_DB_NAME;
@@ -24,6 +24,39 @@ pub fn normalize_path_str(path: &str) -> String {
     path.trim_start_matches(r"\\?\").replace('\\', "/")
 }
 
+/// Normalized `dir` with a trailing `/`, for matching the paths under it
+fn dir_prefix(dir: &Path) -> String {
+    let mut prefix = normalize_path(dir);
+    if !prefix.ends_with('/') {
+        prefix.push('/');
+    }
+    prefix
+}
+
+/// The topmost directories that no longer exist among the parents of `deleted` files
+///
+/// Sorted, normalized and ending in `/`. Files deleted from a directory that still
+/// exists contribute nothing; existence is checked once per directory.
+pub fn removed_dirs<'a>(deleted: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut exists: HashMap<PathBuf, bool> = HashMap::new();
+    let mut dirs = BTreeSet::new();
+    for path in deleted {
+        let mut top = None;
+        let mut dir = Path::new(path).parent();
+        while let Some(d) = dir.filter(|d| !d.as_os_str().is_empty()) {
+            if *exists.entry(d.to_path_buf()).or_insert_with(|| d.exists()) {
+                break;
+            }
+            top = Some(d);
+            dir = d.parent();
+        }
+        if let Some(top) = top {
+            dirs.insert(dir_prefix(top));
+        }
+    }
+    dirs.into_iter().collect()
+}
+
 /// Metadata for a single indexed file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMeta {
@@ -45,6 +78,10 @@ pub struct FileMeta {
 /// 1. Two-level check: mtime first (fast), hash only if mtime changed
 /// 2. Tracks chunk IDs for efficient deletion on file update
 /// 3. Stores chunk count for statistics
+///
+/// With `CODESEARCH_HASH_ONLY` set, the mtime/size shortcut is skipped and every
+/// check compares content hashes. That catches edits that keep mtime and size
+/// (coarse timestamps, tools restoring mtimes) at the cost of reading every file.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileMetaStore {
     /// Map of absolute file path -> metadata
@@ -57,6 +94,9 @@ pub struct FileMetaStore {
     pub last_full_index: Option<u64>,
     /// Version for format compatibility
     version: u32,
+    /// Ignore mtime/size and decide on content hash alone (not persisted)
+    #[serde(skip)]
+    hash_only: bool,
 }
 
 impl FileMetaStore {
@@ -71,35 +111,50 @@ impl FileMetaStore {
             dimensions,
             last_full_index: None,
             version: Self::CURRENT_VERSION,
+            hash_only: hash_only_from_env(),
         }
     }
 
+    /// Decide changes on content hash alone, ignoring mtime and size
+    pub fn set_hash_only(&mut self, hash_only: bool) {
+        self.hash_only = hash_only;
+    }
+
     /// Load from database directory, or create new if doesn't exist
     pub fn load_or_create(db_path: &Path, model_name: &str, dimensions: usize) -> Result<Self> {
-        let meta_path = db_path.join(Self::FILENAME);
-
-        if meta_path.exists() {
-            let content = fs::read_to_string(&meta_path)?;
-            let mut store: FileMetaStore = serde_json::from_str(&content)
-                .map_err(|e| anyhow!("Failed to parse file metadata: {}", e))?;
-
-            // Check if model changed - if so, invalidate everything
-            if store.model_name != model_name || store.dimensions != dimensions {
+        match Self::load(db_path)? {
+            Some(store) if store.model_name != model_name || store.dimensions != dimensions => {
+                // Model changed - invalidate everything
                 println!(
                     "⚠️  Model changed ({} -> {}), full re-index required",
                     store.model_name, model_name
                 );
-                store = Self::new(model_name.to_string(), dimensions);
+                Ok(Self::new(model_name.to_string(), dimensions))
             }
+            Some(store) => Ok(store),
+            None => Ok(Self::new(model_name.to_string(), dimensions)),
+        }
+    }
 
-            // Migrate stored paths to normalized format (strip UNC prefix, forward slashes).
-            // Existing stores may have Windows backslash paths or \\?\ prefixed paths.
-            store.migrate_paths();
-
-            Ok(store)
-        } else {
-            Ok(Self::new(model_name.to_string(), dimensions))
+    /// Load from database directory as stored, without checking the model
+    ///
+    /// Returns `Ok(None)` if the database has no file metadata yet.
+    pub fn load(db_path: &Path) -> Result<Option<Self>> {
+        let meta_path = db_path.join(Self::FILENAME);
+        if !meta_path.exists() {
+            return Ok(None);
         }
+
+        let content = fs::read_to_string(&meta_path)?;
+        let mut store: FileMetaStore = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse file metadata: {}", e))?;
+
+        // Migrate stored paths to normalized format (strip UNC prefix, forward slashes).
+        // Existing stores may have Windows backslash paths or \\?\ prefixed paths.
+        store.migrate_paths();
+        store.hash_only = hash_only_from_env();
+
+        Ok(Some(store))
     }
 
     /// Save to database directory
@@ -162,7 +217,7 @@ impl FileMetaStore {
 
         if let Some(meta) = self.files.get(&path_str) {
             // Quick check: if mtime and size unchanged, file is unchanged
-            if meta.mtime == current_mtime && meta.size == current_size {
+            if !self.hash_only && meta.mtime == current_mtime && meta.size == current_size {
                 return Ok((false, vec![]));
             }
 
@@ -202,18 +257,79 @@ impl FileMetaStore {
         Ok(())
     }
 
+    /// Chunk IDs currently stored for a file (empty if untracked)
+    pub fn chunk_ids(&self, path: &Path) -> Vec<u32> {
+        self.files
+            .get(&normalize_path(path))
+            .map(|meta| meta.chunk_ids.clone())
+            .unwrap_or_default()
+    }
+
     /// Mark a file as deleted
     pub fn remove_file(&mut self, path: &Path) -> Option<FileMeta> {
         let path_str = normalize_path(path);
         self.files.remove(&path_str)
     }
 
+    /// Stop tracking every file under `dir`, returning their paths and chunk IDs
+    pub fn remove_dir(&mut self, dir: &Path) -> Vec<(String, Vec<u32>)> {
+        let prefix = dir_prefix(dir);
+        let paths: Vec<String> = self
+            .files
+            .keys()
+            .filter(|path| path.starts_with(&prefix))
+            .cloned()
+            .collect();
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                let meta = self.files.remove(&path)?;
+                Some((path, meta.chunk_ids))
+            })
+            .collect()
+    }
+
     /// Get all tracked files
     #[allow(dead_code)] // Reserved for file listing feature
     pub fn tracked_files(&self) -> impl Iterator<Item = &String> {
         self.files.keys()
     }
 
+    /// Iterate over all tracked files and their metadata
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &FileMeta)> {
+        self.files.iter()
+    }
+
+    /// Stat up to `max_files` tracked files, spread evenly over the store, and
+    /// count those modified or deleted since they were indexed
+    ///
+    /// Only file metadata is read (no hashing), so the cost is bounded by
+    /// `max_files` regardless of project size. New, untracked files are not seen.
+    pub fn sample_staleness(&self, max_files: usize) -> StalenessSample {
+        let total = self.files.len();
+        let step = total.div_ceil(max_files.max(1)).max(1);
+        let mut sample = StalenessSample {
+            total,
+            ..Default::default()
+        };
+
+        for (path, meta) in self.files.iter().step_by(step) {
+            sample.checked += 1;
+            let unchanged = fs::metadata(path).is_ok_and(|current| {
+                let mtime = current
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs());
+                current.len() == meta.size && mtime == Some(meta.mtime)
+            });
+            if !unchanged {
+                sample.stale += 1;
+            }
+        }
+        sample
+    }
+
     /// Find files that were deleted (exist in store but not on disk)
     pub fn find_deleted_files(&self) -> Vec<(String, Vec<u32>)> {
         self.files
@@ -254,6 +370,22 @@ impl FileMetaStore {
     }
 }
 
+/// Whether `CODESEARCH_HASH_ONLY` asks for content-hash-only change detection
+fn hash_only_from_env() -> bool {
+    std::env::var("CODESEARCH_HASH_ONLY").is_ok_and(|v| v != "0" && !v.is_empty())
+}
+
+/// Outcome of `FileMetaStore::sample_staleness`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StalenessSample {
+    /// Tracked files in the store
+    pub total: usize,
+    /// Tracked files that were checked
+    pub checked: usize,
+    /// Checked files modified or deleted since they were indexed
+    pub stale: usize,
+}
+
 #[derive(Debug)]
 #[allow(dead_code)] // Used with stats() method
 pub struct FileMetaStats {
@@ -391,6 +523,48 @@ mod tests {
         assert!(!store.files.contains_key(r"\\?\C:\WorkArea\src\lib.rs"));
     }
 
+    #[test]
+    fn test_removed_dirs_and_remove_dir() {
+        let dir = tempdir().unwrap();
+        let root = normalize_path(dir.path());
+        fs::create_dir(dir.path().join("kept")).unwrap();
+        let kept = format!("{}/kept/a.rs", root);
+        let gone_nested = format!("{}/gone/sub/b.rs", root);
+        let gone = format!("{}/gone/c.rs", root);
+        let gone_sibling = format!("{}/gone2/d.rs", root);
+
+        assert_eq!(
+            removed_dirs([
+                kept.as_str(),
+                gone_nested.as_str(),
+                gone.as_str(),
+                gone_sibling.as_str()
+            ]),
+            vec![format!("{}/gone/", root), format!("{}/gone2/", root)]
+        );
+
+        let mut store = FileMetaStore::new("test-model".to_string(), 384);
+        for (i, path) in [&kept, &gone_nested, &gone, &gone_sibling]
+            .into_iter()
+            .enumerate()
+        {
+            store.files.insert(
+                path.clone(),
+                FileMeta {
+                    hash: String::new(),
+                    mtime: 0,
+                    size: 0,
+                    chunk_count: 1,
+                    chunk_ids: vec![i as u32],
+                },
+            );
+        }
+        let mut removed = store.remove_dir(&dir.path().join("gone"));
+        removed.sort();
+        assert_eq!(removed, vec![(gone, vec![2]), (gone_nested, vec![1])]);
+        assert_eq!(store.files.len(), 2);
+    }
+
     #[test]
     fn test_file_meta_store() {
         let dir = tempdir().unwrap();
@@ -428,6 +602,37 @@ mod tests {
         assert_eq!(loaded.files.len(), 1);
     }
 
+    #[test]
+    fn test_hash_only_ignores_mtime() {
+        let dir = tempdir().unwrap();
+        let test_file = dir.path().join("test.txt");
+        fs::write(&test_file, "hello world").unwrap();
+
+        let mut store = FileMetaStore::new("test-model".to_string(), 384);
+        store.set_hash_only(true);
+        store.update_file(&test_file, vec![1]).unwrap();
+        let indexed_mtime = fs::metadata(&test_file).unwrap().modified().unwrap();
+
+        // Touched (new mtime, same content): no reindex
+        let file = fs::File::options().write(true).open(&test_file).unwrap();
+        file.set_modified(indexed_mtime + std::time::Duration::from_secs(60))
+            .unwrap();
+        let (needs_reindex, _) = store.check_file(&test_file).unwrap();
+        assert!(!needs_reindex);
+
+        // Same size and mtime, different content: only hash-only mode notices
+        fs::write(&test_file, "HELLO WORLD").unwrap();
+        let file = fs::File::options().write(true).open(&test_file).unwrap();
+        file.set_modified(indexed_mtime).unwrap();
+        let (needs_reindex, old_chunks) = store.check_file(&test_file).unwrap();
+        assert!(needs_reindex);
+        assert_eq!(old_chunks, vec![1]);
+
+        store.set_hash_only(false);
+        let (needs_reindex, _) = store.check_file(&test_file).unwrap();
+        assert!(!needs_reindex);
+    }
+
     #[test]
     fn test_file_meta_store_stats() {
         let mut store = FileMetaStore::new("test-model".to_string(), 384);
@@ -669,6 +874,35 @@ mod tests {
         assert_eq!(tracked.len(), 2);
     }
 
+    #[test]
+    fn test_sample_staleness_counts_modified_and_deleted_files() {
+        let dir = tempdir().unwrap();
+        let mut store = FileMetaStore::new("model".to_string(), 384);
+        let paths: Vec<_> = (0..4)
+            .map(|i| dir.path().join(format!("f{}.rs", i)))
+            .collect();
+        for path in &paths {
+            fs::write(path, "fn main() {}").unwrap();
+            store.update_file(path, vec![]).unwrap();
+        }
+
+        assert_eq!(
+            store.sample_staleness(10),
+            StalenessSample {
+                total: 4,
+                checked: 4,
+                stale: 0
+            }
+        );
+
+        fs::write(&paths[0], "fn main() { changed(); }").unwrap();
+        fs::remove_file(&paths[1]).unwrap();
+        assert_eq!(store.sample_staleness(10).stale, 2);
+
+        // The sample is capped
+        assert_eq!(store.sample_staleness(2).checked, 2);
+    }
+
     #[test]
     fn test_load_corrupt_json() {
         let dir = tempdir().unwrap();
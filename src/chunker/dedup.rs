@@ -57,6 +57,37 @@ impl ChunkDeduplicator {
             .collect()
     }
 
+    /// Split chunks into first occurrences and repeats of content already seen
+    ///
+    /// `known` marks content stored before this deduplicator saw it (e.g. by an
+    /// earlier indexing run); those chunks count as duplicates too. Order is
+    /// preserved within both lists.
+    pub fn partition(
+        &self,
+        chunks: Vec<Chunk>,
+        known: impl Fn(&Chunk) -> bool,
+    ) -> (Vec<Chunk>, Vec<Chunk>) {
+        let mut unique = Vec::new();
+        let mut duplicates = Vec::new();
+        for chunk in chunks {
+            let index = self.unique_count.load(Ordering::Relaxed)
+                + self.duplicate_count.load(Ordering::Relaxed);
+            if self.seen.contains_key(&chunk.hash) {
+                self.duplicate_count.fetch_add(1, Ordering::Relaxed);
+                duplicates.push(chunk);
+            } else if known(&chunk) {
+                self.seen.insert(chunk.hash.clone(), index);
+                self.duplicate_count.fetch_add(1, Ordering::Relaxed);
+                duplicates.push(chunk);
+            } else {
+                self.seen.insert(chunk.hash.clone(), index);
+                self.unique_count.fetch_add(1, Ordering::Relaxed);
+                unique.push(chunk);
+            }
+        }
+        (unique, duplicates)
+    }
+
     /// Check if a chunk is a duplicate without consuming it
     pub fn is_duplicate(&self, chunk: &Chunk) -> bool {
         self.seen.contains_key(&chunk.hash)
@@ -277,6 +308,28 @@ mod tests {
         assert_eq!(stats.dedup_percentage(), 40.0);
     }
 
+    #[test]
+    fn test_partition_counts_known_content_as_duplicate() {
+        let deduper = ChunkDeduplicator::new();
+
+        let chunks = vec![
+            create_test_chunk("license", "f1"),
+            create_test_chunk("code", "f1"),
+            create_test_chunk("license", "f2"),
+            create_test_chunk("stored", "f2"),
+            create_test_chunk("stored", "f3"),
+        ];
+
+        let (unique, duplicates) = deduper.partition(chunks, |c| c.content == "stored");
+
+        let paths = |chunks: &[Chunk]| chunks.iter().map(|c| c.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths(&unique), vec!["f1", "f1"]);
+        assert_eq!(paths(&duplicates), vec!["f2", "f2", "f3"]);
+        let stats = deduper.stats();
+        assert_eq!(stats.unique_chunks, 2);
+        assert_eq!(stats.duplicate_chunks, 3);
+    }
+
     #[test]
     fn test_order_preservation() {
         let deduper = ChunkDeduplicator::new();
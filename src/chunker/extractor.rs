@@ -1,7 +1,9 @@
 #![allow(dead_code)]
 
+use super::grammar::registered_grammar;
 use super::ChunkKind;
 use crate::file::Language;
+use std::sync::Arc;
 use tree_sitter::Node;
 
 /// Language-specific code extraction logic
@@ -72,26 +74,86 @@ pub trait LanguageExtractor: Send + Sync {
             ChunkKind::Imports => format!("Imports: {}", name),
             ChunkKind::ModuleDocs => format!("ModuleDocs: {}", name),
             ChunkKind::Comment => format!("Comment: {}", name),
+            ChunkKind::Test => format!("Test: {}", name),
             _ => format!("Symbol: {}", name),
         })
     }
 }
 
 /// Get the appropriate extractor for a language
-pub fn get_extractor(language: Language) -> Option<Box<dyn LanguageExtractor>> {
+///
+/// Extractors registered with `register_grammar` take precedence over the built-in ones.
+pub fn get_extractor(language: Language) -> Option<Arc<dyn LanguageExtractor>> {
+    if let Some(registration) = registered_grammar(language) {
+        return Some(registration.extractor);
+    }
     match language {
-        Language::Rust => Some(Box::new(RustExtractor)),
-        Language::Python => Some(Box::new(PythonExtractor)),
-        Language::JavaScript | Language::TypeScript => Some(Box::new(TypeScriptExtractor)),
-        Language::C => Some(Box::new(CExtractor)),
-        Language::Cpp => Some(Box::new(CppExtractor)),
-        Language::CSharp => Some(Box::new(CSharpExtractor)),
-        Language::Go => Some(Box::new(GoExtractor)),
-        Language::Java => Some(Box::new(JavaExtractor)),
+        Language::Rust => Some(Arc::new(RustExtractor)),
+        Language::Python => Some(Arc::new(PythonExtractor)),
+        Language::JavaScript | Language::TypeScript => Some(Arc::new(TypeScriptExtractor)),
+        Language::C => Some(Arc::new(CExtractor)),
+        Language::Cpp => Some(Arc::new(CppExtractor)),
+        Language::CSharp => Some(Arc::new(CSharpExtractor)),
+        Language::Go => Some(Arc::new(GoExtractor)),
+        Language::Java => Some(Arc::new(JavaExtractor)),
         _ => None,
     }
 }
 
+/// Table-driven extractor for plugged-in grammars: node kind -> `ChunkKind`
+///
+/// Names come from the node's `name` field (or its first identifier child) and
+/// the signature is the node's first line. Write a dedicated extractor when a
+/// language needs more (docstrings, methods vs functions, ...).
+pub struct NodeKindExtractor {
+    kinds: Vec<(&'static str, ChunkKind)>,
+    definition_types: Vec<&'static str>,
+}
+
+impl NodeKindExtractor {
+    pub fn new(kinds: &[(&'static str, ChunkKind)]) -> Self {
+        Self {
+            kinds: kinds.to_vec(),
+            definition_types: kinds.iter().map(|(kind, _)| *kind).collect(),
+        }
+    }
+}
+
+impl LanguageExtractor for NodeKindExtractor {
+    fn definition_types(&self) -> &[&'static str] {
+        &self.definition_types
+    }
+
+    fn extract_name(&self, node: Node, source: &[u8]) -> Option<String> {
+        let name = node.child_by_field_name("name").or_else(|| {
+            let mut cursor = node.walk();
+            let found = node
+                .named_children(&mut cursor)
+                .find(|c| c.kind().ends_with("identifier"));
+            found
+        })?;
+        name.utf8_text(source).ok().map(String::from)
+    }
+
+    fn extract_signature(&self, node: Node, source: &[u8]) -> Option<String> {
+        let text = node.utf8_text(source).ok()?;
+        let first_line = text.lines().next()?.trim();
+        Some(first_line.trim_end_matches('{').trim_end().to_string())
+    }
+
+    fn extract_docstring(&self, _node: Node, _source: &[u8]) -> Option<String> {
+        None
+    }
+
+    fn classify(&self, node: Node) -> ChunkKind {
+        self.kinds
+            .iter()
+            .find(|(kind, _)| *kind == node.kind())
+            .map(|(_, chunk_kind)| *chunk_kind)
+            .unwrap_or(ChunkKind::Other)
+    }
+}
+
 /// Rust language extractor
 pub struct RustExtractor;
 
@@ -1054,6 +1116,46 @@ mod tests {
         assert!(types.contains(&"impl_item"));
     }
 
+    #[test]
+    fn test_node_kind_extractor() {
+        let extractor = NodeKindExtractor::new(&[
+            ("function_item", ChunkKind::Function),
+            ("struct_item", ChunkKind::Struct),
+        ]);
+        assert_eq!(
+            extractor.definition_types(),
+            &["function_item", "struct_item"]
+        );
+
+        let source =
+            "struct Point {\n    x: i32,\n}\n\nfn origin() -> Point {\n    Point { x: 0 }\n}\n";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_rust::LANGUAGE.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let root = tree.root_node();
+
+        let function = root.named_child(1).unwrap();
+        assert_eq!(extractor.classify(function), ChunkKind::Function);
+        assert_eq!(
+            extractor
+                .extract_name(function, source.as_bytes())
+                .as_deref(),
+            Some("origin")
+        );
+        assert_eq!(
+            extractor
+                .extract_signature(function, source.as_bytes())
+                .as_deref(),
+            Some("fn origin() -> Point")
+        );
+        assert_eq!(
+            extractor.classify(root.named_child(0).unwrap()),
+            ChunkKind::Struct
+        );
+    }
+
     #[test]
     fn test_python_definition_types() {
         let extractor = PythonExtractor;
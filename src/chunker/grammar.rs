@@ -1,10 +1,97 @@
+//! Tree-sitter grammars, and how they pair with `Language`
+//!
+//! `Language` (detected from the file extension) is the key for everything here:
+//! the grammar parses the file and the `LanguageExtractor` from
+//! `extractor::get_extractor` decides which node kinds become chunks. A language
+//! needs both to be chunked semantically; with either missing, the chunker falls
+//! back to line-based chunking.
+//!
+//! Besides the built-in grammars, more can be plugged in with [`register_grammar`],
+//! for languages that are detected but have no built-in grammar (Zig, Ruby,
+//! Kotlin, ...). Grammars shipped behind `lang-*` cargo features (e.g. `lang-zig`)
+//! register themselves the same way.
+
+use super::extractor::LanguageExtractor;
 use crate::file::Language;
 use anyhow::{anyhow, Result};
 use dashmap::DashMap;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
 use tracing::{debug, warn};
 use tree_sitter::Language as TsLanguage;
 
+/// A grammar plugged in at runtime together with its extractor
+#[derive(Clone)]
+pub struct GrammarRegistration {
+    pub grammar: TsLanguage,
+    pub extractor: Arc<dyn LanguageExtractor>,
+}
+
+/// Registered grammars, seeded with the ones enabled by `lang-*` features
+fn registry() -> &'static RwLock<HashMap<Language, GrammarRegistration>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<Language, GrammarRegistration>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        #[allow(unused_mut)]
+        let mut grammars = HashMap::new();
+        #[cfg(feature = "lang-zig")]
+        grammars.insert(
+            Language::Zig,
+            GrammarRegistration {
+                grammar: tree_sitter_zig::LANGUAGE.into(),
+                extractor: Arc::new(super::zig::ZigExtractor),
+            },
+        );
+        RwLock::new(grammars)
+    })
+}
+
+/// Register a tree-sitter grammar and extractor for `language`
+///
+/// Takes precedence over the built-in grammar for that language, and applies to
+/// `GrammarManager`s created after the call (existing ones cache their grammars).
+///
+/// ```ignore
+/// register_grammar(
+///     Language::Ruby,
+///     tree_sitter_ruby::LANGUAGE.into(),
+///     NodeKindExtractor::new(&[("method", ChunkKind::Method), ("class", ChunkKind::Class)]),
+/// );
+/// ```
+pub fn register_grammar(
+    language: Language,
+    grammar: TsLanguage,
+    extractor: impl LanguageExtractor + 'static,
+) {
+    let registration = GrammarRegistration {
+        grammar,
+        extractor: Arc::new(extractor),
+    };
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(language, registration);
+    debug!("Registered tree-sitter grammar for {}", language.name());
+}
+
+/// The registration for `language`, if one was plugged in
+pub fn registered_grammar(language: Language) -> Option<GrammarRegistration> {
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&language)
+        .cloned()
+}
+
+/// Languages with a registered grammar
+fn registered_languages() -> Vec<Language> {
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .keys()
+        .copied()
+        .collect()
+}
+
 /// Manages tree-sitter grammars for multiple languages
 ///
 /// This uses compiled-in grammars (no downloads needed!), making it:
@@ -55,8 +142,11 @@ impl GrammarManager {
         }
     }
 
-    /// Load the compiled grammar for a language
+    /// Load the registered or compiled grammar for a language
     fn load_grammar(&self, language: Language) -> Result<TsLanguage> {
+        if let Some(registration) = registered_grammar(language) {
+            return Ok(registration.grammar);
+        }
         match language {
             Language::Rust => Ok(tree_sitter_rust::LANGUAGE.into()),
             Language::Python => Ok(tree_sitter_python::LANGUAGE.into()),
@@ -76,7 +166,7 @@ impl GrammarManager {
 
     /// Get list of languages that have tree-sitter support
     pub fn supported_languages(&self) -> Vec<Language> {
-        vec![
+        let mut languages = vec![
             Language::Rust,
             Language::Python,
             Language::JavaScript,
@@ -86,7 +176,13 @@ impl GrammarManager {
             Language::CSharp,
             Language::Go,
             Language::Java,
-        ]
+        ];
+        for language in registered_languages() {
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+        languages
     }
 
     /// Check if a language has tree-sitter support
@@ -11,7 +11,13 @@ mod grammar;
 mod parser;
 mod semantic;
 mod tree_sitter;
+#[cfg(feature = "lang-zig")]
+mod zig;
 
+#[allow(unused_imports)] // Library API, unused by the CLI binary
+pub use extractor::{LanguageExtractor, NodeKindExtractor};
+#[allow(unused_imports)] // Library API, unused by the CLI binary
+pub use grammar::{register_grammar, GrammarRegistration};
 pub use semantic::SemanticChunker;
 
 /// Default number of context lines before/after a chunk
@@ -59,6 +65,13 @@ pub struct Chunk {
 
     /// Lines of code immediately after this chunk (for context)
     pub context_next: Option<String>,
+
+    /// Language of the file the chunk came from (set by `SemanticChunker::chunk_semantic`)
+    pub language: Option<crate::file::Language>,
+
+    /// Code-only text to embed instead of `content` (comments and string
+    /// literals stripped); not stored, `content` is what gets displayed
+    pub embed_text: Option<String>,
 }
 
 impl Chunk {
@@ -86,6 +99,8 @@ impl Chunk {
             hash,
             context_prev: None,
             context_next: None,
+            language: None,
+            embed_text: None,
         }
     }
 
@@ -136,6 +151,15 @@ impl Chunk {
     }
 }
 
+/// Size limits for a chunk; larger chunks are split into overlapping parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkLimits {
+    /// Maximum number of lines per chunk
+    pub max_lines: usize,
+    /// Maximum number of characters per chunk
+    pub max_chars: usize,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChunkKind {
     Function,   // Standalone function
@@ -155,9 +179,42 @@ pub enum ChunkKind {
     Comment,    // Standalone comment block (gap between definitions)
     Imports,    // Import/use statements block
     ModuleDocs, // Module-level documentation (//!, /*!)
+    Test,       // Test function/module/block (#[test], tests/, describe/it)
     Other,      // Catch-all
 }
 
+impl ChunkKind {
+    /// Every kind, in declaration order
+    pub const ALL: [ChunkKind; 19] = [
+        ChunkKind::Function,
+        ChunkKind::Class,
+        ChunkKind::Method,
+        ChunkKind::Struct,
+        ChunkKind::Enum,
+        ChunkKind::Trait,
+        ChunkKind::Interface,
+        ChunkKind::Impl,
+        ChunkKind::Mod,
+        ChunkKind::TypeAlias,
+        ChunkKind::Const,
+        ChunkKind::Static,
+        ChunkKind::Block,
+        ChunkKind::Anchor,
+        ChunkKind::Comment,
+        ChunkKind::Imports,
+        ChunkKind::ModuleDocs,
+        ChunkKind::Test,
+        ChunkKind::Other,
+    ];
+
+    /// Parse a kind by its `Debug` name as stored in the index (case-insensitive)
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| format!("{:?}", kind).eq_ignore_ascii_case(name))
+    }
+}
+
 /// Trait for chunking strategies
 pub trait Chunker: Send + Sync {
     /// Chunk a file into semantic pieces
@@ -173,4 +230,15 @@ mod tests {
     fn test_chunker() {
         // TODO: Add tests
     }
+
+    #[test]
+    fn test_chunk_kind_parse() {
+        assert_eq!(ChunkKind::parse("Method"), Some(ChunkKind::Method));
+        assert_eq!(ChunkKind::parse("typealias"), Some(ChunkKind::TypeAlias));
+        assert_eq!(ChunkKind::parse("function "), None);
+        assert_eq!(ChunkKind::parse("lambda"), None);
+        for kind in ChunkKind::ALL {
+            assert_eq!(ChunkKind::parse(&format!("{:?}", kind)), Some(kind));
+        }
+    }
 }
@@ -53,6 +53,27 @@ impl CodeParser {
     }
 }
 
+/// Byte ranges of the outermost comment and string nodes under `node`, in order
+fn collect_comments_and_strings(
+    node: Node,
+    language: Language,
+    out: &mut Vec<(usize, usize, bool)>,
+) {
+    let kind = node.kind();
+    if language.is_comment_or_string_node(kind) {
+        out.push((
+            node.start_byte(),
+            node.end_byte(),
+            !kind.contains("comment"),
+        ));
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_comments_and_strings(child, language, out);
+    }
+}
+
 impl Default for CodeParser {
     fn default() -> Self {
         Self::new()
@@ -93,6 +114,32 @@ impl ParsedCode {
         self.root_node().has_error()
     }
 
+    /// The source with comments removed and string literals emptied to `""`
+    ///
+    /// Line breaks inside removed nodes are kept, so line `n` of the result
+    /// holds what is left of line `n` of the source.
+    pub fn strip_comments_and_strings(&self) -> String {
+        let mut removed = Vec::new();
+        collect_comments_and_strings(self.root_node(), self.language, &mut removed);
+
+        let mut out = String::with_capacity(self.source.len());
+        let mut pos = 0;
+        for (start, end, is_string) in removed {
+            if start < pos {
+                continue;
+            }
+            out.push_str(&self.source[pos..start]);
+            if is_string {
+                out.push_str("\"\"");
+            }
+            let newlines = self.source[start..end].matches('\n').count();
+            out.push_str(&"\n".repeat(newlines));
+            pos = end;
+        }
+        out.push_str(&self.source[pos..]);
+        out
+    }
+
     /// Walk the tree and find all nodes of a given type
     ///
     /// Note: This returns node IDs that can be used to access nodes via the tree
@@ -185,6 +232,23 @@ fn main() {
         assert!(!parsed.has_errors());
     }
 
+    #[test]
+    fn test_strip_comments_and_strings() {
+        let mut parser = CodeParser::new();
+        let source =
+            "/// Greets\nfn greet() {\n    /* multi\n       line */\n    say(\"hi\"); // loud\n}\n";
+
+        let stripped = parser
+            .parse(Language::Rust, source)
+            .unwrap()
+            .strip_comments_and_strings();
+        let lines: Vec<&str> = stripped.lines().map(str::trim_end).collect();
+        assert_eq!(
+            lines,
+            vec!["", "fn greet() {", "", "", "    say(\"\");", "}"]
+        );
+    }
+
     #[test]
     fn test_parse_python_code() {
         let mut parser = CodeParser::new();
@@ -1,11 +1,12 @@
 #![allow(dead_code)]
 
-use super::{Chunk, ChunkKind, Chunker, DEFAULT_CONTEXT_LINES};
+use super::{Chunk, ChunkKind, ChunkLimits, Chunker, DEFAULT_CONTEXT_LINES};
 use crate::cache::normalize_path;
 use crate::chunker::extractor::{get_extractor, LanguageExtractor};
 use crate::chunker::parser::CodeParser;
 use crate::file::Language;
 use anyhow::Result;
+use std::collections::HashMap;
 use std::path::Path;
 use tree_sitter::Node;
 
@@ -16,6 +17,10 @@ pub struct SemanticChunker {
     max_chunk_chars: usize,
     overlap_lines: usize,
     context_lines: usize,
+    /// Embed a copy of each chunk without comments and string literals
+    strip_comments: bool,
+    /// Per-language overrides of `max_chunk_lines`/`max_chunk_chars`
+    language_limits: HashMap<Language, ChunkLimits>,
 }
 
 impl SemanticChunker {
@@ -26,15 +31,65 @@ impl SemanticChunker {
             max_chunk_chars,
             overlap_lines,
             context_lines: DEFAULT_CONTEXT_LINES,
+            strip_comments: false,
+            language_limits: HashMap::new(),
         }
     }
 
+    /// Override the chunk size limits for specific languages
+    ///
+    /// Languages without an entry use the limits passed to `new`.
+    pub fn with_language_limits(mut self, limits: HashMap<Language, ChunkLimits>) -> Self {
+        self.language_limits = limits;
+        self
+    }
+
+    /// The limits used for languages without an override
+    pub fn default_limits(&self) -> ChunkLimits {
+        ChunkLimits {
+            max_lines: self.max_chunk_lines,
+            max_chars: self.max_chunk_chars,
+        }
+    }
+
+    /// The limits used for chunks of `language`
+    pub fn limits_for(&self, language: Language) -> ChunkLimits {
+        self.language_limits
+            .get(&language)
+            .copied()
+            .unwrap_or_else(|| self.default_limits())
+    }
+
     /// Set the number of context lines to extract before/after each chunk
     pub fn with_context_lines(mut self, lines: usize) -> Self {
         self.context_lines = lines;
         self
     }
 
+    /// Set whether chunks get a comment- and string-free `embed_text`
+    ///
+    /// Only languages with a tree-sitter grammar are stripped; `content` is
+    /// left as is either way.
+    pub fn with_strip_comments(mut self, strip: bool) -> Self {
+        self.strip_comments = strip;
+        self
+    }
+
+    /// Set `embed_text` on a chunk that was stored without it, e.g. when
+    /// re-embedding an index built with `--strip-comments`
+    ///
+    /// The chunk is parsed on its own; it is left unchanged if its language
+    /// has no grammar.
+    pub fn strip_chunk(&mut self, chunk: &mut Chunk) {
+        let Some(language) = chunk.language else {
+            return;
+        };
+        if let Ok(parsed) = self.parser.parse(language, &chunk.content) {
+            let stripped = parsed.strip_comments_and_strings();
+            chunk.embed_text = code_lines(stripped.lines());
+        }
+    }
+
     /// Chunk a file using semantic analysis
     pub fn chunk_semantic(
         &mut self,
@@ -42,12 +97,31 @@ impl SemanticChunker {
         path: &Path,
         content: &str,
     ) -> Result<Vec<Chunk>> {
+        let mut chunks = self.chunk_by_language(language, path, content)?;
+        for chunk in &mut chunks {
+            chunk.language = Some(language);
+        }
+        Ok(chunks)
+    }
+
+    fn chunk_by_language(
+        &mut self,
+        language: Language,
+        path: &Path,
+        content: &str,
+    ) -> Result<Vec<Chunk>> {
+        let limits = self.limits_for(language);
+
         // 1. Check if we have an extractor for this language
         let extractor = match get_extractor(language) {
             Some(ext) => ext,
             None => {
                 // Fall back to simple chunking for unsupported languages
-                return Ok(self.fallback_chunk(path, content));
+                let mut chunks = self.fallback_chunk(path, content, limits);
+                if is_test_path(path) {
+                    mark_as_tests(&mut chunks);
+                }
+                return Ok(chunks);
             }
         };
 
@@ -66,6 +140,7 @@ impl SemanticChunker {
             &[file_context],
             &mut definition_chunks,
             &mut gap_tracker,
+            false,
         );
 
         // 4. Extract gap chunks (code between definitions)
@@ -76,16 +151,32 @@ impl SemanticChunker {
         all_chunks.extend(gap_chunks);
         all_chunks.sort_by_key(|c| c.start_line);
 
+        // Everything in a test file (tests/, *_test.go, foo.spec.ts, ...) is test code
+        if is_test_path(path) {
+            mark_as_tests(&mut all_chunks);
+        }
+
         // 6. Populate context windows (lines before/after each chunk)
         let source_lines: Vec<&str> = content.lines().collect();
         self.populate_context_windows(&mut all_chunks, &source_lines);
 
         // 7. Split oversized chunks
-        let final_chunks = all_chunks
+        let mut final_chunks: Vec<Chunk> = all_chunks
             .into_iter()
-            .flat_map(|c| self.split_if_needed(c))
+            .flat_map(|c| self.split_if_needed(c, limits))
             .collect();
 
+        // 8. Code-only text to embed, cut from the stripped file by line range
+        if self.strip_comments {
+            let stripped = parsed.strip_comments_and_strings();
+            let stripped_lines: Vec<&str> = stripped.lines().collect();
+            for chunk in &mut final_chunks {
+                let end = chunk.end_line.min(stripped_lines.len());
+                let start = chunk.start_line.min(end);
+                chunk.embed_text = code_lines(stripped_lines[start..end].iter().copied());
+            }
+        }
+
         Ok(final_chunks)
     }
 
@@ -123,6 +214,11 @@ impl SemanticChunker {
     }
 
     /// Recursively visit AST nodes and extract chunks
+    ///
+    /// `in_test` is true when an enclosing definition or block is test code
+    /// (e.g. a `#[cfg(test)]` module or a JS `describe` block); every definition
+    /// below it is then classified as `ChunkKind::Test`.
+    #[allow(clippy::too_many_arguments)]
     fn visit_node(
         &self,
         node: Node,
@@ -131,6 +227,7 @@ impl SemanticChunker {
         context_stack: &[String],
         chunks: &mut Vec<Chunk>,
         gap_tracker: &mut GapTracker,
+        in_test: bool,
     ) {
         // Check if this node is a definition
         let is_definition = extractor.definition_types().contains(&node.kind());
@@ -175,8 +272,19 @@ impl SemanticChunker {
             }
 
             // Extract metadata using the language extractor
-            let kind = extractor.classify(node);
+            let mut kind = extractor.classify(node);
             let name = extractor.extract_name(node, source);
+
+            // Test detection: enclosing test scope, test attributes/annotations,
+            // or test_* / *_test function naming
+            let is_test = in_test
+                || has_test_marker(node, source)
+                || (matches!(kind, ChunkKind::Function | ChunkKind::Method)
+                    && name.as_deref().is_some_and(is_test_name));
+            if is_test {
+                kind = ChunkKind::Test;
+            }
+
             let signature = extractor.extract_signature(node, source);
             let docstring = extractor.extract_docstring(node, source);
 
@@ -219,29 +327,86 @@ impl SemanticChunker {
             // Visit children with updated context
             let mut cursor = node.walk();
             for child in node.named_children(&mut cursor) {
-                self.visit_node(child, source, extractor, &new_context, chunks, gap_tracker);
+                self.visit_node(
+                    child,
+                    source,
+                    extractor,
+                    &new_context,
+                    chunks,
+                    gap_tracker,
+                    is_test,
+                );
+            }
+        } else if let Some(label) = test_block_label(node, source) {
+            // JS/TS test block (describe/it/test called with a callback):
+            // not a definition, but still a meaningful unit to index
+            gap_tracker.mark_covered(node.start_position().row, node.end_position().row);
+
+            let content = match node.utf8_text(source) {
+                Ok(text) => text.to_string(),
+                Err(_) => return,
+            };
+
+            let mut new_context = context_stack.to_vec();
+            new_context.push(label);
+
+            let path_str = context_stack
+                .first()
+                .map(|s| s.strip_prefix("File: ").unwrap_or(s))
+                .unwrap_or("")
+                .to_string();
+
+            let mut chunk = Chunk::new(
+                content,
+                node.start_position().row,
+                node.end_position().row + 1,
+                ChunkKind::Test,
+                path_str,
+            );
+            chunk.context = new_context.clone();
+            chunks.push(chunk);
+
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                self.visit_node(
+                    child,
+                    source,
+                    extractor,
+                    &new_context,
+                    chunks,
+                    gap_tracker,
+                    true,
+                );
             }
         } else {
             // Not a definition, just visit children with same context
             let mut cursor = node.walk();
             for child in node.named_children(&mut cursor) {
-                self.visit_node(child, source, extractor, context_stack, chunks, gap_tracker);
+                self.visit_node(
+                    child,
+                    source,
+                    extractor,
+                    context_stack,
+                    chunks,
+                    gap_tracker,
+                    in_test,
+                );
             }
         }
     }
 
     /// Fallback chunking for unsupported languages
-    fn fallback_chunk(&self, path: &Path, content: &str) -> Vec<Chunk> {
+    fn fallback_chunk(&self, path: &Path, content: &str, limits: ChunkLimits) -> Vec<Chunk> {
         let lines: Vec<&str> = content.lines().collect();
         let mut chunks = Vec::new();
-        let stride = (self.max_chunk_lines - self.overlap_lines).max(1);
+        let stride = limits.max_lines.saturating_sub(self.overlap_lines).max(1);
 
         let path_str = normalize_path(path);
         let context = vec![format!("File: {}", path_str)];
 
         let mut i = 0;
         while i < lines.len() {
-            let end = (i + self.max_chunk_lines).min(lines.len());
+            let end = (i + limits.max_lines).min(lines.len());
             let chunk_lines = &lines[i..end];
 
             if !chunk_lines.is_empty() {
@@ -258,25 +423,25 @@ impl SemanticChunker {
     }
 
     /// Split a chunk if it exceeds size limits
-    fn split_if_needed(&self, chunk: Chunk) -> Vec<Chunk> {
+    fn split_if_needed(&self, chunk: Chunk, limits: ChunkLimits) -> Vec<Chunk> {
         let line_count = chunk.line_count();
         let char_count = chunk.size_bytes();
 
         // Check if splitting is needed
-        if line_count <= self.max_chunk_lines && char_count <= self.max_chunk_chars {
+        if line_count <= limits.max_lines && char_count <= limits.max_chars {
             return vec![chunk];
         }
 
         // Need to split
         let lines: Vec<&str> = chunk.content.lines().collect();
         let mut split_chunks = Vec::new();
-        let stride = (self.max_chunk_lines - self.overlap_lines).max(1);
+        let stride = limits.max_lines.saturating_sub(self.overlap_lines).max(1);
 
         let mut i = 0;
         let mut split_index = 0;
 
         while i < lines.len() {
-            let end = (i + self.max_chunk_lines).min(lines.len());
+            let end = (i + limits.max_lines).min(lines.len());
             let chunk_lines = &lines[i..end];
 
             if !chunk_lines.is_empty() {
@@ -339,12 +504,185 @@ impl Chunker for SemanticChunker {
             self.max_chunk_lines,
             self.max_chunk_chars,
             self.overlap_lines,
-        );
+        )
+        .with_language_limits(self.language_limits.clone())
+        .with_context_lines(self.context_lines)
+        .with_strip_comments(self.strip_comments);
 
         temp_chunker.chunk_semantic(language, path, content)
     }
 }
 
+/// Join the non-blank lines of stripped code, or `None` if nothing is left
+fn code_lines<'a>(lines: impl Iterator<Item = &'a str>) -> Option<String> {
+    let code: Vec<&str> = lines
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    (!code.is_empty()).then(|| code.join("\n"))
+}
+
+/// Mark every chunk as test code
+fn mark_as_tests(chunks: &mut [Chunk]) {
+    for chunk in chunks.iter_mut() {
+        chunk.kind = ChunkKind::Test;
+    }
+}
+
+/// Check if a file path looks like a test file
+///
+/// Matches files under `tests/`, `__tests__/` or `src/test/` directories and
+/// common test file naming conventions (`test_*.py`, `*_test.go`, `*_test.rs`,
+/// `*.test.ts`, `*.spec.js`, `FooTest.java`, `FooTests.cs`).
+pub fn is_test_path(path: &Path) -> bool {
+    let components: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    // Directory components (everything except the file name)
+    let dirs = &components[..components.len().saturating_sub(1)];
+    for (i, dir) in dirs.iter().enumerate() {
+        if dir == "tests" || dir == "__tests__" {
+            return true;
+        }
+        // Maven/Gradle layout: src/test/java/...
+        if dir == "test" && i > 0 && dirs[i - 1] == "src" {
+            return true;
+        }
+    }
+
+    let file_name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return false,
+    };
+    let stem = file_name.split('.').next().unwrap_or("");
+
+    file_name.starts_with("test_")
+        || stem.ends_with("_test")
+        || file_name.contains(".test.")
+        || file_name.contains(".spec.")
+        || stem.ends_with("Test")
+        || stem.ends_with("Tests")
+}
+
+/// Check if a function name follows the `test_*` / `*_test` convention
+fn is_test_name(name: &str) -> bool {
+    name.starts_with("test_") || name.ends_with("_test")
+}
+
+/// Check if a definition carries a test marker
+///
+/// Looks at preceding attributes/decorators (`#[test]`, `#[tokio::test]`,
+/// `#[cfg(test)]`, `@pytest.mark.*`) and at modifier/attribute children
+/// (Java `@Test`, C# `[Test]`/`[Fact]`/`[TestMethod]`).
+fn has_test_marker(node: Node, source: &[u8]) -> bool {
+    let mut prev = node.prev_named_sibling();
+    while let Some(sibling) = prev {
+        match sibling.kind() {
+            "attribute_item" | "attribute" | "decorator" => {
+                if let Ok(text) = sibling.utf8_text(source) {
+                    if is_test_attribute(text) {
+                        return true;
+                    }
+                }
+            }
+            "line_comment" | "block_comment" | "comment" => {}
+            _ => break,
+        }
+        prev = sibling.prev_named_sibling();
+    }
+
+    // Python decorated definitions wrap the function in a decorated_definition
+    if let Some(parent) = node.parent() {
+        if parent.kind() == "decorated_definition" {
+            let mut cursor = parent.walk();
+            for child in parent.named_children(&mut cursor) {
+                if child.kind() == "decorator" {
+                    if let Ok(text) = child.utf8_text(source) {
+                        if is_test_attribute(text) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.kind() == "modifiers" || child.kind() == "attribute_list" {
+            if let Ok(text) = child.utf8_text(source) {
+                if text.contains("@Test")
+                    || text.contains("[Test")
+                    || text.contains("[Fact")
+                    || text.contains("[Theory")
+                {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Check if attribute/decorator text marks test code
+fn is_test_attribute(text: &str) -> bool {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Some(inner) = text.strip_prefix("#[").and_then(|t| t.strip_suffix(']')) {
+        return inner == "test"
+            || inner.ends_with("::test")
+            || inner.contains("::test(")
+            || inner.contains("cfg(test)");
+    }
+    text.starts_with("@pytest.mark")
+}
+
+/// Build a label for a JS/TS test block (`describe("...", () => {...})`)
+///
+/// Returns None unless the node is a call to describe/it/test (including
+/// variants like `describe.only` or `it.each(...)`) with a function callback.
+fn test_block_label(node: Node, source: &[u8]) -> Option<String> {
+    if node.kind() != "call_expression" {
+        return None;
+    }
+
+    let function = node.child_by_field_name("function")?;
+    let callee = function.utf8_text(source).ok()?;
+    let base = callee.split(['.', '(']).next().unwrap_or("");
+    if !matches!(base, "describe" | "it" | "test") {
+        return None;
+    }
+
+    let args = node.child_by_field_name("arguments")?;
+    let mut cursor = args.walk();
+    let arg_nodes: Vec<Node> = args.named_children(&mut cursor).collect();
+    let has_callback = arg_nodes.iter().any(|a| {
+        matches!(
+            a.kind(),
+            "arrow_function" | "function" | "function_expression"
+        )
+    });
+    if !has_callback {
+        return None;
+    }
+
+    let title = arg_nodes
+        .first()
+        .filter(|a| matches!(a.kind(), "string" | "template_string"))
+        .and_then(|a| a.utf8_text(source).ok())
+        .map(|t| {
+            t.trim_matches(|c| c == '"' || c == '\'' || c == '`')
+                .to_string()
+        });
+
+    Some(match title {
+        Some(title) => format!("Test: {} {}", base, title),
+        None => format!("Test: {}", base),
+    })
+}
+
 /// Helper to track gaps (code between definitions)
 struct GapTracker<'a> {
     #[allow(dead_code)]
@@ -529,6 +867,7 @@ struct Point {
             "Expected at least 3 chunks, got {}",
             chunks.len()
         );
+        assert!(chunks.iter().all(|c| c.language == Some(Language::Rust)));
 
         // Check that we have function chunks
         let function_chunks: Vec<_> = chunks
@@ -637,7 +976,7 @@ class Calculator:
             "test.rs".to_string(),
         );
 
-        let splits = chunker.split_if_needed(chunk);
+        let splits = chunker.split_if_needed(chunk, chunker.default_limits());
 
         // Should be split into multiple chunks
         assert!(splits.len() > 1, "Should split large chunk");
@@ -655,6 +994,70 @@ class Calculator:
         }
     }
 
+    #[test]
+    fn test_strip_comments_embed_text() {
+        let code = r#"// Connection setup
+fn connect(url: &str) -> Client {
+    // retry once before giving up
+    Client::new(url, "user-agent")
+}
+"#;
+        let path = PathBuf::from("net.rs");
+
+        let mut chunker = SemanticChunker::new(100, 2000, 10);
+        let chunks = chunker.chunk_semantic(Language::Rust, &path, code).unwrap();
+        assert!(chunks.iter().all(|c| c.embed_text.is_none()));
+
+        let mut chunker = SemanticChunker::new(100, 2000, 10).with_strip_comments(true);
+        let chunks = chunker.chunk_semantic(Language::Rust, &path, code).unwrap();
+        let connect = chunks
+            .iter()
+            .find(|c| c.content.starts_with("fn connect"))
+            .unwrap();
+        // Content is untouched; only the embedded text loses comments and strings
+        assert!(connect.content.contains("retry once"));
+        assert_eq!(
+            connect.embed_text.as_deref(),
+            Some("fn connect(url: &str) -> Client {\n    Client::new(url, \"\")\n}")
+        );
+
+        let mut rebuilt = Chunk::new(
+            connect.content.clone(),
+            connect.start_line,
+            connect.end_line,
+            connect.kind,
+            connect.path.clone(),
+        );
+        rebuilt.language = Some(Language::Rust);
+        chunker.strip_chunk(&mut rebuilt);
+        assert_eq!(rebuilt.embed_text, connect.embed_text);
+    }
+
+    #[test]
+    fn test_language_limits_override() {
+        let mut limits = HashMap::new();
+        limits.insert(
+            Language::Markdown,
+            ChunkLimits {
+                max_lines: 5,
+                max_chars: 2000,
+            },
+        );
+        let mut chunker = SemanticChunker::new(100, 2000, 1).with_language_limits(limits);
+        assert_eq!(chunker.limits_for(Language::Markdown).max_lines, 5);
+        assert_eq!(chunker.limits_for(Language::Rust).max_lines, 100);
+
+        let content = (0..20)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chunks = chunker
+            .chunk_semantic(Language::Markdown, Path::new("notes.md"), &content)
+            .unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.line_count() <= 5));
+    }
+
     #[test]
     fn test_context_breadcrumbs() {
         let mut chunker = SemanticChunker::new(100, 2000, 10);
@@ -681,4 +1084,130 @@ impl MyStruct {
             assert!(chunk.context[0].contains("File:"));
         }
     }
+
+    #[test]
+    fn test_rust_test_module_detection() {
+        let mut chunker = SemanticChunker::new(100, 2000, 10);
+
+        let rust_code = r#"
+fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_numbers() {
+        assert_eq!(add(1, 2), 3);
+    }
+}
+"#;
+
+        let path = Path::new("src/math.rs");
+        let chunks = chunker
+            .chunk_semantic(Language::Rust, path, rust_code)
+            .unwrap();
+
+        let add = chunks.iter().find(|c| c.content.starts_with("fn add("));
+        assert_eq!(add.unwrap().kind, ChunkKind::Function);
+
+        let module = chunks.iter().find(|c| c.content.starts_with("mod tests"));
+        assert_eq!(module.unwrap().kind, ChunkKind::Test);
+
+        let test_fn = chunks
+            .iter()
+            .find(|c| c.content.starts_with("fn adds_numbers"));
+        assert_eq!(test_fn.unwrap().kind, ChunkKind::Test);
+    }
+
+    #[test]
+    fn test_test_name_detection() {
+        let mut chunker = SemanticChunker::new(100, 2000, 10);
+
+        let python_code = r#"
+def parse(value):
+    return int(value)
+
+def test_parse():
+    assert parse("1") == 1
+"#;
+
+        let path = Path::new("src/parser.py");
+        let chunks = chunker
+            .chunk_semantic(Language::Python, path, python_code)
+            .unwrap();
+
+        let parse = chunks.iter().find(|c| c.content.starts_with("def parse"));
+        assert_eq!(parse.unwrap().kind, ChunkKind::Function);
+
+        let test_fn = chunks
+            .iter()
+            .find(|c| c.content.starts_with("def test_parse"));
+        assert_eq!(test_fn.unwrap().kind, ChunkKind::Test);
+    }
+
+    #[test]
+    fn test_js_describe_block_detection() {
+        let mut chunker = SemanticChunker::new(100, 2000, 10);
+
+        let js_code = r#"
+describe("parser", () => {
+  it("parses numbers", () => {
+    expect(parse("1")).toBe(1);
+  });
+});
+"#;
+
+        let path = Path::new("src/parser.js");
+        let chunks = chunker
+            .chunk_semantic(Language::JavaScript, path, js_code)
+            .unwrap();
+
+        let describe = chunks.iter().find(|c| c.content.starts_with("describe("));
+        let describe = describe.expect("describe block should be chunked");
+        assert_eq!(describe.kind, ChunkKind::Test);
+        assert!(describe
+            .context
+            .iter()
+            .any(|c| c == "Test: describe parser"));
+
+        let it = chunks.iter().find(|c| c.content.starts_with("it("));
+        assert_eq!(it.unwrap().kind, ChunkKind::Test);
+    }
+
+    #[test]
+    fn test_test_path_marks_all_chunks() {
+        let mut chunker = SemanticChunker::new(100, 2000, 10);
+
+        let rust_code = r#"
+fn helper() -> u32 {
+    42
+}
+"#;
+
+        let path = Path::new("tests/integration.rs");
+        let chunks = chunker
+            .chunk_semantic(Language::Rust, path, rust_code)
+            .unwrap();
+
+        assert!(!chunks.is_empty());
+        assert!(chunks.iter().all(|c| c.kind == ChunkKind::Test));
+    }
+
+    #[test]
+    fn test_is_test_path() {
+        assert!(is_test_path(Path::new("tests/integration.rs")));
+        assert!(is_test_path(Path::new("web/__tests__/app.js")));
+        assert!(is_test_path(Path::new("src/test/java/FooTest.java")));
+        assert!(is_test_path(Path::new("pkg/server_test.go")));
+        assert!(is_test_path(Path::new("app/test_models.py")));
+        assert!(is_test_path(Path::new("src/button.spec.ts")));
+        assert!(is_test_path(Path::new("src/button.test.js")));
+
+        assert!(!is_test_path(Path::new("src/main.rs")));
+        assert!(!is_test_path(Path::new("src/contest.py")));
+        assert!(!is_test_path(Path::new("src/testing/mod.rs")));
+    }
 }
@@ -0,0 +1,188 @@
+//! Zig support (`lang-zig` feature), using the tree-sitter-grammars Zig grammar
+//!
+//! Containers are anonymous in Zig (`const Point = struct { ... };`), so
+//! `struct`/`enum`/`union` declarations take their name from the enclosing
+//! variable declaration, and functions inside them are classified as methods.
+
+use super::extractor::LanguageExtractor;
+use super::ChunkKind;
+use tree_sitter::Node;
+
+/// Container node kinds that get their name from the enclosing `const`
+const CONTAINER_KINDS: [&str; 4] = [
+    "struct_declaration",
+    "enum_declaration",
+    "union_declaration",
+    "opaque_declaration",
+];
+
+/// Zig language extractor
+pub struct ZigExtractor;
+
+impl ZigExtractor {
+    /// The `const Name = ...` declaration wrapping a container, if any
+    fn declaration_of(node: Node) -> Option<Node> {
+        node.parent()
+            .filter(|parent| parent.kind() == "variable_declaration")
+    }
+
+    fn first_identifier<'a>(node: Node<'a>) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let found = node
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "identifier");
+        found
+    }
+}
+
+impl LanguageExtractor for ZigExtractor {
+    fn definition_types(&self) -> &[&'static str] {
+        &[
+            "function_declaration",
+            "struct_declaration",
+            "enum_declaration",
+            "union_declaration",
+            "opaque_declaration",
+            "error_set_declaration",
+            "test_declaration",
+        ]
+    }
+
+    fn extract_name(&self, node: Node, source: &[u8]) -> Option<String> {
+        let name = match node.kind() {
+            "function_declaration" => node
+                .child_by_field_name("name")
+                .or_else(|| Self::first_identifier(node))?,
+            // test "name" { ... }
+            "test_declaration" => {
+                let mut cursor = node.walk();
+                let found = node
+                    .named_children(&mut cursor)
+                    .find(|c| c.kind() == "string" || c.kind() == "identifier");
+                found?
+            }
+            _ => Self::first_identifier(Self::declaration_of(node)?)?,
+        };
+        name.utf8_text(source)
+            .ok()
+            .map(|s| s.trim_matches('"').to_string())
+    }
+
+    fn extract_signature(&self, node: Node, source: &[u8]) -> Option<String> {
+        if node.kind() != "function_declaration" {
+            return None;
+        }
+        // Everything before the body: `pub fn add(a: i32, b: i32) i32`
+        let end = node
+            .child_by_field_name("body")
+            .map(|body| body.start_byte())
+            .unwrap_or(node.end_byte());
+        let text = std::str::from_utf8(&source[node.start_byte()..end]).ok()?;
+        Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    fn extract_docstring(&self, node: Node, source: &[u8]) -> Option<String> {
+        // Doc comments (`///`) precede the declaration, which for containers is the `const`
+        let anchor = if CONTAINER_KINDS.contains(&node.kind()) {
+            Self::declaration_of(node)?
+        } else {
+            node
+        };
+
+        let mut lines = Vec::new();
+        let mut prev = anchor.prev_named_sibling();
+        while let Some(sibling) = prev {
+            if sibling.kind() != "comment" {
+                break;
+            }
+            let text = sibling.utf8_text(source).ok()?;
+            let Some(doc) = text.trim().strip_prefix("///") else {
+                break;
+            };
+            lines.push(doc.trim().to_string());
+            prev = sibling.prev_named_sibling();
+        }
+
+        if lines.is_empty() {
+            return None;
+        }
+        lines.reverse();
+        Some(lines.join("\n"))
+    }
+
+    fn classify(&self, node: Node) -> ChunkKind {
+        match node.kind() {
+            "function_declaration" => {
+                let mut parent = node.parent();
+                while let Some(p) = parent {
+                    if CONTAINER_KINDS.contains(&p.kind()) {
+                        return ChunkKind::Method;
+                    }
+                    parent = p.parent();
+                }
+                ChunkKind::Function
+            }
+            "struct_declaration" | "union_declaration" | "opaque_declaration" => ChunkKind::Struct,
+            "enum_declaration" | "error_set_declaration" => ChunkKind::Enum,
+            "test_declaration" => ChunkKind::Test,
+            _ => ChunkKind::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunker::{ChunkKind, SemanticChunker};
+    use crate::file::Language;
+    use std::path::Path;
+
+    #[test]
+    fn test_chunk_zig_code() {
+        let mut chunker = SemanticChunker::new(100, 2000, 10);
+
+        let zig_code = r#"
+const std = @import("std");
+
+/// A point in 2D space
+const Point = struct {
+    x: i32,
+    y: i32,
+
+    pub fn origin() Point {
+        return .{ .x = 0, .y = 0 };
+    }
+};
+
+pub fn add(a: i32, b: i32) i32 {
+    return a + b;
+}
+
+test "add works" {
+    try std.testing.expect(add(1, 2) == 3);
+}
+"#;
+
+        let chunks = chunker
+            .chunk_semantic(Language::Zig, Path::new("math.zig"), zig_code)
+            .unwrap();
+
+        let point = chunks
+            .iter()
+            .find(|c| c.kind == ChunkKind::Struct)
+            .expect("struct chunk");
+        assert!(point.context.iter().any(|c| c == "Struct: Point"));
+        assert_eq!(point.docstring.as_deref(), Some("A point in 2D space"));
+
+        let add = chunks
+            .iter()
+            .find(|c| c.kind == ChunkKind::Function)
+            .expect("function chunk");
+        assert_eq!(
+            add.signature.as_deref(),
+            Some("pub fn add(a: i32, b: i32) i32")
+        );
+
+        assert!(chunks.iter().any(|c| c.kind == ChunkKind::Method));
+        assert!(chunks.iter().any(|c| c.kind == ChunkKind::Test));
+    }
+}
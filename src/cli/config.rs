@@ -0,0 +1,207 @@
+//! User config (`~/.codesearch/config.toml`): defaults for frequently passed flags
+//!
+//! Values only fill in flags that were not given on the command line, so an
+//! explicit flag always wins.
+
+use anyhow::{anyhow, Context, Result};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::path::Path;
+
+use super::{Cli, Commands};
+use crate::embed::ModelType;
+use crate::rerank::RerankerType;
+
+/// Defaults loaded from the user config file
+///
+/// Example:
+/// ```toml
+/// model = "bge-small-q"
+///
+/// [search]
+/// max_results = 10
+/// rrf_k = 30
+/// rerank = true
+/// ```
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserConfig {
+    /// Embedding model for new indexes when `--model` is not given
+    ///
+    /// Existing indexes keep the model recorded in their metadata.json.
+    pub model: Option<String>,
+    /// Defaults for `codesearch search`
+    pub search: SearchDefaults,
+}
+
+/// Defaults for `codesearch search` flags; unset fields keep the built-in defaults
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchDefaults {
+    pub max_results: Option<usize>,
+    pub per_file: Option<usize>,
+    pub rrf_k: Option<f32>,
+    pub vector_only: Option<bool>,
+    pub rerank: Option<bool>,
+    pub rerank_top: Option<usize>,
+    pub rerank_model: Option<String>,
+}
+
+impl UserConfig {
+    /// Load the user config file
+    ///
+    /// Returns `Ok(None)` if the file does not exist, and an error if it cannot be
+    /// read, parsed or validated (callers should warn and fall back to defaults).
+    pub fn load() -> Result<Option<Self>> {
+        match crate::constants::get_user_config_path() {
+            Some(path) if path.exists() => Self::load_from(&path).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: UserConfig = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        config
+            .validate()
+            .with_context(|| format!("Invalid config in {}", path.display()))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(model) = &self.model {
+            ModelType::parse(model).ok_or_else(|| anyhow!("Unknown model '{}'", model))?;
+        }
+        if let Some(model) = &self.search.rerank_model {
+            RerankerType::parse(model).ok_or_else(|| anyhow!("Unknown reranker '{}'", model))?;
+        }
+        if self.search.max_results == Some(0) {
+            return Err(anyhow!("search.max_results must be greater than 0"));
+        }
+        if matches!(self.search.rrf_k, Some(k) if k <= 0.0) {
+            return Err(anyhow!("search.rrf_k must be greater than 0"));
+        }
+        Ok(())
+    }
+
+    /// Fill in flags of `cli` that were not given on the command line
+    pub fn apply(&self, cli: &mut Cli, matches: &ArgMatches) {
+        cli.default_model = self.model.clone();
+
+        let Commands::Search {
+            max_results,
+            per_file,
+            rrf_k,
+            vector_only,
+            rerank,
+            rerank_top,
+            rerank_model,
+            ..
+        } = &mut cli.command
+        else {
+            return;
+        };
+        let Some(search) = matches.subcommand_matches("search") else {
+            return;
+        };
+        let given = |id: &str| search.value_source(id) == Some(ValueSource::CommandLine);
+        let defaults = &self.search;
+
+        if !given("max_results") {
+            *max_results = defaults.max_results.unwrap_or(*max_results);
+        }
+        if !given("per_file") {
+            *per_file = defaults.per_file.unwrap_or(*per_file);
+        }
+        if !given("rrf_k") {
+            *rrf_k = defaults.rrf_k.unwrap_or(*rrf_k);
+        }
+        if !given("vector_only") {
+            *vector_only = defaults.vector_only.unwrap_or(*vector_only);
+        }
+        if !given("rerank") {
+            *rerank = defaults.rerank.unwrap_or(*rerank);
+        }
+        if !given("rerank_top") {
+            *rerank_top = defaults.rerank_top.unwrap_or(*rerank_top);
+        }
+        // `--rerank-model` implies `--rerank`, the configured model shouldn't
+        if *rerank && rerank_model.is_none() {
+            *rerank_model = defaults.rerank_model.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn parse(args: &[&str]) -> (Cli, ArgMatches) {
+        let matches = Cli::command().try_get_matches_from(args).unwrap();
+        let cli = Cli::from_arg_matches(&matches).unwrap();
+        (cli, matches)
+    }
+
+    fn config(toml: &str) -> UserConfig {
+        let config: UserConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    #[test]
+    fn test_config_fills_unset_flags_only() {
+        let config = config(
+            r#"
+model = "bge-small-q"
+
+[search]
+max_results = 7
+rrf_k = 30
+rerank = true
+"#,
+        );
+
+        let (mut cli, matches) = parse(&["codesearch", "search", "query", "-m", "3"]);
+        config.apply(&mut cli, &matches);
+
+        // The config model is only a default for new indexes, never `--model`
+        assert_eq!(cli.model, None);
+        assert_eq!(cli.default_model.as_deref(), Some("bge-small-q"));
+        let Commands::Search {
+            max_results,
+            rrf_k,
+            rerank,
+            per_file,
+            ..
+        } = cli.command
+        else {
+            panic!("expected search");
+        };
+        // Explicit flag wins over the config
+        assert_eq!(max_results, 3);
+        assert_eq!(rrf_k, 30.0);
+        assert!(rerank);
+        // Not in the config: built-in default
+        assert_eq!(per_file, 1);
+    }
+
+    #[test]
+    fn test_explicit_model_wins() {
+        let config = config(r#"model = "bge-small-q""#);
+        let (mut cli, matches) = parse(&["codesearch", "--model", "minilm-l6", "search", "q"]);
+        config.apply(&mut cli, &matches);
+        assert_eq!(cli.model.as_deref(), Some("minilm-l6"));
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_model() {
+        let config: UserConfig = toml::from_str(r#"model = "not-a-model""#).unwrap();
+        assert!(config.validate().is_err());
+
+        assert!(toml::from_str::<UserConfig>("max_results = 5").is_err());
+    }
+}
@@ -1,13 +1,228 @@
 use anyhow::Result;
+use colored::Colorize;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+use crate::cache::FileMetaStore;
+use crate::constants::WRITER_LOCK_FILE;
+use crate::db_discovery::find_best_database;
+use crate::embed::ModelType;
+use crate::error::CodeSearchError;
+use crate::fts::FtsStore;
+use crate::index::{is_database_locked, process_exists, writer_lock_owner};
+use crate::search::read_metadata;
+use crate::vectordb::VectorStore;
 
 pub async fn run() -> Result<()> {
     println!("🔍 Checking codesearch installation...");
 
     // TODO: Check installation health
     // - Model paths
-    // - Database integrity
     // - Dependencies
 
-    println!("✅ All checks passed!");
+    let issues = match find_best_database(None)? {
+        Some(db_info) => check_database(&db_info.db_path)?,
+        None => {
+            println!("ℹ️  No database found for the current directory, skipping index checks");
+            0
+        }
+    };
+
+    if issues > 0 {
+        println!(
+            "\n{}",
+            format!("❌ Found {} issue(s) with the index", issues).red()
+        );
+        println!(
+            "   Run {} to rebuild it",
+            "codesearch index --force".bright_cyan()
+        );
+    } else {
+        println!("✅ All checks passed!");
+    }
+    Ok(())
+}
+
+/// Check that the vector store, FTS index and file metadata agree with each other
+///
+/// Returns the number of issues found.
+fn check_database(db_path: &Path) -> Result<usize> {
+    println!("\n💾 Database: {}", db_path.display());
+
+    check_writer_lock(db_path)?;
+
+    let Some((model_name, dimensions, _)) = read_metadata(db_path) else {
+        println!("   {} metadata.json is missing or unreadable", "❌".red());
+        return Ok(1);
+    };
+
+    let store = VectorStore::open_readonly(db_path, dimensions)?;
+    let stats = store.stats()?;
+    println!(
+        "   Vector store: {} chunks in {} files",
+        stats.total_chunks, stats.total_files
+    );
+    if let Some(model) = ModelType::parse(&model_name) {
+        if store.metric() != model.metric() {
+            // Searches still work, just with worse ranking than the model allows
+            println!(
+                "   {}",
+                format!(
+                    "⚠️  Index uses the {} metric but {} is trained for {}; consider `codesearch index --force --metric {}`",
+                    store.metric(),
+                    model.short_name(),
+                    model.metric(),
+                    model.metric()
+                )
+                .yellow()
+            );
+        }
+    }
+
+    let mut issues = 0;
+
+    // 1. FTS document count must match the vector store chunk count
+    if !db_path.join("fts").exists() {
+        println!("   {} FTS index is missing", "❌".red());
+        issues += 1;
+    } else {
+        match FtsStore::new(db_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|fts| fts.stats())
+        {
+            Ok(fts_stats) if fts_stats.num_documents == stats.total_chunks => {
+                println!(
+                    "   ✅ FTS index in sync ({} documents)",
+                    fts_stats.num_documents
+                );
+            }
+            Ok(fts_stats) => {
+                println!(
+                    "   {} FTS index out of sync: {} documents vs {} vector chunks",
+                    "❌".red(),
+                    fts_stats.num_documents,
+                    stats.total_chunks
+                );
+                issues += 1;
+            }
+            Err(e) => match e.downcast_ref::<CodeSearchError>() {
+                Some(CodeSearchError::FtsOutdated {
+                    found, expected, ..
+                }) => {
+                    println!(
+                        "   {} FTS index uses schema version {} (expected {})",
+                        "❌".red(),
+                        found,
+                        expected
+                    );
+                    issues += 1;
+                }
+                _ => {
+                    println!("   {} Could not open FTS index: {}", "❌".red(), e);
+                    issues += 1;
+                }
+            },
+        }
+    }
+
+    // Only FTS checks have run so far
+    if issues > 0 {
+        println!(
+            "   Run {} to rebuild the full-text index from the stored chunks (no re-embedding)",
+            "codesearch rebuild-fts".bright_cyan()
+        );
+    }
+
+    // 2. Every chunk ID tracked in file metadata must exist in the vector store
+    let file_meta = FileMetaStore::load_or_create(db_path, &model_name, dimensions)?;
+    let mut missing_chunks = 0;
+    let mut affected_files = 0;
+    for (path, meta) in file_meta.entries() {
+        let mut missing_in_file = 0;
+        for &id in &meta.chunk_ids {
+            if store.get_chunk(id)?.is_none() {
+                missing_in_file += 1;
+            }
+        }
+        if missing_in_file > 0 {
+            tracing::debug!("{} missing chunk(s) for {}", missing_in_file, path);
+            missing_chunks += missing_in_file;
+            affected_files += 1;
+        }
+    }
+
+    if missing_chunks == 0 {
+        println!("   ✅ File metadata references only existing chunks");
+    } else {
+        println!(
+            "   {} {} chunk ID(s) from {} file(s) in file metadata are missing from the vector store",
+            "❌".red(),
+            missing_chunks,
+            affected_files
+        );
+        issues += 1;
+    }
+
+    Ok(issues)
+}
+
+/// Report who holds the writer lock, and offer to remove a lock left by a crashed writer
+///
+/// A stale lock isn't counted as an index issue: the index itself is fine.
+fn check_writer_lock(db_path: &Path) -> Result<()> {
+    let lock_path = db_path.join(WRITER_LOCK_FILE);
+    if !lock_path.exists() {
+        println!("   ✅ Not write-locked");
+        return Ok(());
+    }
+
+    let owner = writer_lock_owner(db_path);
+    if is_database_locked(db_path) {
+        match owner {
+            Some(pid) => println!(
+                "   🔒 Write-locked by PID {} (other instances open readonly)",
+                pid
+            ),
+            None => {
+                println!("   🔒 Write-locked by another process (other instances open readonly)")
+            }
+        }
+        return Ok(());
+    }
+
+    let alive = owner.and_then(process_exists);
+    if alive == Some(true) {
+        println!(
+            "   ✅ Not write-locked (lock file from PID {}, which no longer holds it)",
+            owner.unwrap_or_default()
+        );
+        return Ok(());
+    }
+
+    match (owner, alive) {
+        (Some(pid), Some(false)) => println!(
+            "   {} Stale writer lock: PID {} is no longer running",
+            "⚠️ ".yellow(),
+            pid
+        ),
+        _ => println!(
+            "   {} Writer lock file exists but no process holds it",
+            "⚠️ ".yellow()
+        ),
+    }
+
+    if !std::io::stdin().is_terminal() {
+        println!("   Remove {} to clear it", lock_path.display());
+        return Ok(());
+    }
+
+    print!("   Remove it? (y/N): ");
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    if input.trim().eq_ignore_ascii_case("y") {
+        std::fs::remove_file(&lock_path)?;
+        println!("   {} Removed stale writer lock", "✅".green());
+    }
     Ok(())
 }
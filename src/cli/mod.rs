@@ -1,9 +1,10 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use std::path::PathBuf;
 use tokio_util::sync::CancellationToken;
 
 use crate::embed::ModelType;
+use crate::rerank::RerankerType;
 use crate::search::SearchOptions;
 
 /// Index subcommands
@@ -56,6 +57,16 @@ pub struct Cli {
     ///            jina-code, e5-multilingual, mxbai-large, modernbert-large
     #[arg(long, global = true)]
     pub model: Option<String>,
+
+    /// Model for new indexes, from the user config's `model`
+    ///
+    /// Unlike `--model` it never overrides the model an index was built with.
+    #[arg(skip)]
+    pub default_model: Option<String>,
+
+    /// Worker threads for parallel chunking and search (default: one per core)
+    #[arg(long, global = true, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub threads: Option<u16>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -63,7 +74,17 @@ pub enum Commands {
     /// Search the codebase using natural language
     Search {
         /// Search query (e.g., "where do we handle authentication?")
-        query: String,
+        #[arg(required_unless_present = "interactive")]
+        query: Option<String>,
+
+        /// Read queries from stdin in a loop, loading the index and model once
+        /// (`:limit N` and `:filter PATH` change options between queries)
+        #[arg(short, long, conflicts_with_all = ["json", "jsonl", "count", "sync_dry_run"])]
+        interactive: bool,
+
+        /// Re-run the query whenever indexed files change, syncing them into the index first
+        #[arg(short = 'w', long, conflicts_with_all = ["interactive", "count", "open", "sync_dry_run"])]
+        watch: bool,
 
         /// Maximum total results to return
         #[arg(short = 'm', long, default_value = "25")]
@@ -73,49 +94,188 @@ pub enum Commands {
         #[arg(long, default_value = "1")]
         per_file: usize,
 
+        /// Largest share (0-1] of --max-results one file may fill; other files backfill the rest
+        #[arg(long, value_name = "RATIO")]
+        max_file_results_ratio: Option<f32>,
+
+        /// Merge matches from the same function or class (parts of a long definition) into one result
+        #[arg(long)]
+        group_by_symbol: bool,
+
         /// Show full chunk content instead of snippets
         #[arg(short, long)]
         content: bool,
 
+        /// Show N lines of context around each match, re-read from the file on disk (like grep -C)
+        #[arg(short = 'C', long, value_name = "N")]
+        context: Option<usize>,
+
         /// Show relevance scores
         #[arg(long)]
         scores: bool,
 
+        /// Show why each result ranked where it did (vector/FTS ranks and scores, boosts)
+        #[arg(long)]
+        explain: bool,
+
+        /// Print a per-stage timing breakdown to stderr (JSON with --json/--jsonl)
+        #[arg(long)]
+        profile: bool,
+
+        /// Don't highlight matched query terms (clean copy-paste, no JSON highlights)
+        #[arg(long)]
+        no_highlight: bool,
+
+        /// Add the signature's line and column (UTF-16 and character) to JSON results
+        #[arg(long)]
+        positions: bool,
+
+        /// After printing results, open result N (default 1, the top one) in $VISUAL/$EDITOR at its line
+        #[arg(long, value_name = "N", num_args = 0..=1, require_equals = true, default_missing_value = "1", conflicts_with_all = ["interactive", "count"])]
+        open: Option<usize>,
+
         /// Show file paths only (like grep -l)
         #[arg(long)]
         compact: bool,
 
+        /// Output format: text, or grep for `path:line:col: text` lines (vim :grep, quickfix)
+        #[arg(long, value_name = "FORMAT", value_parser = ["text", "grep"], default_value = "text")]
+        output_format: String,
+
         /// Force re-index changed files before searching
         #[arg(short, long)]
         sync: bool,
 
-        /// Output JSON for agents
+        /// Show which files --sync would re-index or remove, without changing the index
+        #[arg(long, conflicts_with = "sync")]
+        sync_dry_run: bool,
+
+        /// Sync automatically if a quick check finds files changed since indexing
         #[arg(long)]
+        auto_sync: bool,
+
+        /// Output JSON for agents
+        #[arg(long, conflicts_with = "jsonl")]
         json: bool,
 
+        /// Output JSON Lines: one result object per line (plus a summary line with --scores)
+        #[arg(long)]
+        jsonl: bool,
+
+        /// Print only the number of results (JSON: {"query": ..., "count": N})
+        #[arg(long, alias = "count-only")]
+        count: bool,
+
+        /// With --count, count all matches instead of capping at --max-results
+        #[arg(long, requires = "count")]
+        count_uncapped: bool,
+
         /// Path to search in (defaults to current directory)
         #[arg(long)]
         path: Option<PathBuf>,
 
+        /// Use this .codesearch.db directory directly (skips database discovery)
+        #[arg(long, value_name = "DIR", conflicts_with = "path")]
+        db: Option<PathBuf>,
+
         /// Use vector-only search (disable hybrid FTS)
         #[arg(long)]
         vector_only: bool,
 
+        /// Use full-text (BM25) search only, without loading the embedding model (offline-safe)
+        #[arg(long, conflicts_with = "vector_only")]
+        fts_only: bool,
+
         /// RRF k parameter for score fusion (default 20)
         #[arg(long, default_value = "20")]
         rrf_k: f32,
 
+        /// Scale scores to 0-1 within this query's results (best = 1.0), for thresholding
+        #[arg(long)]
+        normalize_scores: bool,
+
+        /// Hide results scoring below this threshold (pairs well with --normalize-scores)
+        #[arg(long, value_name = "SCORE")]
+        min_score: Option<f32>,
+
+        /// Boost for results in the project's primary language, multiplying the fused score by 1 + F (0 disables)
+        #[arg(long, value_name = "F", default_value_t = crate::search::DEFAULT_LANGUAGE_BOOST)]
+        lang_boost: f32,
+
+        /// Boost for results whose kind matches the query (e.g. "struct Foo"), multiplying the fused score by 1 + F (0 disables)
+        #[arg(long, value_name = "F", default_value_t = crate::search::DEFAULT_KIND_BOOST)]
+        kind_boost: f32,
+
+        /// Cap candidates retrieved per query variant (bounds latency on very large indexes)
+        #[arg(long, value_name = "N")]
+        retrieval_limit: Option<usize>,
+
+        /// Retry identifiers with fuzzy matching (typos, partial names) when exact matching finds nothing
+        #[arg(long)]
+        fuzzy: bool,
+
+        /// Match identifiers like `getUser` case-sensitively when boosting exact matches
+        #[arg(long)]
+        case_sensitive: bool,
+
+        /// Embed only the query itself, skipping query variants (fastest for exact identifiers)
+        #[arg(long)]
+        no_expand: bool,
+
         /// Enable neural reranking for better accuracy (uses Jina Reranker)
         #[arg(long)]
         rerank: bool,
 
+        /// Rerank only when the top results are ambiguous (close scores, no high-confidence match)
+        #[arg(long, conflicts_with = "rerank")]
+        auto_rerank: bool,
+
         /// Number of top results to rerank (default 50)
         #[arg(long, default_value = "50")]
         rerank_top: usize,
 
-        /// Filter results to files under this path (e.g., "src/")
+        /// Reranker model: jina-v1-turbo (default), jina-v2-multilingual, bge-base, bge-v2-m3,
+        /// or a directory with a local ONNX cross-encoder (implies --rerank)
+        #[arg(long, value_name = "NAME|DIR")]
+        rerank_model: Option<String>,
+
+        /// Filter results to files under this path (repeatable; a result under any of them is kept, e.g., "src/")
+        #[arg(long, value_name = "PATH")]
+        filter_path: Vec<String>,
+
+        /// Exclude results from files under this path (repeatable, e.g., --exclude target/)
+        #[arg(long = "exclude", value_name = "PATH")]
+        exclude_paths: Vec<String>,
+
+        /// Only return test code (#[test], tests/, describe/it blocks, ...)
+        #[arg(long, conflicts_with = "no_tests")]
+        tests_only: bool,
+
+        /// Exclude test code from results
+        #[arg(long)]
+        no_tests: bool,
+    },
+
+    /// Find code similar to the chunk at a location (e.g., src/auth.rs:42)
+    Similar {
+        /// Location as <file>:<line> (line is 1-based)
+        location: String,
+
+        /// Maximum results to return
+        #[arg(short = 'm', long, default_value = "10")]
+        max_results: usize,
+
+        /// Show full chunk content instead of snippets
+        #[arg(short, long)]
+        content: bool,
+
+        /// Output JSON for agents
         #[arg(long)]
-        filter_path: Option<String>,
+        json: bool,
+
+        /// Project path whose index to use (defaults to current directory)
+        #[arg(long)]
+        path: Option<PathBuf>,
     },
 
     /// Index the repository or manage global index registry
@@ -131,6 +291,34 @@ pub enum Commands {
         #[arg(short = 'f', long, alias = "full")]
         force: bool,
 
+        /// Only re-index files changed since this git ref (e.g., HEAD~1, origin/main)
+        #[arg(long, value_name = "REF", conflicts_with = "force")]
+        since: Option<String>,
+
+        /// Lines of surrounding code stored before/after each chunk (default: the index's current value, or 3)
+        #[arg(long, value_name = "N")]
+        context_lines: Option<usize>,
+
+        /// Embed code with comments and string literals stripped; displayed results keep them
+        /// (default: the index's current setting, or false; use --strip-comments=false to turn off)
+        #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+        strip_comments: Option<bool>,
+
+        /// Vector similarity of a new index: cosine, dot (dot product) or euclidean
+        /// (default: the index's current metric, or cosine; changing it needs --force)
+        #[arg(long, value_name = "METRIC", value_parser = ["cosine", "dot", "euclidean"])]
+        metric: Option<String>,
+
+        /// Only index files matching this glob (repeatable, e.g. --include '**/*.rs');
+        /// .gitignore and other excludes still apply. Kept for later updates; --force
+        /// without it indexes everything again
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+
+        /// Only rewrite metadata.json (model, dimensions, primary language) from the existing index; no re-embedding
+        #[arg(long, conflicts_with_all = ["dry_run", "force", "since", "context_lines", "strip_comments", "metric", "include", "add", "remove", "list"])]
+        stats_only: bool,
+
         /// Add a repository to the index (creates local or global index)
         #[arg(long)]
         add: bool,
@@ -156,6 +344,16 @@ pub enum Commands {
 
         /// Path to serve (defaults to current directory)
         path: Option<PathBuf>,
+
+        /// Serve the index as-is: open it read-only, skip the refresh and file watching
+        #[arg(long)]
+        read_only: bool,
+    },
+
+    /// Watch for file changes and keep the index fresh (no HTTP server)
+    Watch {
+        /// Path to watch (defaults to current directory)
+        path: Option<PathBuf>,
     },
 
     /// Show statistics about the vector database
@@ -164,6 +362,55 @@ pub enum Commands {
         path: Option<PathBuf>,
     },
 
+    /// Export all indexed chunks as JSON Lines
+    Export {
+        /// Path to export (defaults to current directory)
+        path: Option<PathBuf>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Include each chunk's embedding vector
+        #[arg(long)]
+        with_vectors: bool,
+    },
+
+    /// Compare the chunks of two indexes (e.g. before and after a chunking or model change)
+    DiffIndex {
+        /// First database directory, or a project containing one
+        a: PathBuf,
+
+        /// Second database directory, or a project containing one
+        b: PathBuf,
+
+        /// Match chunks by content hash instead of path and line range
+        #[arg(long)]
+        by_hash: bool,
+
+        /// Output the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Re-embed the existing index with a different model (requires --model)
+    Reembed {
+        /// Path of the indexed project (defaults to current directory)
+        path: Option<PathBuf>,
+    },
+
+    /// Rebuild the vector store from live chunks to reclaim space left by deletions
+    Compact {
+        /// Path of the indexed project (defaults to current directory)
+        path: Option<PathBuf>,
+    },
+
+    /// Rebuild the full-text (BM25) index from the stored chunks, without re-embedding
+    RebuildFts {
+        /// Path of the indexed project (defaults to current directory)
+        path: Option<PathBuf>,
+    },
+
     /// Clear the vector database
     Clear {
         /// Path to clear (defaults to current directory)
@@ -177,9 +424,51 @@ pub enum Commands {
     /// Check installation health
     Doctor,
 
+    /// Benchmark indexing, query latency and recall on a scratch index (use --model to compare models)
+    Bench {
+        /// Project to benchmark (defaults to current directory)
+        path: Option<PathBuf>,
+
+        /// JSON file of queries: [{"query": "...", "relevant": ["path/fragment.rs"]}]
+        /// (benchmarks/queries/codesearch.json covers codesearch's own sources)
+        #[arg(long, value_name = "FILE")]
+        queries: Option<PathBuf>,
+
+        /// Extra query to time, without relevance judgements (repeatable)
+        #[arg(long = "query", value_name = "TEXT")]
+        query: Vec<String>,
+
+        /// Number of results scored per query
+        #[arg(short = 'k', long, default_value = "10")]
+        top_k: usize,
+
+        /// Include neural reranking in the measured pipeline
+        #[arg(long)]
+        rerank: bool,
+
+        /// Embed code with comments and string literals stripped (compare against a run without)
+        #[arg(long)]
+        strip_comments: bool,
+
+        /// Run once raw and once with --strip-comments and print the before/after difference
+        #[arg(long, conflicts_with = "strip_comments")]
+        compare_strip_comments: bool,
+
+        /// Print the report as JSON (for CI)
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List available embedding models and which are downloaded
+    Models {
+        /// Output JSON for tooling
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Download embedding models
     Setup {
-        /// Model to download (defaults to mxbai-embed-xsmall-v1)
+        /// Model to download (defaults to minilm-l6-q)
         #[arg(long)]
         model: Option<String>,
     },
@@ -199,7 +488,15 @@ pub enum Commands {
 }
 
 pub async fn run(cancel_token: CancellationToken) -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // Fill in flags that weren't given from ~/.codesearch/config.toml
+    match config::UserConfig::load() {
+        Ok(Some(user_config)) => user_config.apply(&mut cli, &matches),
+        Ok(None) => {}
+        Err(e) => eprintln!("⚠️  Ignoring user config, using defaults: {:#}", e),
+    }
 
     // Parse model from CLI flag
     let model_type = cli.model.as_ref().and_then(|m| ModelType::parse(m));
@@ -213,12 +510,24 @@ pub async fn run(cancel_token: CancellationToken) -> Result<()> {
         eprintln!("  jina-code, e5-multilingual, mxbai-large, modernbert-large");
         std::process::exit(1);
     }
+    // Validated when the user config was loaded
+    let default_model = cli.default_model.as_deref().and_then(ModelType::parse);
 
     // Set quiet mode if requested
     if cli.quiet {
         crate::output::set_quiet(true);
     }
 
+    // Size the global rayon pool before anything uses it
+    if let Some(threads) = cli.threads {
+        if let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads as usize)
+            .build_global()
+        {
+            eprintln!("⚠️  Could not set --threads {}: {}", threads, e);
+        }
+    }
+
     // Parse loglevel from CLI
     let log_level =
         crate::logger::LogLevel::parse(&cli.loglevel).unwrap_or(crate::logger::LogLevel::Info);
@@ -226,54 +535,166 @@ pub async fn run(cancel_token: CancellationToken) -> Result<()> {
     match cli.command {
         Commands::Search {
             query,
+            interactive,
+            watch,
             max_results,
             per_file,
+            max_file_results_ratio,
+            group_by_symbol,
             content,
+            context,
             scores,
+            explain,
+            profile,
+            no_highlight,
+            positions,
+            open,
             compact,
+            output_format,
             sync,
+            sync_dry_run,
+            auto_sync,
             json,
+            jsonl,
+            count,
+            count_uncapped,
             path,
+            db,
             vector_only,
+            fts_only,
             rrf_k,
+            normalize_scores,
+            min_score,
+            lang_boost,
+            kind_boost,
+            retrieval_limit,
+            fuzzy,
+            case_sensitive,
+            no_expand,
             rerank,
+            auto_rerank,
             rerank_top,
+            rerank_model,
             filter_path,
+            exclude_paths,
+            tests_only,
+            no_tests,
         } => {
-            // Auto-enable quiet mode for JSON output
-            if json {
+            let grep = output_format == "grep";
+            // Auto-enable quiet mode for JSON, grep and count output
+            if json || jsonl || count || grep {
                 crate::output::set_quiet(true);
             }
+            let rerank_model = match rerank_model.as_deref().map(RerankerType::parse) {
+                Some(None) => {
+                    eprintln!(
+                        "Unknown reranker: '{}'. Available: jina-v1-turbo, jina-v2-multilingual, \
+                         bge-base, bge-v2-m3, or a directory containing a local model.onnx",
+                        rerank_model.unwrap_or_default()
+                    );
+                    std::process::exit(1);
+                }
+                parsed => parsed.flatten(),
+            };
+            if max_file_results_ratio.is_some_and(|r| !(r > 0.0 && r <= 1.0)) {
+                eprintln!("--max-file-results-ratio must be greater than 0 and at most 1");
+                std::process::exit(1);
+            }
+            if open == Some(0) {
+                eprintln!("--open takes a 1-based result number");
+                std::process::exit(1);
+            }
+            if !(lang_boost >= 0.0 && kind_boost >= 0.0) {
+                eprintln!("--lang-boost and --kind-boost must be 0 or greater");
+                std::process::exit(1);
+            }
             let options = SearchOptions {
                 max_results,
                 per_file: if per_file == 0 { None } else { Some(per_file) },
+                max_file_ratio: max_file_results_ratio,
+                group_by_symbol,
                 content_lines: if content { 3 } else { 0 },
+                context,
                 show_scores: scores,
                 compact,
+                grep,
                 sync,
+                sync_dry_run,
+                auto_sync,
+                db_path: db,
                 json,
-                filter_path,
+                jsonl,
+                count,
+                count_uncapped,
+                filter_paths: filter_path,
+                exclude_paths,
+                tests_only,
+                exclude_tests: no_tests,
+                filter_kinds: Vec::new(),
+                filter_languages: Vec::new(),
+                filter_signatures: Vec::new(),
+                filter_names: Vec::new(),
                 model_override: model_type.map(|mt| format!("{:?}", mt)),
                 vector_only,
+                fts_only,
                 rrf_k: if rrf_k == 60.0 {
                     None
                 } else {
                     Some(rrf_k as usize)
                 },
-                rerank,
+                fuzzy,
+                case_sensitive,
+                expand_query: !no_expand,
+                rerank: rerank || (rerank_model.is_some() && !auto_rerank),
+                auto_rerank,
                 rerank_top: if rerank_top == 50 {
                     None
                 } else {
                     Some(rerank_top)
                 },
+                rerank_model,
+                retrieval_limit,
+                normalize_scores,
+                min_score,
+                highlight: !no_highlight,
+                positions,
+                open,
+                explain,
+                profile,
+                language_boost: lang_boost,
+                kind_boost,
             };
 
-            crate::search::search(&query, path, options).await
+            match query {
+                Some(query) if watch => {
+                    crate::search::search_watch(&query, path, options, cancel_token.clone()).await
+                }
+                Some(query) if !interactive => crate::search::search(&query, path, options).await,
+                _ => crate::search::interactive(path, options).await,
+            }
+        }
+        Commands::Similar {
+            location,
+            max_results,
+            content,
+            json,
+            path,
+        } => {
+            if json {
+                crate::output::set_quiet(true);
+            }
+            crate::search::similar(&location, path, max_results, content, json).await
         }
         Commands::Index {
             path,
             dry_run,
             force,
+            since,
+            context_lines,
+            strip_comments,
+            metric,
+            include,
+            stats_only,
             add,
             global,
             remove,
@@ -290,29 +711,46 @@ pub async fn run(cancel_token: CancellationToken) -> Result<()> {
             if add || is_add_cmd {
                 // Clear path if it's "add" to avoid treating it as a directory
                 let effective_path = if is_add_cmd { None } else { path };
-                crate::index::add_to_index(effective_path, global, cancel_token.clone()).await
+                crate::index::add_to_index(
+                    effective_path,
+                    global,
+                    model_type.or(default_model),
+                    cancel_token.clone(),
+                )
+                .await
             } else if remove || is_rm_cmd {
                 // Clear path if it's "rm"/"remove" to avoid treating it as a directory
                 let effective_path = if is_rm_cmd { None } else { path };
                 crate::index::remove_from_index(effective_path).await
             } else if list || is_list_cmd {
                 crate::index::list_index_status().await
+            } else if stats_only {
+                crate::index::refresh_metadata(path, model_type).await
             } else {
                 // For 'codesearch index .' or 'codesearch index <path>', just run indexing
                 // The index() function will handle checking for existing indexes
-                crate::index::index(
-                    path,
+                let options = crate::index::IndexOptions {
                     dry_run,
                     force,
-                    false,
-                    model_type,
-                    cancel_token.clone(),
-                )
-                .await
+                    model: model_type,
+                    default_model,
+                    since,
+                    context_lines,
+                    strip_comments,
+                    metric: metric.as_deref().and_then(crate::vectordb::Metric::parse),
+                    include,
+                    ..Default::default()
+                };
+                crate::index::index(path, options, cancel_token.clone()).await
             }
         }
+        Commands::Watch { path } => crate::server::watch(path, cancel_token.clone()).await,
         Commands::Stats { path } => crate::index::stats(path).await,
-        Commands::Serve { port, path } => {
+        Commands::Serve {
+            port,
+            path,
+            read_only,
+        } => {
             // Discover database path and initialize logger with file output
             // NOTE: For Serve, tracing is NOT initialized in main.rs — init_logger
             // is the first and only call to set the global subscriber
@@ -332,10 +770,57 @@ pub async fn run(cancel_token: CancellationToken) -> Result<()> {
                     }
                 }
             }
-            crate::server::serve(port, path).await
+            crate::server::serve(port, path, read_only).await
+        }
+        Commands::Export {
+            path,
+            output,
+            with_vectors,
+        } => crate::index::export(path, output, with_vectors).await,
+        Commands::DiffIndex {
+            a,
+            b,
+            by_hash,
+            json,
+        } => crate::index::diff_index(a, b, by_hash, json).await,
+        Commands::Reembed { path } => {
+            let Some(model_type) = model_type else {
+                eprintln!("reembed requires --model <name> (the model to switch to)");
+                std::process::exit(1);
+            };
+            crate::index::reembed(path, model_type, cancel_token.clone()).await
         }
+        Commands::Compact { path } => crate::index::compact(path, cancel_token.clone()).await,
+        Commands::RebuildFts { path } => crate::index::rebuild_fts_command(path).await,
         Commands::Clear { path, yes } => crate::index::clear(path, yes).await,
         Commands::Doctor => crate::cli::doctor::run().await,
+        Commands::Bench {
+            path,
+            queries,
+            query,
+            top_k,
+            rerank,
+            strip_comments,
+            compare_strip_comments,
+            json,
+        } => {
+            if json {
+                crate::output::set_quiet(true);
+            }
+            crate::bench::run(
+                path,
+                model_type,
+                queries,
+                query,
+                top_k,
+                rerank,
+                strip_comments,
+                compare_strip_comments,
+                json,
+            )
+            .await
+        }
+        Commands::Models { json } => crate::cli::models::run(json).await,
         Commands::Setup { model } => crate::cli::setup::run(model).await,
         Commands::Mcp { path } => {
             // Discover database path and initialize logger with file output
@@ -373,11 +858,12 @@ pub async fn run(cancel_token: CancellationToken) -> Result<()> {
                 _ => {}
             }
 
-            let daemon_config = crate::daemon::DaemonConfig::load(&config)?;
-            crate::daemon::run_daemon(daemon_config, cancel_token).await
+            crate::daemon::run_daemon(config, cancel_token).await
         }
     }
 }
 
+mod config;
 mod doctor;
+mod models;
 mod setup;
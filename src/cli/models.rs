@@ -0,0 +1,151 @@
+//! `codesearch models`: list embedding models and which are in the local cache
+
+use anyhow::Result;
+use colored::Colorize;
+use fastembed::TextEmbedding;
+use serde::Serialize;
+use std::path::Path;
+
+use super::setup::hf_repo_dir;
+use crate::embed::ModelType;
+use crate::output::format_size;
+
+/// One row of `codesearch models --json`
+#[derive(Debug, Serialize)]
+struct ModelEntry {
+    short_name: &'static str,
+    name: &'static str,
+    dimensions: usize,
+    quantized: bool,
+    default: bool,
+    cached: bool,
+    /// Bytes used in the models cache (0 when not cached)
+    size_bytes: u64,
+}
+
+pub async fn run(json: bool) -> Result<()> {
+    let cache_dir = crate::constants::get_global_models_cache_dir()?;
+
+    let entries: Vec<ModelEntry> = ModelType::all()
+        .iter()
+        .map(|&model_type| {
+            let (cached, size_bytes) = cache_status(&cache_dir, model_type);
+            ModelEntry {
+                short_name: model_type.short_name(),
+                name: model_type.name(),
+                dimensions: model_type.dimensions(),
+                quantized: model_type.is_quantized(),
+                default: model_type == ModelType::default(),
+                cached,
+                size_bytes,
+            }
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string(&entries)?);
+        return Ok(());
+    }
+
+    println!("{}", "🧠 Embedding Models".bright_cyan().bold());
+    println!("{}", "=".repeat(60));
+    println!("💾 Cache: {}\n", cache_dir.display());
+
+    for entry in &entries {
+        let marker = if entry.default { "*" } else { " " };
+        let cached = if entry.cached {
+            format!("✓ {:>7}", format_size(entry.size_bytes))
+                .green()
+                .to_string()
+        } else {
+            format!("{:<9}", "-").dimmed().to_string()
+        };
+        println!(
+            "{} {:<18} {:>5}  {}  {}",
+            marker,
+            entry.short_name.bright_cyan(),
+            entry.dimensions,
+            cached,
+            entry.name.dimmed()
+        );
+    }
+
+    let cached_bytes: u64 = entries.iter().map(|e| e.size_bytes).sum();
+    println!(
+        "\n* default   •   {} cached ({})",
+        entries.iter().filter(|e| e.cached).count(),
+        format_size(cached_bytes)
+    );
+    println!(
+        "{}",
+        "Use --model <short name> to pick one, `codesearch setup --model <name>` to download it."
+            .dimmed()
+    );
+
+    Ok(())
+}
+
+/// Whether the model's ONNX file is in the cache, and the bytes its repo uses
+fn cache_status(cache_dir: &Path, model_type: ModelType) -> (bool, u64) {
+    let Ok(info) = TextEmbedding::get_model_info(&model_type.to_fastembed_model()) else {
+        return (false, 0);
+    };
+    let repo_dir = hf_repo_dir(cache_dir, &info.model_code);
+    (
+        is_cached(&repo_dir, &info.model_file),
+        blobs_size(&repo_dir),
+    )
+}
+
+/// A model is cached when `refs/main` points at a snapshot containing its model file
+fn is_cached(repo_dir: &Path, model_file: &str) -> bool {
+    std::fs::read_to_string(repo_dir.join("refs").join("main"))
+        .map(|commit| {
+            repo_dir
+                .join("snapshots")
+                .join(commit.trim())
+                .join(model_file)
+                .exists()
+        })
+        .unwrap_or(false)
+}
+
+/// Total size of downloaded blobs (symlinked snapshots aren't counted twice)
+fn blobs_size(repo_dir: &Path) -> u64 {
+    std::fs::read_dir(repo_dir.join("blobs"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_is_cached_follows_refs_main() {
+        let dir = tempdir().unwrap();
+        let repo_dir = hf_repo_dir(dir.path(), "Qdrant/all-MiniLM-L6-v2-onnx");
+        assert!(!is_cached(&repo_dir, "model.onnx"));
+
+        std::fs::create_dir_all(repo_dir.join("refs")).unwrap();
+        std::fs::write(repo_dir.join("refs").join("main"), "abc123\n").unwrap();
+        assert!(!is_cached(&repo_dir, "model.onnx"));
+
+        let snapshot = repo_dir.join("snapshots").join("abc123");
+        std::fs::create_dir_all(&snapshot).unwrap();
+        std::fs::write(snapshot.join("model.onnx"), b"onnx").unwrap();
+        assert!(is_cached(&repo_dir, "model.onnx"));
+
+        std::fs::create_dir_all(repo_dir.join("blobs")).unwrap();
+        std::fs::write(repo_dir.join("blobs").join("deadbeef"), vec![0u8; 10]).unwrap();
+        assert_eq!(blobs_size(&repo_dir), 10);
+    }
+}
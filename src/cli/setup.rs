@@ -1,12 +1,338 @@
-use anyhow::Result;
+//! `codesearch setup`: download an embedding model into the global models cache
+//!
+//! Files are written in the Hugging Face hub cache layout that fastembed reads
+//! (`models--<org>--<name>/{blobs,snapshots,refs}`), so loading the model later
+//! never touches the network. Downloads resume from a `.part` file via HTTP
+//! range requests and are verified against the size (and, for LFS files, the
+//! SHA-256) reported by the hub before they are moved into place.
+
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use fastembed::TextEmbedding;
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::header::{CONTENT_LENGTH, ETAG, RANGE};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::embed::{EmbeddingService, ModelType};
+
+/// Tokenizer/config files fastembed loads next to the ONNX model
+const TOKENIZER_FILES: [&str; 4] = [
+    "tokenizer.json",
+    "config.json",
+    "special_tokens_map.json",
+    "tokenizer_config.json",
+];
+
+/// Download attempts per file before giving up (a checksum mismatch restarts from scratch)
+const MAX_ATTEMPTS: usize = 3;
+
+/// What the hub reports about a file before downloading it
+#[derive(Debug)]
+struct RemoteFile {
+    commit: String,
+    etag: String,
+    size: u64,
+}
 
 pub async fn run(model: Option<String>) -> Result<()> {
-    let model_name = model.unwrap_or_else(|| "mxbai-embed-xsmall-v1".to_string());
+    let model_type = match model {
+        Some(name) => ModelType::parse(&name).ok_or_else(|| {
+            anyhow!(
+                "Unknown model: '{}'. Run `codesearch setup --model <name>` with one of: \
+                 minilm-l6, minilm-l6-q, minilm-l12, minilm-l12-q, paraphrase-minilm, \
+                 bge-small, bge-small-q, bge-base, nomic-v1, nomic-v1.5, nomic-v1.5-q, \
+                 jina-code, e5-multilingual, mxbai-large, modernbert-large",
+                name
+            )
+        })?,
+        None => ModelType::default(),
+    };
+
+    let info = TextEmbedding::get_model_info(&model_type.to_fastembed_model())
+        .map_err(|e| anyhow!("No download info for {}: {}", model_type.name(), e))?;
+    let repo_id = info.model_code.clone();
+    let mut files = vec![info.model_file.clone()];
+    files.extend(info.additional_files.iter().cloned());
+    files.extend(TOKENIZER_FILES.iter().map(|f| f.to_string()));
 
-    println!("📦 Downloading embedding model: {}", model_name);
+    let cache_dir = crate::constants::get_global_models_cache_dir()?;
+    let repo_dir = hf_repo_dir(&cache_dir, &repo_id);
 
-    // TODO: Download model from HuggingFace Hub
+    println!(
+        "📦 Downloading embedding model: {} ({})",
+        model_type.short_name(),
+        repo_id
+    );
+    println!("💾 Cache: {}", cache_dir.display());
+
+    let endpoint =
+        std::env::var("HF_ENDPOINT").unwrap_or_else(|_| "https://huggingface.co".to_string());
+    let client = reqwest::Client::new();
+    let metadata_client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let mut commit = None;
+    for file in &files {
+        let url = format!("{}/{}/resolve/main/{}", endpoint, repo_id, file);
+        let remote = match fetch_metadata(&metadata_client, &url).await {
+            Ok(remote) => remote,
+            // Tokenizer extras are optional for some models
+            Err(e) if TOKENIZER_FILES.contains(&file.as_str()) && file != "tokenizer.json" => {
+                println!("   {} {} ({})", "–".dimmed(), file, e.to_string().dimmed());
+                continue;
+            }
+            Err(e) => return Err(e.context(format!("Failed to look up {}", file))),
+        };
+
+        let blob = repo_dir.join("blobs").join(&remote.etag);
+        ensure_blob(&client, &url, file, &blob, &remote).await?;
+        link_snapshot(
+            &blob,
+            &repo_dir.join("snapshots").join(&remote.commit).join(file),
+        )?;
+        commit.get_or_insert(remote.commit);
+    }
+
+    if let Some(commit) = commit {
+        let refs_dir = repo_dir.join("refs");
+        fs::create_dir_all(&refs_dir)?;
+        fs::write(refs_dir.join("main"), commit)?;
+    }
+
+    // Loading the model confirms the cache is complete and usable
+    print!("🧠 Loading model to verify... ");
+    std::io::stdout().flush()?;
+    EmbeddingService::with_cache_dir(model_type, Some(cache_dir.as_path()))
+        .context("Model files downloaded but failed to load")?;
+    println!("{}", "ok".green());
 
     println!("✅ Setup complete!");
     Ok(())
 }
+
+/// Directory of a model repo inside the hub cache layout (`models--<org>--<name>`)
+pub(super) fn hf_repo_dir(cache_dir: &Path, repo_id: &str) -> PathBuf {
+    cache_dir.join(format!("models--{}", repo_id.replace('/', "--")))
+}
+
+/// Resolve the commit, etag and size of `url` without downloading it
+async fn fetch_metadata(client: &reqwest::Client, url: &str) -> Result<RemoteFile> {
+    let response = client.head(url).send().await?;
+    let status = response.status();
+    if !(status.is_success() || status.is_redirection()) {
+        return Err(anyhow!("HTTP {}", status));
+    }
+
+    let headers = response.headers();
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    // LFS files redirect to a CDN and report their SHA-256 as the linked etag
+    let etag = header("x-linked-etag")
+        .or_else(|| header(ETAG.as_str()))
+        .ok_or_else(|| anyhow!("missing etag header"))?
+        .trim_start_matches("W/")
+        .replace('"', "");
+    let commit = header("x-repo-commit")
+        .ok_or_else(|| anyhow!("missing x-repo-commit header"))?
+        .to_string();
+    let size = header("x-linked-size")
+        .or_else(|| header(CONTENT_LENGTH.as_str()))
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| anyhow!("missing file size"))?;
+
+    Ok(RemoteFile { commit, etag, size })
+}
+
+/// Make sure `blob` holds a verified copy of `url`, resuming or re-downloading as needed
+async fn ensure_blob(
+    client: &reqwest::Client,
+    url: &str,
+    file: &str,
+    blob: &Path,
+    remote: &RemoteFile,
+) -> Result<()> {
+    let expected_sha = is_sha256(&remote.etag).then_some(remote.etag.as_str());
+
+    if blob.exists() {
+        if verify_file(blob, remote.size, expected_sha)? {
+            println!("   {} {} (cached)", "✓".green(), file);
+            return Ok(());
+        }
+        println!(
+            "   {} {} is corrupt in the cache, re-downloading",
+            "⚠️".yellow(),
+            file
+        );
+        fs::remove_file(blob)?;
+    }
+
+    fs::create_dir_all(blob.parent().unwrap_or(Path::new(".")))?;
+    let part = blob.with_file_name(format!("{}.part", remote.etag));
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        if let Err(e) = download_resumable(client, url, file, &part, remote.size).await {
+            // Keep the partial file: the next attempt resumes from it
+            eprintln!(
+                "   {} {} (attempt {}/{}): {:#}",
+                "⚠️".yellow(),
+                file,
+                attempt,
+                MAX_ATTEMPTS,
+                e
+            );
+            continue;
+        }
+
+        if verify_file(&part, remote.size, expected_sha)? {
+            fs::rename(&part, blob)?;
+            println!("   {} {}", "✓".green(), file);
+            return Ok(());
+        }
+
+        eprintln!(
+            "   {} {} failed verification (attempt {}/{}), restarting download",
+            "⚠️".yellow(),
+            file,
+            attempt,
+            MAX_ATTEMPTS
+        );
+        fs::remove_file(&part)?;
+    }
+
+    Err(anyhow!(
+        "Failed to download {} after {} attempts. Run `codesearch setup` again to resume.",
+        file,
+        MAX_ATTEMPTS
+    ))
+}
+
+/// Download `url` into `part`, continuing from its current length with a range request
+async fn download_resumable(
+    client: &reqwest::Client,
+    url: &str,
+    file: &str,
+    part: &Path,
+    size: u64,
+) -> Result<()> {
+    let mut start = fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+    if start > size {
+        // Longer than the remote file, can't be a prefix of it
+        fs::remove_file(part)?;
+        start = 0;
+    }
+    if start == size {
+        return Ok(());
+    }
+
+    let mut response = client
+        .get(url)
+        .header(RANGE, format!("bytes={}-", start))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    // A server that ignores the range sends the whole file again
+    let mut out = match response.status() {
+        StatusCode::PARTIAL_CONTENT => OpenOptions::new().create(true).append(true).open(part)?,
+        _ => {
+            start = 0;
+            File::create(part)?
+        }
+    };
+
+    let pb = ProgressBar::new(size);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("   {msg} [{elapsed_precise}] {bar:30.cyan/blue} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})")
+            .unwrap()
+            .progress_chars("█▓▒░ "),
+    );
+    pb.set_message(file.to_string());
+    pb.set_position(start);
+
+    while let Some(chunk) = response.chunk().await? {
+        out.write_all(&chunk)?;
+        pb.inc(chunk.len() as u64);
+    }
+    out.flush()?;
+    pb.finish_and_clear();
+
+    Ok(())
+}
+
+/// Check a downloaded file against the expected size and optional SHA-256
+fn verify_file(path: &Path, size: u64, sha256: Option<&str>) -> Result<bool> {
+    if fs::metadata(path)?.len() != size {
+        return Ok(false);
+    }
+    let Some(expected) = sha256 else {
+        return Ok(true);
+    };
+
+    let mut hasher = Sha256::new();
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; 1 << 16];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()).eq_ignore_ascii_case(expected))
+}
+
+/// LFS etags are the hex SHA-256 of the content; plain git etags are not
+fn is_sha256(etag: &str) -> bool {
+    etag.len() == 64 && etag.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Point the snapshot entry for a file at its blob (copy where symlinks aren't available)
+fn link_snapshot(blob: &Path, pointer: &Path) -> Result<()> {
+    if let Some(parent) = pointer.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if pointer.symlink_metadata().is_ok() {
+        fs::remove_file(pointer)?;
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(blob, pointer)?;
+    #[cfg(not(unix))]
+    fs::copy(blob, pointer).map(|_| ())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_verify_file_checks_size_and_sha256() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("blob");
+        fs::write(&path, b"hello").unwrap();
+        let sha = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+        assert!(verify_file(&path, 5, None).unwrap());
+        assert!(verify_file(&path, 5, Some(sha)).unwrap());
+        assert!(!verify_file(&path, 4, Some(sha)).unwrap());
+        assert!(!verify_file(&path, 5, Some(&"0".repeat(64))).unwrap());
+    }
+
+    #[test]
+    fn test_is_sha256() {
+        assert!(is_sha256(
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        ));
+        // Git blob ids (non-LFS files) are SHA-1
+        assert!(!is_sha256("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"));
+    }
+}
@@ -38,6 +38,12 @@ pub const DB_DIR_NAME: &str = ".codesearch.db";
 /// Name of the global config directory in user home
 pub const CONFIG_DIR_NAME: &str = ".codesearch";
 
+/// Name of the optional project-level config file (in the project root)
+pub const PROJECT_CONFIG_FILE_NAME: &str = ".codesearch.toml";
+
+/// Name of the optional user config file (in the global config dir)
+pub const USER_CONFIG_FILE_NAME: &str = "config.toml";
+
 /// Name of the file metadata database
 pub const FILE_META_DB_NAME: &str = "file_meta.json";
 
@@ -50,12 +56,20 @@ pub const LOG_DIR_NAME: &str = "logs";
 /// Default log file name
 pub const LOG_FILE_NAME: &str = "codesearch.log";
 
+/// Query log file name prefix within .codesearch.db (rotated daily like the main log)
+pub const QUERY_LOG_FILE_NAME: &str = "queries.log";
+
 /// Default number of log files to retain
 pub const DEFAULT_LOG_MAX_FILES: usize = 5;
 
 /// Default log retention period in days
 pub const DEFAULT_LOG_RETENTION_DAYS: u64 = 5;
 
+/// Path of the user config file (~/.codesearch/config.toml), if the home directory is known
+pub fn get_user_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(CONFIG_DIR_NAME).join(USER_CONFIG_FILE_NAME))
+}
+
 /// Get the global models cache directory (~/.codesearch/models/).
 ///
 /// This centralizes embedding model downloads so they are shared across all
@@ -102,9 +116,113 @@ pub const DEFAULT_LMDB_MAP_SIZE_MB: usize = 512;
 /// Override with `CODESEARCH_CACHE_MAX_MEMORY` environment variable.
 pub const DEFAULT_CACHE_MAX_MEMORY_MB: usize = 100;
 
+/// Name of the persisted query embedding cache file within a database directory
+pub const QUERY_CACHE_FILE_NAME: &str = "query_cache.bin";
+
+/// Maximum number of query embeddings kept in the persisted query cache.
+///
+/// Oldest entries are evicted first when the cache is written to disk.
+/// Override with `CODESEARCH_QUERY_CACHE_MAX_ENTRIES` environment variable.
+pub const DEFAULT_QUERY_CACHE_MAX_ENTRIES: usize = 1000;
+
+/// Number of chunk metadata entries kept in memory by each open vector store.
+///
+/// Speeds up repeated point lookups (search result hydration, `get_chunk`) in
+/// long-running servers. Set to 0 to disable.
+/// Override with `CODESEARCH_CHUNK_CACHE_SIZE` environment variable.
+pub const DEFAULT_CHUNK_CACHE_SIZE: u64 = 4096;
+
+/// Number of (query, document) pairs scored per reranker inference run.
+///
+/// Covers the default `--rerank-top 50` in a single run; lower it to reduce
+/// peak memory with large cross-encoders.
+/// Override with `CODESEARCH_RERANK_BATCH` environment variable.
+pub const DEFAULT_RERANK_BATCH_SIZE: usize = 64;
+
+/// Default maximum file size (in bytes) considered for indexing (2MB).
+///
+/// Larger files (usually generated code, lockfiles, minified bundles) are skipped
+/// by `FileWalker` before being read.
+/// Override with `CODESEARCH_MAX_FILE_SIZE` environment variable (bytes, 0 = no limit).
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Default number of files embedded between ONNX session resets during indexing.
+///
+/// The ONNX arena allocator never returns memory to the OS, so the session is
+/// recreated periodically to bound RSS.
+/// Override with `CODESEARCH_ARENA_RESET_INTERVAL` environment variable (0 = disabled).
+pub const DEFAULT_ARENA_RESET_INTERVAL: usize = 500;
+
+/// Default number of source bytes embedded between ONNX session resets (64MB).
+///
+/// Complements the file-count interval: a run of large files triggers a reset
+/// long before the file count is reached.
+/// Override with `CODESEARCH_ARENA_RESET_BYTES` environment variable (0 = disabled).
+pub const DEFAULT_ARENA_RESET_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Default BM25 boost for the `signature` field in full-text queries.
+///
+/// A term matching a function/type signature counts this many times more than
+/// the same term in the chunk body.
+/// Override with `CODESEARCH_FTS_SIGNATURE_BOOST` environment variable.
+pub const DEFAULT_FTS_SIGNATURE_BOOST: f32 = 2.0;
+
+/// Default BM25 boost for the `kind` field when the query has a structural intent
+/// (e.g. "struct", "enum").
+/// Override with `CODESEARCH_FTS_KIND_BOOST` environment variable.
+pub const DEFAULT_FTS_KIND_BOOST: f32 = 3.0;
+
 /// File watcher debounce time in milliseconds
 pub const DEFAULT_FSW_DEBOUNCE_MS: u64 = 2000;
 
+/// Deadline for a single HTTP search request in milliseconds
+/// Override with `CODESEARCH_SEARCH_TIMEOUT_MS` environment variable.
+pub const DEFAULT_SEARCH_TIMEOUT_MS: u64 = 30_000;
+
+/// How many times the requested result count to fetch when filters drop
+/// candidates after retrieval, so the survivors can still fill the limit.
+pub const FILTERED_OVERFETCH_FACTOR: usize = 5;
+
+/// Furthest a filtered search widens its candidates (when no retrieval cap is
+/// set), as a multiple of its limit, so a filter that matches next to nothing
+/// can't walk the whole index.
+pub const MAX_FILTER_WIDENING_FACTOR: usize = 64;
+
+/// Largest result `limit` accepted by the MCP tools and HTTP search endpoints.
+///
+/// Larger requests are clamped (with a note in the response) so a client can't
+/// make the server allocate and rank an unbounded number of results.
+/// Override with `CODESEARCH_MAX_LIMIT` environment variable.
+pub const DEFAULT_MAX_REQUEST_LIMIT: usize = 200;
+
+/// Validate a client-requested result limit and clamp it to `CODESEARCH_MAX_LIMIT`
+///
+/// Returns the effective limit plus a note when it was clamped, or an error for a zero limit.
+pub fn clamp_request_limit(limit: usize) -> Result<(usize, Option<String>), String> {
+    let max = std::env::var("CODESEARCH_MAX_LIMIT")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|&max| max > 0)
+        .unwrap_or(DEFAULT_MAX_REQUEST_LIMIT);
+    clamp_limit(limit, max)
+}
+
+fn clamp_limit(limit: usize, max: usize) -> Result<(usize, Option<String>), String> {
+    if limit == 0 {
+        return Err("limit must be at least 1".to_string());
+    }
+    if limit > max {
+        return Ok((
+            max,
+            Some(format!(
+                "limit {} exceeds the maximum of {}; returning at most {} results",
+                limit, max, max
+            )),
+        ));
+    }
+    Ok((limit, None))
+}
+
 /// Lock file name to indicate an active writer instance
 /// This prevents multiple processes from writing to the same database
 pub const WRITER_LOCK_FILE: &str = ".writer.lock";
@@ -217,6 +335,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_clamp_limit() {
+        assert_eq!(clamp_limit(10, 200), Ok((10, None)));
+        assert_eq!(clamp_limit(200, 200), Ok((200, None)));
+
+        let (limit, note) = clamp_limit(1_000_000, 200).unwrap();
+        assert_eq!(limit, 200);
+        assert!(note.unwrap().contains("1000000"));
+
+        assert!(clamp_limit(0, 200).is_err());
+    }
+
     #[test]
     fn test_get_global_models_cache_dir() {
         let result = get_global_models_cache_dir();
@@ -230,6 +360,8 @@ mod tests {
     fn test_lmdb_defaults() {
         assert!(DEFAULT_LMDB_MAP_SIZE_MB > 0);
         assert!(DEFAULT_CACHE_MAX_MEMORY_MB > 0);
+        assert!(DEFAULT_QUERY_CACHE_MAX_ENTRIES > 0);
+        assert!(DEFAULT_MAX_FILE_SIZE > 0);
     }
 
     #[test]
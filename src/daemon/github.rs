@@ -10,7 +10,7 @@ use anyhow::{Context, Result};
 use serde::Deserialize;
 use tracing::{error, info, warn};
 
-use super::{GitHubConfig, GitHubSource, OwnerKind};
+use super::{GitHubConfig, GitHubSource, OwnerKind, RepoConfig};
 
 /// Minimal GitHub repo response (only fields we need).
 #[derive(Debug, Deserialize)]
@@ -228,12 +228,13 @@ async fn clone_repo(clone_url: &str, dest: &Path, token: &str) -> Result<()> {
 
 /// Resolve all repos from GitHub sources + explicit list.
 ///
-/// Returns a deduplicated list of repo paths. All GitHub errors are non-fatal.
+/// Returns a list of repos deduplicated by path; discovered repos carry their
+/// source's `model`. All GitHub errors are non-fatal.
 pub async fn resolve_all_repos(
-    explicit: Vec<PathBuf>,
+    explicit: Vec<RepoConfig>,
     github_config: Option<&GitHubConfig>,
-) -> Vec<PathBuf> {
-    let mut all_paths: Vec<PathBuf> = explicit;
+) -> Vec<RepoConfig> {
+    let mut all_paths: Vec<RepoConfig> = explicit;
     let mut seen = HashSet::new();
 
     let config = match github_config {
@@ -295,16 +296,20 @@ pub async fn resolve_all_repos(
 
         for repo in &filtered {
             let local_path = clone_base.join(&repo.name);
+            let entry = RepoConfig::Detailed {
+                path: local_path.clone(),
+                model: source.model.clone(),
+            };
 
             if local_path.exists() {
                 info!("Found local clone: {}", local_path.display());
-                all_paths.push(local_path);
+                all_paths.push(entry);
             } else if source.auto_clone {
                 info!("Cloning {} → {}", repo.name, local_path.display());
                 match clone_repo(&repo.clone_url, &local_path, &token).await {
                     Ok(()) => {
                         info!("Cloned {}", repo.name);
-                        all_paths.push(local_path);
+                        all_paths.push(entry);
                     }
                     Err(e) => {
                         error!("Failed to clone {}: {}", repo.name, e);
@@ -316,9 +321,10 @@ pub async fn resolve_all_repos(
         }
     }
 
-    // Deduplicate by canonical path
-    all_paths.retain(|p| {
-        let key = p.canonicalize().unwrap_or_else(|_| p.clone());
+    // Deduplicate by canonical path; explicit entries come first and win
+    all_paths.retain(|repo| {
+        let path = repo.path();
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
         seen.insert(key)
     });
 
@@ -11,19 +11,49 @@
 //!
 //! ```yaml
 //! port: 4444
+//! bind: 127.0.0.1   # listen address; 0.0.0.0 to serve other machines
 //! index_interval: 300
 //! lmdb_map_size_mb: 2048
+//! api_token: s3cret     # require `Authorization: Bearer s3cret` (optional)
+//! max_concurrent_searches: 4
+//! max_queued_searches: 32
+//! rate_limit_per_minute: 120   # per client IP (optional)
+//! model: minilm-l6-q
 //! repos:
 //!   - /path/to/repo1
-//!   - /path/to/repo2
+//!   - path: /path/to/docs
+//!     model: bge-base   # per-repo override
 //! ```
 //!
+//! Each repo is searched with the model its index was built with, so repos
+//! on different models (and dimensions) can be served side by side; one
+//! embedding service is loaded per distinct model. `model` only picks the
+//! model for repos that have no index yet. A per-repo `model` (or one set on
+//! a GitHub source) must match the repo's existing index.
+//!
+//! `rate_limit_per_minute` counts requests per connecting IP. On the default
+//! loopback `bind` every client connects as 127.0.0.1 and shares one budget,
+//! as do clients behind a reverse proxy; it only tells clients apart when
+//! they connect to a non-loopback `bind` directly.
+//!
 //! Load order: defaults → YAML file → env vars
+//!
+//! # Reloading
+//!
+//! Sending the daemon `SIGHUP` (`pkill -HUP -f "codesearch daemon"`) re-reads
+//! the config file. Repos added to it are initialized, removed ones are
+//! dropped, and repos in both keep their open stores untouched. A new
+//! `index_interval` applies from the next wait; `port`, `bind`, `lmdb_map_size_mb`,
+//! `api_token` and the search limits still need a restart. A config that
+//! fails to load leaves the daemon as is.
 
 pub mod github;
 pub mod server;
 
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -35,7 +65,8 @@ use tracing::{error, info, warn};
 use crate::constants::DB_DIR_NAME;
 use crate::db_discovery::find_best_database;
 use crate::embed::{EmbeddingService, ModelType};
-use crate::index::{IndexManager, SharedStores};
+use crate::index::{IndexManager, RefreshProgress, SharedStores};
+use crate::search::IndexMetadata;
 use crate::vectordb::VectorStore;
 
 /// Daemon configuration loaded from YAML.
@@ -45,11 +76,15 @@ pub struct DaemonConfig {
     #[serde(default = "default_port")]
     pub port: u16,
 
-    /// Repository paths to manage
+    /// HTTP listen address (loopback by default, so only local clients can connect)
+    #[serde(default = "default_bind")]
+    pub bind: IpAddr,
+
+    /// Repositories to manage
     #[serde(default)]
-    pub repos: Vec<PathBuf>,
+    pub repos: Vec<RepoConfig>,
 
-    /// Re-index interval in seconds
+    /// Re-index interval in seconds (at least 1)
     #[serde(default = "default_index_interval")]
     pub index_interval: u64,
 
@@ -57,13 +92,60 @@ pub struct DaemonConfig {
     #[serde(default)]
     pub lmdb_map_size_mb: Option<usize>,
 
-    /// Embedding model name (null = default mxbai-embed-xsmall-v1)
+    /// Embedding model for repos without an index yet (null = default minilm-l6-q);
+    /// indexed repos keep the model they were built with
     #[serde(default)]
     pub model: Option<String>,
 
     /// GitHub auto-discovery configuration
     #[serde(default)]
     pub github: Option<GitHubConfig>,
+
+    /// Bearer token required on every HTTP route (CODESEARCH_API_TOKEN, when set,
+    /// takes precedence); unset = no authentication
+    #[serde(default)]
+    pub api_token: Option<String>,
+
+    /// Searches embedding and searching at once (at least 1)
+    #[serde(default = "default_max_concurrent_searches")]
+    pub max_concurrent_searches: usize,
+
+    /// Searches waiting for a free slot; more are rejected with 429
+    #[serde(default = "default_max_queued_searches")]
+    pub max_queued_searches: usize,
+
+    /// Searches allowed per client IP per minute (null = unlimited); with the
+    /// loopback `bind` all clients share one budget
+    #[serde(default)]
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+/// A `repos` entry: a bare path, or a path with per-repo settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RepoConfig {
+    Path(PathBuf),
+    Detailed {
+        path: PathBuf,
+        /// Embedding model for this repo (overrides the daemon-wide `model`)
+        #[serde(default)]
+        model: Option<String>,
+    },
+}
+
+impl RepoConfig {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Path(path) | Self::Detailed { path, .. } => path,
+        }
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        match self {
+            Self::Path(_) => None,
+            Self::Detailed { model, .. } => model.as_deref(),
+        }
+    }
 }
 
 /// GitHub auto-discovery: resolve repos from GitHub orgs/users.
@@ -98,6 +180,9 @@ pub struct GitHubSource {
     /// Glob patterns to exclude repo names (e.g. "*.wiki", "legacy-*")
     #[serde(default)]
     pub exclude: Vec<String>,
+    /// Embedding model for repos discovered from this source
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 /// Whether a GitHub source is an organization or user.
@@ -113,10 +198,22 @@ fn default_port() -> u16 {
     4444
 }
 
+fn default_bind() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+}
+
 fn default_index_interval() -> u64 {
     300
 }
 
+fn default_max_concurrent_searches() -> usize {
+    4
+}
+
+fn default_max_queued_searches() -> usize {
+    32
+}
+
 fn default_true() -> bool {
     true
 }
@@ -125,9 +222,14 @@ impl Default for DaemonConfig {
     fn default() -> Self {
         Self {
             port: default_port(),
+            bind: default_bind(),
             repos: Vec::new(),
             index_interval: default_index_interval(),
             lmdb_map_size_mb: None,
+            api_token: None,
+            max_concurrent_searches: default_max_concurrent_searches(),
+            max_queued_searches: default_max_queued_searches(),
+            rate_limit_per_minute: None,
             model: None,
             github: None,
         }
@@ -148,6 +250,11 @@ impl DaemonConfig {
                 config.port = p;
             }
         }
+        if let Ok(bind) = std::env::var("CODESEARCH_DAEMON_BIND") {
+            if let Ok(b) = bind.parse() {
+                config.bind = b;
+            }
+        }
         if let Ok(interval) = std::env::var("CODESEARCH_INDEX_INTERVAL") {
             if let Ok(i) = interval.parse() {
                 config.index_interval = i;
@@ -158,6 +265,13 @@ impl DaemonConfig {
                 config.lmdb_map_size_mb = Some(s);
             }
         }
+        if let Some(token) = crate::server::auth::api_token_from_env() {
+            config.api_token = Some(token);
+        }
+        config.api_token = config
+            .api_token
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty());
 
         Ok(config)
     }
@@ -166,19 +280,48 @@ impl DaemonConfig {
 /// Per-repo handle holding its stores and metadata.
 pub struct RepoHandle {
     pub name: String,
+    /// Canonical path the repo is configured with; identifies it across reloads
+    pub source: PathBuf,
     pub project_path: PathBuf,
     pub db_path: PathBuf,
+    /// Model the repo's index was built with; its queries are embedded with it
+    pub model: ModelType,
     pub stores: Arc<SharedStores>,
 }
 
+/// The embedding service of one served index
+pub type SharedEmbedder = Arc<tokio::sync::Mutex<EmbeddingService>>;
+
 /// Shared daemon state accessible from HTTP handlers and the reindex task.
 pub struct DaemonState {
-    pub repos: Vec<RepoHandle>,
-    pub embedding_service: tokio::sync::Mutex<EmbeddingService>,
+    /// Served repos; replaced as a whole when the config is reloaded
+    pub repos: tokio::sync::RwLock<Vec<Arc<RepoHandle>>>,
+    /// One embedding service per served index, keyed by database path
+    ///
+    /// Not shared per model: repos on the same model can still differ in how
+    /// their vectors were normalized, and each keeps its own query cache.
+    pub embedding_services: tokio::sync::RwLock<HashMap<PathBuf, SharedEmbedder>>,
+    /// Re-index interval in seconds
+    pub index_interval: AtomicU64,
+}
+
+impl DaemonState {
+    /// The repos served right now
+    pub async fn repos(&self) -> Vec<Arc<RepoHandle>> {
+        self.repos.read().await.clone()
+    }
+
+    /// The embedding service of the index at `db_path`, if it is served
+    pub async fn embedding_service(&self, db_path: &Path) -> Option<SharedEmbedder> {
+        self.embedding_services.read().await.get(db_path).cloned()
+    }
 }
 
 /// Main daemon entry point.
-pub async fn run_daemon(config: DaemonConfig, cancel_token: CancellationToken) -> Result<()> {
+///
+/// `config_path` is re-read when the daemon receives `SIGHUP`.
+pub async fn run_daemon(config_path: PathBuf, cancel_token: CancellationToken) -> Result<()> {
+    let config = DaemonConfig::load(&config_path)?;
     info!("Starting codesearch daemon on port {}", config.port);
 
     // Set LMDB map size env var if configured (used by VectorStore::new)
@@ -195,32 +338,8 @@ pub async fn run_daemon(config: DaemonConfig, cancel_token: CancellationToken) -
         config.index_interval
     );
 
-    // Load embedding model once (respects config or uses default)
-    let cache_dir = crate::constants::get_global_models_cache_dir()?;
-    let model_type = config
-        .model
-        .as_ref()
-        .and_then(|m| ModelType::parse(m))
-        .unwrap_or_default();
-    info!("Loading embedding model: {:?}", model_type);
-    let embedding_service = EmbeddingService::with_cache_dir(model_type, Some(&cache_dir))?;
-    let dimensions = embedding_service.dimensions();
-
     // Initialize repos
-    let mut repo_handles = Vec::new();
-
-    for repo_path in &all_repos {
-        match init_repo(repo_path, dimensions, &cancel_token).await {
-            Ok(handle) => {
-                info!("Initialized repo: {} ({})", handle.name, handle.db_path.display());
-                repo_handles.push(handle);
-            }
-            Err(e) => {
-                error!("Failed to initialize repo {}: {}", repo_path.display(), e);
-                // Continue with other repos — don't fail the whole daemon
-            }
-        }
-    }
+    let repo_handles = init_repos(&all_repos, default_model(&config), &cancel_token).await;
 
     if repo_handles.is_empty() {
         return Err(anyhow::anyhow!(
@@ -229,34 +348,306 @@ pub async fn run_daemon(config: DaemonConfig, cancel_token: CancellationToken) -
         ));
     }
 
-    info!("{}/{} repos initialized", repo_handles.len(), all_repos.len());
+    info!(
+        "{}/{} repos initialized",
+        repo_handles.len(),
+        all_repos.len()
+    );
+
+    // Load each repo's model the way its index was embedded
+    let mut embedding_services = HashMap::new();
+    for repo in &repo_handles {
+        let embedding_service = load_embedding_service(repo)?;
+        embedding_services.insert(
+            repo.db_path.clone(),
+            Arc::new(tokio::sync::Mutex::new(embedding_service)),
+        );
+    }
 
+    let server_config = config.clone();
     let state = Arc::new(DaemonState {
-        repos: repo_handles,
-        embedding_service: tokio::sync::Mutex::new(embedding_service),
+        repos: tokio::sync::RwLock::new(repo_handles),
+        embedding_services: tokio::sync::RwLock::new(embedding_services),
+        index_interval: AtomicU64::new(config.index_interval),
     });
 
     // Start periodic re-index task
     let reindex_state = state.clone();
     let reindex_cancel = cancel_token.clone();
-    let interval = Duration::from_secs(config.index_interval);
     tokio::spawn(async move {
-        periodic_reindex(reindex_state, interval, reindex_cancel).await;
+        periodic_reindex(reindex_state, reindex_cancel).await;
     });
 
+    // Re-read the config on SIGHUP
+    #[cfg(unix)]
+    {
+        let reload_state = state.clone();
+        let reload_cancel = cancel_token.clone();
+        tokio::spawn(async move {
+            reload_on_sighup(reload_state, config_path, config, reload_cancel).await;
+        });
+    }
+
     // Start HTTP server (blocks until shutdown)
-    server::run_server(state, config.port, cancel_token).await
+    server::run_server(state, &server_config, cancel_token).await
+}
+
+/// Model for repos that are indexed for the first time
+fn default_model(config: &DaemonConfig) -> ModelType {
+    match config.model.as_deref().map(parse_model) {
+        Some(Ok(model)) => model,
+        Some(Err(e)) => {
+            warn!("{}; using {}", e, ModelType::default().short_name());
+            ModelType::default()
+        }
+        None => ModelType::default(),
+    }
+}
+
+/// Initialize each repo, logging and skipping the ones that fail
+async fn init_repos(
+    repos: &[RepoConfig],
+    default_model: ModelType,
+    cancel_token: &CancellationToken,
+) -> Vec<Arc<RepoHandle>> {
+    let mut repo_handles = Vec::new();
+
+    for repo in repos {
+        match init_repo(repo, default_model, cancel_token).await {
+            Ok(handle) => {
+                info!(
+                    "Initialized repo: {} ({}, model {})",
+                    handle.name,
+                    handle.db_path.display(),
+                    handle.model.short_name()
+                );
+                repo_handles.push(Arc::new(handle));
+            }
+            Err(e) => {
+                error!("Failed to initialize repo {}: {}", repo.path().display(), e);
+                // Continue with other repos — don't fail the whole daemon
+            }
+        }
+    }
+    repo_handles
+}
+
+/// Load the embedding model `repo` is searched with
+fn load_embedding_service(repo: &RepoHandle) -> Result<EmbeddingService> {
+    info!("Loading embedding model {:?} for {}", repo.model, repo.name);
+    let mut embedding_service = IndexMetadata::load_or_default(&repo.db_path)?
+        .embedding_service(&repo.db_path, repo.model)?;
+    // Initialize the inference session now rather than inside the first request
+    match embedding_service.warm_up() {
+        Ok(took) => info!("Model {} warmed up in {:?}", repo.model.short_name(), took),
+        Err(e) => warn!("Model warm-up failed: {}", e),
+    }
+    Ok(embedding_service)
+}
+
+/// Reload the config from `config_path` on every SIGHUP until shutdown
+#[cfg(unix)]
+async fn reload_on_sighup(
+    state: Arc<DaemonState>,
+    config_path: PathBuf,
+    mut config: DaemonConfig,
+    cancel_token: CancellationToken,
+) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            warn!("Cannot listen for SIGHUP, config reload disabled: {}", e);
+            return;
+        }
+    };
+
+    loop {
+        tokio::select! {
+            _ = hangup.recv() => {
+                info!("SIGHUP received, reloading {}", config_path.display());
+                match DaemonConfig::load(&config_path) {
+                    Ok(new_config) => {
+                        reload_config(&state, &config, &new_config, &cancel_token).await;
+                        config = new_config;
+                    }
+                    Err(e) => error!("Config reload failed, keeping the running config: {}", e),
+                }
+            }
+            _ = cancel_token.cancelled() => return,
+        }
+    }
+}
+
+/// Apply `new` over the running `old` config
+///
+/// Repos are matched by their canonical path: new ones are initialized,
+/// missing ones dropped (their stores close once in-flight searches finish),
+/// and the rest are kept as they are.
+async fn reload_config(
+    state: &DaemonState,
+    old: &DaemonConfig,
+    new: &DaemonConfig,
+    cancel_token: &CancellationToken,
+) {
+    if new.port != old.port {
+        warn!(
+            "port changed to {}; still listening on {} until restarted",
+            new.port, old.port
+        );
+    }
+    if new.bind != old.bind {
+        warn!("bind changed; it takes effect after a restart");
+    }
+    if new.lmdb_map_size_mb != old.lmdb_map_size_mb {
+        warn!("lmdb_map_size_mb changed; it takes effect after a restart");
+    }
+    if new.api_token != old.api_token {
+        warn!("api_token changed; it takes effect after a restart");
+    }
+    if new.max_concurrent_searches != old.max_concurrent_searches
+        || new.max_queued_searches != old.max_queued_searches
+        || new.rate_limit_per_minute != old.rate_limit_per_minute
+    {
+        warn!("Search limits changed; they take effect after a restart");
+    }
+    state
+        .index_interval
+        .store(new.index_interval, Ordering::Relaxed);
+
+    let wanted = github::resolve_all_repos(new.repos.clone(), new.github.as_ref()).await;
+    let wanted_sources: Vec<Option<PathBuf>> = wanted
+        .iter()
+        .map(|repo| repo.path().canonicalize().ok())
+        .collect();
+
+    let current = state.repos().await;
+    let current_sources: Vec<PathBuf> = current.iter().map(|repo| repo.source.clone()).collect();
+    let diff = diff_repos(&current_sources, &wanted_sources);
+    let (kept, removed): (Vec<_>, Vec<_>) = current
+        .into_iter()
+        .enumerate()
+        .partition(|(i, _)| diff.kept.contains(i));
+    let kept: Vec<_> = kept.into_iter().map(|(_, repo)| repo).collect();
+    let removed: Vec<_> = removed.into_iter().map(|(_, repo)| repo).collect();
+
+    let to_add: Vec<RepoConfig> = diff.added.iter().map(|&i| wanted[i].clone()).collect();
+    let added = init_repos(&to_add, default_model(new), cancel_token).await;
+
+    // Load the models the new repos need; a repo whose model fails to load is not served
+    let mut embedding_services = state.embedding_services.read().await.clone();
+    let mut repos = kept;
+    let mut added_count = 0;
+    for repo in added {
+        match load_embedding_service(&repo) {
+            Ok(service) => {
+                embedding_services.insert(
+                    repo.db_path.clone(),
+                    Arc::new(tokio::sync::Mutex::new(service)),
+                );
+            }
+            Err(e) => {
+                error!(
+                    "Failed to load {} for repo {}: {}",
+                    repo.model.short_name(),
+                    repo.name,
+                    e
+                );
+                continue;
+            }
+        }
+        added_count += 1;
+        repos.push(repo);
+    }
+
+    // Publish the models before the repos that use them, and drop unused ones after
+    *state.embedding_services.write().await = embedding_services;
+    let served = repos.len();
+    let db_paths: Vec<PathBuf> = repos.iter().map(|repo| repo.db_path.clone()).collect();
+    *state.repos.write().await = repos;
+    retain_served(&mut *state.embedding_services.write().await, &db_paths);
+
+    for repo in &removed {
+        info!("Removed repo: {}", repo.name);
+    }
+    info!(
+        "Config reloaded: {} added, {} removed, {} repos served, re-index every {}s",
+        added_count,
+        removed.len(),
+        served,
+        new.index_interval
+    );
+}
+
+/// How a reload changes the served repos, as positions in the current and wanted lists
+#[derive(Debug, Default, PartialEq)]
+struct RepoDiff {
+    /// Current repos that are still wanted
+    kept: Vec<usize>,
+    /// Current repos that are no longer wanted
+    removed: Vec<usize>,
+    /// Wanted repos to initialize: the first of each source not served yet, and
+    /// every unresolvable one (so its error is logged)
+    added: Vec<usize>,
+}
+
+/// Compare the canonical sources of the served repos with the wanted ones
+/// (`None` where a wanted path doesn't resolve)
+fn diff_repos(current: &[PathBuf], wanted: &[Option<PathBuf>]) -> RepoDiff {
+    let mut diff = RepoDiff::default();
+    for (i, source) in current.iter().enumerate() {
+        if wanted.iter().flatten().any(|w| w == source) {
+            diff.kept.push(i);
+        } else {
+            diff.removed.push(i);
+        }
+    }
+    let mut seen: Vec<&PathBuf> = Vec::new();
+    for (i, source) in wanted.iter().enumerate() {
+        match source {
+            Some(source) if current.contains(source) || seen.contains(&source) => {}
+            Some(source) => {
+                seen.push(source);
+                diff.added.push(i);
+            }
+            None => diff.added.push(i),
+        }
+    }
+    diff
+}
+
+/// Drop the embedding services of indexes no longer served
+fn retain_served<T>(services: &mut HashMap<PathBuf, T>, db_paths: &[PathBuf]) {
+    services.retain(|db_path, _| db_paths.contains(db_path));
+}
+
+/// Parse a model name from the daemon config
+fn parse_model(name: &str) -> Result<ModelType> {
+    ModelType::parse(name).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unknown model '{}' in daemon config (see `codesearch models`)",
+            name
+        )
+    })
 }
 
 /// Initialize a single repo: find/create DB, open stores, clear stale readers, refresh index.
+///
+/// The repo is served with the model its index was built with. A model set
+/// for the repo in the config must agree with it; `default_model` is only
+/// used to build a missing index.
 async fn init_repo(
-    repo_path: &Path,
-    dimensions: usize,
+    repo: &RepoConfig,
+    default_model: ModelType,
     cancel_token: &CancellationToken,
 ) -> Result<RepoHandle> {
-    let canonical = repo_path.canonicalize().map_err(|e| {
-        anyhow::anyhow!("Cannot canonicalize {}: {}", repo_path.display(), e)
-    })?;
+    let repo_path = repo.path();
+    let configured = repo.model().map(parse_model).transpose()?;
+
+    let canonical = repo_path
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Cannot canonicalize {}: {}", repo_path.display(), e))?;
 
     let name = canonical
         .file_name()
@@ -271,7 +662,13 @@ async fn init_repo(
     } else {
         // No DB found — create a global index
         info!("No index found for {}, creating global index...", name);
-        crate::index::add_to_index(Some(canonical.clone()), true, cancel_token.clone()).await?;
+        crate::index::add_to_index(
+            Some(canonical.clone()),
+            true,
+            Some(configured.unwrap_or(default_model)),
+            cancel_token.clone(),
+        )
+        .await?;
 
         // Symlink workaround for DB discovery
         let global_db = dirs::home_dir()
@@ -291,8 +688,27 @@ async fn init_repo(
         (info.project_path, info.db_path)
     };
 
+    // Queries must be embedded with the model that built the index
+    let indexed = IndexMetadata::load_or_default(&db_path)?.model()?;
+    let model = match (configured, indexed) {
+        (Some(configured), Some(indexed)) if configured != indexed => {
+            return Err(anyhow::anyhow!(
+                "{} is configured with {} ({} dims) but was indexed with {} ({} dims); \
+                 re-index it with `codesearch index --force --model {}` or drop the repo's model setting",
+                name,
+                configured.short_name(),
+                configured.dimensions(),
+                indexed.short_name(),
+                indexed.dimensions(),
+                configured.short_name()
+            ));
+        }
+        (_, Some(indexed)) => indexed,
+        (configured, None) => configured.unwrap_or(default_model),
+    };
+
     // Open shared stores (read-write, acquires writer lock)
-    let stores = SharedStores::new(&db_path, dimensions)?;
+    let stores = SharedStores::new(&db_path, model.dimensions())?;
     let stores = Arc::new(stores);
 
     // Clear stale LMDB readers from crashed processes
@@ -309,31 +725,76 @@ async fn init_repo(
 
     // Perform incremental refresh to bring index up to date
     info!("Refreshing index for {}...", name);
-    IndexManager::perform_incremental_refresh_with_stores(&project_path, &db_path, &stores).await?;
+    refresh_repo(&name, &project_path, &db_path, &stores).await?;
 
     Ok(RepoHandle {
         name,
+        source: canonical,
         project_path,
         db_path,
+        model,
         stores,
     })
 }
 
-/// Periodically re-index all repos on a timer.
-async fn periodic_reindex(
-    state: Arc<DaemonState>,
-    interval: Duration,
-    cancel_token: CancellationToken,
-) {
-    let mut timer = tokio::time::interval(interval);
-    // First tick fires immediately — skip it since we just indexed
-    timer.tick().await;
+/// Run an incremental refresh for one repo, logging its progress events.
+async fn refresh_repo(
+    name: &str,
+    project_path: &Path,
+    db_path: &Path,
+    stores: &SharedStores,
+) -> Result<()> {
+    let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+    let repo_name = name.to_string();
+    let logger = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                RefreshProgress::Scanned { files } => {
+                    info!("[{}] Scanned {} files", repo_name, files)
+                }
+                RefreshProgress::Changes {
+                    changed,
+                    deleted,
+                    unchanged,
+                } => info!(
+                    "[{}] {} changed, {} deleted, {} unchanged",
+                    repo_name, changed, deleted, unchanged
+                ),
+                RefreshProgress::ChunksAdded { chunks } => {
+                    info!("[{}] Indexed {} chunks", repo_name, chunks)
+                }
+                RefreshProgress::Done { elapsed_ms } => {
+                    info!("[{}] Refresh done in {}ms", repo_name, elapsed_ms)
+                }
+            }
+        }
+    });
+
+    let result = IndexManager::perform_incremental_refresh_with_progress(
+        project_path,
+        db_path,
+        stores,
+        Some(tx),
+    )
+    .await;
+    // The sender was moved into the refresh and is dropped by now, so the logger drains and exits
+    let _ = logger.await;
+    result
+}
 
+/// Periodically re-index all repos on a timer.
+///
+/// The interval is read before each wait, so a reloaded `index_interval`
+/// applies from the next one. Repos are refreshed when initialized, so the
+/// first run comes one interval after startup.
+async fn periodic_reindex(state: Arc<DaemonState>, cancel_token: CancellationToken) {
     loop {
+        // An interval of 0 would re-index back to back
+        let interval = Duration::from_secs(state.index_interval.load(Ordering::Relaxed).max(1));
         tokio::select! {
-            _ = timer.tick() => {
+            _ = tokio::time::sleep(interval) => {
                 info!("Periodic re-index starting...");
-                for repo in &state.repos {
+                for repo in state.repos().await.iter() {
                     if cancel_token.is_cancelled() {
                         return;
                     }
@@ -344,7 +805,8 @@ async fn periodic_reindex(
                         let _ = vs.clear_stale_readers();
                     }
 
-                    match IndexManager::perform_incremental_refresh_with_stores(
+                    match refresh_repo(
+                        &repo.name,
                         &repo.project_path,
                         &repo.db_path,
                         &repo.stores,
@@ -362,3 +824,54 @@ async fn periodic_reindex(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(items: &[&str]) -> Vec<PathBuf> {
+        items.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_diff_repos() {
+        let current = paths(&["/code/api", "/code/web", "/code/docs"]);
+        let wanted = vec![
+            Some(PathBuf::from("/code/web")),
+            Some(PathBuf::from("/code/cli")),
+            None,
+            Some(PathBuf::from("/code/api")),
+            // The same repo listed twice (e.g. by path and by a GitHub source)
+            Some(PathBuf::from("/code/cli")),
+            None,
+        ];
+
+        let diff = diff_repos(&current, &wanted);
+        assert_eq!(diff.kept, vec![0, 1]);
+        assert_eq!(diff.removed, vec![2]);
+        assert_eq!(diff.added, vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn test_diff_repos_unchanged_and_emptied() {
+        let current = paths(&["/code/api", "/code/web"]);
+        let wanted: Vec<_> = current.iter().cloned().map(Some).collect();
+        let diff = diff_repos(&current, &wanted);
+        assert_eq!(diff.kept, vec![0, 1]);
+        assert!(diff.removed.is_empty() && diff.added.is_empty());
+
+        let diff = diff_repos(&current, &[]);
+        assert!(diff.kept.is_empty());
+        assert_eq!(diff.removed, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_retain_served_prunes_unused_services() {
+        let mut services: HashMap<PathBuf, &str> = HashMap::new();
+        services.insert(PathBuf::from("/code/api/.codesearch.db"), "minilm");
+        services.insert(PathBuf::from("/code/docs/.codesearch.db"), "bge");
+        retain_served(&mut services, &paths(&["/code/api/.codesearch.db"]));
+        assert_eq!(services.len(), 1);
+        assert!(services.contains_key(Path::new("/code/api/.codesearch.db")));
+    }
+}
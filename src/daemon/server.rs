@@ -1,23 +1,37 @@
 //! Multi-repo HTTP server for the daemon.
 //!
 //! Fan-out search across all managed repos, merge results by RRF score.
-
-use std::sync::Arc;
+//!
+//! `/search` is admission-controlled: at most `max_concurrent_searches` run
+//! at once, up to `max_queued_searches` more wait for a slot, and anything
+//! beyond that (or over a client's `rate_limit_per_minute`) gets
+//! `429 Too Many Requests`, so one busy client can't stall the daemon.
+//! Clients are told apart by the IP they connect from, so on a loopback
+//! `bind` the rate limit is one budget shared by every client.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use axum::{
-    extract::{Json, State},
-    http::StatusCode,
+    extract::{ConnectInfo, Json, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
 use tokio_util::sync::CancellationToken;
 use tracing::info;
 
 use crate::fts::FtsStore;
 use crate::vectordb::VectorStore;
 
-use super::DaemonState;
+use super::{DaemonConfig, DaemonState};
 
 // ── Request / Response types ─────────────────────────────────────────
 
@@ -31,6 +45,21 @@ pub struct SearchRequest {
     /// Filter to a specific repo by name
     #[serde(default)]
     pub repo: Option<String>,
+    /// Restrict the search to these repos (by name); empty = all repos
+    #[serde(default)]
+    pub repos: Vec<String>,
+}
+
+impl SearchRequest {
+    /// Whether a repo is selected by the `repo` / `repos` filters
+    fn includes_repo(&self, name: &str) -> bool {
+        if let Some(ref filter) = self.repo {
+            if filter != name {
+                return false;
+            }
+        }
+        self.repos.is_empty() || self.repos.iter().any(|r| r == name)
+    }
 }
 
 fn default_limit() -> usize {
@@ -47,6 +76,8 @@ pub struct SearchResponse {
 #[derive(Debug, Serialize)]
 pub struct SearchResult {
     pub repo: String,
+    /// Absolute path of the repo root (`path` is relative to it)
+    pub repo_path: String,
     pub path: String,
     pub content: String,
     pub start_line: usize,
@@ -81,33 +112,186 @@ pub struct RepoInfo {
     pub files: usize,
     pub chunks: usize,
     pub indexed: bool,
+    /// Embedding model the repo is indexed and searched with
+    pub model: String,
+}
+
+// ── Search limits ────────────────────────────────────────────────────
+
+/// Length of a per-IP rate limit window
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Tracked client IPs above which expired rate windows are pruned
+const RATE_PRUNE_THRESHOLD: usize = 1024;
+
+/// Admission control for `/search`: a concurrency limit with a bounded queue,
+/// plus an optional per-IP rate limit
+struct SearchLimiter {
+    running: Semaphore,
+    /// Searches running or waiting for a slot
+    admitted: AtomicUsize,
+    capacity: usize,
+    per_ip: Option<IpRateLimiter>,
+}
+
+impl SearchLimiter {
+    fn new(max_concurrent: usize, max_queued: usize, per_minute: Option<u32>) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            running: Semaphore::new(max_concurrent),
+            admitted: AtomicUsize::new(0),
+            capacity: max_concurrent + max_queued,
+            per_ip: per_minute.map(IpRateLimiter::new),
+        }
+    }
+
+    /// Take a place among the running and queued searches, `None` when all are taken
+    fn try_admit(&self) -> Option<Admission<'_>> {
+        if self.admitted.fetch_add(1, Ordering::SeqCst) >= self.capacity {
+            self.admitted.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Admission(&self.admitted))
+    }
+}
+
+/// A place held in [`SearchLimiter`], released on drop
+struct Admission<'a>(&'a AtomicUsize);
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Fixed one-minute windows of request counts per client IP
+struct IpRateLimiter {
+    per_minute: u32,
+    windows: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl IpRateLimiter {
+    fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request from `ip`, or return how long until its window resets
+    fn check(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() > RATE_PRUNE_THRESHOLD {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < RATE_WINDOW);
+        }
+        let (start, count) = windows.entry(ip).or_insert((now, 0));
+        if now.duration_since(*start) >= RATE_WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if *count >= self.per_minute {
+            return Err(RATE_WINDOW.saturating_sub(now.duration_since(*start)));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+fn too_many_requests(retry_after: Duration, message: String) -> Response {
+    let seconds = retry_after.as_secs().max(1).to_string();
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, seconds)],
+        message,
+    )
+        .into_response()
+}
+
+async fn limit_searches(
+    State(limiter): State<Arc<SearchLimiter>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(per_ip) = &limiter.per_ip {
+        if let Err(retry_after) = per_ip.check(client.ip(), Instant::now()) {
+            return too_many_requests(
+                retry_after,
+                format!(
+                    "Rate limit of {} searches per minute exceeded",
+                    per_ip.per_minute
+                ),
+            );
+        }
+    }
+    let Some(_admission) = limiter.try_admit() else {
+        return too_many_requests(
+            Duration::from_secs(1),
+            "Too many searches in progress, retry shortly".to_string(),
+        );
+    };
+    // The semaphore is never closed
+    let Ok(_permit) = limiter.running.acquire().await else {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    };
+    next.run(request).await
 }
 
 // ── Server ───────────────────────────────────────────────────────────
 
+/// Serve the daemon API with the config's search limits; with `api_token`,
+/// every route requires it as a bearer token
 pub async fn run_server(
     state: Arc<DaemonState>,
-    port: u16,
+    config: &DaemonConfig,
     cancel_token: CancellationToken,
 ) -> anyhow::Result<()> {
+    let limiter = Arc::new(SearchLimiter::new(
+        config.max_concurrent_searches,
+        config.max_queued_searches,
+        config.rate_limit_per_minute,
+    ));
     let app = Router::new()
         .route("/health", get(health_handler))
         .route("/status", get(status_handler))
-        .route("/search", post(search_handler))
+        .route(
+            "/search",
+            post(search_handler)
+                .route_layer(middleware::from_fn_with_state(limiter, limit_searches)),
+        )
         .route("/repos", get(repos_handler))
         .with_state(state);
+    if config.api_token.is_some() {
+        info!("Bearer token authentication enabled");
+    }
+    let app = crate::server::auth::require_token(app, config.api_token.clone());
+    info!(
+        "Search limits: {} concurrent, {} queued, {}",
+        config.max_concurrent_searches.max(1),
+        config.max_queued_searches,
+        config
+            .rate_limit_per_minute
+            .map(|n| format!("{} per minute per IP", n))
+            .unwrap_or_else(|| "no per-IP rate limit".to_string())
+    );
+    if config.rate_limit_per_minute.is_some() && config.bind.is_loopback() {
+        info!("Listening on loopback: every client shares the per-IP rate limit");
+    }
 
-    let addr = format!("127.0.0.1:{}", port);
+    let addr = SocketAddr::new(config.bind, config.port);
     info!("Daemon HTTP server listening on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(async move {
-            cancel_token.cancelled().await;
-            info!("HTTP server shutting down");
-        })
-        .await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
+        cancel_token.cancelled().await;
+        info!("HTTP server shutting down");
+    })
+    .await?;
 
     Ok(())
 }
@@ -117,7 +301,7 @@ pub async fn run_server(
 async fn health_handler(State(state): State<Arc<DaemonState>>) -> Json<HealthResponse> {
     let mut repos = Vec::new();
 
-    for repo in &state.repos {
+    for repo in state.repos().await.iter() {
         let vs: tokio::sync::RwLockReadGuard<'_, VectorStore> =
             repo.stores.vector_store.read().await;
         let stats = vs.stats().unwrap_or(crate::vectordb::StoreStats {
@@ -149,7 +333,7 @@ async fn status_handler(State(state): State<Arc<DaemonState>>) -> Json<HealthRes
 async fn repos_handler(State(state): State<Arc<DaemonState>>) -> Json<ReposResponse> {
     let mut repos = Vec::new();
 
-    for repo in &state.repos {
+    for repo in state.repos().await.iter() {
         let vs: tokio::sync::RwLockReadGuard<'_, VectorStore> =
             repo.stores.vector_store.read().await;
         let stats = vs.stats().unwrap_or(crate::vectordb::StoreStats {
@@ -167,6 +351,7 @@ async fn repos_handler(State(state): State<Arc<DaemonState>>) -> Json<ReposRespo
             files: stats.total_files,
             chunks: stats.total_chunks,
             indexed: stats.indexed,
+            model: repo.model.short_name().to_string(),
         });
     }
 
@@ -178,25 +363,48 @@ async fn search_handler(
     Json(req): Json<SearchRequest>,
 ) -> Result<Json<SearchResponse>, (StatusCode, String)> {
     let start = std::time::Instant::now();
-
-    // Embed query once
-    let query_embedding = {
-        let mut es = state.embedding_service.lock().await;
-        es.embed_query(&req.query)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    };
+    let repos = state.repos().await;
+
+    // Reject unknown repo names instead of silently returning nothing
+    let unknown: Vec<&str> = req
+        .repo
+        .iter()
+        .chain(req.repos.iter())
+        .filter(|name| !repos.iter().any(|r| &r.name == *name))
+        .map(|name| name.as_str())
+        .collect();
+    if !unknown.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Unknown repo(s): {}", unknown.join(", ")),
+        ));
+    }
 
     // Fan-out search across all repos (or filtered repo)
     let mut all_results: Vec<SearchResult> = Vec::new();
 
-    for repo in &state.repos {
-        // Filter by repo name if requested
-        if let Some(ref filter) = req.repo {
-            if &repo.name != filter {
-                continue;
-            }
+    for repo in &repos {
+        // Filter by repo name(s) if requested
+        if !req.includes_repo(&repo.name) {
+            continue;
         }
 
+        // Each repo embeds the query with its own model and normalization
+        let service = state
+            .embedding_service(&repo.db_path)
+            .await
+            .ok_or_else(|| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("No embedding model loaded for {}", repo.name),
+                )
+            })?;
+        let query_embedding = service
+            .lock()
+            .await
+            .embed_query(&req.query)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
         // Vector search
         let vector_results = {
             let vs: tokio::sync::RwLockReadGuard<'_, VectorStore> =
@@ -242,8 +450,9 @@ async fn search_handler(
 
                 all_results.push(SearchResult {
                     repo: repo.name.clone(),
+                    repo_path: repo.project_path.display().to_string(),
                     path: rel_path,
-                    content: truncate_content(&chunk.content, 500),
+                    content: crate::output::truncate_with_ellipsis(&chunk.content, 500),
                     start_line: chunk.start_line,
                     end_line: chunk.end_line,
                     kind: chunk.kind.clone(),
@@ -270,10 +479,60 @@ async fn search_handler(
     }))
 }
 
-fn truncate_content(content: &str, max_len: usize) -> String {
-    if content.len() <= max_len {
-        content.to_string()
-    } else {
-        format!("{}...", &content[..max_len])
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> SearchRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_search_request_defaults() {
+        let req = request(r#"{"query": "auth"}"#);
+        assert_eq!(req.limit, 25);
+        assert!(req.repo.is_none());
+        assert!(req.repos.is_empty());
+        assert!(req.includes_repo("any"));
+    }
+
+    #[test]
+    fn test_search_request_repos_filter() {
+        let req = request(r#"{"query": "auth", "repos": ["api", "web"]}"#);
+        assert!(req.includes_repo("api"));
+        assert!(req.includes_repo("web"));
+        assert!(!req.includes_repo("docs"));
+    }
+
+    #[test]
+    fn test_search_request_repo_and_repos_filter() {
+        let req = request(r#"{"query": "auth", "repo": "api", "repos": ["api", "web"]}"#);
+        assert!(req.includes_repo("api"));
+        assert!(!req.includes_repo("web"));
+    }
+
+    #[test]
+    fn test_search_limiter_rejects_beyond_queue() {
+        let limiter = SearchLimiter::new(1, 1, None);
+        let first = limiter.try_admit();
+        let second = limiter.try_admit();
+        assert!(first.is_some() && second.is_some());
+        assert!(limiter.try_admit().is_none());
+        drop(first);
+        assert!(limiter.try_admit().is_some());
+    }
+
+    #[test]
+    fn test_ip_rate_limiter_resets_each_window() {
+        let limiter = IpRateLimiter::new(2);
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        let now = Instant::now();
+        assert!(limiter.check(ip, now).is_ok());
+        assert!(limiter.check(ip, now).is_ok());
+        let retry_after = limiter.check(ip, now).unwrap_err();
+        assert!(retry_after <= RATE_WINDOW);
+        assert!(limiter.check(other, now).is_ok());
+        assert!(limiter.check(ip, now + RATE_WINDOW).is_ok());
     }
 }
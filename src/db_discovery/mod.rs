@@ -404,6 +404,34 @@ pub fn resolve_database_with_message(
     Ok((db_path, canonical_path))
 }
 
+/// Use an explicitly given database directory, bypassing discovery
+///
+/// The directory must be a complete database (see [`is_valid_database`]).
+/// The project root is taken to be its parent directory.
+///
+/// # Returns
+/// * `Ok((db_path, project_path))` - Canonical database path and project root path
+pub fn resolve_explicit_database(db_path: &Path) -> Result<(PathBuf, PathBuf)> {
+    if !is_valid_database(db_path) {
+        let reason =
+            check_database_integrity(db_path).unwrap_or_else(|| "does not exist".to_string());
+        return Err(anyhow::anyhow!(
+            "{} is not a valid codesearch database ({})",
+            db_path.display(),
+            reason
+        ));
+    }
+
+    let db_path = db_path
+        .canonicalize()
+        .unwrap_or_else(|_| db_path.to_path_buf());
+    let project_path = db_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    Ok((db_path, project_path))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -513,6 +541,31 @@ mod tests {
         assert!(check_database_integrity(&db_dir).is_none());
     }
 
+    #[test]
+    fn test_resolve_explicit_database() {
+        let temp = tempdir().unwrap();
+        let db_dir = temp.path().join("checkout").join(".codesearch.db");
+        fs::create_dir_all(db_dir.join("fts")).unwrap();
+        fs::write(db_dir.join("metadata.json"), "{}").unwrap();
+        fs::write(db_dir.join("data.mdb"), "").unwrap();
+
+        let (db_path, project_path) = resolve_explicit_database(&db_dir).unwrap();
+        assert!(db_path.ends_with("checkout/.codesearch.db"));
+        assert!(project_path.ends_with("checkout"));
+    }
+
+    #[test]
+    fn test_resolve_explicit_database_rejects_incomplete() {
+        let temp = tempdir().unwrap();
+        let db_dir = temp.path().join(".codesearch.db");
+        fs::create_dir_all(&db_dir).unwrap();
+        fs::write(db_dir.join("metadata.json"), "{}").unwrap();
+
+        let err = resolve_explicit_database(&db_dir).unwrap_err();
+        assert!(err.to_string().contains("not a valid codesearch database"));
+        assert!(resolve_explicit_database(&temp.path().join("missing")).is_err());
+    }
+
     #[test]
     fn test_find_best_database_nonexistent_path() {
         let result = find_best_database(Some(Path::new("/tmp/nonexistent_project_path_12345")));
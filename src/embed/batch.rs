@@ -174,8 +174,11 @@ impl BatchEmbedder {
             }
         }
 
-        // Add main content
-        parts.push(format!("Code:\n{}", chunk.content));
+        // Add main content (the code-only variant when the chunker made one)
+        parts.push(format!(
+            "Code:\n{}",
+            chunk.embed_text.as_deref().unwrap_or(&chunk.content)
+        ));
 
         parts.join("\n")
     }
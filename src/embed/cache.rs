@@ -2,6 +2,8 @@ use super::batch::EmbeddedChunk;
 use crate::chunker::Chunk;
 use anyhow::Result;
 use moka::sync::Cache;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
@@ -136,11 +138,29 @@ impl Default for EmbeddingCache {
 /// Query reuse is very high in interactive sessions (e.g., "authentication",
 /// "handle_file_modified"). Uses Moka LRU cache with automatic eviction.
 pub struct QueryCache {
-    cache: Cache<String, Arc<Vec<f32>>>,
+    cache: Cache<String, Arc<CachedQuery>>,
+    /// Next insertion sequence number, used to keep the newest entries when persisting
+    next_seq: AtomicU64,
     hits: AtomicU64,
     misses: AtomicU64,
 }
 
+/// A cached query embedding with its insertion sequence number
+struct CachedQuery {
+    embedding: Vec<f32>,
+    seq: u64,
+}
+
+/// On-disk format of the query cache.
+///
+/// Keyed by model short name so that switching models invalidates the file.
+#[derive(Serialize, Deserialize)]
+struct PersistedQueryCache {
+    model: String,
+    /// Entries ordered oldest first
+    entries: Vec<(String, Vec<f32>)>,
+}
+
 impl QueryCache {
     /// Create a new query cache with default limit (50MB)
     pub fn new() -> Self {
@@ -153,13 +173,14 @@ impl QueryCache {
 
         let cache = Cache::builder()
             .max_capacity(max_weight)
-            .weigher(|_key: &String, value: &Arc<Vec<f32>>| {
-                (value.len() * std::mem::size_of::<f32>()) as u32
+            .weigher(|_key: &String, value: &Arc<CachedQuery>| {
+                (value.embedding.len() * std::mem::size_of::<f32>()) as u32
             })
             .build();
 
         Self {
             cache,
+            next_seq: AtomicU64::new(0),
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
         }
@@ -167,9 +188,9 @@ impl QueryCache {
 
     /// Get query embedding from cache
     pub fn get(&self, query: &str) -> Option<Vec<f32>> {
-        if let Some(embedding) = self.cache.get(query) {
+        if let Some(entry) = self.cache.get(query) {
             self.hits.fetch_add(1, Ordering::Relaxed);
-            Some(embedding.as_ref().clone())
+            Some(entry.embedding.clone())
         } else {
             self.misses.fetch_add(1, Ordering::Relaxed);
             None
@@ -178,7 +199,83 @@ impl QueryCache {
 
     /// Store query embedding in cache
     pub fn put(&self, query: &str, embedding: Vec<f32>) {
-        self.cache.insert(query.to_string(), Arc::new(embedding));
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        self.cache
+            .insert(query.to_string(), Arc::new(CachedQuery { embedding, seq }));
+    }
+
+    /// Load persisted query embeddings from `path`.
+    ///
+    /// Returns the number of entries loaded. A missing file, or a file written
+    /// for a different model, loads nothing.
+    pub fn load_from_file(&self, path: &Path, model_short_name: &str) -> Result<usize> {
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let bytes = std::fs::read(path)?;
+        let persisted: PersistedQueryCache = bincode::deserialize(&bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to decode query cache: {}", e))?;
+
+        if persisted.model != model_short_name {
+            tracing::debug!(
+                "Ignoring query cache for model '{}' (current model: '{}')",
+                persisted.model,
+                model_short_name
+            );
+            return Ok(0);
+        }
+
+        let count = persisted.entries.len();
+        for (query, embedding) in persisted.entries {
+            self.put(&query, embedding);
+        }
+        Ok(count)
+    }
+
+    /// Persist query embeddings to `path`, keeping at most `max_entries` of the
+    /// most recently inserted queries.
+    ///
+    /// Reads straight from the in-memory cache, so queries it has already
+    /// evicted are not written.
+    ///
+    /// Returns the number of entries written.
+    pub fn save_to_file(
+        &self,
+        path: &Path,
+        model_short_name: &str,
+        max_entries: usize,
+    ) -> Result<usize> {
+        let mut cached: Vec<(String, Arc<CachedQuery>)> = self
+            .cache
+            .iter()
+            .map(|(query, entry)| (query.as_ref().clone(), entry))
+            .collect();
+        cached.sort_by_key(|(_, entry)| entry.seq);
+
+        // Evict oldest entries beyond the cap
+        if cached.len() > max_entries {
+            cached.drain(..cached.len() - max_entries);
+        }
+
+        let entries: Vec<(String, Vec<f32>)> = cached
+            .into_iter()
+            .map(|(query, entry)| (query, entry.embedding.clone()))
+            .collect();
+
+        let count = entries.len();
+        let persisted = PersistedQueryCache {
+            model: model_short_name.to_string(),
+            entries,
+        };
+        let bytes = bincode::serialize(&persisted)
+            .map_err(|e| anyhow::anyhow!("Failed to encode query cache: {}", e))?;
+
+        // Write to a temp file and rename so a crash never leaves a truncated cache
+        let tmp_path = path.with_extension(format!("bin.{}.tmp", std::process::id()));
+        std::fs::write(&tmp_path, bytes)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(count)
     }
 
     /// Check if cache contains query embedding
@@ -196,8 +293,12 @@ impl QueryCache {
         }
     }
 
+    /// Number of `put` calls so far, to tell whether anything changed since a load
+    pub fn insertions(&self) -> u64 {
+        self.next_seq.load(Ordering::Relaxed)
+    }
+
     /// Clear cache
-    #[allow(dead_code)]
     pub fn clear(&self) {
         self.cache.invalidate_all();
         self.cache.run_pending_tasks();
@@ -604,4 +705,64 @@ mod tests {
         let stats = cache.stats();
         assert!(stats.size < 10, "Cache should have evicted entries");
     }
+
+    #[test]
+    fn test_query_cache_persistence_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("query_cache.bin");
+
+        let cache = QueryCache::new();
+        cache.put("find auth", vec![1.0, 2.0]);
+        cache.put("parse config", vec![3.0, 4.0]);
+        assert_eq!(cache.save_to_file(&path, "minilm-l6", 100).unwrap(), 2);
+
+        let loaded = QueryCache::new();
+        assert_eq!(loaded.load_from_file(&path, "minilm-l6").unwrap(), 2);
+        assert_eq!(loaded.get("find auth"), Some(vec![1.0, 2.0]));
+        assert_eq!(loaded.get("parse config"), Some(vec![3.0, 4.0]));
+    }
+
+    #[test]
+    fn test_query_cache_persistence_model_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("query_cache.bin");
+
+        let cache = QueryCache::new();
+        cache.put("find auth", vec![1.0, 2.0]);
+        cache.save_to_file(&path, "minilm-l6", 100).unwrap();
+
+        let loaded = QueryCache::new();
+        assert_eq!(loaded.load_from_file(&path, "bge-small").unwrap(), 0);
+        assert!(loaded.get("find auth").is_none());
+    }
+
+    #[test]
+    fn test_query_cache_persistence_evicts_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("query_cache.bin");
+
+        let cache = QueryCache::new();
+        cache.put("first", vec![1.0]);
+        cache.put("second", vec![2.0]);
+        cache.put("third", vec![3.0]);
+        // Re-inserting moves a query to the back of the eviction order
+        cache.put("first", vec![1.0]);
+        assert_eq!(cache.save_to_file(&path, "minilm-l6", 2).unwrap(), 2);
+
+        let loaded = QueryCache::new();
+        loaded.load_from_file(&path, "minilm-l6").unwrap();
+        assert!(loaded.get("second").is_none());
+        assert!(loaded.get("third").is_some());
+        assert!(loaded.get("first").is_some());
+    }
+
+    #[test]
+    fn test_query_cache_load_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = QueryCache::new();
+        let loaded = cache
+            .load_from_file(&dir.path().join("missing.bin"), "minilm-l6")
+            .unwrap();
+        assert_eq!(loaded, 0);
+    }
 }
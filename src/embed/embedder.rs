@@ -1,9 +1,8 @@
 use anyhow::{anyhow, Result};
 use fastembed::{EmbeddingModel as FastEmbedModel, InitOptions, TextEmbedding};
-use ort::execution_providers::CPUExecutionProvider;
 
 /// Available embedding models
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum ModelType {
     // === MiniLM Family ===
     /// All-MiniLM-L6-v2 - 384 dimensions, fast and efficient
@@ -117,7 +116,6 @@ impl ModelType {
     }
 
     /// Check if model is quantized (faster but slightly less accurate)
-    #[allow(dead_code)] // Reserved for model selection UI
     pub fn is_quantized(&self) -> bool {
         matches!(
             self,
@@ -150,8 +148,15 @@ impl ModelType {
         }
     }
 
+    /// Vector metric the model was trained for
+    ///
+    /// Every bundled model produces embeddings meant for cosine similarity;
+    /// `codesearch doctor` warns when an index was built with another metric.
+    pub fn metric(&self) -> crate::vectordb::Metric {
+        crate::vectordb::Metric::Cosine
+    }
+
     /// List all available models
-    #[allow(dead_code)] // Reserved for model listing command
     pub fn all() -> &'static [ModelType] {
         &[
             Self::AllMiniLML6V2,
@@ -197,10 +202,37 @@ impl ModelType {
     }
 }
 
+/// Whether embeddings are L2-normalized, from `CODESEARCH_NORMALIZE_EMBEDDINGS`
+///
+/// On unless set to `0`, `false` or `off`. Applies to indexes built from now
+/// on; searches follow whatever the index recorded in `metadata.json`.
+pub fn normalize_embeddings_default() -> bool {
+    std::env::var("CODESEARCH_NORMALIZE_EMBEDDINGS")
+        .map(|v| !matches!(v.trim().to_lowercase().as_str(), "0" | "false" | "off"))
+        .unwrap_or(true)
+}
+
+/// Scale `vector` to unit length
+///
+/// Vectors that are already unit length (most models normalize their own
+/// output) are left untouched, as are all-zero vectors.
+pub fn l2_normalize(vector: &mut [f32]) {
+    let norm_sq: f32 = vector.iter().map(|x| x * x).sum();
+    if norm_sq == 0.0 || (norm_sq - 1.0).abs() < 1e-6 {
+        return;
+    }
+    let norm = norm_sq.sqrt();
+    for x in vector.iter_mut() {
+        *x /= norm;
+    }
+}
+
 /// Fast embedding model using fastembed library
 pub struct FastEmbedder {
     model: TextEmbedding,
     model_type: ModelType,
+    /// L2-normalize every embedding, for chunks and queries alike
+    normalize: bool,
 }
 
 impl FastEmbedder {
@@ -228,20 +260,29 @@ impl FastEmbedder {
             );
         }
 
-        // Use CPU execution provider WITH arena allocator for speed.
-        // Arena allocator provides fast memory reuse during inference.
-        let cpu_ep = CPUExecutionProvider::default()
-            .with_arena_allocator(true)
-            .build();
-
+        // CPU by default; CODESEARCH_EXECUTION_PROVIDER selects CoreML/CUDA/TensorRT
         let model = TextEmbedding::try_new(
             InitOptions::new(model_type.to_fastembed_model())
                 .with_show_download_progress(false)
-                .with_execution_providers(vec![cpu_ep]),
+                .with_execution_providers(super::provider::execution_providers()),
         )
         .map_err(|e| anyhow!("Failed to initialize embedding model: {}", e))?;
 
-        Ok(Self { model, model_type })
+        Ok(Self {
+            model,
+            model_type,
+            normalize: normalize_embeddings_default(),
+        })
+    }
+
+    /// Turn L2 normalization of the produced embeddings on or off
+    pub fn set_normalize(&mut self, normalize: bool) {
+        self.normalize = normalize;
+    }
+
+    /// Whether produced embeddings are L2-normalized
+    pub fn normalizes(&self) -> bool {
+        self.normalize
     }
     /// Embed a batch of texts (processes in mini-batches to avoid OOM)
     /// Uses adaptive batch size based on model dimensions
@@ -283,10 +324,13 @@ impl FastEmbedder {
 
             let text_refs: Vec<&str> = chunk.iter().map(|s| s.as_str()).collect();
 
-            let embeddings = self
+            let mut embeddings = self
                 .model
                 .embed(text_refs, None)
                 .map_err(|e| anyhow!("Failed to generate embeddings: {}", e))?;
+            if self.normalize {
+                embeddings.iter_mut().for_each(|e| l2_normalize(e));
+            }
 
             all_embeddings.extend(embeddings);
         }
@@ -435,6 +479,43 @@ mod tests {
         assert!(!ModelType::JinaEmbeddingsV2BaseCode.is_quantized());
     }
 
+    #[test]
+    fn test_l2_normalize_gives_unit_norm() {
+        let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+        let mut vector = vec![3.0, 4.0, 0.0];
+        l2_normalize(&mut vector);
+        assert!((norm(&vector) - 1.0).abs() < 1e-6);
+        assert_eq!(vector, vec![0.6, 0.8, 0.0]);
+
+        // Already normalized vectors are left bit-for-bit alone
+        let unit = vec![0.6, 0.8];
+        let mut again = unit.clone();
+        l2_normalize(&mut again);
+        assert_eq!(again, unit);
+
+        let mut zero = vec![0.0; 4];
+        l2_normalize(&mut zero);
+        assert_eq!(zero, vec![0.0; 4]);
+    }
+
+    #[test]
+    #[ignore] // Requires model
+    fn test_inserted_embeddings_have_unit_norm() {
+        let mut embedder = FastEmbedder::new().unwrap();
+        embedder.set_normalize(true);
+        let embeddings = embedder
+            .embed_batch(vec![
+                "fn main() {}".to_string(),
+                "struct Config;".to_string(),
+            ])
+            .unwrap();
+        for embedding in embeddings {
+            let magnitude: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+            assert!((magnitude - 1.0).abs() < 1e-4);
+        }
+    }
+
     #[test]
     #[ignore] // Requires downloading model
     fn test_embedder_creation() {
@@ -1,20 +1,28 @@
 mod batch;
 mod cache;
 mod embedder;
+mod provider;
 
 pub use batch::{BatchEmbedder, EmbeddedChunk};
 pub use cache::{CacheStats, CachedBatchEmbedder, QueryCache, QueryCacheStats};
-pub use embedder::{FastEmbedder, ModelType};
+pub use embedder::{normalize_embeddings_default, FastEmbedder, ModelType};
+pub use provider::init_runtime;
 
 use anyhow::Result;
 use std::env;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// High-level embedding service that combines all features
 pub struct EmbeddingService {
     cached_embedder: CachedBatchEmbedder,
     model_type: ModelType,
     query_cache: QueryCache,
+    /// Where to persist the query cache on drop (None = in-memory only)
+    query_cache_path: Option<PathBuf>,
+    /// Query cache insertions after loading it, so an unchanged cache isn't rewritten
+    query_cache_loaded: u64,
 }
 
 impl EmbeddingService {
@@ -53,9 +61,98 @@ impl EmbeddingService {
             cached_embedder,
             model_type,
             query_cache,
+            query_cache_path: None,
+            query_cache_loaded: 0,
         })
     }
 
+    /// Persist query embeddings across runs in the database at `db_path`
+    ///
+    /// The cache lives in the database's `query_cache.bin`: loaded now and
+    /// written back when the service is dropped. Entries written for a
+    /// different model or normalization are ignored.
+    pub fn with_query_cache(mut self, db_path: &Path) -> Self {
+        self.query_cache_path = Some(db_path.join(crate::constants::QUERY_CACHE_FILE_NAME));
+        self.load_query_cache();
+        self
+    }
+
+    /// L2-normalize embeddings (or not), for chunks and queries alike
+    ///
+    /// Searches must match the setting their index was built with; call this
+    /// before any embedding is made so no cached vector is left over.
+    pub fn with_normalization(mut self, normalize: bool) -> Self {
+        if normalize == self.normalizes() {
+            return self;
+        }
+        if let Ok(mut embedder) = self.cached_embedder.batch_embedder.embedder.lock() {
+            embedder.set_normalize(normalize);
+        }
+        // Cached query vectors were made with the other setting
+        self.query_cache.clear();
+        self.load_query_cache();
+        self
+    }
+
+    /// Whether embeddings are L2-normalized
+    pub fn normalizes(&self) -> bool {
+        self.cached_embedder
+            .batch_embedder
+            .embedder
+            .lock()
+            .map(|embedder| embedder.normalizes())
+            .unwrap_or(true)
+    }
+
+    /// Identity of the persisted query cache: the model, plus whether its
+    /// vectors are normalized
+    fn query_cache_tag(&self) -> String {
+        if self.normalizes() {
+            self.model_type.short_name().to_string()
+        } else {
+            format!("{}+unnormalized", self.model_type.short_name())
+        }
+    }
+
+    /// Load the persisted query cache, if there is one
+    fn load_query_cache(&mut self) {
+        let Some(path) = &self.query_cache_path else {
+            return;
+        };
+        match self
+            .query_cache
+            .load_from_file(path, &self.query_cache_tag())
+        {
+            Ok(count) if count > 0 => {
+                tracing::debug!("Loaded {} cached query embeddings", count);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable query cache {}: {}", path.display(), e);
+            }
+        }
+        self.query_cache_loaded = self.query_cache.insertions();
+    }
+
+    /// Recreate the ONNX session to release arena allocator memory
+    ///
+    /// The model files are already in the cache directory, so this only pays
+    /// the session setup cost. The chunk and query caches are kept, and so is
+    /// the normalization setting.
+    pub fn reset_embedder(&mut self) -> Result<()> {
+        let mut fresh = FastEmbedder::with_model(self.model_type)?;
+        let mut embedder = self
+            .cached_embedder
+            .batch_embedder
+            .embedder
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Embedder mutex poisoned: {}", e))?;
+        fresh.set_normalize(embedder.normalizes());
+        // Assigning drops the old session along with its arena
+        *embedder = fresh;
+        Ok(())
+    }
+
     /// Embed a batch of chunks with caching
     pub fn embed_chunks(
         &mut self,
@@ -84,6 +181,20 @@ impl EmbeddingService {
         Ok(embedding)
     }
 
+    /// Run a throwaway embedding so the first real query doesn't pay session start-up costs
+    ///
+    /// Bypasses the query cache. Returns how long the warm-up took.
+    pub fn warm_up(&mut self) -> Result<Duration> {
+        let start = Instant::now();
+        self.cached_embedder
+            .batch_embedder
+            .embedder
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Embedder mutex poisoned: {}", e))?
+            .embed_one("warmup")?;
+        Ok(start.elapsed())
+    }
+
     /// Batch embed multiple query texts with caching (single ONNX call for misses)
     pub fn embed_queries_batch(&mut self, queries: &[String]) -> Result<Vec<Vec<f32>>> {
         if queries.is_empty() {
@@ -157,6 +268,27 @@ impl EmbeddingService {
     }
 }
 
+impl Drop for EmbeddingService {
+    fn drop(&mut self) {
+        if let Some(ref path) = self.query_cache_path {
+            if self.query_cache.insertions() == self.query_cache_loaded {
+                return;
+            }
+            let max_entries = env::var("CODESEARCH_QUERY_CACHE_MAX_ENTRIES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(crate::constants::DEFAULT_QUERY_CACHE_MAX_ENTRIES);
+
+            if let Err(e) =
+                self.query_cache
+                    .save_to_file(path, &self.query_cache_tag(), max_entries)
+            {
+                tracing::debug!("Failed to persist query cache to {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
 impl Default for EmbeddingService {
     fn default() -> Self {
         Self::new().expect("Failed to create default embedding service")
@@ -192,6 +324,17 @@ mod tests {
         assert_eq!(query_embedding.len(), 384);
     }
 
+    #[test]
+    #[ignore] // Requires model
+    fn test_reset_embedder_keeps_normalization() {
+        let normalize = !normalize_embeddings_default();
+        let mut service = EmbeddingService::new()
+            .unwrap()
+            .with_normalization(normalize);
+        service.reset_embedder().unwrap();
+        assert_eq!(service.normalizes(), normalize);
+    }
+
     #[test]
     #[ignore] // search method not implemented - uses VectorStore instead
     fn test_embed_and_search() {
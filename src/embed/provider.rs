@@ -0,0 +1,246 @@
+//! ONNX Runtime execution providers and thread pools for embedding
+//!
+//! `CODESEARCH_EXECUTION_PROVIDER` picks where models run: `cpu` (default),
+//! `coreml` (Apple), `cuda` or `tensorrt` (NVIDIA), or `auto` for the best one
+//! this build and machine support. Hardware providers are registered ahead of
+//! the CPU provider, so operators they can't run still fall back to the CPU.
+//!
+//! `CODESEARCH_ORT_INTRA_THREADS` / `CODESEARCH_ORT_INTER_THREADS` size a global
+//! ONNX Runtime thread pool shared by every session (embedder and reranker).
+
+use ort::execution_providers::{
+    CPUExecutionProvider, CUDAExecutionProvider, CoreMLExecutionProvider, ExecutionProvider,
+    ExecutionProviderDispatch, TensorRTExecutionProvider,
+};
+use std::sync::{Once, OnceLock};
+
+/// Hardware an embedding model runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionTarget {
+    Cpu,
+    CoreMl,
+    Cuda,
+    TensorRt,
+}
+
+impl ExecutionTarget {
+    /// Parse a `CODESEARCH_EXECUTION_PROVIDER` value; `None` for `auto`
+    pub fn parse(name: &str) -> Option<Option<Self>> {
+        match name.trim().to_lowercase().as_str() {
+            "" | "cpu" => Some(Some(Self::Cpu)),
+            "coreml" => Some(Some(Self::CoreMl)),
+            "cuda" | "gpu" => Some(Some(Self::Cuda)),
+            "tensorrt" | "trt" => Some(Some(Self::TensorRt)),
+            "auto" => Some(None),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cpu => "CPU",
+            Self::CoreMl => "CoreML",
+            Self::Cuda => "CUDA",
+            Self::TensorRt => "TensorRT",
+        }
+    }
+
+    /// Cargo feature that links this provider into ONNX Runtime, if one is needed
+    fn feature(&self) -> Option<&'static str> {
+        match self {
+            Self::Cpu => None,
+            Self::CoreMl => Some("coreml"),
+            Self::Cuda => Some("cuda"),
+            Self::TensorRt => Some("tensorrt"),
+        }
+    }
+
+    /// Whether this binary was built with the provider's Cargo feature
+    fn compiled_in(&self) -> bool {
+        match self {
+            Self::Cpu => true,
+            Self::CoreMl => cfg!(feature = "coreml"),
+            Self::Cuda => cfg!(feature = "cuda"),
+            Self::TensorRt => cfg!(feature = "tensorrt"),
+        }
+    }
+
+    /// Whether the provider is compiled in and ONNX Runtime reports it as available
+    fn is_available(&self) -> bool {
+        self.compiled_in()
+            && match self {
+                Self::Cpu => true,
+                Self::CoreMl => CoreMLExecutionProvider::default().is_available(),
+                Self::Cuda => CUDAExecutionProvider::default().is_available(),
+                Self::TensorRt => TensorRTExecutionProvider::default().is_available(),
+            }
+            .unwrap_or(false)
+    }
+
+    /// Providers to register for this target, fastest first
+    fn dispatch(&self) -> Vec<ExecutionProviderDispatch> {
+        // CPU with the arena allocator for fast buffer reuse between batches
+        let cpu = CPUExecutionProvider::default()
+            .with_arena_allocator(true)
+            .build();
+        match self {
+            Self::Cpu => vec![cpu],
+            Self::CoreMl => vec![CoreMLExecutionProvider::default().build(), cpu],
+            Self::Cuda => vec![CUDAExecutionProvider::default().build(), cpu],
+            Self::TensorRt => vec![
+                TensorRTExecutionProvider::default().build(),
+                CUDAExecutionProvider::default().build(),
+                cpu,
+            ],
+        }
+    }
+}
+
+/// Resolve `CODESEARCH_EXECUTION_PROVIDER` to a target this machine can use
+///
+/// Unknown or unavailable providers fall back to the CPU with a warning.
+fn resolve_target() -> ExecutionTarget {
+    let requested = std::env::var("CODESEARCH_EXECUTION_PROVIDER").unwrap_or_default();
+    let Some(requested) = ExecutionTarget::parse(&requested) else {
+        tracing::warn!(
+            "Unknown CODESEARCH_EXECUTION_PROVIDER '{}' (expected cpu, coreml, cuda, tensorrt or auto), using CPU",
+            requested
+        );
+        return ExecutionTarget::Cpu;
+    };
+
+    match requested {
+        Some(target) if target.is_available() => target,
+        Some(target) => {
+            match target.feature().filter(|_| !target.compiled_in()) {
+                Some(feature) => tracing::warn!(
+                    "{} execution provider requested, but codesearch was built without the `{}` feature; using CPU",
+                    target.name(),
+                    feature
+                ),
+                None => tracing::warn!(
+                    "{} execution provider is not available in this ONNX Runtime; using CPU",
+                    target.name()
+                ),
+            }
+            ExecutionTarget::Cpu
+        }
+        None => [
+            ExecutionTarget::TensorRt,
+            ExecutionTarget::Cuda,
+            ExecutionTarget::CoreMl,
+        ]
+        .into_iter()
+        .find(ExecutionTarget::is_available)
+        .unwrap_or(ExecutionTarget::Cpu),
+    }
+}
+
+/// Read a positive thread count from `var`
+fn thread_count(var: &str) -> Option<usize> {
+    std::env::var(var)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&n| n > 0)
+}
+
+/// Global thread pool options from `CODESEARCH_ORT_*_THREADS`, if any are set
+fn thread_pool_options(
+    intra: Option<usize>,
+    inter: Option<usize>,
+) -> ort::Result<Option<ort::environment::GlobalThreadPoolOptions>> {
+    if intra.is_none() && inter.is_none() {
+        return Ok(None);
+    }
+    let mut options = ort::environment::GlobalThreadPoolOptions::default();
+    if let Some(n) = intra {
+        options = options.with_intra_threads(n)?;
+    }
+    if let Some(n) = inter {
+        options = options.with_inter_threads(n)?;
+    }
+    Ok(Some(options))
+}
+
+/// Create the ONNX Runtime environment, with a global thread pool when thread
+/// counts are configured
+///
+/// Runs once per process and must come before the first session is built, so
+/// the embedder and the reranker both call it.
+pub fn init_runtime() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let intra = thread_count("CODESEARCH_ORT_INTRA_THREADS");
+        let inter = thread_count("CODESEARCH_ORT_INTER_THREADS");
+        let options = match thread_pool_options(intra, inter) {
+            Ok(Some(options)) => options,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Ignoring CODESEARCH_ORT_*_THREADS: {}", e);
+                return;
+            }
+        };
+        match ort::init().with_global_thread_pool(options).commit() {
+            Ok(true) => tracing::info!(
+                "ONNX Runtime threads: intra-op {}, inter-op {}",
+                intra.map_or("default".to_string(), |n| n.to_string()),
+                inter.map_or("default".to_string(), |n| n.to_string())
+            ),
+            Ok(false) => tracing::warn!(
+                "ONNX Runtime was already initialized; CODESEARCH_ORT_*_THREADS not applied"
+            ),
+            Err(e) => tracing::warn!("Failed to set ONNX Runtime thread pool: {}", e),
+        }
+    });
+}
+
+/// Execution providers for a new embedding session
+///
+/// The target is resolved once per process (the embedder is rebuilt
+/// periodically to free arena memory) and logged then.
+pub fn execution_providers() -> Vec<ExecutionProviderDispatch> {
+    static TARGET: OnceLock<ExecutionTarget> = OnceLock::new();
+    init_runtime();
+    TARGET
+        .get_or_init(|| {
+            let target = resolve_target();
+            tracing::info!("Embedding execution provider: {}", target.name());
+            target
+        })
+        .dispatch()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_execution_target() {
+        assert_eq!(
+            ExecutionTarget::parse("cpu"),
+            Some(Some(ExecutionTarget::Cpu))
+        );
+        assert_eq!(ExecutionTarget::parse(""), Some(Some(ExecutionTarget::Cpu)));
+        assert_eq!(
+            ExecutionTarget::parse("CoreML"),
+            Some(Some(ExecutionTarget::CoreMl))
+        );
+        assert_eq!(
+            ExecutionTarget::parse("gpu"),
+            Some(Some(ExecutionTarget::Cuda))
+        );
+        assert_eq!(
+            ExecutionTarget::parse("trt"),
+            Some(Some(ExecutionTarget::TensorRt))
+        );
+        assert_eq!(ExecutionTarget::parse("auto"), Some(None));
+        assert_eq!(ExecutionTarget::parse("tpu"), None);
+    }
+
+    #[test]
+    fn test_cpu_always_available() {
+        assert!(ExecutionTarget::Cpu.is_available());
+        assert_eq!(ExecutionTarget::Cpu.dispatch().len(), 1);
+        assert_eq!(ExecutionTarget::Cuda.dispatch().len(), 2);
+    }
+}
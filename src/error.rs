@@ -55,6 +55,34 @@ pub enum CodeSearchError {
     /// Validation errors
     #[error("Validation error: {message}")]
     Validation { message: String },
+
+    /// No database exists at the given path (nothing indexed there yet)
+    #[error("No database found at {}. Run 'codesearch index' first.", path.display())]
+    DatabaseNotFound { path: PathBuf },
+
+    /// Query embeddings and stored vectors have different dimensions
+    #[error("{message}")]
+    DimensionMismatch {
+        expected: usize,
+        actual: usize,
+        message: String,
+    },
+
+    /// An embedding model is unknown or its files can't be loaded
+    #[error("{message}")]
+    ModelNotFound { model: String, message: String },
+
+    /// Index files exist but can't be read
+    #[error("Corrupt index at {}: {message}. Re-index with 'codesearch index --force'.", path.display())]
+    Corrupt { path: PathBuf, message: String },
+
+    /// The full-text index was built with a different schema version
+    #[error("Full-text index at {} uses schema version {found}, expected {expected}. Run 'codesearch rebuild-fts' to rebuild it from the stored chunks (no re-embedding).", path.display())]
+    FtsOutdated {
+        path: PathBuf,
+        found: u32,
+        expected: u32,
+    },
 }
 
 impl CodeSearchError {
@@ -125,6 +153,45 @@ impl CodeSearchError {
             message: message.into(),
         }
     }
+
+    /// Create a database-not-found error
+    pub fn database_not_found(path: impl Into<PathBuf>) -> Self {
+        Self::DatabaseNotFound { path: path.into() }
+    }
+
+    /// Create a dimension mismatch error
+    pub fn dimension_mismatch(expected: usize, actual: usize, message: impl Into<String>) -> Self {
+        Self::DimensionMismatch {
+            expected,
+            actual,
+            message: message.into(),
+        }
+    }
+
+    /// Create a model-not-found error
+    pub fn model_not_found(model: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::ModelNotFound {
+            model: model.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Create a corrupt index error
+    pub fn corrupt(path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Self::Corrupt {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Create an outdated FTS schema error
+    pub fn fts_outdated(path: impl Into<PathBuf>, found: u32, expected: u32) -> Self {
+        Self::FtsOutdated {
+            path: path.into(),
+            found,
+            expected,
+        }
+    }
 }
 
 /// Result type alias for codesearch operations
@@ -211,6 +278,34 @@ mod tests {
         assert!(err.to_string().contains("Syntax error"));
     }
 
+    #[test]
+    fn test_database_not_found_error() {
+        let err = CodeSearchError::database_not_found("/repo/.codesearch.db");
+        assert!(err.to_string().contains("/repo/.codesearch.db"));
+        assert!(err.to_string().contains("codesearch index"));
+    }
+
+    #[test]
+    fn test_dimension_mismatch_error() {
+        let err = CodeSearchError::dimension_mismatch(384, 768, "Index has 384 dims, model 768");
+        assert!(matches!(
+            err,
+            CodeSearchError::DimensionMismatch {
+                expected: 384,
+                actual: 768,
+                ..
+            }
+        ));
+        assert_eq!(err.to_string(), "Index has 384 dims, model 768");
+    }
+
+    #[test]
+    fn test_corrupt_error() {
+        let err = CodeSearchError::corrupt("/repo/.codesearch.db", "bad header");
+        assert!(err.to_string().contains("Corrupt index"));
+        assert!(err.to_string().contains("--force"));
+    }
+
     #[test]
     fn test_from_io_error() {
         let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
@@ -2,6 +2,34 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+/// Files above this size are sniffed even when their extension says source code,
+/// since unusually large "source" files are often generated or embedded data
+const TRUSTED_EXTENSION_MAX_SIZE: u64 = 1024 * 1024;
+
+/// Whether a file with an indexable extension still needs its content checked
+///
+/// The extension is trusted for ordinary source files, which saves an open and
+/// read per walked file. Extensions shared with binary formats and unusually
+/// large files are still sniffed with [`is_binary_file`].
+pub fn needs_binary_sniff(path: &Path, size: u64) -> bool {
+    size > TRUSTED_EXTENSION_MAX_SIZE || has_ambiguous_extension(path)
+}
+
+/// Check if an indexable extension is also used by common binary formats
+fn has_ambiguous_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| {
+            matches!(
+                ext.to_lowercase().as_str(),
+                // MPEG transport streams share TypeScript's extension
+                "ts"
+                // Plain text is as often a data dump as prose
+                | "txt"
+            )
+        })
+}
+
 /// Check if a file is binary using multiple heuristics
 ///
 /// This function uses several techniques to detect binary files:
@@ -125,6 +153,18 @@ mod tests {
         assert!(!is_binary_by_extension(Path::new("README.md")));
     }
 
+    #[test]
+    fn test_needs_binary_sniff() {
+        assert!(!needs_binary_sniff(Path::new("main.rs"), 4096));
+        assert!(!needs_binary_sniff(Path::new("app.py"), 0));
+        assert!(needs_binary_sniff(Path::new("index.ts"), 4096));
+        assert!(needs_binary_sniff(Path::new("NOTES.TXT"), 4096));
+        assert!(needs_binary_sniff(
+            Path::new("generated.rs"),
+            TRUSTED_EXTENSION_MAX_SIZE + 1
+        ));
+    }
+
     #[test]
     fn test_text_file_detection() {
         let dir = TempDir::new().unwrap();
@@ -0,0 +1,228 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::Language;
+use crate::chunker::ChunkLimits;
+use crate::constants::PROJECT_CONFIG_FILE_NAME;
+
+/// Project-level indexing configuration loaded from `.codesearch.toml`
+///
+/// Example:
+/// ```toml
+/// exclude = ["generated/**", "*.min.js"]
+/// include_extensions = ["rs", "py"]
+/// max_file_size = 1048576
+///
+/// [chunk_limits.java]
+/// max_lines = 150
+/// max_chars = 4000
+///
+/// [watch]
+/// debounce_ms = 500
+/// max_batch = 2000
+/// ```
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Additional gitignore-style globs to exclude from indexing
+    pub exclude: Vec<String>,
+    /// If non-empty, only files with these extensions are indexed
+    pub include_extensions: Vec<String>,
+    /// Maximum file size in bytes (0 = no limit); overrides `CODESEARCH_MAX_FILE_SIZE`
+    pub max_file_size: Option<u64>,
+    /// Per-language chunk size overrides, keyed by language name (e.g. `java`, `python`)
+    pub chunk_limits: HashMap<String, ChunkLimitsConfig>,
+    /// File watcher tuning for `serve` and `watch`
+    pub watch: WatchConfig,
+}
+
+/// Chunk size overrides for one language; unset fields keep the global default
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChunkLimitsConfig {
+    pub max_lines: Option<usize>,
+    pub max_chars: Option<usize>,
+}
+
+/// `[watch]` overrides; unset fields fall back to the environment, then defaults
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchConfig {
+    /// Debounce window in milliseconds (overrides `CODESEARCH_WATCH_DEBOUNCE_MS`)
+    pub debounce_ms: Option<u64>,
+    /// Most file events applied per batch, 0 = no limit (overrides `CODESEARCH_WATCH_MAX_BATCH`)
+    pub max_batch: Option<usize>,
+}
+
+impl ProjectConfig {
+    /// Path of the config file for a project root
+    pub fn path_for(project_root: &Path) -> PathBuf {
+        project_root.join(PROJECT_CONFIG_FILE_NAME)
+    }
+
+    /// Load `.codesearch.toml` from the project root
+    ///
+    /// Returns `Ok(None)` if the file does not exist, and an error if it cannot be
+    /// read or parsed (callers should warn and fall back to defaults).
+    pub fn load(project_root: &Path) -> Result<Option<Self>> {
+        let path = Self::path_for(project_root);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: ProjectConfig = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        Ok(Some(config))
+    }
+
+    /// Resolve `[chunk_limits]` into per-language limits on top of `defaults`
+    ///
+    /// Errors on unknown language names and on zero limits.
+    pub fn language_chunk_limits(
+        &self,
+        defaults: ChunkLimits,
+    ) -> Result<HashMap<Language, ChunkLimits>> {
+        let mut limits = HashMap::new();
+        for (name, overrides) in &self.chunk_limits {
+            let language = Language::from_name(name)
+                .ok_or_else(|| anyhow!("Unknown language '{}' in [chunk_limits]", name))?;
+            let resolved = ChunkLimits {
+                max_lines: overrides.max_lines.unwrap_or(defaults.max_lines),
+                max_chars: overrides.max_chars.unwrap_or(defaults.max_chars),
+            };
+            if resolved.max_lines == 0 || resolved.max_chars == 0 {
+                return Err(anyhow!(
+                    "[chunk_limits.{}] limits must be greater than 0",
+                    name
+                ));
+            }
+            limits.insert(language, resolved);
+        }
+        Ok(limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_config() {
+        let dir = TempDir::new().unwrap();
+        assert!(ProjectConfig::load(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_config() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            ProjectConfig::path_for(dir.path()),
+            r#"
+exclude = ["generated/**"]
+include_extensions = ["rs", ".py"]
+max_file_size = 1024
+"#,
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(dir.path()).unwrap().unwrap();
+        assert_eq!(config.exclude, vec!["generated/**"]);
+        assert_eq!(config.include_extensions, vec!["rs", ".py"]);
+        assert_eq!(config.max_file_size, Some(1024));
+        assert_eq!(config.watch, WatchConfig::default());
+    }
+
+    #[test]
+    fn test_load_watch_config() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            ProjectConfig::path_for(dir.path()),
+            "[watch]\ndebounce_ms = 500\n",
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(dir.path()).unwrap().unwrap();
+        assert_eq!(config.watch.debounce_ms, Some(500));
+        assert_eq!(config.watch.max_batch, None);
+    }
+
+    #[test]
+    fn test_load_partial_config() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            ProjectConfig::path_for(dir.path()),
+            "exclude = [\"out/\"]\n",
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(dir.path()).unwrap().unwrap();
+        assert_eq!(config.exclude, vec!["out/"]);
+        assert!(config.include_extensions.is_empty());
+        assert!(config.max_file_size.is_none());
+    }
+
+    #[test]
+    fn test_language_chunk_limits() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            ProjectConfig::path_for(dir.path()),
+            r#"
+[chunk_limits.java]
+max_lines = 150
+max_chars = 4000
+
+[chunk_limits.Python]
+max_lines = 60
+"#,
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(dir.path()).unwrap().unwrap();
+        let defaults = ChunkLimits {
+            max_lines: 100,
+            max_chars: 2000,
+        };
+        let limits = config.language_chunk_limits(defaults).unwrap();
+        assert_eq!(
+            limits[&Language::Java],
+            ChunkLimits {
+                max_lines: 150,
+                max_chars: 4000
+            }
+        );
+        assert_eq!(
+            limits[&Language::Python],
+            ChunkLimits {
+                max_lines: 60,
+                max_chars: 2000
+            }
+        );
+        assert!(!limits.contains_key(&Language::Rust));
+    }
+
+    #[test]
+    fn test_language_chunk_limits_rejects_unknown_language() {
+        let config: ProjectConfig =
+            toml::from_str("[chunk_limits.cobol]\nmax_lines = 10\n").unwrap();
+        let defaults = ChunkLimits {
+            max_lines: 100,
+            max_chars: 2000,
+        };
+        assert!(config.language_chunk_limits(defaults).is_err());
+    }
+
+    #[test]
+    fn test_load_invalid_config() {
+        let dir = TempDir::new().unwrap();
+        fs::write(ProjectConfig::path_for(dir.path()), "exclude = 42\n").unwrap();
+
+        assert!(ProjectConfig::load(dir.path()).is_err());
+    }
+}
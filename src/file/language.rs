@@ -16,6 +16,7 @@ pub enum Language {
     Php,
     Swift,
     Kotlin,
+    Zig,
     Shell,
     Markdown,
     Json,
@@ -52,6 +53,7 @@ impl Language {
             "php" => Self::Php,
             "swift" => Self::Swift,
             "kt" | "kts" => Self::Kotlin,
+            "zig" => Self::Zig,
             "sh" | "bash" | "zsh" => Self::Shell,
             "md" | "markdown" | "txt" => Self::Markdown, // Treat txt as markdown-like
             "json" => Self::Json,
@@ -64,7 +66,10 @@ impl Language {
         }
     }
 
-    /// Check if this language is supported for semantic chunking
+    /// Check if this language has a built-in tree-sitter grammar
+    ///
+    /// Grammars registered at runtime (see `chunker::register_grammar`) or enabled
+    /// through `lang-*` features are not included here.
     #[allow(dead_code)] // Reserved for tree-sitter chunking feature
     pub fn supports_tree_sitter(&self) -> bool {
         matches!(
@@ -86,6 +91,66 @@ impl Language {
         !matches!(self, Self::Unknown)
     }
 
+    /// Parse a language from its name (case-insensitive), e.g. "rust", "C++", "csharp"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "rust" => Some(Self::Rust),
+            "python" => Some(Self::Python),
+            "javascript" => Some(Self::JavaScript),
+            "typescript" => Some(Self::TypeScript),
+            "go" => Some(Self::Go),
+            "java" => Some(Self::Java),
+            "c" => Some(Self::C),
+            "c++" | "cpp" => Some(Self::Cpp),
+            "c#" | "csharp" => Some(Self::CSharp),
+            "ruby" => Some(Self::Ruby),
+            "php" => Some(Self::Php),
+            "swift" => Some(Self::Swift),
+            "kotlin" => Some(Self::Kotlin),
+            "zig" => Some(Self::Zig),
+            "shell" => Some(Self::Shell),
+            "markdown" => Some(Self::Markdown),
+            "json" => Some(Self::Json),
+            "yaml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "sql" => Some(Self::Sql),
+            "html" => Some(Self::Html),
+            "css" => Some(Self::Css),
+            _ => None,
+        }
+    }
+
+    /// Whether a tree-sitter node of `kind` is a comment or a string/char literal
+    ///
+    /// Used to build the code-only text that is embedded with `--strip-comments`.
+    pub fn is_comment_or_string_node(&self, kind: &str) -> bool {
+        if kind.contains("comment") {
+            return true;
+        }
+        match self {
+            Self::Rust | Self::C | Self::Cpp => matches!(
+                kind,
+                "string_literal" | "raw_string_literal" | "char_literal"
+            ),
+            Self::Python => kind == "string",
+            Self::JavaScript | Self::TypeScript => matches!(kind, "string" | "template_string"),
+            Self::Go => matches!(
+                kind,
+                "interpreted_string_literal" | "raw_string_literal" | "rune_literal"
+            ),
+            Self::Java => matches!(kind, "string_literal" | "character_literal" | "text_block"),
+            Self::CSharp => matches!(
+                kind,
+                "string_literal"
+                    | "verbatim_string_literal"
+                    | "raw_string_literal"
+                    | "interpolated_string_expression"
+                    | "character_literal"
+            ),
+            _ => kind == "string" || kind.ends_with("string_literal"),
+        }
+    }
+
     /// Get the language name as a string
     pub fn name(&self) -> &'static str {
         match self {
@@ -102,6 +167,7 @@ impl Language {
             Self::Php => "PHP",
             Self::Swift => "Swift",
             Self::Kotlin => "Kotlin",
+            Self::Zig => "Zig",
             Self::Shell => "Shell",
             Self::Markdown => "Markdown",
             Self::Json => "JSON",
@@ -129,12 +195,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_comment_or_string_node() {
+        assert!(Language::Rust.is_comment_or_string_node("line_comment"));
+        assert!(Language::Rust.is_comment_or_string_node("raw_string_literal"));
+        assert!(!Language::Rust.is_comment_or_string_node("identifier"));
+        assert!(Language::Python.is_comment_or_string_node("string"));
+        assert!(Language::TypeScript.is_comment_or_string_node("template_string"));
+        assert!(Language::Go.is_comment_or_string_node("rune_literal"));
+        assert!(!Language::Go.is_comment_or_string_node("string"));
+    }
+
     #[test]
     fn test_python_detection() {
         assert_eq!(Language::from_extension("py"), Language::Python);
         assert_eq!(Language::from_extension("pyi"), Language::Python);
     }
 
+    #[test]
+    fn test_zig_detection() {
+        assert_eq!(Language::from_extension("zig"), Language::Zig);
+        assert_eq!(Language::from_name("Zig"), Some(Language::Zig));
+    }
+
     #[test]
     fn test_typescript_detection() {
         assert_eq!(Language::from_extension("ts"), Language::TypeScript);
@@ -197,9 +280,22 @@ mod tests {
         assert_eq!(Language::Unknown.name(), "Unknown");
     }
 
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Language::from_name("rust"), Some(Language::Rust));
+        assert_eq!(Language::from_name("Java"), Some(Language::Java));
+        assert_eq!(Language::from_name("C++"), Some(Language::Cpp));
+        assert_eq!(Language::from_name("csharp"), Some(Language::CSharp));
+        assert_eq!(Language::from_name("unknown"), None);
+        assert_eq!(Language::from_name("cobol"), None);
+    }
+
     #[test]
     fn test_from_path_no_extension() {
-        assert_eq!(Language::from_path(&PathBuf::from("Makefile")), Language::Unknown);
+        assert_eq!(
+            Language::from_path(&PathBuf::from("Makefile")),
+            Language::Unknown
+        );
     }
 
     #[test]
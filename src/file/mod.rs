@@ -1,17 +1,36 @@
 use anyhow::Result;
+use ignore::overrides::{Override, OverrideBuilder};
 use ignore::WalkBuilder;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
-use crate::constants::ALWAYS_EXCLUDED;
+use crate::constants::{ALWAYS_EXCLUDED, DEFAULT_MAX_FILE_SIZE};
 
 mod binary;
+mod config;
 mod language;
 
-pub use binary::is_binary_file;
+pub use binary::{is_binary_file, needs_binary_sniff};
+pub use config::{ProjectConfig, WatchConfig};
 pub use language::Language;
 
+/// Matcher for `--include` globs relative to `root`; `None` without globs
+///
+/// A path is included when `matched(path, false).is_whitelist()`.
+pub fn include_matcher(root: &Path, globs: &[String]) -> Result<Option<Override>> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in globs {
+        overrides
+            .add(glob)
+            .map_err(|e| anyhow::anyhow!("Invalid --include glob '{}': {}", glob, e))?;
+    }
+    Ok(Some(overrides.build()?))
+}
+
 /// Information about a discovered file
 #[derive(Debug, Clone)]
 pub struct FileInfo {
@@ -28,6 +47,11 @@ pub struct WalkStats {
     pub indexable_files: usize,
     pub skipped_binary: usize,
     pub skipped_ignored: usize,
+    pub skipped_too_large: usize,
+    /// Files left out because they match no `--include` glob
+    pub skipped_not_included: usize,
+    /// Files whose content was read to rule out binary data
+    pub content_sniffed: usize,
     pub files_by_language: HashMap<Language, usize>,
     pub total_size_bytes: u64,
 }
@@ -47,6 +71,32 @@ impl WalkStats {
         self.skipped_binary += 1;
     }
 
+    pub fn add_skipped_too_large(&mut self) {
+        self.skipped_too_large += 1;
+    }
+
+    /// Language with the most indexable files, used for the search-time language boost.
+    ///
+    /// Docs and config (Markdown, JSON, YAML, TOML) don't count, so a repo with a
+    /// large `docs/` tree still reports its source language. Ties go to the name
+    /// that sorts first so repeated runs agree.
+    pub fn primary_language(&self) -> Option<Language> {
+        self.files_by_language
+            .iter()
+            .filter(|(lang, _)| {
+                !matches!(
+                    lang,
+                    Language::Markdown
+                        | Language::Json
+                        | Language::Yaml
+                        | Language::Toml
+                        | Language::Unknown
+                )
+            })
+            .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.name().cmp(a.0.name())))
+            .map(|(lang, _)| *lang)
+    }
+
     pub fn total_size_mb(&self) -> f64 {
         self.total_size_bytes as f64 / (1024.0 * 1024.0)
     }
@@ -56,6 +106,13 @@ impl WalkStats {
         info!("  Total files found: {}", self.total_files);
         info!("  Indexable files: {}", self.indexable_files);
         info!("  Binary/skipped: {}", self.skipped_binary);
+        if self.skipped_too_large > 0 {
+            info!("  Too large (skipped): {}", self.skipped_too_large);
+        }
+        debug!(
+            "  Content-sniffed for binary data: {}",
+            self.content_sniffed
+        );
         info!("  Total size: {:.2} MB", self.total_size_mb());
 
         if !self.files_by_language.is_empty() {
@@ -70,10 +127,25 @@ impl WalkStats {
 }
 
 /// Smart file walker that respects .gitignore and .codesearchignore
+///
+/// Precedence, highest first: always-excluded directories (`node_modules`,
+/// `.git`, ...), `exclude` globs from `.codesearch.toml`, `.codesearchignore`
+/// and `.osgrepignore`, then `.ignore` and the gitignore files. A `!pattern`
+/// in `.codesearchignore` therefore re-includes files that `.gitignore`
+/// excludes, but not files inside a gitignored directory unless the
+/// directory is re-included too.
+///
+/// `--include` globs narrow the result further: when any are given, only
+/// files matching one of them are kept, and everything above still applies.
 pub struct FileWalker {
     root: PathBuf,
     respect_gitignore: bool,
     include_hidden: bool,
+    max_file_size: Option<u64>,
+    exclude_globs: Vec<String>,
+    include_extensions: Vec<String>,
+    include_globs: Vec<String>,
+    sniff_all: bool,
 }
 
 impl FileWalker {
@@ -82,7 +154,50 @@ impl FileWalker {
             root: root.into(),
             respect_gitignore: true,
             include_hidden: false,
+            max_file_size: max_file_size_from_env(),
+            exclude_globs: Vec::new(),
+            include_extensions: Vec::new(),
+            include_globs: Vec::new(),
+            sniff_all: false,
+        }
+    }
+
+    /// Apply a project config (`.codesearch.toml`): extra excludes, extension
+    /// allowlist and max file size
+    pub fn with_config(mut self, config: &ProjectConfig) -> Self {
+        self.exclude_globs.extend(config.exclude.iter().cloned());
+        self.include_extensions.extend(
+            config
+                .include_extensions
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_lowercase()),
+        );
+        if let Some(max) = config.max_file_size {
+            self.max_file_size = if max == 0 { None } else { Some(max) };
         }
+        self
+    }
+
+    /// Only keep files matching at least one of `globs` (relative to the root,
+    /// gitignore syntax, e.g. `**/*.rs` or `src/**`); no globs keeps everything
+    pub fn with_include_globs(mut self, globs: &[String]) -> Self {
+        self.include_globs.extend(globs.iter().cloned());
+        self
+    }
+
+    /// Set the maximum file size in bytes (None = no limit)
+    #[allow(dead_code)] // Library API; the CLI relies on CODESEARCH_MAX_FILE_SIZE
+    pub fn with_max_file_size(mut self, max_file_size: Option<u64>) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Read every indexable file to rule out binary data, not only ambiguous
+    /// or large ones (the behavior before the extension fast path, for comparing walk times)
+    #[allow(dead_code)] // Library API; used by the file_walker_demo example
+    pub fn with_sniff_all(mut self, sniff_all: bool) -> Self {
+        self.sniff_all = sniff_all;
+        self
     }
 
     /// Walk files, returning detailed file information
@@ -117,6 +232,19 @@ impl FileWalker {
                 true
             });
 
+        // Extra excludes from .codesearch.toml (negated overrides = ignore)
+        if !self.exclude_globs.is_empty() {
+            let mut overrides = OverrideBuilder::new(&self.root);
+            for glob in &self.exclude_globs {
+                overrides.add(&format!("!{}", glob))?;
+            }
+            builder.overrides(overrides.build()?);
+        }
+
+        // `--include` allowlist. Matched per file rather than installed as a
+        // builder override: a whitelist override would win over .gitignore.
+        let include = include_matcher(&self.root, &self.include_globs)?;
+
         for result in builder.build() {
             match result {
                 Ok(entry) => {
@@ -129,24 +257,54 @@ impl FileWalker {
                     }
 
                     let path = entry.path();
+                    if let Some(include) = &include {
+                        if !include.matched(path, false).is_whitelist() {
+                            stats.skipped_not_included += 1;
+                            continue;
+                        }
+                    }
+                    // Extension allowlist from .codesearch.toml
+                    if !self.include_extensions.is_empty() {
+                        let ext = path
+                            .extension()
+                            .and_then(|e| e.to_str())
+                            .map(|e| e.to_lowercase())
+                            .unwrap_or_default();
+                        if !self.include_extensions.contains(&ext) {
+                            stats.skipped_ignored += 1;
+                            continue;
+                        }
+                    }
 
-                    // Check if file is binary
-                    if is_binary_file(path) {
-                        stats.add_skipped_binary();
-                        debug!("Skipping binary file: {}", path.display());
-                        continue;
+                    let size = entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
+
+                    // Skip oversized files before reading them
+                    if let Some(max) = self.max_file_size {
+                        if size > max {
+                            stats.add_skipped_too_large();
+                            debug!("Skipping large file ({} bytes): {}", size, path.display());
+                            continue;
+                        }
                     }
 
-                    // Get file info
                     let language = Language::from_path(path);
 
-                    // Skip unknown/non-indexable files
+                    // Skip unknown/non-indexable files without reading them
                     if !language.is_indexable() {
                         stats.add_skipped_binary();
                         continue;
                     }
 
-                    let size = entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
+                    // Trust the extension of ordinary source files; only sniff
+                    // ambiguous extensions and unusually large files
+                    if self.sniff_all || needs_binary_sniff(path, size) {
+                        stats.content_sniffed += 1;
+                        if is_binary_file(path) {
+                            stats.add_skipped_binary();
+                            debug!("Skipping binary file: {}", path.display());
+                            continue;
+                        }
+                    }
 
                     let file_info = FileInfo {
                         path: path.to_path_buf(),
@@ -176,10 +334,23 @@ impl FileWalker {
     }
 }
 
+/// Read the max file size from `CODESEARCH_MAX_FILE_SIZE` (bytes, 0 = no limit)
+fn max_file_size_from_env() -> Option<u64> {
+    match std::env::var("CODESEARCH_MAX_FILE_SIZE")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+    {
+        Some(0) => None,
+        Some(size) => Some(size),
+        None => Some(DEFAULT_MAX_FILE_SIZE),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
+    use std::path::Path;
     use tempfile::TempDir;
 
     #[test]
@@ -217,6 +388,33 @@ mod tests {
         assert!(stats.skipped_binary > 0);
     }
 
+    #[test]
+    fn test_binary_sniff_only_for_ambiguous_extensions() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        // An MPEG transport stream shares TypeScript's extension
+        fs::write(dir.path().join("clip.ts"), [0x47u8, 0, 0x11, 0, 0xFF]).unwrap();
+        fs::write(dir.path().join("app.ts"), "export const x = 1;").unwrap();
+
+        let walker = FileWalker::new(dir.path());
+        let (files, stats) = walker.walk().unwrap();
+
+        let mut names: Vec<_> = files
+            .iter()
+            .map(|f| f.path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["app.ts", "main.rs"]);
+        assert_eq!(stats.content_sniffed, 2);
+
+        let (files, stats) = FileWalker::new(dir.path())
+            .with_sniff_all(true)
+            .walk()
+            .unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(stats.content_sniffed, 3);
+    }
+
     #[test]
     fn test_language_detection() {
         let dir = TempDir::new().unwrap();
@@ -234,6 +432,24 @@ mod tests {
         assert_eq!(stats.files_by_language.get(&Language::JavaScript), Some(&1));
     }
 
+    #[test]
+    fn test_primary_language_ignores_docs_and_config() {
+        let dir = TempDir::new().unwrap();
+
+        fs::write(dir.path().join("main.py"), "pass").unwrap();
+        fs::write(dir.path().join("util.py"), "pass").unwrap();
+        fs::write(dir.path().join("app.js"), "console.log()").unwrap();
+        for name in ["a.md", "b.md", "c.md", "d.json"] {
+            fs::write(dir.path().join(name), "text").unwrap();
+        }
+
+        let walker = FileWalker::new(dir.path());
+        let (_, stats) = walker.walk().unwrap();
+
+        assert_eq!(stats.primary_language(), Some(Language::Python));
+        assert_eq!(WalkStats::new().primary_language(), None);
+    }
+
     #[test]
     fn test_excluded_directories() {
         let dir = TempDir::new().unwrap();
@@ -253,4 +469,146 @@ mod tests {
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].path.file_name().unwrap(), "index.js");
     }
+
+    #[test]
+    fn test_skip_large_files() {
+        let dir = TempDir::new().unwrap();
+
+        fs::write(dir.path().join("small.rs"), "fn main() {}").unwrap();
+        fs::write(dir.path().join("large.rs"), "// x\n".repeat(100)).unwrap();
+
+        let walker = FileWalker::new(dir.path()).with_max_file_size(Some(64));
+        let (files, stats) = walker.walk().unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path.file_name().unwrap(), "small.rs");
+        assert_eq!(stats.skipped_too_large, 1);
+
+        let walker = FileWalker::new(dir.path()).with_max_file_size(None);
+        let (files, stats) = walker.walk().unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(stats.skipped_too_large, 0);
+    }
+
+    #[test]
+    fn test_walker_with_project_config() {
+        let dir = TempDir::new().unwrap();
+
+        let generated = dir.path().join("generated");
+        fs::create_dir(&generated).unwrap();
+        fs::write(generated.join("api.rs"), "fn generated() {}").unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.path().join("script.py"), "pass").unwrap();
+
+        let config = ProjectConfig {
+            exclude: vec!["generated/".to_string()],
+            include_extensions: vec![".RS".to_string()],
+            max_file_size: None,
+            ..Default::default()
+        };
+        let walker = FileWalker::new(dir.path()).with_config(&config);
+        let (files, stats) = walker.walk().unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path.file_name().unwrap(), "main.rs");
+        assert_eq!(stats.skipped_ignored, 1);
+    }
+
+    /// Walked paths relative to `root`, with forward slashes, sorted
+    fn walked(walker: &FileWalker, root: &Path) -> Vec<String> {
+        let (files, _) = walker.walk().unwrap();
+        let mut paths: Vec<String> = files
+            .iter()
+            .map(|f| {
+                let rel = f.path.strip_prefix(root).unwrap();
+                rel.to_string_lossy().replace('\\', "/")
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_codesearchignore_negation_reincludes_gitignored_files() {
+        let dir = TempDir::new().unwrap();
+        // .gitignore is only honored inside a git repository
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.gen.rs\n").unwrap();
+        fs::write(dir.path().join(".codesearchignore"), "!important.gen.rs\n").unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.path().join("important.gen.rs"), "fn important() {}").unwrap();
+        fs::write(dir.path().join("noise.gen.rs"), "fn noise() {}").unwrap();
+
+        let walker = FileWalker::new(dir.path());
+        assert_eq!(
+            walked(&walker, dir.path()),
+            vec!["important.gen.rs", "main.rs"]
+        );
+
+        // Excludes from .codesearch.toml still win over a re-include
+        let config = ProjectConfig {
+            exclude: vec!["important.gen.rs".to_string()],
+            ..Default::default()
+        };
+        let walker = FileWalker::new(dir.path()).with_config(&config);
+        assert_eq!(walked(&walker, dir.path()), vec!["main.rs"]);
+    }
+
+    #[test]
+    fn test_include_globs_narrow_the_indexable_set() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "pub fn lib() {}").unwrap();
+        fs::write(dir.path().join("src/nested/mod.rs"), "fn nested() {}").unwrap();
+        fs::write(dir.path().join("src/app.py"), "def app(): pass").unwrap();
+        fs::write(dir.path().join("target/build.rs"), "fn build() {}").unwrap();
+
+        let walker = FileWalker::new(dir.path()).with_include_globs(&["**/*.rs".to_string()]);
+        assert_eq!(
+            walked(&walker, dir.path()),
+            vec!["src/lib.rs", "src/nested/mod.rs"]
+        );
+        let (_, stats) = walker.walk().unwrap();
+        // Only app.py: target/ is gitignored and never reached
+        assert_eq!(stats.skipped_not_included, 1);
+
+        // Several globs are a union
+        let walker = FileWalker::new(dir.path())
+            .with_include_globs(&["src/nested/**".to_string(), "*.py".to_string()]);
+        assert_eq!(
+            walked(&walker, dir.path()),
+            vec!["src/app.py", "src/nested/mod.rs"]
+        );
+
+        let walker = FileWalker::new(dir.path()).with_include_globs(&["src/[".to_string()]);
+        assert!(walker.walk().is_err());
+    }
+
+    #[test]
+    fn test_codesearchignore_negation_inside_gitignored_directory() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "generated/\n").unwrap();
+        let generated = dir.path().join("generated");
+        fs::create_dir(&generated).unwrap();
+        fs::write(generated.join("keep.rs"), "fn keep() {}").unwrap();
+        fs::write(generated.join("skip.rs"), "fn skip() {}").unwrap();
+
+        // Re-including only the file doesn't help: the directory is never entered
+        fs::write(dir.path().join(".codesearchignore"), "!generated/keep.rs\n").unwrap();
+        let walker = FileWalker::new(dir.path());
+        assert!(walked(&walker, dir.path()).is_empty());
+
+        // Re-include the directory, ignore its contents, then re-include the file
+        fs::write(
+            dir.path().join(".codesearchignore"),
+            "!generated/\ngenerated/*\n!generated/keep.rs\n",
+        )
+        .unwrap();
+        assert_eq!(walked(&walker, dir.path()), vec!["generated/keep.rs"]);
+    }
 }
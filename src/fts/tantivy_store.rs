@@ -14,11 +14,55 @@ use tantivy::{
     directory::MmapDirectory,
     merge_policy::NoMergePolicy,
     query::QueryParser,
-    schema::{Field, NumericOptions, Schema, Value, STORED, STRING, TEXT},
+    schema::{
+        Field, IndexRecordOption, NumericOptions, Schema, TextFieldIndexing, TextOptions, Value,
+        STORED, STRING, TEXT,
+    },
+    tokenizer::{RegexTokenizer, RemoveLongFilter, TextAnalyzer},
     Index, IndexReader, IndexSettings, IndexWriter, TantivyDocument, Term,
 };
 
 use crate::chunker::ChunkKind;
+use crate::constants::{DEFAULT_FTS_KIND_BOOST, DEFAULT_FTS_SIGNATURE_BOOST};
+use crate::error::{CodeSearchError, Result as CsResult};
+
+/// Version of the Tantivy schema built by [`FtsStore::new`]
+///
+/// Bump this whenever the fields or their options change: an index written with
+/// another version is reported as [`CodeSearchError::FtsOutdated`] so it can be
+/// rebuilt from the stored chunks instead of failing to open.
+///
+/// Version 2 added the case-preserving `content_cs`/`signature_cs` fields.
+pub const FTS_SCHEMA_VERSION: u32 = 2;
+
+/// File inside `fts/` holding the schema version the index was built with
+const SCHEMA_VERSION_FILE: &str = "schema_version";
+
+/// Tokenizer for the case-sensitive fields: whole identifiers, case preserved
+const CASE_SENSITIVE_TOKENIZER: &str = "code_cs";
+
+/// Register [`CASE_SENSITIVE_TOKENIZER`] on `index`
+///
+/// Unlike the default tokenizer this keeps `_` inside tokens and doesn't
+/// lowercase, so `getUser` and `get_user` are indexed as single, exact terms.
+fn register_case_sensitive_tokenizer(index: &Index) -> Result<()> {
+    let analyzer = TextAnalyzer::builder(RegexTokenizer::new(r"[\p{L}\p{N}_]+")?)
+        .filter(RemoveLongFilter::limit(64))
+        .build();
+    index
+        .tokenizers()
+        .register(CASE_SENSITIVE_TOKENIZER, analyzer);
+    Ok(())
+}
+
+/// Read a field boost from the environment, ignoring non-positive or invalid values
+fn boost_from_env(var: &str, default: f32) -> f32 {
+    std::env::var(var)
+        .ok()
+        .and_then(|s| s.parse::<f32>().ok())
+        .filter(|b| b.is_finite() && *b > 0.0)
+        .unwrap_or(default)
+}
 
 /// Result from FTS search
 #[derive(Debug, Clone)]
@@ -45,16 +89,29 @@ pub struct FtsStore {
     path_field: Field,
     signature_field: Field,
     kind_field: Field,
+    // Case-preserving copies of content and signature for `search_exact_cs()`
+    content_cs_field: Field,
+    signature_cs_field: Field,
+    // BM25 field boosts for `search()`
+    signature_boost: f32,
+    kind_boost: f32,
 }
 
 impl FtsStore {
     /// Create or open an FTS index at the given path.
     ///
     /// Opens in a mode that supports both reading and writing.
-    /// Writer is lazy-initialized on first write operation.
-    pub fn new(db_path: &Path) -> Result<Self> {
+    /// Writer is lazy-initialized on first write operation. An index that exists
+    /// but can't be opened is reported as [`CodeSearchError::Corrupt`].
+    /// Fails with [`CodeSearchError::FtsOutdated`] if the existing index was built
+    /// with another [`FTS_SCHEMA_VERSION`].
+    pub fn new(db_path: &Path) -> CsResult<Self> {
+        Self::check_schema(db_path)?;
+
         let fts_path = db_path.join("fts");
-        std::fs::create_dir_all(&fts_path)?;
+        let is_new = !fts_path.join("meta.json").exists();
+        std::fs::create_dir_all(&fts_path)
+            .map_err(|e| CodeSearchError::io(&fts_path, e.to_string()))?;
 
         // Build schema
         let mut schema_builder = Schema::builder();
@@ -77,13 +134,32 @@ impl FtsStore {
         // Kind - stored for filtering (function, class, etc)
         let kind_field = schema_builder.add_text_field("kind", STRING | STORED);
 
+        // Content and signature again, without lowercasing, for case-sensitive exact matches
+        let case_sensitive = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(CASE_SENSITIVE_TOKENIZER)
+                .set_index_option(IndexRecordOption::Basic),
+        );
+        let content_cs_field = schema_builder.add_text_field("content_cs", case_sensitive.clone());
+        let signature_cs_field = schema_builder.add_text_field("signature_cs", case_sensitive);
+
         let schema = schema_builder.build();
 
         // Open or create index with retry logic for Windows file locking
-        let index = Self::open_or_create_index_with_retry(&fts_path, &schema)?;
+        let index = Self::open_or_create_index_with_retry(&fts_path, &schema)
+            .map_err(|e| CodeSearchError::corrupt(&fts_path, e.to_string()))?;
+        register_case_sensitive_tokenizer(&index)
+            .map_err(|e| CodeSearchError::corrupt(&fts_path, e.to_string()))?;
+        if is_new {
+            let version_path = fts_path.join(SCHEMA_VERSION_FILE);
+            std::fs::write(&version_path, FTS_SCHEMA_VERSION.to_string())
+                .map_err(|e| CodeSearchError::io(&version_path, e.to_string()))?;
+        }
 
         // Create reader for searching
-        let reader = index.reader()?;
+        let reader = index
+            .reader()
+            .map_err(|e| CodeSearchError::corrupt(&fts_path, e.to_string()))?;
 
         Ok(Self {
             index,
@@ -95,9 +171,45 @@ impl FtsStore {
             path_field,
             signature_field,
             kind_field,
+            content_cs_field,
+            signature_cs_field,
+            signature_boost: boost_from_env(
+                "CODESEARCH_FTS_SIGNATURE_BOOST",
+                DEFAULT_FTS_SIGNATURE_BOOST,
+            ),
+            kind_boost: boost_from_env("CODESEARCH_FTS_KIND_BOOST", DEFAULT_FTS_KIND_BOOST),
         })
     }
 
+    /// Check that an existing FTS index under `db_path` matches [`FTS_SCHEMA_VERSION`]
+    ///
+    /// Indexes created before versioning have no version file and use schema 1.
+    /// A missing index is fine (it will be created); only a mismatch is an error.
+    pub fn check_schema(db_path: &Path) -> CsResult<()> {
+        let fts_path = db_path.join("fts");
+        if !fts_path.join("meta.json").exists() {
+            return Ok(());
+        }
+        let found = match std::fs::read_to_string(fts_path.join(SCHEMA_VERSION_FILE)) {
+            Ok(s) => s.trim().parse().unwrap_or(0),
+            Err(_) => 1,
+        };
+        if found != FTS_SCHEMA_VERSION {
+            tracing::warn!(
+                "FTS index at {} has schema version {}, expected {}",
+                fts_path.display(),
+                found,
+                FTS_SCHEMA_VERSION
+            );
+            return Err(CodeSearchError::fts_outdated(
+                fts_path,
+                found,
+                FTS_SCHEMA_VERSION,
+            ));
+        }
+        Ok(())
+    }
+
     /// Create or open an FTS index with writer ready for indexing.
     ///
     /// Use this when you know you'll be writing immediately (e.g., during indexing).
@@ -233,14 +345,18 @@ impl FtsStore {
         let path_field = self.path_field;
         let signature_field = self.signature_field;
         let kind_field = self.kind_field;
+        let content_cs_field = self.content_cs_field;
+        let signature_cs_field = self.signature_cs_field;
 
         let mut doc = TantivyDocument::new();
         doc.add_u64(chunk_id_field, chunk_id as u64);
         doc.add_text(content_field, content);
+        doc.add_text(content_cs_field, content);
         doc.add_text(path_field, path);
         doc.add_text(kind_field, kind);
         if let Some(sig) = signature {
             doc.add_text(signature_field, sig);
+            doc.add_text(signature_cs_field, sig);
         }
 
         let writer = self.writer.as_mut().unwrap();
@@ -264,10 +380,12 @@ impl FtsStore {
                     let mut retry_doc = TantivyDocument::new();
                     retry_doc.add_u64(chunk_id_field, chunk_id as u64);
                     retry_doc.add_text(content_field, content);
+                    retry_doc.add_text(content_cs_field, content);
                     retry_doc.add_text(path_field, path);
                     retry_doc.add_text(kind_field, kind);
                     if let Some(sig) = signature {
                         retry_doc.add_text(signature_field, sig);
+                        retry_doc.add_text(signature_cs_field, sig);
                     }
 
                     let writer = self.writer.as_mut().unwrap();
@@ -406,11 +524,11 @@ impl FtsStore {
         );
 
         // Boost signature field for better matching of function names, class names, etc.
-        query_parser.set_field_boost(self.signature_field, 2.0);
+        query_parser.set_field_boost(self.signature_field, self.signature_boost);
 
         // Boost kind field when structural intent is detected
         if let Some(ref _kind) = target_kind {
-            query_parser.set_field_boost(self.kind_field, 3.0); // High boost for kind field
+            query_parser.set_field_boost(self.kind_field, self.kind_boost);
         }
 
         // Parse query, fall back to match-all on error
@@ -462,18 +580,53 @@ impl FtsStore {
         identifier: &str,
         limit: usize,
         target_kind: Option<ChunkKind>,
+    ) -> Result<Vec<FtsResult>> {
+        self.search_exact_in(
+            self.signature_field,
+            self.content_field,
+            identifier,
+            limit,
+            target_kind,
+        )
+    }
+
+    /// Case-sensitive variant of [`search_exact`](Self::search_exact)
+    ///
+    /// Matches whole identifiers against the case-preserving fields, so
+    /// `getUser` doesn't match `getuser` or `GetUser`.
+    pub fn search_exact_cs(
+        &self,
+        identifier: &str,
+        limit: usize,
+        target_kind: Option<ChunkKind>,
+    ) -> Result<Vec<FtsResult>> {
+        self.search_exact_in(
+            self.signature_cs_field,
+            self.content_cs_field,
+            identifier,
+            limit,
+            target_kind,
+        )
+    }
+
+    fn search_exact_in(
+        &self,
+        signature_field: Field,
+        content_field: Field,
+        identifier: &str,
+        limit: usize,
+        target_kind: Option<ChunkKind>,
     ) -> Result<Vec<FtsResult>> {
         use tantivy::query::{BooleanQuery, BoostQuery, Occur, TermQuery};
-        use tantivy::schema::IndexRecordOption;
 
         let searcher = self.reader.searcher();
 
         // Search signature field with exact term
-        let term = Term::from_field_text(self.signature_field, identifier);
+        let term = Term::from_field_text(signature_field, identifier);
         let term_query = TermQuery::new(term, IndexRecordOption::Basic);
 
         // Also search content field for the identifier as a phrase
-        let content_term = Term::from_field_text(self.content_field, identifier);
+        let content_term = Term::from_field_text(content_field, identifier);
         let content_query = TermQuery::new(content_term, IndexRecordOption::Basic);
 
         // Boost signature matches 3x over content matches
@@ -521,6 +674,92 @@ impl FtsStore {
         Ok(results)
     }
 
+    /// Search for an exact phrase in chunk content (word order preserved)
+    ///
+    /// The phrase is tokenized with the content field's tokenizer; single-token
+    /// phrases fall back to a plain term query.
+    pub fn search_phrase(&self, phrase: &str, limit: usize) -> Result<Vec<FtsResult>> {
+        use tantivy::query::{PhraseQuery, Query, TermQuery};
+        use tantivy::tokenizer::TokenStream;
+
+        let searcher = self.reader.searcher();
+
+        let mut tokenizer = self.index.tokenizer_for_field(self.content_field)?;
+        let mut token_stream = tokenizer.token_stream(phrase);
+        let mut terms = Vec::new();
+        while let Some(token) = token_stream.next() {
+            terms.push(Term::from_field_text(self.content_field, &token.text));
+        }
+
+        let query: Box<dyn Query> = match terms.len() {
+            0 => return Ok(Vec::new()),
+            1 => Box::new(TermQuery::new(
+                terms.remove(0),
+                IndexRecordOption::WithFreqs,
+            )),
+            _ => Box::new(PhraseQuery::new(terms)),
+        };
+
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(limit))?;
+
+        let mut results = Vec::with_capacity(top_docs.len());
+        for (score, doc_address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+
+            if let Some(chunk_id) = doc.get_first(self.chunk_id_field) {
+                if let Some(id) = chunk_id.as_u64() {
+                    results.push(FtsResult {
+                        chunk_id: id as u32,
+                        score,
+                    });
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Fuzzy search for a near-miss identifier (typos, partial names)
+    ///
+    /// Matches indexed terms within `distance` edits (clamped to 1..=2) of the
+    /// identifier, treating the identifier as a prefix so partial names like
+    /// `UserServ` still match `userservice`. Signature matches are boosted 3x
+    /// over content matches, mirroring `search_exact`.
+    pub fn search_fuzzy(&self, term: &str, distance: u8, limit: usize) -> Result<Vec<FtsResult>> {
+        use tantivy::query::{BooleanQuery, BoostQuery, FuzzyTermQuery};
+
+        let searcher = self.reader.searcher();
+        let distance = distance.clamp(1, 2);
+        // Indexed TEXT fields are lowercased by the default tokenizer
+        let term_text = term.to_lowercase();
+
+        let sig_term = Term::from_field_text(self.signature_field, &term_text);
+        let sig_query = FuzzyTermQuery::new_prefix(sig_term, distance, true);
+        let content_term = Term::from_field_text(self.content_field, &term_text);
+        let content_query = FuzzyTermQuery::new_prefix(content_term, distance, true);
+
+        let boosted_sig = BoostQuery::new(Box::new(sig_query), 3.0);
+        let combined = BooleanQuery::union(vec![Box::new(boosted_sig), Box::new(content_query)]);
+
+        let top_docs = searcher.search(&combined, &TopDocs::with_limit(limit))?;
+
+        let mut results = Vec::with_capacity(top_docs.len());
+        for (score, doc_address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+
+            if let Some(chunk_id) = doc.get_first(self.chunk_id_field) {
+                if let Some(id) = chunk_id.as_u64() {
+                    results.push(FtsResult {
+                        chunk_id: id as u32,
+                        score,
+                    });
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
     /// Get statistics about the index
     pub fn stats(&self) -> Result<FtsStats> {
         let searcher = self.reader.searcher();
@@ -606,6 +845,155 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_fts_signature_match_outranks_comment_mention() -> Result<()> {
+        let dir = tempdir()?;
+        let mut store = FtsStore::new(dir.path())?;
+
+        store.add_chunk(
+            1,
+            "// authenticate the user first, then authenticate the session\nfn handle(req: Request) { route(req) }",
+            "src/handler.rs",
+            Some("fn handle(req: Request)"),
+            "Function",
+        )?;
+        store.add_chunk(
+            2,
+            "fn authenticate(user: &User) -> bool { user.token.is_valid() }",
+            "src/auth.rs",
+            Some("fn authenticate(user: &User) -> bool"),
+            "Function",
+        )?;
+        store.commit()?;
+
+        let results = store.search("authenticate", 10, None)?;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].chunk_id, 2);
+
+        // A larger signature boost widens the gap
+        let default_gap = results[0].score - results[1].score;
+        store.signature_boost = DEFAULT_FTS_SIGNATURE_BOOST * 4.0;
+        let boosted = store.search("authenticate", 10, None)?;
+        assert_eq!(boosted[0].chunk_id, 2);
+        assert!(boosted[0].score - boosted[1].score > default_gap);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fts_phrase() -> Result<()> {
+        let dir = tempdir()?;
+        let mut store = FtsStore::new(dir.path())?;
+
+        store.add_chunk(
+            1,
+            "// close the connection pool after timeout",
+            "src/pool.rs",
+            None,
+            "comment",
+        )?;
+        store.add_chunk(
+            2,
+            "// timeout for the pool connection",
+            "src/net.rs",
+            None,
+            "comment",
+        )?;
+        store.commit()?;
+
+        // Word order matters
+        let results = store.search_phrase("connection pool", 10)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk_id, 1);
+
+        let results = store.search_phrase("pool connection", 10)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk_id, 2);
+
+        // Single-word phrase behaves like a term search
+        assert_eq!(store.search_phrase("timeout", 10)?.len(), 2);
+
+        // Empty phrase
+        assert!(store.search_phrase("  ", 10)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fts_fuzzy() -> Result<()> {
+        let dir = tempdir()?;
+        let mut store = FtsStore::new(dir.path())?;
+
+        store.add_chunk(
+            1,
+            "fn authenticate(token: &str) -> bool { true }",
+            "src/auth.rs",
+            Some("authenticate"),
+            "function",
+        )?;
+        store.add_chunk(
+            2,
+            "struct UserConfig { name: String }",
+            "src/config.rs",
+            Some("UserConfig"),
+            "struct",
+        )?;
+        store.commit()?;
+
+        // Partial identifier (prefix)
+        let results = store.search_fuzzy("authenticat", 1, 10)?;
+        assert_eq!(results[0].chunk_id, 1);
+
+        // Typo within edit distance
+        let results = store.search_fuzzy("UsrConfig", 1, 10)?;
+        assert_eq!(results[0].chunk_id, 2);
+
+        // Too far away
+        let results = store.search_fuzzy("xyzzy", 1, 10)?;
+        assert!(results.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fts_exact_case_sensitive() -> Result<()> {
+        let dir = tempdir()?;
+        let mut store = FtsStore::new(dir.path())?;
+
+        store.add_chunk(
+            1,
+            "fn getUser(id: u64) -> User { lookup(id) }",
+            "src/users.rs",
+            Some("fn getUser(id: u64) -> User"),
+            "Function",
+        )?;
+        store.add_chunk(
+            2,
+            "fn getuser(name: &str) -> User { find(name) }",
+            "src/legacy.rs",
+            Some("fn getuser(name: &str) -> User"),
+            "Function",
+        )?;
+        store.add_chunk(3, "let user = get_user(id);", "src/main.rs", None, "Block")?;
+        store.commit()?;
+
+        let ids = |results: Vec<FtsResult>| {
+            let mut ids: Vec<u32> = results.iter().map(|r| r.chunk_id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(store.search_exact_cs("getUser", 10, None)?), vec![1]);
+        assert_eq!(ids(store.search_exact_cs("getuser", 10, None)?), vec![2]);
+        assert_eq!(ids(store.search_exact_cs("get_user", 10, None)?), vec![3]);
+        assert!(store.search_exact_cs("GetUser", 10, None)?.is_empty());
+        assert_eq!(
+            ids(store.search_exact_cs("getUser", 10, Some(ChunkKind::Function))?),
+            vec![1]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_fts_delete() -> Result<()> {
         let dir = tempdir()?;
@@ -632,4 +1020,46 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_fts_schema_version() -> Result<()> {
+        let dir = tempdir()?;
+        let version_path = dir.path().join("fts").join(SCHEMA_VERSION_FILE);
+
+        drop(FtsStore::new(dir.path())?);
+        assert_eq!(
+            std::fs::read_to_string(&version_path)?,
+            FTS_SCHEMA_VERSION.to_string()
+        );
+
+        // Indexes from before versioning count as schema 1, which is outdated now
+        std::fs::remove_file(&version_path)?;
+        assert!(matches!(
+            FtsStore::check_schema(dir.path()),
+            Err(CodeSearchError::FtsOutdated { found: 1, .. })
+        ));
+
+        std::fs::write(&version_path, (FTS_SCHEMA_VERSION + 1).to_string())?;
+        assert!(matches!(
+            FtsStore::new(dir.path()),
+            Err(CodeSearchError::FtsOutdated { found, expected, .. })
+                if found == FTS_SCHEMA_VERSION + 1 && expected == FTS_SCHEMA_VERSION
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fts_unreadable_index_is_corrupt() -> Result<()> {
+        let dir = tempdir()?;
+        std::fs::create_dir_all(dir.path().join("fts"))?;
+        std::fs::write(dir.path().join("fts").join("meta.json"), "not json")?;
+
+        assert!(matches!(
+            FtsStore::new(dir.path()),
+            Err(CodeSearchError::Corrupt { .. })
+        ));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,171 @@
+//! Compact the vector store by rebuilding it from its live chunks
+//!
+//! Incremental updates delete and re-insert chunks, which leaves freed LMDB pages
+//! and stale arroy tree nodes behind, so the store keeps growing under watch/serve.
+//! Copying every live chunk (metadata and stored embedding) into a fresh store
+//! drops that slack without re-embedding. Chunk IDs are preserved, so the FTS
+//! index and `file_meta.json` stay valid and are left untouched.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use super::manager::acquire_writer_lock;
+use super::reembed::{staging_path_for, swap_lmdb_files};
+use crate::db_discovery::resolve_database_with_message;
+use crate::output::format_size;
+use crate::search::read_metadata;
+use crate::vectordb::VectorStore;
+
+/// Number of chunks copied per write transaction
+const COMPACT_BATCH_SIZE: usize = 1024;
+
+/// Rebuild the vector store for `path` from its live chunks and report the size change
+pub async fn compact(path: Option<PathBuf>, cancel_token: CancellationToken) -> Result<()> {
+    let (db_path, project_path) = resolve_database_with_message(path.as_deref(), "compacting")?;
+
+    let (model, dimensions, _) = read_metadata(&db_path).ok_or_else(|| {
+        anyhow!(
+            "No usable index at {}. Run `codesearch index` first.",
+            db_path.display()
+        )
+    })?;
+
+    println!("{}", "🗜️  Codesearch Compact".bright_cyan().bold());
+    println!("{}", "=".repeat(60));
+    println!("📂 Project: {}", project_path.display());
+    println!("💾 Database: {}", db_path.display());
+    println!("🧠 Model: {} ({} dims)", model, dimensions);
+
+    let _lock = acquire_writer_lock(&db_path).ok_or_else(|| {
+        anyhow!("Database is locked by another process (serve/mcp/index). Stop it and retry.")
+    })?;
+
+    let staging_path = staging_path_for(&db_path, "compact");
+    if staging_path.exists() {
+        std::fs::remove_dir_all(&staging_path)?;
+    }
+
+    let size_before = lmdb_size(&db_path);
+    let start = Instant::now();
+    let result = VectorStore::open_readonly(&db_path, dimensions)
+        .map_err(anyhow::Error::from)
+        .and_then(|source| {
+            let mut target =
+                VectorStore::new_with_metric(&staging_path, dimensions, source.metric())?;
+            let copied = copy_live_chunks(&source, &mut target, &cancel_token)?;
+            // An empty store has nothing to build (searching it errors as before)
+            if matches!(copied, Some(n) if n > 0) {
+                target.build_index()?;
+            }
+            Ok(copied)
+        });
+    let copied = match result {
+        Ok(Some(copied)) => copied,
+        Ok(None) => {
+            let _ = std::fs::remove_dir_all(&staging_path);
+            println!("\n{}", "⚠️  Compaction cancelled, index unchanged".yellow());
+            return Ok(());
+        }
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging_path);
+            return Err(e);
+        }
+    };
+
+    swap_lmdb_files(&db_path, &staging_path)?;
+    std::fs::remove_dir_all(&staging_path)?;
+    let size_after = lmdb_size(&db_path);
+
+    println!(
+        "{}",
+        format!(
+            "✅ Compacted {} chunks in {:?}: {} -> {} ({} reclaimed)",
+            copied,
+            start.elapsed(),
+            format_size(size_before),
+            format_size(size_after),
+            format_size(size_before.saturating_sub(size_after))
+        )
+        .green()
+    );
+
+    Ok(())
+}
+
+/// Copy every chunk that still has metadata and an embedding, keeping its ID
+///
+/// Returns the number of chunks copied, or `Ok(None)` if cancelled.
+fn copy_live_chunks(
+    source: &VectorStore,
+    target: &mut VectorStore,
+    cancel_token: &CancellationToken,
+) -> Result<Option<usize>> {
+    let mut copied = 0;
+    for batch in source.chunk_ids()?.chunks(COMPACT_BATCH_SIZE) {
+        if crate::constants::check_shutdown(cancel_token) {
+            return Ok(None);
+        }
+
+        let mut items = Vec::with_capacity(batch.len());
+        for &id in batch {
+            // A chunk without a stored vector can't be searched; dropping it is what a rebuild would do
+            if let (Some(meta), Some(vector)) = (source.get_chunk(id)?, source.get_vector(id)?) {
+                items.push((id, meta, vector));
+            }
+        }
+        copied += items.len();
+        target.insert_with_ids(items)?;
+    }
+    Ok(Some(copied))
+}
+
+/// On-disk size of the LMDB data file (0 if missing)
+fn lmdb_size(db_path: &Path) -> u64 {
+    std::fs::metadata(db_path.join("data.mdb"))
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunker::{Chunk, ChunkKind};
+    use crate::embed::EmbeddedChunk;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_copy_live_chunks_keeps_ids_and_skips_deleted() {
+        let temp_dir = tempdir().unwrap();
+        let mut source = VectorStore::new(&temp_dir.path().join("source.db"), 4).unwrap();
+        let chunks = (0..3)
+            .map(|i| {
+                EmbeddedChunk::new(
+                    Chunk::new(
+                        format!("fn f{}() {{}}", i),
+                        i,
+                        i + 1,
+                        ChunkKind::Function,
+                        format!("f{}.rs", i),
+                    ),
+                    vec![1.0, i as f32, 0.0, 0.0],
+                )
+            })
+            .collect();
+        let ids = source.insert_chunks_with_ids(chunks).unwrap();
+        source.delete_chunks(&[ids[1]]).unwrap();
+        source.build_index().unwrap();
+
+        let mut target = VectorStore::new(&temp_dir.path().join("target.db"), 4).unwrap();
+        let copied = copy_live_chunks(&source, &mut target, &CancellationToken::new()).unwrap();
+        assert_eq!(copied, Some(2));
+        assert_eq!(target.chunk_ids().unwrap(), vec![ids[0], ids[2]]);
+        assert_eq!(
+            target.get_vector(ids[2]).unwrap(),
+            source.get_vector(ids[2]).unwrap()
+        );
+        assert_eq!(target.get_chunk(ids[2]).unwrap().unwrap().path, "f2.rs");
+    }
+}
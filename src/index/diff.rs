@@ -0,0 +1,238 @@
+//! Compare the chunks of two indexes
+//!
+//! Used to see what a chunking or model change did to an index: chunks are
+//! matched by location (path and line range) or by content hash, and the
+//! report counts chunks only in A, only in B, and in both, per chunk kind.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::constants::DB_DIR_NAME;
+use crate::db_discovery::{is_valid_database, resolve_explicit_database};
+use crate::search::read_metadata;
+use crate::vectordb::VectorStore;
+
+/// How chunks of the two indexes are matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKey {
+    /// Same path and line range
+    Location,
+    /// Same content hash (ignores moves between files or lines)
+    Hash,
+}
+
+/// Chunk counts of one kind in both indexes
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct KindDiff {
+    pub a: usize,
+    pub b: usize,
+    pub only_a: usize,
+    pub only_b: usize,
+}
+
+/// Result of comparing two indexes
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexDiff {
+    pub total_a: usize,
+    pub total_b: usize,
+    pub only_a: usize,
+    pub only_b: usize,
+    pub common: usize,
+    /// Per chunk kind, sorted by name
+    pub kinds: BTreeMap<String, KindDiff>,
+}
+
+/// Count the chunks of `store` per (match key, kind)
+fn chunk_keys(store: &VectorStore, by: DiffKey) -> Result<HashMap<(String, String), usize>> {
+    let mut keys = HashMap::new();
+    for id in store.chunk_ids()? {
+        let Some(chunk) = store.get_chunk_as_result(id)? else {
+            continue;
+        };
+        let key = match by {
+            DiffKey::Location => format!("{}:{}-{}", chunk.path, chunk.start_line, chunk.end_line),
+            DiffKey::Hash => chunk.hash,
+        };
+        *keys.entry((key, chunk.kind)).or_insert(0) += 1;
+    }
+    Ok(keys)
+}
+
+/// Compare the chunks of `a` and `b`
+///
+/// Chunks match when both their key and kind are equal; duplicates (e.g. the
+/// same content hash in several files) are matched one to one.
+pub fn diff_stores(a: &VectorStore, b: &VectorStore, by: DiffKey) -> Result<IndexDiff> {
+    let keys_a = chunk_keys(a, by)?;
+    let mut keys_b = chunk_keys(b, by)?;
+    let mut diff = IndexDiff::default();
+
+    for ((key, kind), count_a) in keys_a {
+        let count_b = keys_b.remove(&(key, kind.clone())).unwrap_or(0);
+        let common = count_a.min(count_b);
+        let entry = diff.kinds.entry(kind).or_default();
+        entry.a += count_a;
+        entry.b += count_b;
+        entry.only_a += count_a - common;
+        entry.only_b += count_b - common;
+        diff.common += common;
+    }
+    for ((_, kind), count_b) in keys_b {
+        let entry = diff.kinds.entry(kind).or_default();
+        entry.b += count_b;
+        entry.only_b += count_b;
+    }
+
+    for kind in diff.kinds.values() {
+        diff.total_a += kind.a;
+        diff.total_b += kind.b;
+        diff.only_a += kind.only_a;
+        diff.only_b += kind.only_b;
+    }
+    Ok(diff)
+}
+
+/// Open the index at `path`, which is a database directory or a project containing one
+fn open_store(path: &Path) -> Result<(PathBuf, VectorStore)> {
+    let db_path = if is_valid_database(path) {
+        path.to_path_buf()
+    } else {
+        path.join(DB_DIR_NAME)
+    };
+    let (db_path, _) = resolve_explicit_database(&db_path)?;
+    let (_, dimensions, _) = read_metadata(&db_path).ok_or_else(|| {
+        anyhow!(
+            "Missing or unreadable {}",
+            db_path.join("metadata.json").display()
+        )
+    })?;
+    let store = VectorStore::open_readonly(&db_path, dimensions)?;
+    Ok((db_path, store))
+}
+
+/// `codesearch diff-index`: report how the chunks of two indexes differ
+pub async fn diff_index(a: PathBuf, b: PathBuf, by_hash: bool, json: bool) -> Result<()> {
+    let (db_a, store_a) = open_store(&a)?;
+    let (db_b, store_b) = open_store(&b)?;
+    let by = if by_hash {
+        DiffKey::Hash
+    } else {
+        DiffKey::Location
+    };
+    let diff = diff_stores(&store_a, &store_b, by)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    println!("{}", "🔀 Codesearch Index Diff".bright_cyan().bold());
+    println!("{}", "=".repeat(60));
+    println!("📂 A: {} ({} chunks)", db_a.display(), diff.total_a);
+    println!("📂 B: {} ({} chunks)", db_b.display(), diff.total_b);
+    println!(
+        "🔑 Matched by: {}",
+        match by {
+            DiffKey::Location => "path and line range",
+            DiffKey::Hash => "content hash",
+        }
+    );
+    println!();
+    println!("   Common: {}", diff.common);
+    println!("   Only in A: {}", diff.only_a.to_string().red());
+    println!("   Only in B: {}", diff.only_b.to_string().green());
+
+    if !diff.kinds.is_empty() {
+        println!();
+        println!(
+            "   {:<16} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "Kind", "A", "B", "Δ", "Only A", "Only B"
+        );
+        for (kind, k) in &diff.kinds {
+            println!(
+                "   {:<16} {:>8} {:>8} {:>+8} {:>8} {:>8}",
+                kind,
+                k.a,
+                k.b,
+                k.b as i64 - k.a as i64,
+                k.only_a,
+                k.only_b
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunker::{Chunk, ChunkKind};
+    use crate::embed::EmbeddedChunk;
+    use tempfile::tempdir;
+
+    fn store_with(db_path: &Path, chunks: &[(&str, &str, usize, ChunkKind)]) -> VectorStore {
+        let mut store = VectorStore::new(db_path, 2).unwrap();
+        let chunks = chunks
+            .iter()
+            .map(|(content, path, start, kind)| {
+                EmbeddedChunk::new(
+                    Chunk::new(
+                        content.to_string(),
+                        *start,
+                        start + 2,
+                        *kind,
+                        path.to_string(),
+                    ),
+                    vec![1.0, 0.0],
+                )
+            })
+            .collect();
+        store.insert_chunks_with_ids(chunks).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_diff_stores_by_location_and_hash() {
+        let temp_dir = tempdir().unwrap();
+        let a = store_with(
+            &temp_dir.path().join("a.db"),
+            &[
+                ("fn a() {}", "a.rs", 0, ChunkKind::Function),
+                ("fn b() {}", "a.rs", 4, ChunkKind::Function),
+                ("struct S;", "s.rs", 0, ChunkKind::Struct),
+            ],
+        );
+        let b = store_with(
+            &temp_dir.path().join("b.db"),
+            &[
+                ("fn a() {}", "a.rs", 0, ChunkKind::Function),
+                // Same content, moved down by a line
+                ("fn b() {}", "a.rs", 5, ChunkKind::Function),
+                ("enum E {}", "e.rs", 0, ChunkKind::Enum),
+            ],
+        );
+
+        let diff = diff_stores(&a, &b, DiffKey::Location).unwrap();
+        assert_eq!((diff.total_a, diff.total_b), (3, 3));
+        assert_eq!((diff.common, diff.only_a, diff.only_b), (1, 2, 2));
+        assert_eq!(
+            diff.kinds["Function"],
+            KindDiff {
+                a: 2,
+                b: 2,
+                only_a: 1,
+                only_b: 1
+            }
+        );
+        assert_eq!(diff.kinds["Struct"].only_a, 1);
+        assert_eq!(diff.kinds["Enum"].only_b, 1);
+
+        let diff = diff_stores(&a, &b, DiffKey::Hash).unwrap();
+        assert_eq!((diff.common, diff.only_a, diff.only_b), (2, 1, 1));
+        assert_eq!(diff.kinds["Function"].only_a, 0);
+    }
+}
@@ -0,0 +1,199 @@
+//! Dump the contents of an index as JSON Lines
+//!
+//! One object per chunk, in chunk ID order. Used for offline analysis,
+//! diffing two indexes, or feeding chunks to other tools.
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::db_discovery::resolve_database_with_message;
+use crate::info_print;
+use crate::search::read_metadata;
+use crate::vectordb::{SearchResult, VectorStore};
+
+/// A single exported chunk
+#[derive(Debug, Serialize)]
+pub struct ExportRecord {
+    pub id: u32,
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docstring: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    pub content: String,
+    pub hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+impl ExportRecord {
+    fn from_result(result: SearchResult, embedding: Option<Vec<f32>>) -> Self {
+        Self {
+            id: result.id,
+            path: result.path,
+            start_line: result.start_line,
+            end_line: result.end_line,
+            kind: result.kind,
+            signature: result.signature,
+            docstring: result.docstring,
+            context: result.context,
+            content: result.content,
+            hash: result.hash,
+            embedding,
+        }
+    }
+}
+
+/// Write every chunk in `store` to `writer` as JSON Lines
+///
+/// Returns the number of chunks written.
+pub fn write_jsonl<W: Write>(
+    store: &VectorStore,
+    writer: &mut W,
+    with_vectors: bool,
+) -> Result<usize> {
+    if with_vectors && !store.is_indexed() {
+        return Err(anyhow!(
+            "Index is not built, embeddings are unavailable. Run `codesearch index` first."
+        ));
+    }
+
+    let mut written = 0;
+    for id in store.chunk_ids()? {
+        let Some(result) = store.get_chunk_as_result(id)? else {
+            continue;
+        };
+        let embedding = if with_vectors {
+            store.get_vector(id)?
+        } else {
+            None
+        };
+        serde_json::to_writer(&mut *writer, &ExportRecord::from_result(result, embedding))?;
+        writer.write_all(b"\n")?;
+        written += 1;
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+/// Export the index for `path` to `output` (stdout when `None`)
+pub async fn export(
+    path: Option<PathBuf>,
+    output: Option<PathBuf>,
+    with_vectors: bool,
+) -> Result<()> {
+    let (db_path, _project_path) = resolve_database_with_message(path.as_deref(), "exporting")?;
+
+    if !db_path.exists() {
+        return Err(anyhow!(
+            "No database found at {}. Run `codesearch index` first.",
+            db_path.display()
+        ));
+    }
+
+    let (_, dimensions, _) = read_metadata(&db_path).ok_or_else(|| {
+        anyhow!(
+            "Missing or unreadable {}",
+            db_path.join("metadata.json").display()
+        )
+    })?;
+    let store = VectorStore::open_readonly(&db_path, dimensions)?;
+
+    let written = match &output {
+        Some(file) => {
+            let mut writer = BufWriter::new(File::create(file)?);
+            write_jsonl(&store, &mut writer, with_vectors)?
+        }
+        None => {
+            let stdout = std::io::stdout();
+            let mut writer = BufWriter::new(stdout.lock());
+            write_jsonl(&store, &mut writer, with_vectors)?
+        }
+    };
+
+    match output {
+        Some(file) => info_print!("✅ Exported {} chunks to {}", written, file.display()),
+        None => info_print!("✅ Exported {} chunks", written),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunker::{Chunk, ChunkKind};
+    use crate::embed::EmbeddedChunk;
+    use tempfile::tempdir;
+
+    fn store_with_chunks(db_path: &std::path::Path) -> VectorStore {
+        let mut store = VectorStore::new(db_path, 4).unwrap();
+        let chunks = vec![
+            EmbeddedChunk::new(
+                Chunk::new(
+                    "fn authenticate() {}".to_string(),
+                    0,
+                    1,
+                    ChunkKind::Function,
+                    "auth.rs".to_string(),
+                ),
+                vec![1.0, 0.0, 0.0, 0.0],
+            ),
+            EmbeddedChunk::new(
+                Chunk::new(
+                    "struct User {}".to_string(),
+                    3,
+                    5,
+                    ChunkKind::Struct,
+                    "user.rs".to_string(),
+                ),
+                vec![0.0, 1.0, 0.0, 0.0],
+            ),
+        ];
+        store.insert_chunks_with_ids(chunks).unwrap();
+        store.build_index().unwrap();
+        store
+    }
+
+    #[test]
+    fn test_write_jsonl_without_vectors() {
+        let temp_dir = tempdir().unwrap();
+        let store = store_with_chunks(&temp_dir.path().join("test.db"));
+
+        let mut out = Vec::new();
+        let written = write_jsonl(&store, &mut out, false).unwrap();
+        assert_eq!(written, 2);
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["path"], "auth.rs");
+        assert_eq!(lines[0]["kind"], "Function");
+        assert_eq!(lines[1]["start_line"], 3);
+        assert!(lines[0].get("embedding").is_none());
+    }
+
+    #[test]
+    fn test_write_jsonl_with_vectors() {
+        let temp_dir = tempdir().unwrap();
+        let store = store_with_chunks(&temp_dir.path().join("test.db"));
+
+        let mut out = Vec::new();
+        write_jsonl(&store, &mut out, true).unwrap();
+
+        let first: serde_json::Value =
+            serde_json::from_str(String::from_utf8(out).unwrap().lines().next().unwrap()).unwrap();
+        assert_eq!(first["embedding"].as_array().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,195 @@
+//! Rebuild the full-text index from the chunks in the vector store
+//!
+//! The FTS index only holds each chunk's content, path, signature and kind, all of
+//! which the vector store keeps, so it can be recreated without re-embedding after
+//! an FTS schema version change, or with `codesearch rebuild-fts` when the
+//! full-text side is damaged or out of sync.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::manager::acquire_writer_lock;
+use super::reembed::{staging_path_for, swap_dir};
+use crate::db_discovery::resolve_database_with_message;
+use crate::fts::FtsStore;
+use crate::search::read_metadata;
+use crate::vectordb::VectorStore;
+
+/// Number of chunks read from the vector store between progress callbacks
+const REBUILD_BATCH_SIZE: usize = 1024;
+
+/// `codesearch rebuild-fts`: recreate the FTS index for `path` from its stored chunks
+pub async fn rebuild_fts_command(path: Option<PathBuf>) -> Result<()> {
+    let (db_path, project_path) =
+        resolve_database_with_message(path.as_deref(), "rebuilding the full-text index of")?;
+
+    let (model, dimensions, _) = read_metadata(&db_path).ok_or_else(|| {
+        anyhow!(
+            "No usable index at {}. Run `codesearch index` first.",
+            db_path.display()
+        )
+    })?;
+
+    println!("{}", "🔤 Codesearch FTS Rebuild".bright_cyan().bold());
+    println!("{}", "=".repeat(60));
+    println!("📂 Project: {}", project_path.display());
+    println!("💾 Database: {}", db_path.display());
+    println!(
+        "🧠 Model: {} ({} dims, embeddings reused)",
+        model, dimensions
+    );
+
+    let _lock = acquire_writer_lock(&db_path).ok_or_else(|| {
+        anyhow!("Database is locked by another process (serve/mcp/index). Stop it and retry.")
+    })?;
+
+    let store = VectorStore::open_readonly(&db_path, dimensions)?;
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} chunks")
+            .unwrap()
+            .progress_chars("█▓▒░ "),
+    );
+
+    let start = Instant::now();
+    let docs = rebuild_fts(&db_path, &store, |done, total| {
+        pb.set_length(total as u64);
+        pb.set_position(done as u64);
+    })?;
+    pb.finish_and_clear();
+
+    println!(
+        "{}",
+        format!(
+            "✅ Rebuilt full-text index: {} documents in {:?}",
+            docs,
+            start.elapsed()
+        )
+        .green()
+    );
+    Ok(())
+}
+
+/// Re-add every chunk in `store` to a fresh FTS index that replaces `db_path/fts`
+///
+/// The new index is built next to the database and only swapped in once
+/// complete, so an interrupted rebuild leaves the old one in place.
+/// `progress(done, total)` is called after each batch of chunks. Returns the
+/// number of documents in the new index.
+pub(crate) fn rebuild_fts(
+    db_path: &Path,
+    store: &VectorStore,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<usize> {
+    let staging_path = staging_path_for(db_path, "fts-staging");
+    if staging_path.exists() {
+        std::fs::remove_dir_all(&staging_path)?;
+    }
+    std::fs::create_dir_all(&staging_path)?;
+    let docs = build_fts(&staging_path, store, &mut progress)?;
+
+    swap_dir(&staging_path.join("fts"), &db_path.join("fts"))?;
+    std::fs::remove_dir_all(&staging_path)?;
+    Ok(docs)
+}
+
+/// Fill a new FTS index in `staging_path/fts` from the chunks in `store`
+fn build_fts(
+    staging_path: &Path,
+    store: &VectorStore,
+    progress: &mut impl FnMut(usize, usize),
+) -> Result<usize> {
+    let mut fts_store = FtsStore::new_with_writer(staging_path)?;
+
+    let ids = store.chunk_ids()?;
+    let mut done = 0;
+    for batch in ids.chunks(REBUILD_BATCH_SIZE) {
+        for &id in batch {
+            if let Some(meta) = store.get_chunk(id)? {
+                fts_store.add_chunk(
+                    id,
+                    &meta.content,
+                    &meta.path,
+                    meta.signature.as_deref(),
+                    &meta.kind,
+                )?;
+            }
+        }
+        done += batch.len();
+        progress(done, ids.len());
+    }
+    fts_store.commit()?;
+
+    Ok(fts_store.stats()?.num_documents)
+}
+
+/// Rebuild the FTS index if [`FtsStore::check_schema`] reports an outdated schema
+///
+/// Returns the new document count when a rebuild happened.
+pub(crate) fn rebuild_fts_if_outdated(
+    db_path: &Path,
+    store: &VectorStore,
+) -> Result<Option<usize>> {
+    if let Err(e) = FtsStore::check_schema(db_path) {
+        tracing::warn!("{}", e);
+        return rebuild_fts(db_path, store, |_, _| {}).map(Some);
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunker::{Chunk, ChunkKind};
+    use crate::embed::EmbeddedChunk;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_rebuild_fts_from_stored_chunks() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path();
+        let mut store = VectorStore::new(db_path, 4).unwrap();
+        let chunks = ["fn authenticate() {}", "fn parse_config() {}"]
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                EmbeddedChunk::new(
+                    Chunk::new(
+                        content.to_string(),
+                        0,
+                        1,
+                        ChunkKind::Function,
+                        format!("f{}.rs", i),
+                    ),
+                    vec![1.0, i as f32, 0.0, 0.0],
+                )
+            })
+            .collect();
+        let ids = store.insert_chunks_with_ids(chunks).unwrap();
+
+        // An FTS index from a different schema version is replaced
+        drop(FtsStore::new(db_path).unwrap());
+        std::fs::write(db_path.join("fts").join("schema_version"), "0").unwrap();
+        assert!(FtsStore::new(db_path).is_err());
+
+        // Left over by an interrupted rebuild
+        let staging_path = staging_path_for(db_path, "fts-staging");
+        std::fs::create_dir_all(staging_path.join("fts")).unwrap();
+        std::fs::write(staging_path.join("fts").join("meta.json"), "partial").unwrap();
+
+        let mut calls = Vec::new();
+        let docs = rebuild_fts(db_path, &store, |done, total| calls.push((done, total))).unwrap();
+        assert_eq!(docs, 2);
+        assert_eq!(calls, vec![(2, 2)]);
+        assert!(!staging_path.exists());
+
+        let fts = FtsStore::new(db_path).unwrap();
+        let results = fts.search("authenticate", 10, None).unwrap();
+        assert_eq!(results[0].chunk_id, ids[0]);
+        assert_eq!(rebuild_fts_if_outdated(db_path, &store).unwrap(), None);
+    }
+}
@@ -0,0 +1,67 @@
+//! Changed-file detection via git, for `codesearch index --since <ref>`
+//!
+//! Lets CI index exactly the files touched since a base ref instead of
+//! diffing against `file_meta.json`.
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+use crate::cache::normalize_path;
+
+/// Files changed between `git_ref` and the working tree, under `project_path`
+///
+/// Returns normalized absolute paths (same form as `FileMetaStore` keys),
+/// including files that were deleted since `git_ref`.
+pub(crate) fn changed_files_since(project_path: &Path, git_ref: &str) -> Result<HashSet<String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_path)
+        .args([
+            "diff",
+            "--name-only",
+            "--relative",
+            "--no-renames",
+            git_ref,
+            "--",
+        ])
+        .output()
+        .context("failed to run git")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git diff {} failed: {}",
+            git_ref,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(parse_name_only(
+        &String::from_utf8_lossy(&output.stdout),
+        project_path,
+    ))
+}
+
+/// Turn `git diff --name-only --relative` output into absolute path keys
+fn parse_name_only(stdout: &str, project_path: &Path) -> HashSet<String> {
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| normalize_path(&project_path.join(line)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name_only_joins_project_path() {
+        let changed = parse_name_only("src/lib.rs\n\nsrc/auth/mod.rs\n", Path::new("/repo"));
+        assert_eq!(changed.len(), 2);
+        assert!(changed.contains("/repo/src/lib.rs"));
+        assert!(changed.contains("/repo/src/auth/mod.rs"));
+    }
+}
@@ -12,20 +12,22 @@
 //! - **Readonly mode**: Subsequent instances open in readonly mode (no writes, no watcher)
 //!
 //! A lock file (`.writer.lock`) in the database directory indicates an active writer.
+//! The writer records its PID in the file so a lock left by a crashed process can be
+//! told apart from a live one (`codesearch doctor`).
 //!
 #![allow(dead_code)]
 
 use crate::cache::{normalize_path, normalize_path_str};
 use crate::constants::{DB_DIR_NAME, DEFAULT_FSW_DEBOUNCE_MS, FILE_META_DB_NAME, WRITER_LOCK_FILE};
-use crate::embed::ModelType;
 use crate::fts::FtsStore;
+use crate::search::IndexMetadata;
 use crate::vectordb::VectorStore;
 use crate::watch::{FileEvent, FileWatcher};
 use std::collections::HashSet;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
@@ -105,6 +107,9 @@ pub fn acquire_writer_lock(db_path: &Path) -> Option<File> {
         Ok(()) => {
             // Successfully acquired lock
             debug!("🔒 Writer lock acquired");
+            if let Err(e) = write_lock_owner(&file) {
+                warn!("Failed to record PID in lock file: {}", e);
+            }
             Some(file)
         }
         Err(e) => {
@@ -115,6 +120,56 @@ pub fn acquire_writer_lock(db_path: &Path) -> Option<File> {
     }
 }
 
+/// Replace the contents of a held lock file with this process's PID
+fn write_lock_owner(mut file: &File) -> std::io::Result<()> {
+    use std::io::{Seek, Write};
+
+    file.set_len(0)?;
+    file.rewind()?;
+    write!(file, "{}", std::process::id())?;
+    file.flush()
+}
+
+/// PID recorded in the database's writer lock file, if any
+///
+/// Lock files written by older versions are empty and return `None`.
+pub fn writer_lock_owner(db_path: &Path) -> Option<u32> {
+    std::fs::read_to_string(db_path.join(WRITER_LOCK_FILE))
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+}
+
+/// Whether a process with this PID is running (`None` if it can't be determined)
+pub fn process_exists(pid: u32) -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        Some(Path::new("/proc").join(pid.to_string()).exists())
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        std::process::Command::new("ps")
+            .args(["-p", &pid.to_string()])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .ok()
+            .map(|status| status.success())
+    }
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output()
+            .ok()
+            .map(|out| String::from_utf8_lossy(&out.stdout).contains(&pid.to_string()))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
 /// Release the writer lock (done automatically when File is dropped)
 #[allow(dead_code)]
 pub fn release_writer_lock(_lock: File) {
@@ -150,6 +205,12 @@ impl SharedStores {
         }
 
         let vector_store = VectorStore::new(db_path, dimensions)?;
+        if let Some(docs) = super::fts_rebuild::rebuild_fts_if_outdated(db_path, &vector_store)? {
+            info!(
+                "🔄 Rebuilt outdated FTS index from stored chunks ({} documents)",
+                docs
+            );
+        }
         let fts_store = FtsStore::new_with_writer(db_path)?;
 
         info!("📦 SharedStores created in read-write mode");
@@ -208,6 +269,32 @@ impl SharedStores {
     }
 }
 
+/// Progress events emitted during an incremental refresh.
+///
+/// See [`IndexManager::perform_incremental_refresh_with_progress`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshProgress {
+    /// File walk finished
+    Scanned { files: usize },
+    /// Change detection finished
+    Changes {
+        changed: usize,
+        deleted: usize,
+        unchanged: usize,
+    },
+    /// Chunks for changed files were embedded and written to the stores
+    ChunksAdded { chunks: usize },
+    /// Refresh finished (also sent when there was nothing to do)
+    Done { elapsed_ms: u64 },
+}
+
+/// Send a progress event if a listener is attached. A dropped receiver is not an error.
+async fn emit(progress: Option<&mpsc::Sender<RefreshProgress>>, event: RefreshProgress) {
+    if let Some(tx) = progress {
+        let _ = tx.send(event).await;
+    }
+}
+
 /// Index manager that handles index lifecycle and file watching.
 ///
 /// Provides two-phase initialization:
@@ -272,7 +359,10 @@ impl IndexManager {
 
         // Create file watcher (but don't start it yet)
         debug!("👀 Creating file watcher...");
-        let watcher = FileWatcher::new(path_buf.clone());
+        let include = IndexMetadata::load_or_default(&db_path)?
+            .include
+            .unwrap_or_default();
+        let watcher = FileWatcher::new(path_buf.clone()).with_include_globs(&include)?;
         let watcher = Arc::new(Mutex::new(watcher));
 
         info!("✅ Index manager initialized successfully");
@@ -329,7 +419,10 @@ impl IndexManager {
 
         // Create file watcher (but don't start it yet)
         debug!("👀 Creating file watcher...");
-        let watcher = FileWatcher::new(path_buf.clone());
+        let include = IndexMetadata::load_or_default(&db_path)?
+            .include
+            .unwrap_or_default();
+        let watcher = FileWatcher::new(path_buf.clone()).with_include_globs(&include)?;
         let watcher = Arc::new(Mutex::new(watcher));
 
         info!("✅ Index manager initialized successfully (refresh skipped)");
@@ -351,38 +444,42 @@ impl IndexManager {
         db_path: &Path,
         stores: &SharedStores,
     ) -> Result<()> {
+        Self::perform_incremental_refresh_with_progress(codebase_path, db_path, stores, None).await
+    }
+
+    /// Same as [`Self::perform_incremental_refresh_with_stores`], reporting
+    /// [`RefreshProgress`] events on `progress` as the refresh advances.
+    pub async fn perform_incremental_refresh_with_progress(
+        codebase_path: &Path,
+        db_path: &Path,
+        stores: &SharedStores,
+        progress: Option<mpsc::Sender<RefreshProgress>>,
+    ) -> Result<()> {
+        let progress = progress.as_ref();
         use crate::cache::FileMetaStore;
         use crate::chunker::SemanticChunker;
-        use crate::embed::EmbeddingService;
-        use crate::file::FileWalker;
+        use crate::file::{FileWalker, ProjectConfig};
 
         info!("🔄 Performing incremental refresh with shared stores...");
         let start = std::time::Instant::now();
 
         // Read model metadata
-        let metadata_path = db_path.join("metadata.json");
-        let (model_name, dimensions) = if metadata_path.exists() {
-            let content = std::fs::read_to_string(&metadata_path)?;
-            let json: serde_json::Value = serde_json::from_str(&content)?;
-            let model = json
-                .get("model")
-                .and_then(|v| v.as_str())
-                .unwrap_or("minilm-l6-q");
-            let dims = json
-                .get("dimensions")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(384) as usize;
-            (model.to_string(), dims)
-        } else {
-            return Err(anyhow::anyhow!("No metadata.json found in database"));
-        };
+        let metadata = IndexMetadata::load(db_path)?
+            .ok_or_else(|| anyhow::anyhow!("No metadata.json found in database"))?;
 
         // Load FileMetaStore
-        let mut file_meta_store = FileMetaStore::load_or_create(db_path, &model_name, dimensions)?;
+        let (model_name, dimensions) = metadata.file_meta_key();
+        let mut file_meta_store = FileMetaStore::load_or_create(db_path, model_name, dimensions)?;
 
         // Walk files
-        let walker = FileWalker::new(codebase_path.to_path_buf());
+        let mut walker =
+            FileWalker::new(codebase_path.to_path_buf()).with_include_globs(metadata.include());
+        let project_config = ProjectConfig::load(codebase_path).ok().flatten();
+        if let Some(config) = &project_config {
+            walker = walker.with_config(config);
+        }
         let (files, _stats) = walker.walk()?;
+        emit(progress, RefreshProgress::Scanned { files: files.len() }).await;
 
         // Find changed and deleted files
         let mut changed_files = Vec::new();
@@ -408,31 +505,51 @@ impl IndexManager {
             deleted_files.len()
         );
 
+        emit(
+            progress,
+            RefreshProgress::Changes {
+                changed: changed_files.len(),
+                deleted: deleted_files.len(),
+                unchanged: unchanged_count,
+            },
+        )
+        .await;
+
         // If no changes, we're done
         if changed_files.is_empty() && deleted_files.is_empty() {
             info!("✅ Index is up to date!");
+            emit(
+                progress,
+                RefreshProgress::Done {
+                    elapsed_ms: start.elapsed().as_millis() as u64,
+                },
+            )
+            .await;
             return Ok(());
         }
 
-        // Delete chunks for deleted files
-        for (file_path, chunk_ids) in &deleted_files {
-            if !chunk_ids.is_empty() {
-                debug!("🗑️  Deleting {} chunks for: {}", chunk_ids.len(), file_path);
-
-                // Delete from vector store
-                {
-                    let mut store = stores.vector_store.write().await;
-                    store.delete_chunks(chunk_ids)?;
-                }
+        // Delete chunks for deleted files, a removed directory at a time
+        if !deleted_files.is_empty() {
+            // Delete from vector store
+            let chunk_ids = {
+                let mut store = stores.vector_store.write().await;
+                crate::index::delete_removed_files(&mut store, &deleted_files)?
+            };
+            debug!(
+                "🗑️  Deleted {} chunks for {} deleted files",
+                chunk_ids.len(),
+                deleted_files.len()
+            );
 
-                // Delete from FTS
-                {
-                    let mut fts_store = stores.fts_store.write().await;
-                    for chunk_id in chunk_ids {
-                        fts_store.delete_chunk(*chunk_id)?;
-                    }
+            // Delete from FTS
+            {
+                let mut fts_store = stores.fts_store.write().await;
+                for chunk_id in &chunk_ids {
+                    fts_store.delete_chunk(*chunk_id)?;
                 }
             }
+        }
+        for (file_path, _) in &deleted_files {
             file_meta_store.remove_file(Path::new(file_path));
         }
 
@@ -472,7 +589,15 @@ impl IndexManager {
         if !changed_files.is_empty() {
             info!("🔄 Processing {} changed files...", changed_files.len());
 
-            let mut chunker = SemanticChunker::new(100, 2000, 10);
+            let mut chunker = SemanticChunker::new(100, 2000, 10)
+                .with_context_lines(metadata.context_lines())
+                .with_strip_comments(metadata.strip_comments());
+            if let Some(limits) = project_config
+                .as_ref()
+                .and_then(|c| c.language_chunk_limits(chunker.default_limits()).ok())
+            {
+                chunker = chunker.with_language_limits(limits);
+            }
             let mut all_chunks = Vec::new();
 
             for file in &changed_files {
@@ -487,11 +612,9 @@ impl IndexManager {
             if !all_chunks.is_empty() {
                 // Embed chunks
                 info!("📦 Embedding {} chunks...", all_chunks.len());
-                let cache_dir = crate::constants::get_global_models_cache_dir()?;
-                let mut embedding_service = EmbeddingService::with_cache_dir(
-                    ModelType::default(),
-                    Some(cache_dir.as_path()),
-                )?;
+                // Embed with the model (and normalization) the index was built with
+                let mut embedding_service =
+                    metadata.embedding_service(db_path, metadata.model()?.unwrap_or_default())?;
                 let embedded_chunks = embedding_service.embed_chunks(all_chunks)?;
 
                 // Insert into vector store
@@ -539,6 +662,13 @@ impl IndexManager {
                 }
 
                 info!("✅ Indexed {} chunks", embedded_chunks.len());
+                emit(
+                    progress,
+                    RefreshProgress::ChunksAdded {
+                        chunks: embedded_chunks.len(),
+                    },
+                )
+                .await;
             }
         }
 
@@ -550,6 +680,13 @@ impl IndexManager {
             "✅ Incremental refresh completed in {:.2}s",
             elapsed.as_secs_f64()
         );
+        emit(
+            progress,
+            RefreshProgress::Done {
+                elapsed_ms: elapsed.as_millis() as u64,
+            },
+        )
+        .await;
 
         Ok(())
     }
@@ -745,67 +882,14 @@ impl IndexManager {
 
             // Also handle directory deletion: on Windows, rm -rf of a directory may only
             // produce a Remove event for the directory itself, not for individual files.
-            // Find all tracked files under this path prefix and remove them too.
+            if let Err(e) =
+                Self::remove_dir_from_index_with_stores(db_path, stores, file_path).await
             {
-                use crate::cache::FileMetaStore;
-
-                // Load FileMetaStore from disk to query tracked files
-                let metadata_path = db_path.join("metadata.json");
-                if metadata_path.exists() {
-                    if let Ok(metadata_str) = std::fs::read_to_string(&metadata_path) {
-                        if let Ok(metadata) =
-                            serde_json::from_str::<serde_json::Value>(&metadata_str)
-                        {
-                            let dimensions =
-                                metadata["dimensions"].as_u64().unwrap_or(384) as usize;
-                            let model_name = metadata["model"].as_str().unwrap_or("minilm-l6-q");
-
-                            if let Ok(file_meta_store) =
-                                FileMetaStore::load_or_create(db_path, model_name, dimensions)
-                            {
-                                // Normalize the directory prefix for consistent matching
-                                // (tracked files are normalized to forward slashes)
-                                let dir_prefix = normalize_path(file_path);
-                                let dir_prefix_slash = if dir_prefix.ends_with('/') {
-                                    dir_prefix.clone()
-                                } else {
-                                    format!("{}/", dir_prefix)
-                                };
-
-                                let files_under_dir: Vec<String> = file_meta_store
-                                    .tracked_files()
-                                    .filter(|f| f.starts_with(&dir_prefix_slash))
-                                    .cloned()
-                                    .collect();
-
-                                if !files_under_dir.is_empty() {
-                                    info!(
-                                        "🗑️  Directory deleted: {} ({} files under it)",
-                                        file_path.display(),
-                                        files_under_dir.len()
-                                    );
-                                    for tracked_file in &files_under_dir {
-                                        let tracked_path = PathBuf::from(tracked_file);
-                                        if let Err(e) = Self::remove_file_from_index_with_stores(
-                                            codebase_path,
-                                            db_path,
-                                            stores,
-                                            &tracked_path,
-                                        )
-                                        .await
-                                        {
-                                            warn!(
-                                                "⚠️  Failed to remove {}: {}",
-                                                tracked_path.display(),
-                                                e
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+                warn!(
+                    "⚠️  Failed to remove directory {}: {}",
+                    file_path.display(),
+                    e
+                );
             }
         }
 
@@ -910,7 +994,6 @@ impl IndexManager {
     async fn index_single_file(codebase_path: &Path, file_path: &Path) -> Result<()> {
         use crate::cache::FileMetaStore;
         use crate::chunker::{Chunker, SemanticChunker};
-        use crate::embed::EmbeddingService;
         use crate::file::Language;
         use crate::fts::FtsStore;
         use crate::vectordb::VectorStore;
@@ -941,8 +1024,11 @@ impl IndexManager {
         // First, remove old chunks for this file
         Self::remove_file_from_index(codebase_path, file_path).await?;
 
-        // Chunk the file
-        let chunker = SemanticChunker::new(100, 4000, 2);
+        // Chunk and embed the way the rest of the index was
+        let metadata = IndexMetadata::load_or_default(&db_path)?;
+        let chunker = SemanticChunker::new(100, 4000, 2)
+            .with_context_lines(metadata.context_lines())
+            .with_strip_comments(metadata.strip_comments());
         let chunks = chunker.chunk_file(file_path, &content)?;
 
         if chunks.is_empty() {
@@ -957,16 +1043,10 @@ impl IndexManager {
         );
 
         // Generate embeddings
-        let cache_dir = crate::constants::get_global_models_cache_dir()?;
         let mut embedding_service =
-            EmbeddingService::with_cache_dir(ModelType::default(), Some(cache_dir.as_path()))?;
+            metadata.embedding_service(&db_path, metadata.model()?.unwrap_or_default())?;
         let embedded_chunks = embedding_service.embed_chunks(chunks)?;
-
-        // Load metadata to get dimensions
-        let metadata_path = db_path.join("metadata.json");
-        let metadata: serde_json::Value =
-            serde_json::from_str(&std::fs::read_to_string(&metadata_path)?)?;
-        let dimensions = metadata["dimensions"].as_u64().unwrap_or(384) as usize;
+        let (model_name, dimensions) = metadata.file_meta_key();
 
         // Open stores
         let mut store = VectorStore::new(&db_path, dimensions)?;
@@ -988,7 +1068,6 @@ impl IndexManager {
         fts_store.commit()?;
 
         // Update file metadata
-        let model_name = metadata["model"].as_str().unwrap_or("minilm-l6-q");
         let mut file_meta_store = FileMetaStore::load_or_create(&db_path, model_name, dimensions)?;
         file_meta_store.update_file(file_path, chunk_ids)?;
         file_meta_store.save(&db_path)?;
@@ -1011,15 +1090,11 @@ impl IndexManager {
         let db_path = codebase_path.join(DB_DIR_NAME);
 
         // Load metadata to get dimensions and model
-        let metadata_path = db_path.join("metadata.json");
-        if !metadata_path.exists() {
+        let Some(metadata) = IndexMetadata::load(&db_path)? else {
             debug!("No metadata found, skipping removal");
             return Ok(());
-        }
-        let metadata: serde_json::Value =
-            serde_json::from_str(&std::fs::read_to_string(&metadata_path)?)?;
-        let dimensions = metadata["dimensions"].as_u64().unwrap_or(384) as usize;
-        let model_name = metadata["model"].as_str().unwrap_or("minilm-l6-q");
+        };
+        let (model_name, dimensions) = metadata.file_meta_key();
 
         // Load file metadata to get chunk IDs
         let mut file_meta_store = FileMetaStore::load_or_create(&db_path, model_name, dimensions)?;
@@ -1082,7 +1157,6 @@ impl IndexManager {
     ) -> Result<()> {
         use crate::cache::FileMetaStore;
         use crate::chunker::{Chunker, SemanticChunker};
-        use crate::embed::EmbeddingService;
         use crate::file::Language;
 
         // Check if file exists and is indexable
@@ -1106,11 +1180,22 @@ impl IndexManager {
             }
         };
 
+        // Touches and checkouts fire events without changing content: skip re-embedding
+        if let Some(file_meta_store) = FileMetaStore::load(db_path)? {
+            if !file_meta_store.check_file(file_path)?.0 {
+                debug!("Content unchanged, skipping: {}", file_path.display());
+                return Ok(());
+            }
+        }
+
         // First, remove old chunks for this file
         Self::remove_file_from_index_with_stores(codebase_path, db_path, stores, file_path).await?;
 
-        // Chunk the file
-        let chunker = SemanticChunker::new(100, 4000, 2);
+        // Chunk and embed the way the rest of the index was
+        let metadata = IndexMetadata::load_or_default(db_path)?;
+        let chunker = SemanticChunker::new(100, 4000, 2)
+            .with_context_lines(metadata.context_lines())
+            .with_strip_comments(metadata.strip_comments());
         let chunks = chunker.chunk_file(file_path, &content)?;
 
         if chunks.is_empty() {
@@ -1125,17 +1210,10 @@ impl IndexManager {
         );
 
         // Generate embeddings
-        let cache_dir = crate::constants::get_global_models_cache_dir()?;
         let mut embedding_service =
-            EmbeddingService::with_cache_dir(ModelType::default(), Some(cache_dir.as_path()))?;
+            metadata.embedding_service(db_path, metadata.model()?.unwrap_or_default())?;
         let embedded_chunks = embedding_service.embed_chunks(chunks)?;
-
-        // Load metadata to get model name
-        let metadata_path = db_path.join("metadata.json");
-        let metadata: serde_json::Value =
-            serde_json::from_str(&std::fs::read_to_string(&metadata_path)?)?;
-        let dimensions = metadata["dimensions"].as_u64().unwrap_or(384) as usize;
-        let model_name = metadata["model"].as_str().unwrap_or("minilm-l6-q");
+        let (model_name, dimensions) = metadata.file_meta_key();
 
         // Use shared stores with write lock
         let chunk_ids = {
@@ -1189,15 +1267,11 @@ impl IndexManager {
         use crate::cache::FileMetaStore;
 
         // Load metadata to get dimensions and model
-        let metadata_path = db_path.join("metadata.json");
-        if !metadata_path.exists() {
+        let Some(metadata) = IndexMetadata::load(db_path)? else {
             debug!("No metadata found, skipping removal");
             return Ok(());
-        }
-        let metadata: serde_json::Value =
-            serde_json::from_str(&std::fs::read_to_string(&metadata_path)?)?;
-        let dimensions = metadata["dimensions"].as_u64().unwrap_or(384) as usize;
-        let model_name = metadata["model"].as_str().unwrap_or("minilm-l6-q");
+        };
+        let (model_name, dimensions) = metadata.file_meta_key();
 
         // Load file metadata to get chunk IDs
         let mut file_meta_store = FileMetaStore::load_or_create(db_path, model_name, dimensions)?;
@@ -1252,6 +1326,54 @@ impl IndexManager {
 
         Ok(())
     }
+
+    /// Remove every tracked file under a deleted directory using shared stores.
+    /// The vector store drops the whole directory with one path-prefix deletion.
+    async fn remove_dir_from_index_with_stores(
+        db_path: &Path,
+        stores: &SharedStores,
+        dir: &Path,
+    ) -> Result<()> {
+        use crate::cache::FileMetaStore;
+
+        let Some(metadata) = IndexMetadata::load(db_path)? else {
+            debug!("No metadata found, skipping removal");
+            return Ok(());
+        };
+        let (model_name, dimensions) = metadata.file_meta_key();
+
+        let mut file_meta_store = FileMetaStore::load_or_create(db_path, model_name, dimensions)?;
+        let files = file_meta_store.remove_dir(dir);
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        // Delete chunks from vector store with write lock
+        let chunk_ids = {
+            let mut store = stores.vector_store.write().await;
+            crate::index::delete_removed_files(&mut store, &files)?
+        };
+
+        // Delete from FTS with write lock
+        {
+            let mut fts_store = stores.fts_store.write().await;
+            for chunk_id in &chunk_ids {
+                fts_store.delete_chunk(*chunk_id)?;
+            }
+            fts_store.commit()?;
+        }
+
+        file_meta_store.save(db_path)?;
+
+        info!(
+            "🗑️  Directory deleted: {} ({} files, {} chunks)",
+            dir.display(),
+            files.len(),
+            chunk_ids.len()
+        );
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1272,4 +1394,61 @@ mod tests {
         println!("Test path: {}", test_path.display());
         println!("Expected: Index manager creation will fail (no test codebase)");
     }
+
+    // Windows byte-range locks also block reading the locked file
+    #[cfg(unix)]
+    #[test]
+    fn test_writer_lock_records_pid() {
+        let db_dir = tempfile::tempdir().unwrap();
+        // Stale content from a previous writer is replaced
+        std::fs::write(db_dir.path().join(WRITER_LOCK_FILE), "999999999").unwrap();
+
+        let lock = acquire_writer_lock(db_dir.path()).unwrap();
+        assert_eq!(writer_lock_owner(db_dir.path()), Some(std::process::id()));
+        assert_eq!(process_exists(std::process::id()), Some(true));
+        drop(lock);
+
+        std::fs::write(db_dir.path().join(WRITER_LOCK_FILE), "").unwrap();
+        assert_eq!(writer_lock_owner(db_dir.path()), None);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reports_progress() {
+        let codebase = tempfile::tempdir().unwrap();
+        let db_dir = tempfile::tempdir().unwrap();
+        let db_path = db_dir.path().join(DB_DIR_NAME);
+        std::fs::create_dir_all(&db_path).unwrap();
+        let stores = SharedStores::new(&db_path, 384).unwrap();
+        std::fs::write(
+            db_path.join("metadata.json"),
+            r#"{"model_short_name": "minilm-l6-q", "dimensions": 384}"#,
+        )
+        .unwrap();
+
+        let (tx, mut rx) = mpsc::channel(16);
+        IndexManager::perform_incremental_refresh_with_progress(
+            codebase.path(),
+            &db_path,
+            &stores,
+            Some(tx),
+        )
+        .await
+        .unwrap();
+
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        assert_eq!(events[0], RefreshProgress::Scanned { files: 0 });
+        assert_eq!(
+            events[1],
+            RefreshProgress::Changes {
+                changed: 0,
+                deleted: 0,
+                unchanged: 0
+            }
+        );
+        assert!(matches!(events[2], RefreshProgress::Done { .. }));
+        assert_eq!(events.len(), 3);
+    }
 }
@@ -0,0 +1,270 @@
+//! Rewrite `metadata.json` for an existing index without re-embedding
+//!
+//! Indexes built by older versions can lack fields newer features read
+//! (`primary_language`, `context_lines`, ...), and a missing or truncated
+//! `metadata.json` makes search refuse an index whose chunks are fine.
+//! `codesearch index --stats-only` recomputes the file from what is on disk:
+//! the model from the old metadata or `file_meta.json`, the dimensions from a
+//! stored vector, and the primary language from a walk of the project. Chunks,
+//! embeddings and the FTS index are left untouched.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use super::manager::acquire_writer_lock;
+use crate::cache::FileMetaStore;
+use crate::db_discovery::resolve_database_with_message;
+use crate::embed::ModelType;
+use crate::file::{FileWalker, ProjectConfig};
+use crate::search::IndexMetadata;
+use crate::vectordb::{Metric, VectorStore};
+
+/// `codesearch index --stats-only`: recompute `metadata.json` for the index at `path`
+///
+/// `model` names the model for databases where neither `metadata.json` nor
+/// `file_meta.json` records one. It never replaces a recorded model, and a
+/// model whose dimensions differ from the stored vectors is refused.
+pub async fn refresh_metadata(path: Option<PathBuf>, model: Option<ModelType>) -> Result<()> {
+    let (db_path, project_path) =
+        resolve_database_with_message(path.as_deref(), "refreshing metadata of")?;
+
+    let existing = read_metadata_json(&db_path);
+    let file_meta_model = FileMetaStore::load(&db_path)
+        .ok()
+        .flatten()
+        .map(|store| store.model_name);
+    let model_type = pick_model(
+        recorded_model(existing.as_ref(), file_meta_model.as_deref()),
+        model,
+        &db_path,
+    )?;
+
+    println!("{}", "🧾 Codesearch Metadata Refresh".bright_cyan().bold());
+    println!("{}", "=".repeat(60));
+    println!("📂 Project: {}", project_path.display());
+    println!("💾 Database: {}", db_path.display());
+
+    let _lock = acquire_writer_lock(&db_path).ok_or_else(|| {
+        anyhow!("Database is locked by another process (serve/mcp/index). Stop it and retry.")
+    })?;
+
+    let (dimensions, metric) = {
+        let store = VectorStore::open_readonly(&db_path, model_type.dimensions())?;
+        let dimensions = stored_dimensions(&store)?.unwrap_or_else(|| model_type.dimensions());
+        (dimensions, store.metric())
+    };
+    if dimensions != model_type.dimensions() {
+        return Err(anyhow!(
+            "Stored vectors have {} dimensions but {} produces {}; metadata.json left unchanged. \
+             Re-index with `codesearch index --force` to switch models.",
+            dimensions,
+            model_type.short_name(),
+            model_type.dimensions()
+        ));
+    }
+
+    // Settings the old metadata recorded; a file this damaged keeps the defaults
+    let recorded: IndexMetadata = existing
+        .clone()
+        .and_then(|json| serde_json::from_value(json).ok())
+        .unwrap_or_default();
+
+    let mut walker = FileWalker::new(project_path.clone()).with_include_globs(recorded.include());
+    if let Ok(Some(config)) = ProjectConfig::load(&project_path) {
+        walker = walker.with_config(&config);
+    }
+    let (_, stats) = walker.walk()?;
+    let primary_language = stats.primary_language().map(|lang| format!("{:?}", lang));
+    let metadata = refreshed_metadata(
+        existing,
+        model_type,
+        dimensions,
+        metric,
+        primary_language.clone(),
+        recorded.context_lines(),
+        recorded.strip_comments(),
+    );
+    std::fs::write(
+        db_path.join("metadata.json"),
+        serde_json::to_string_pretty(&metadata)?,
+    )?;
+
+    println!(
+        "🧠 Model: {} ({} dims)",
+        model_type.short_name(),
+        dimensions
+    );
+    println!(
+        "🗣️  Primary language: {}",
+        primary_language.as_deref().unwrap_or("unknown")
+    );
+    println!(
+        "{}",
+        "✅ Rewrote metadata.json (chunks and embeddings untouched)".green()
+    );
+    Ok(())
+}
+
+/// The parsed `metadata.json` of `db_path`, if it exists and is valid JSON
+fn read_metadata_json(db_path: &Path) -> Option<serde_json::Value> {
+    std::fs::read_to_string(db_path.join("metadata.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// The model an index was built with, from its old metadata or else `file_meta.json`
+///
+/// `file_meta.json` records the model's full name (`BAAI/bge-small-en-v1.5`).
+fn recorded_model(
+    metadata: Option<&serde_json::Value>,
+    file_meta_model: Option<&str>,
+) -> Option<ModelType> {
+    metadata
+        .and_then(|json| json.get("model_short_name")?.as_str())
+        .and_then(ModelType::parse)
+        .or_else(|| {
+            let name = file_meta_model?;
+            ModelType::all().iter().copied().find(|m| m.name() == name)
+        })
+}
+
+/// The model to record: the one the index names, else the one passed in
+///
+/// A passed model that conflicts with the recorded one is an error rather
+/// than a silent rewrite of the index's model record.
+fn pick_model(
+    recorded: Option<ModelType>,
+    requested: Option<ModelType>,
+    db_path: &Path,
+) -> Result<ModelType> {
+    match (recorded, requested) {
+        (Some(recorded), Some(requested)) if recorded != requested => Err(anyhow!(
+            "The index at {} was built with {}, not {}. Re-index with `codesearch index --force` to switch models.",
+            db_path.display(),
+            recorded.short_name(),
+            requested.short_name()
+        )),
+        (Some(model), _) | (None, Some(model)) => Ok(model),
+        (None, None) => Err(anyhow!(
+            "Can't tell which model built the index at {}. Pass it with --model.",
+            db_path.display()
+        )),
+    }
+}
+
+/// Length of the first stored vector, or `None` for an empty or unbuilt store
+fn stored_dimensions(store: &VectorStore) -> Result<Option<usize>> {
+    let Some(&id) = store.chunk_ids()?.first() else {
+        return Ok(None);
+    };
+    Ok(store.get_vector(id).ok().flatten().map(|v| v.len()))
+}
+
+/// `metadata.json` with every recomputed field set
+///
+/// Fields this version doesn't know about are kept, and so is the original
+/// `indexed_at`, since the chunks were not re-indexed.
+fn refreshed_metadata(
+    existing: Option<serde_json::Value>,
+    model_type: ModelType,
+    dimensions: usize,
+    metric: Metric,
+    primary_language: Option<String>,
+    context_lines: usize,
+    strip_comments: bool,
+) -> serde_json::Value {
+    let mut metadata = match existing {
+        Some(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let indexed_at = metadata
+        .get("indexed_at")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    let fields = serde_json::json!({
+        "model_short_name": model_type.short_name(),
+        "model_name": model_type.name(),
+        "dimensions": dimensions,
+        "metric": metric.name(),
+        "context_lines": context_lines,
+        "strip_comments": strip_comments,
+        "primary_language": primary_language,
+        "indexed_at": indexed_at,
+    });
+    if let serde_json::Value::Object(fields) = fields {
+        metadata.extend(fields);
+    }
+    serde_json::Value::Object(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refreshed_metadata_fills_gaps_and_keeps_extras() {
+        let old = serde_json::json!({
+            "model_short_name": "bge-small",
+            "dimensions": 384,
+            "indexed_at": "2024-01-01T00:00:00+00:00",
+            "custom": "kept",
+        });
+        let model = recorded_model(Some(&old), None).unwrap();
+        assert_eq!(model, ModelType::BGESmallENV15);
+
+        let json = refreshed_metadata(
+            Some(old),
+            model,
+            384,
+            Metric::Euclidean,
+            Some("Rust".into()),
+            3,
+            false,
+        );
+        assert_eq!(json["model_name"], "BAAI/bge-small-en-v1.5");
+        assert_eq!(json["primary_language"], "Rust");
+        assert_eq!(json["context_lines"], 3);
+        assert_eq!(json["metric"], "euclidean");
+        assert_eq!(json["indexed_at"], "2024-01-01T00:00:00+00:00");
+        assert_eq!(json["custom"], "kept");
+
+        // No metadata.json: fall back to the full name in file_meta.json
+        assert_eq!(
+            recorded_model(None, Some("jinaai/jina-embeddings-v2-base-code")),
+            Some(ModelType::JinaEmbeddingsV2BaseCode)
+        );
+        assert_eq!(recorded_model(None, Some("unknown/model")), None);
+        let json = refreshed_metadata(None, model, 384, Metric::Cosine, None, 3, true);
+        assert!(json["indexed_at"].is_string());
+        assert!(json["primary_language"].is_null());
+    }
+
+    #[test]
+    fn test_pick_model_never_replaces_recorded_model() {
+        let db = Path::new(".codesearch.db");
+        let recorded = Some(ModelType::BGESmallENV15);
+
+        assert_eq!(
+            pick_model(recorded, None, db).unwrap(),
+            ModelType::BGESmallENV15
+        );
+        assert_eq!(
+            pick_model(recorded, recorded, db).unwrap(),
+            ModelType::BGESmallENV15
+        );
+        let err = pick_model(recorded, Some(ModelType::JinaEmbeddingsV2BaseCode), db)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("bge-small"), "{}", err);
+
+        // Only an index with no recorded model takes the passed one
+        assert_eq!(
+            pick_model(None, Some(ModelType::JinaEmbeddingsV2BaseCode), db).unwrap(),
+            ModelType::JinaEmbeddingsV2BaseCode
+        );
+        assert!(pick_model(None, None, db).is_err());
+    }
+}
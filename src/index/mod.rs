@@ -1,23 +1,42 @@
 use anyhow::Result;
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info};
 
-use crate::cache::{normalize_path, FileMetaStore};
-use crate::chunker::SemanticChunker;
+use crate::cache::{normalize_path, removed_dirs, FileMetaStore};
+use crate::chunker::{Chunk, ChunkDeduplicator, ChunkLimits, SemanticChunker};
 use crate::db_discovery::{find_best_database, register_repository, unregister_repository};
-use crate::embed::{EmbeddingService, ModelType};
-use crate::file::FileWalker;
+use crate::embed::{normalize_embeddings_default, EmbeddingService, ModelType};
+use crate::file::{FileInfo, FileWalker, ProjectConfig};
 use crate::fts::FtsStore;
-use crate::vectordb::VectorStore;
+use crate::search::IndexMetadata;
+use crate::vectordb::{Metric, VectorStore};
 
 // Index manager module
 mod manager;
-pub use manager::{IndexManager, SharedStores};
+pub use manager::{
+    is_database_locked, process_exists, writer_lock_owner, IndexManager, RefreshProgress,
+    SharedStores,
+};
+
+mod compact;
+mod diff;
+mod export;
+mod fts_rebuild;
+mod git_diff;
+mod metadata;
+mod reembed;
+pub use compact::compact;
+pub use diff::diff_index;
+pub use export::export;
+pub use fts_rebuild::rebuild_fts_command;
+pub use metadata::refresh_metadata;
+pub use reembed::reembed;
 
 /// Get the database path and project path for a given directory
 /// Uses automatic database discovery to find indexes in parent/global directories
@@ -29,6 +48,9 @@ fn get_db_path(path: Option<PathBuf>) -> Result<(PathBuf, PathBuf)> {
 /// Smart database path resolution that handles global/local/force scenarios
 /// Ensures only ONE database per repository (local or global, never both)
 ///
+/// Also returns the metadata of any existing database, read before `--force`
+/// (or a move to the global location) deletes it.
+///
 /// # Safety Checks
 /// - Detects git/hg/svn roots to prevent indexing subdirs
 /// - Warns if trying to create a db in a non-root directory
@@ -36,7 +58,7 @@ fn get_db_path_smart(
     path: Option<PathBuf>,
     global: bool,
     force: bool,
-) -> Result<(PathBuf, PathBuf)> {
+) -> Result<(PathBuf, PathBuf, IndexMetadata)> {
     let target = path.as_deref();
     let project_path = path.as_deref().unwrap_or(Path::new("."));
 
@@ -50,6 +72,18 @@ fn get_db_path_smart(
 
     // Step 1: Check if there's an existing database (local or global)
     let existing_db = find_best_database(target)?;
+    let indexed = match existing_db {
+        Some(ref db_info) => match IndexMetadata::load(&db_info.db_path) {
+            Ok(metadata) => metadata.unwrap_or_default(),
+            // A rebuild must still work when the old metadata is unreadable
+            Err(e) if force => {
+                tracing::warn!("Ignoring unreadable index metadata: {}", e);
+                IndexMetadata::default()
+            }
+            Err(e) => return Err(e.into()),
+        },
+        None => IndexMetadata::default(),
+    };
 
     // Step 2: Handle --force flag
     if force {
@@ -87,7 +121,11 @@ fn get_db_path_smart(
                     )
                     .dimmed()
                 );
-                return Ok((db_info.db_path.clone(), db_info.project_path.clone()));
+                return Ok((
+                    db_info.db_path.clone(),
+                    db_info.project_path.clone(),
+                    indexed,
+                ));
             } else if !force && !db_info.is_global {
                 // Local database exists but user wants global
                 println!(
@@ -104,7 +142,8 @@ fn get_db_path_smart(
             }
         }
         // Create or use global database
-        return get_global_db_path(path);
+        let (db_path, project_path) = get_global_db_path(path)?;
+        return Ok((db_path, project_path, indexed));
     }
 
     // Step 4: Use automatic discovery (default behavior)
@@ -127,7 +166,11 @@ fn get_db_path_smart(
                 .dimmed()
             );
         }
-        return Ok((db_info.db_path.clone(), db_info.project_path.clone()));
+        return Ok((
+            db_info.db_path.clone(),
+            db_info.project_path.clone(),
+            indexed,
+        ));
     }
 
     // Step 5: No existing database - SAFETY CHECK before creating
@@ -151,7 +194,7 @@ fn get_db_path_smart(
                 "   Creating database at project root to avoid duplicate indexes.".yellow()
             );
             let db_path = root.join(".codesearch.db");
-            return Ok((db_path, root));
+            return Ok((db_path, root, indexed));
         }
     } else {
         // No project markers found - warn the user
@@ -171,7 +214,7 @@ fn get_db_path_smart(
 
     // Step 6: Create local database in current directory
     let db_path = canonical_path.join(".codesearch.db");
-    Ok((db_path, canonical_path))
+    Ok((db_path, canonical_path, indexed))
 }
 
 /// Find the project root by looking for version control directories
@@ -258,24 +301,99 @@ fn get_global_db_path(path: Option<PathBuf>) -> Result<(PathBuf, PathBuf)> {
     Ok((db_path, canonical_path))
 }
 
-/// Index a repository
+/// Chunk size limits used when indexing (per-language overrides come from the project config)
+const CHUNK_MAX_LINES: usize = 100;
+const CHUNK_MAX_CHARS: usize = 2000;
+const CHUNK_OVERLAP_LINES: usize = 10;
+
+/// Files chunked in parallel per rayon thread before their chunks are embedded
+const CHUNK_WINDOW_PER_THREAD: usize = 4;
+
+/// Result of reading and chunking one file in the parallel stage
+enum ChunkedFile {
+    /// The file isn't valid UTF-8
+    Skipped,
+    Chunks {
+        source_bytes: u64,
+        chunks: Vec<Chunk>,
+    },
+}
+
+/// Read and chunk a single file (runs on a rayon worker)
+fn chunk_file(chunker: &mut SemanticChunker, file: &FileInfo) -> Result<ChunkedFile> {
+    let source_code = match std::fs::read_to_string(&file.path) {
+        Ok(content) => content,
+        Err(_) => {
+            debug!("⚠️  Skipping file (invalid UTF-8): {}", file.path.display());
+            return Ok(ChunkedFile::Skipped);
+        }
+    };
+    let chunks = chunker.chunk_semantic(file.language, &file.path, &source_code)?;
+    Ok(ChunkedFile::Chunks {
+        source_bytes: source_code.len() as u64,
+        chunks,
+    })
+}
+
+/// Delete the chunks of `deleted` files (path, chunk IDs), a removed directory at a time
 ///
-/// # Arguments
-/// * `path` - Path to index (defaults to current directory)
-/// * `dry_run` - Preview what would be indexed without indexing
-/// * `force` - Delete existing index and rebuild from scratch
-/// * `global` - Create global index instead of local
-/// * `model` - Override embedding model
-/// * `quiet` - Suppress verbose output (for server/MCP mode)
-pub async fn index(
-    path: Option<PathBuf>,
-    dry_run: bool,
-    force: bool,
-    global: bool,
-    model: Option<ModelType>,
-    cancel_token: CancellationToken,
-) -> Result<()> {
-    index_with_options(path, dry_run, force, global, model, false, cancel_token).await
+/// Each directory that no longer exists is dropped with one path-prefix deletion;
+/// the remaining chunks (files deleted from surviving directories, or chunks whose
+/// stored path uses other separators) are deleted by ID. Returns every deleted
+/// chunk ID, so callers can drop them from the full-text index too.
+pub(crate) fn delete_removed_files(
+    store: &mut VectorStore,
+    deleted: &[(String, Vec<u32>)],
+) -> Result<Vec<u32>> {
+    let mut removed = std::collections::HashSet::new();
+    for dir in removed_dirs(deleted.iter().map(|(path, _)| path.as_str())) {
+        let ids = store.delete_chunks_by_path_prefix(&dir)?;
+        info!(
+            "🗑️  Deleted {} chunks under removed directory: {}",
+            ids.len(),
+            dir
+        );
+        removed.extend(ids);
+    }
+
+    let rest: Vec<u32> = deleted
+        .iter()
+        .flat_map(|(_, ids)| ids.iter().copied())
+        .filter(|id| !removed.contains(id))
+        .collect();
+    store.delete_chunks(&rest)?;
+    removed.extend(rest);
+
+    Ok(removed.into_iter().collect())
+}
+
+/// Options for [`index`]
+///
+/// Settings left as `None` keep what the existing index was built with.
+#[derive(Debug, Clone, Default)]
+pub struct IndexOptions {
+    /// Preview what would be indexed without indexing
+    pub dry_run: bool,
+    /// Delete existing index and rebuild from scratch
+    pub force: bool,
+    /// Create global index instead of local
+    pub global: bool,
+    /// Override embedding model
+    pub model: Option<ModelType>,
+    /// Model of a new index when `model` is unset (the user config's `model`)
+    pub default_model: Option<ModelType>,
+    /// Only re-index files changed since this git ref
+    pub since: Option<String>,
+    /// Lines stored around each chunk
+    pub context_lines: Option<usize>,
+    /// Embed code without comments and string literals
+    pub strip_comments: Option<bool>,
+    /// Vector distance metric of a new index (cosine unless the index already has one)
+    pub metric: Option<Metric>,
+    /// Only index files matching one of these globs (empty = all indexable files)
+    pub include: Vec<String>,
+    /// Suppress verbose output (for server/MCP mode)
+    pub quiet: bool,
 }
 
 /// Index a repository with quiet mode option (for server/MCP use)
@@ -284,21 +402,95 @@ pub async fn index_quiet(
     force: bool,
     cancel_token: CancellationToken,
 ) -> Result<()> {
-    index_with_options(path, false, force, false, None, true, cancel_token).await
+    let options = IndexOptions {
+        force,
+        quiet: true,
+        ..Default::default()
+    };
+    index(path, options, cancel_token).await
 }
 
-/// Internal index function with all options
-async fn index_with_options(
+/// Files and chunks an index run wrote, and how long its phases took
+#[derive(Debug, Default)]
+pub struct IndexSummary {
+    /// Files chunked and embedded (only the changed ones when incremental)
+    pub files: usize,
+    pub chunks: usize,
+    pub discovery: Duration,
+    pub model_load: Duration,
+    /// Chunking, embedding and storing, including the model load
+    pub chunking: Duration,
+    /// Building the vector index once all chunks are stored
+    pub build: Duration,
+}
+
+/// Index a repository
+///
+/// `path` defaults to the current directory.
+pub async fn index(
     path: Option<PathBuf>,
-    dry_run: bool,
-    force: bool,
-    global: bool,
-    model: Option<ModelType>,
-    quiet: bool,
+    options: IndexOptions,
     cancel_token: CancellationToken,
 ) -> Result<()> {
-    let (db_path, project_path) = get_db_path_smart(path, global, force)?;
-    let model_type = model.unwrap_or_default();
+    let (db_path, project_path, indexed) = get_db_path_smart(path, options.global, options.force)?;
+    index_into(&db_path, project_path, indexed, options, cancel_token)?;
+    Ok(())
+}
+
+/// Index `project_path` into the database at `db_path`
+///
+/// `indexed` is the metadata of the database being updated (the default for a
+/// new one). `options.global` is ignored: the caller has already picked `db_path`.
+pub(crate) fn index_into(
+    db_path: &Path,
+    project_path: PathBuf,
+    indexed: IndexMetadata,
+    options: IndexOptions,
+    cancel_token: CancellationToken,
+) -> Result<IndexSummary> {
+    let IndexOptions {
+        dry_run,
+        force,
+        global: _,
+        model,
+        default_model,
+        since,
+        context_lines,
+        strip_comments,
+        metric,
+        include,
+        quiet,
+    } = options;
+    let since = since.as_deref();
+    let db_path = db_path.to_path_buf();
+    let mut summary = IndexSummary::default();
+    // Without an explicit --model, keep the model the project was indexed with
+    let model_type = match model {
+        Some(model_type) => model_type,
+        None => match indexed.model_or(default_model) {
+            Ok(model_type) => model_type,
+            Err(e) if force => {
+                tracing::warn!("Rebuilding with the default model: {}", e);
+                default_model.unwrap_or_default()
+            }
+            Err(e) => return Err(e.into()),
+        },
+    };
+    // Likewise keep the context window, so re-chunked files match the rest of the index
+    let indexed_context_lines = indexed.context_lines();
+    let context_lines = context_lines.unwrap_or(indexed_context_lines);
+    let indexed_strip_comments = indexed.strip_comments();
+    let strip_comments = strip_comments.unwrap_or(indexed_strip_comments);
+    let indexed_normalized = indexed.normalized();
+    let normalize = normalize_embeddings_default();
+    let indexed_metric = indexed.metric();
+    let metric = metric.unwrap_or(indexed_metric);
+    // Without --include, an update keeps the globs the index was built with
+    let include = if include.is_empty() && !force {
+        indexed.include().to_vec()
+    } else {
+        include
+    };
 
     // Macro to conditionally print
     macro_rules! log_print {
@@ -328,9 +520,28 @@ async fn index_with_options(
     log_print!("{}", "-".repeat(60));
 
     let start = Instant::now();
-    let walker = FileWalker::new(project_path.clone());
+    let mut walker = FileWalker::new(project_path.clone()).with_include_globs(&include);
+    let project_config = match ProjectConfig::load(&project_path) {
+        Ok(Some(config)) => {
+            log_print!(
+                "⚙️  Loaded config: {}",
+                ProjectConfig::path_for(&project_path).display()
+            );
+            walker = walker.with_config(&config);
+            Some(config)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("⚠️  Ignoring project config, using defaults: {:#}", e).yellow()
+            );
+            None
+        }
+    };
     let (mut files, stats) = walker.walk()?;
     let discovery_duration = start.elapsed();
+    summary.discovery = discovery_duration;
 
     log_print!(
         "✅ Found {} indexable files in {:?}",
@@ -338,20 +549,94 @@ async fn index_with_options(
         discovery_duration
     );
     log_print!("   Total files scanned: {}", stats.total_files);
+    if !include.is_empty() {
+        log_print!(
+            "   --include selected {} files ({} others left out)",
+            files.len(),
+            stats.skipped_not_included
+        );
+    }
     log_print!("   Binary/skipped: {}", stats.skipped_binary);
+    if stats.skipped_too_large > 0 {
+        log_print!("   Too large (skipped): {}", stats.skipped_too_large);
+    }
     log_print!("   Total size: {:.2} MB", stats.total_size_mb());
 
     if files.is_empty() {
         log_print!("\n{}", "No files to index!".yellow());
-        return Ok(());
+        return Ok(summary);
     }
 
     if dry_run {
         log_print!("\n{}", "Dry run complete!".green());
-        return Ok(());
+        return Ok(summary);
     }
 
     let is_incremental = db_path.exists() && !force;
+    // Stored vectors can only be searched with the metric they were indexed with
+    if is_incremental && metric != indexed_metric {
+        return Err(anyhow::anyhow!(
+            "Index was built with --metric {}; re-index with `codesearch index --force --metric {}` to switch",
+            indexed_metric,
+            metric
+        ));
+    }
+    if is_incremental && context_lines != indexed_context_lines {
+        eprintln!(
+            "{}",
+            format!(
+                "⚠️  Index was built with --context-lines {}; only changed files will use {}. Run with --force to re-chunk everything.",
+                indexed_context_lines, context_lines
+            )
+            .yellow()
+        );
+    }
+    if is_incremental && strip_comments != indexed_strip_comments {
+        eprintln!(
+            "{}",
+            format!(
+                "⚠️  Index was built with --strip-comments={}; only changed files will use {}. Run with --force to re-embed everything.",
+                indexed_strip_comments, strip_comments
+            )
+            .yellow()
+        );
+    }
+    if is_incremental && normalize != indexed_normalized {
+        eprintln!(
+            "{}",
+            format!(
+                "⚠️  Index was built with CODESEARCH_NORMALIZE_EMBEDDINGS={}; only changed files will use {}. Run with --force to re-embed everything.",
+                indexed_normalized, normalize
+            )
+            .yellow()
+        );
+    }
+
+    // With --since, git decides which files changed instead of file_meta.json
+    let since_changed = match since {
+        Some(_) if !is_incremental => {
+            log_print!(
+                "{}",
+                "ℹ️  --since ignored: no existing index, running a full index".dimmed()
+            );
+            None
+        }
+        Some(git_ref) => match git_diff::changed_files_since(&project_path, git_ref) {
+            Ok(changed) => Some(changed),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "⚠️  --since {}: {:#}. Falling back to metadata-based change detection.",
+                        git_ref, e
+                    )
+                    .yellow()
+                );
+                None
+            }
+        },
+        None => None,
+    };
 
     // Load FileMetaStore for incremental indexing (will be used later to update metadata)
     let mut file_meta_store = if is_incremental {
@@ -370,24 +655,63 @@ async fn index_with_options(
     if is_incremental {
         let file_meta_store = file_meta_store.as_mut().unwrap();
 
-        // Find changed and deleted files
-        let mut changed_files = Vec::new();
+        // A full-text index from another schema version is rebuilt from the stored chunks
+        if FtsStore::check_schema(&db_path).is_err() {
+            log_print!(
+                "{}",
+                "🔄 Full-text index schema changed, rebuilding it from stored chunks (no re-embedding)..."
+                    .yellow()
+            );
+            let store = VectorStore::open_readonly(&db_path, model_type.dimensions())?;
+            let docs = fts_rebuild::rebuild_fts(&db_path, &store, |_, _| {})?;
+            log_print!("✅ Rebuilt full-text index ({} documents)", docs);
+        }
+
+        // Find changed and deleted files, with the old chunk IDs to delete
+        let mut changed_files: Vec<(FileInfo, Vec<u32>)> = Vec::new();
         let mut unchanged_files = 0;
 
-        for file in &files {
-            let (needs_reindex, _old_chunk_ids) = file_meta_store.check_file(&file.path)?;
+        let deleted_files = if let Some(since_changed) = &since_changed {
+            log_print!(
+                "   Git changes since {}: {}",
+                since.unwrap_or_default(),
+                since_changed.len()
+            );
 
-            if needs_reindex {
-                changed_files.push(file.clone());
-                debug!("📝 File changed (needs reindex): {}", file.path.display());
-            } else {
-                unchanged_files += 1;
-                debug!("✅ File unchanged: {}", file.path.display());
+            let mut indexable = std::collections::HashSet::new();
+            for file in &files {
+                let key = normalize_path(&file.path);
+                if since_changed.contains(&key) {
+                    changed_files.push((file.clone(), file_meta_store.chunk_ids(&file.path)));
+                    debug!("📝 File changed in git: {}", file.path.display());
+                } else {
+                    unchanged_files += 1;
+                }
+                indexable.insert(key);
+            }
+
+            // Changed in git and tracked, but no longer indexable (deleted or now ignored)
+            file_meta_store
+                .entries()
+                .filter(|(path, _)| since_changed.contains(*path) && !indexable.contains(*path))
+                .map(|(path, meta)| (path.clone(), meta.chunk_ids.clone()))
+                .collect()
+        } else {
+            for file in &files {
+                let (needs_reindex, old_chunk_ids) = file_meta_store.check_file(&file.path)?;
+
+                if needs_reindex {
+                    changed_files.push((file.clone(), old_chunk_ids));
+                    debug!("📝 File changed (needs reindex): {}", file.path.display());
+                } else {
+                    unchanged_files += 1;
+                    debug!("✅ File unchanged: {}", file.path.display());
+                }
             }
-        }
 
-        // Find deleted files (in metadata but not on disk)
-        let deleted_files = file_meta_store.find_deleted_files();
+            // Find deleted files (in metadata but not on disk)
+            file_meta_store.find_deleted_files()
+        };
 
         for (file_path, _chunk_ids) in &deleted_files {
             debug!("🗑️  File deleted from disk: {}", file_path);
@@ -400,7 +724,7 @@ async fn index_with_options(
         // If no changes and no deleted files, we're done
         if changed_files.is_empty() && deleted_files.is_empty() {
             log_print!("\n{}", "✅ Database is up to date!".green());
-            return Ok(());
+            return Ok(summary);
         }
 
         // Delete chunks for changed and deleted files
@@ -408,8 +732,7 @@ async fn index_with_options(
         for (_, chunk_ids) in deleted_files.iter() {
             total_chunks_to_delete += chunk_ids.len() as u32;
         }
-        for file in &changed_files {
-            let (_, chunk_ids) = file_meta_store.check_file(&file.path)?;
+        for (_, chunk_ids) in &changed_files {
             total_chunks_to_delete += chunk_ids.len() as u32;
         }
 
@@ -419,26 +742,16 @@ async fn index_with_options(
             let mut store = VectorStore::new(&db_path, 384)?; // Will load dimensions from DB
             let mut fts_store = FtsStore::new_with_writer(&db_path)?;
 
-            // Delete deleted files' metadata and chunks
-            for (file_path, chunk_ids) in deleted_files {
-                if !chunk_ids.is_empty() {
-                    info!(
-                        "🗑️  Deleting {} chunks for deleted file: {}",
-                        chunk_ids.len(),
-                        file_path
-                    );
-                    debug!("   File path: {}", file_path);
-                    store.delete_chunks(&chunk_ids)?;
-                    for chunk_id in &chunk_ids {
-                        fts_store.delete_chunk(*chunk_id)?;
-                    }
-                }
-                file_meta_store.remove_file(Path::new(&file_path));
+            // Delete deleted files' metadata and chunks, a removed directory at a time
+            for chunk_id in delete_removed_files(&mut store, &deleted_files)? {
+                fts_store.delete_chunk(chunk_id)?;
+            }
+            for (file_path, _) in &deleted_files {
+                file_meta_store.remove_file(Path::new(file_path));
             }
 
             // Delete changed files' old chunks
-            for file in &changed_files {
-                let (_, old_chunk_ids) = file_meta_store.check_file(&file.path)?;
+            for (file, old_chunk_ids) in &changed_files {
                 if !old_chunk_ids.is_empty() {
                     let file_path_str = file.path.to_string_lossy().to_string();
                     info!(
@@ -447,8 +760,8 @@ async fn index_with_options(
                         file_path_str
                     );
                     debug!("   File path: {}", file.path.display());
-                    store.delete_chunks(&old_chunk_ids)?;
-                    for chunk_id in &old_chunk_ids {
+                    store.delete_chunks(old_chunk_ids)?;
+                    for chunk_id in old_chunk_ids {
                         fts_store.delete_chunk(*chunk_id)?;
                     }
                 }
@@ -469,7 +782,7 @@ async fn index_with_options(
 
         // Only process changed files
         log_print!("\n🔄 Processing {} changed files...", changed_files.len());
-        files = changed_files;
+        files = changed_files.into_iter().map(|(file, _)| file).collect();
     } else {
         // Note: database deletion for --force is handled in get_db_path_smart()
         // (including the delay for Windows file handle release). This else branch
@@ -477,7 +790,8 @@ async fn index_with_options(
     }
 
     // Phase 2: Semantic Chunking + Embedding + Storage (Streaming)
-    // We process files one at a time to keep memory usage low
+    // Files are chunked in parallel a window at a time, then embedded one file at
+    // a time (the ONNX session isn't shared), which keeps memory usage bounded
     log_print!(
         "\n{}",
         "Phase 2: Semantic Chunking, Embedding & Storage".bright_cyan()
@@ -485,7 +799,32 @@ async fn index_with_options(
     log_print!("{}", "-".repeat(60));
 
     let chunking_start = Instant::now();
-    let mut chunker = SemanticChunker::new(100, 2000, 10);
+    summary.files = files.len();
+    let default_limits = ChunkLimits {
+        max_lines: CHUNK_MAX_LINES,
+        max_chars: CHUNK_MAX_CHARS,
+    };
+    let language_limits = match &project_config {
+        Some(config) => match config.language_chunk_limits(default_limits) {
+            Ok(limits) => limits,
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    format!("⚠️  Ignoring [chunk_limits], using defaults: {:#}", e).yellow()
+                );
+                Default::default()
+            }
+        },
+        None => Default::default(),
+    };
+    // The parser inside a chunker isn't shareable, so each rayon worker builds its own
+    let new_chunker = || {
+        SemanticChunker::new(CHUNK_MAX_LINES, CHUNK_MAX_CHARS, CHUNK_OVERLAP_LINES)
+            .with_language_limits(language_limits.clone())
+            .with_context_lines(context_lines)
+            .with_strip_comments(strip_comments)
+    };
+    let chunk_window = rayon::current_num_threads().max(1) * CHUNK_WINDOW_PER_THREAD;
     let mut total_chunks = 0;
 
     let pb = ProgressBar::new(files.len() as u64);
@@ -497,9 +836,12 @@ async fn index_with_options(
     );
 
     // Initialize embedding model (uses global models cache)
+    let model_start = Instant::now();
     let cache_dir = crate::constants::get_global_models_cache_dir()?;
     let mut embedding_service =
-        EmbeddingService::with_cache_dir(model_type, Some(cache_dir.as_path()))?;
+        EmbeddingService::with_cache_dir(model_type, Some(cache_dir.as_path()))?
+            .with_normalization(normalize);
+    summary.model_load = model_start.elapsed();
 
     // Check for shutdown after model loading (can take 5-10 seconds)
     if crate::constants::check_shutdown(&cancel_token) {
@@ -507,11 +849,11 @@ async fn index_with_options(
             "\n{}",
             "⚠️  Indexing cancelled during model loading".yellow()
         );
-        return Ok(());
+        return Ok(summary);
     }
 
     // Initialize vector store
-    let mut store = VectorStore::new(&db_path, embedding_service.dimensions())?;
+    let mut store = VectorStore::new_with_metric(&db_path, embedding_service.dimensions(), metric)?;
 
     // Initialize FTS store
     let mut fts_store = FtsStore::new_with_writer(&db_path)?;
@@ -522,120 +864,180 @@ async fn index_with_options(
 
     // Arena reset interval: periodically recreate the ONNX session to free
     // arena allocator memory that grows monotonically. Model is on disk, so
+    // a reset only costs session setup. Triggers on whichever comes first:
+    // N files or N source bytes embedded since the last reset (0 disables either).
+    let arena_reset_interval = std::env::var("CODESEARCH_ARENA_RESET_INTERVAL")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(crate::constants::DEFAULT_ARENA_RESET_INTERVAL);
+    let arena_reset_bytes = std::env::var("CODESEARCH_ARENA_RESET_BYTES")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(crate::constants::DEFAULT_ARENA_RESET_BYTES);
+    let mut files_since_reset = 0usize;
+    let mut bytes_since_reset = 0u64;
+
+    // Chunks repeating content that is already indexed (license headers,
+    // vendored or generated copies) aren't embedded again: they share the
+    // stored copy's vector and keep their own path and lines
+    let deduper = ChunkDeduplicator::new();
+
     let mut skipped_files = 0;
     let mut cancelled = false;
-    for file in &files {
-        // Check for cancellation before processing each file
+    'files: for window in files.chunks(chunk_window) {
+        // Check for cancellation before chunking each window
         // Uses BOTH global AtomicBool (set by ctrlc OS handler) AND CancellationToken (for programmatic cancel)
         if crate::constants::check_shutdown(&cancel_token) {
             cancelled = true;
             break;
         }
 
-        pb.set_message(format!(
-            "{}",
-            file.path.file_name().unwrap().to_string_lossy()
-        ));
+        // Phase 2a: Read and chunk this window of files in parallel
+        let chunked: Vec<Result<ChunkedFile>> = window
+            .par_iter()
+            .map_init(new_chunker, |chunker, file| chunk_file(chunker, file))
+            .collect();
 
-        debug!("📄 Processing file: {}", file.path.display());
+        for (file, chunked) in window.iter().zip(chunked) {
+            // Check for cancellation before processing each file
+            if crate::constants::check_shutdown(&cancel_token) {
+                cancelled = true;
+                break 'files;
+            }
 
-        // Skip files that aren't valid UTF-8
-        let source_code = match std::fs::read_to_string(&file.path) {
-            Ok(content) => content,
-            Err(_) => {
-                debug!("⚠️  Skipping file (invalid UTF-8): {}", file.path.display());
-                skipped_files += 1;
+            pb.set_message(format!(
+                "{}",
+                file.path.file_name().unwrap().to_string_lossy()
+            ));
+
+            debug!("📄 Processing file: {}", file.path.display());
+
+            let (source_bytes, chunks) = match chunked? {
+                ChunkedFile::Chunks {
+                    source_bytes,
+                    chunks,
+                } => (source_bytes, chunks),
+                // Files that aren't valid UTF-8
+                ChunkedFile::Skipped => {
+                    skipped_files += 1;
+                    pb.inc(1);
+                    continue;
+                }
+            };
+            let chunk_count = chunks.len();
+            debug!(
+                "   Created {} chunks for {}",
+                chunk_count,
+                file.path.display()
+            );
+
+            if chunks.is_empty() {
                 pb.inc(1);
                 continue;
             }
-        };
 
-        // Phase 2a: Chunk this file only (memory efficient!)
-        let chunks = chunker.chunk_semantic(file.language, &file.path, &source_code)?;
-        let chunk_count = chunks.len();
-        debug!(
-            "   Created {} chunks for {}",
-            chunk_count,
-            file.path.display()
-        );
-
-        if chunks.is_empty() {
-            pb.inc(1);
-            continue;
-        }
+            // Phase 2b: Embed this file's chunks with new content (batched internally)
+            // If embedding is interrupted by CTRL-C, catch it as cancellation (not error)
+            let (chunks, duplicates) = deduper.partition(chunks, |chunk| {
+                store.has_content(&chunk.hash).unwrap_or(false)
+            });
+            let embedded_chunks = if chunks.is_empty() {
+                Vec::new()
+            } else {
+                match embedding_service.embed_chunks(chunks) {
+                    Ok(chunks) => chunks,
+                    Err(_) if crate::constants::is_shutdown_requested() => {
+                        cancelled = true;
+                        break 'files;
+                    }
+                    Err(e) => return Err(e),
+                }
+            };
 
-        // Phase 2b: Embed chunks for this file only (batched internally)
-        // If embedding is interrupted by CTRL-C, catch it as cancellation (not error)
-        let embedded_chunks = match embedding_service.embed_chunks(chunks) {
-            Ok(chunks) => chunks,
-            Err(_) if crate::constants::is_shutdown_requested() => {
+            // Check cancellation after embedding (most CPU-intensive step)
+            if crate::constants::check_shutdown(&cancel_token) {
                 cancelled = true;
-                break;
+                break 'files;
             }
-            Err(e) => return Err(e),
-        };
 
-        // Check cancellation after embedding (most CPU-intensive step)
-        if crate::constants::check_shutdown(&cancel_token) {
-            cancelled = true;
-            break;
-        }
+            // Phase 2c: Extract lightweight FTS data before handing ownership to vector store.
+            // We capture just the strings needed for FTS (content, path, signature, kind)
+            // so we can pass full EmbeddedChunks to the vector store without cloning.
+            // Duplicates follow the embedded chunks, matching the order of their IDs.
+            let fts_data: Vec<(String, String, Option<String>, String)> = embedded_chunks
+                .iter()
+                .map(|ec| &ec.chunk)
+                .chain(&duplicates)
+                .map(|chunk| {
+                    (
+                        chunk.content.clone(),
+                        chunk.path.clone(),
+                        chunk.signature.clone(),
+                        format!("{:?}", chunk.kind),
+                    )
+                })
+                .collect();
+
+            // Phase 2d: Insert into vector store (takes ownership, no clone needed);
+            // duplicates only get metadata and share their stored copy's vector
+            let mut chunk_ids = store.insert_chunks_with_ids(embedded_chunks)?;
+            chunk_ids.extend(store.insert_duplicate_chunks(duplicates)?);
+
+            // Phase 2e: Insert into FTS with real chunk IDs from vector store.
+            // FTS failures are non-fatal: vector search is the primary search method,
+            // FTS (BM25) is supplementary for hybrid search. If tantivy encounters
+            // I/O errors (common on Windows due to antivirus interference), we log
+            // a warning and continue rather than aborting the entire indexing run.
+            for ((content, path, signature, kind), &chunk_id) in
+                fts_data.iter().zip(chunk_ids.iter())
+            {
+                if let Err(e) =
+                    fts_store.add_chunk(chunk_id, content, path, signature.as_deref(), kind)
+                {
+                    tracing::warn!(
+                        "FTS add_chunk failed in {}: {} (continuing without FTS for this chunk)",
+                        file.path.display(),
+                        e
+                    );
+                }
+            }
 
-        // Phase 2c: Extract lightweight FTS data before handing ownership to vector store.
-        // We capture just the strings needed for FTS (content, path, signature, kind)
-        // so we can pass full EmbeddedChunks to the vector store without cloning.
-        let fts_data: Vec<(String, String, Option<String>, String)> = embedded_chunks
-            .iter()
-            .map(|ec| {
-                (
-                    ec.chunk.content.clone(),
-                    ec.chunk.path.clone(),
-                    ec.chunk.signature.clone(),
-                    format!("{:?}", ec.chunk.kind),
-                )
-            })
-            .collect();
+            // Track chunk IDs per file for metadata (only paths and IDs, not chunk content)
+            let file_path = file.path.to_string_lossy().to_string();
+            file_chunks.insert(file_path, chunk_ids.clone());
 
-        // Phase 2d: Insert into vector store (takes ownership, no clone needed)
-        let chunk_ids = store.insert_chunks_with_ids(embedded_chunks)?;
+            total_chunks += chunk_count;
+            pb.inc(1);
 
-        // Phase 2e: Insert into FTS with real chunk IDs from vector store.
-        // FTS failures are non-fatal: vector search is the primary search method,
-        // FTS (BM25) is supplementary for hybrid search. If tantivy encounters
-        // I/O errors (common on Windows due to antivirus interference), we log
-        // a warning and continue rather than aborting the entire indexing run.
-        for ((content, path, signature, kind), &chunk_id) in fts_data.iter().zip(chunk_ids.iter()) {
-            if let Err(e) = fts_store.add_chunk(chunk_id, content, path, signature.as_deref(), kind)
+            files_since_reset += 1;
+            bytes_since_reset += source_bytes;
+            if (arena_reset_interval > 0 && files_since_reset >= arena_reset_interval)
+                || (arena_reset_bytes > 0 && bytes_since_reset >= arena_reset_bytes)
             {
-                tracing::warn!(
-                    "FTS add_chunk failed in {}: {} (continuing without FTS for this chunk)",
-                    file.path.display(),
-                    e
+                debug!(
+                    "♻️  Resetting embedder after {} files / {} bytes",
+                    files_since_reset, bytes_since_reset
                 );
+                embedding_service.reset_embedder()?;
+                files_since_reset = 0;
+                bytes_since_reset = 0;
             }
-        }
-
-        // Track chunk IDs per file for metadata (only paths and IDs, not chunk content)
-        let file_path = file.path.to_string_lossy().to_string();
-        file_chunks.insert(file_path, chunk_ids.clone());
 
-        total_chunks += chunk_count;
-        pb.inc(1);
-
-        // Periodic FTS commit to flush the in-memory segment to disk in a controlled
-        // way. Non-fatal: if commit fails, we log and continue. Some FTS data may
-        // be lost but vector search (primary) is unaffected.
-        if total_chunks % 1000 == 0 && total_chunks > 0 {
-            if let Err(e) = fts_store.commit() {
-                tracing::warn!(
-                    "Periodic FTS commit failed at {} chunks: {} (continuing, some FTS data may be lost)",
-                    total_chunks,
-                    e
-                );
+            // Periodic FTS commit to flush the in-memory segment to disk in a controlled
+            // way. Non-fatal: if commit fails, we log and continue. Some FTS data may
+            // be lost but vector search (primary) is unaffected.
+            if total_chunks % 1000 == 0 && total_chunks > 0 {
+                if let Err(e) = fts_store.commit() {
+                    tracing::warn!(
+                        "Periodic FTS commit failed at {} chunks: {} (continuing, some FTS data may be lost)",
+                        total_chunks,
+                        e
+                    );
+                }
             }
-        }
 
-        // Memory is freed here - chunks/embeddings dropped before next file
+            // Memory is freed here - chunks/embeddings dropped before next file
+        }
     }
 
     // Handle cancellation: exit quickly without blocking on build_index
@@ -645,7 +1047,6 @@ async fn index_with_options(
 
         // Free ONNX model memory immediately
         drop(embedding_service);
-        drop(chunker);
 
         // Don't call build_index() — it blocks for 10-30 seconds on large datasets.
         // The database is in a partially written state, user can re-run with --force.
@@ -671,7 +1072,7 @@ async fn index_with_options(
             }
         }
 
-        return Ok(());
+        return Ok(summary);
     }
 
     // Capture model info before dropping the ONNX model
@@ -682,7 +1083,6 @@ async fn index_with_options(
     // Free ONNX model + arena allocator memory before final index operations
     // This releases hundreds of MB of inference buffers
     drop(embedding_service);
-    drop(chunker);
 
     // Commit FTS store (non-fatal: vector search works without FTS)
     if let Err(e) = fts_store.commit() {
@@ -698,6 +1098,8 @@ async fn index_with_options(
 
     pb.finish_with_message("Done!");
     let chunking_duration = chunking_start.elapsed();
+    summary.chunks = total_chunks;
+    summary.chunking = chunking_duration;
 
     log_print!(
         "✅ Created and indexed {} chunks in {:?}",
@@ -707,7 +1109,7 @@ async fn index_with_options(
 
     if total_chunks == 0 {
         log_print!("\n{}", "No chunks created!".yellow());
-        return Ok(());
+        return Ok(summary);
     }
 
     // Capture FTS stats before dropping the store to free memory
@@ -721,19 +1123,22 @@ async fn index_with_options(
     // Build vector index (now that all chunks are inserted)
     let storage_start = Instant::now();
     store.build_index()?;
-    let _storage_duration = storage_start.elapsed();
+    summary.build = storage_start.elapsed();
 
     // Save model metadata
-    let metadata = serde_json::json!({
-        "model_short_name": model_short_name,
-        "model_name": model_name,
-        "dimensions": model_dimensions,
-        "indexed_at": chrono::Utc::now().to_rfc3339(),
-    });
-    std::fs::write(
-        db_path.join("metadata.json"),
-        serde_json::to_string_pretty(&metadata)?,
-    )?;
+    IndexMetadata {
+        model_short_name: Some(model_short_name),
+        model_name: Some(model_name),
+        dimensions: Some(model_dimensions),
+        context_lines: Some(context_lines),
+        strip_comments: Some(strip_comments),
+        normalized: Some(normalize),
+        metric: Some(metric.name().to_string()),
+        primary_language: stats.primary_language().map(|lang| format!("{:?}", lang)),
+        indexed_at: Some(chrono::Utc::now().to_rfc3339()),
+        include: (!include.is_empty()).then_some(include),
+    }
+    .save(&db_path)?;
 
     // Update FileMetaStore with new chunk IDs (incremental mode)
     if is_incremental {
@@ -776,6 +1181,15 @@ async fn index_with_options(
     log_print!("{}", "=".repeat(60));
     log_print!("   Total chunks: {}", db_stats.total_chunks);
     log_print!("   Total files: {}", db_stats.total_files);
+    let dedup_stats = deduper.stats();
+    if dedup_stats.duplicate_chunks > 0 {
+        log_print!(
+            "   Duplicate chunks: {} of {} ({:.1}%) share a stored vector instead of being embedded",
+            dedup_stats.duplicate_chunks,
+            dedup_stats.total_chunks,
+            dedup_stats.dedup_percentage()
+        );
+    }
     log_print!(
         "   Indexed: {}",
         if db_stats.indexed {
@@ -802,7 +1216,7 @@ async fn index_with_options(
         "codesearch search <query>".bright_cyan()
     );
 
-    Ok(())
+    Ok(summary)
 }
 
 /// List all indexed repositories
@@ -855,6 +1269,24 @@ pub async fn stats(path: Option<PathBuf>) -> Result<()> {
         if stats.indexed { "✅ Yes" } else { "❌ No" }
     );
     println!("   Dimensions: {}", stats.dimensions);
+    println!("   Metric: {}", store.metric());
+    let metadata = IndexMetadata::load_or_default(&db_path)?;
+    println!("   Context lines: {}", metadata.context_lines());
+    println!(
+        "   Comments stripped for embedding: {}",
+        if metadata.strip_comments() {
+            "Yes"
+        } else {
+            "No"
+        }
+    );
+    println!(
+        "   Normalized embeddings: {}",
+        if metadata.normalized() { "Yes" } else { "No" }
+    );
+    if let Some(lang) = &metadata.primary_language {
+        println!("   Primary language: {}", lang);
+    }
 
     // Calculate database size
     let mut total_size = 0u64;
@@ -943,6 +1375,7 @@ fn print_repo_stats(repo_path: &Path, db_path: &Path) -> Result<()> {
 pub async fn add_to_index(
     path: Option<PathBuf>,
     global: bool,
+    model: Option<ModelType>,
     cancel_token: CancellationToken,
 ) -> Result<()> {
     let project_path = path.as_deref().unwrap_or_else(|| Path::new("."));
@@ -1034,27 +1467,20 @@ pub async fn add_to_index(
     // Create the index
     if global {
         println!("\n{}", "Creating global index...".cyan());
-        index(
-            Some(canonical_path.clone()),
-            false,
-            false,
-            true,
-            None,
-            cancel_token.clone(),
-        )
-        .await?;
+        let options = IndexOptions {
+            global: true,
+            model,
+            ..Default::default()
+        };
+        index(Some(canonical_path.clone()), options, cancel_token.clone()).await?;
         println!("\n{}", "✅ Global index created!".green());
     } else {
         println!("\n{}", "Creating local index...".cyan());
-        index(
-            Some(canonical_path.clone()),
-            false,
-            false,
-            false,
-            None,
-            cancel_token,
-        )
-        .await?;
+        let options = IndexOptions {
+            model,
+            ..Default::default()
+        };
+        index(Some(canonical_path.clone()), options, cancel_token).await?;
         println!("\n{}", "✅ Local index created!".green());
     }
 
@@ -1197,3 +1623,41 @@ struct DbStats {
     chunk_count: usize,
     size_mb: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    #[ignore] // Requires model download
+    fn test_index_records_primary_language() {
+        let project = TempDir::new().unwrap();
+        for name in ["app.py", "models.py", "views.py"] {
+            fs::write(
+                project.path().join(name),
+                "def handler(request):\n    return request\n",
+            )
+            .unwrap();
+        }
+        fs::write(project.path().join("lib.rs"), "pub fn helper() {}\n").unwrap();
+        let db = TempDir::new().unwrap();
+        let db_path = db.path().join(".codesearch.db");
+
+        let summary = index_into(
+            &db_path,
+            project.path().to_path_buf(),
+            IndexMetadata::default(),
+            IndexOptions {
+                quiet: true,
+                ..Default::default()
+            },
+            CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!(summary.files, 4);
+
+        let metadata = IndexMetadata::load(&db_path).unwrap().unwrap();
+        assert_eq!(metadata.primary_language.as_deref(), Some("Python"));
+    }
+}
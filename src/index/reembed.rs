@@ -0,0 +1,336 @@
+//! Re-embed an existing index with a different model
+//!
+//! Reads chunk text back out of the vector store instead of walking and
+//! re-chunking the source tree, so switching models only costs the embedding
+//! pass. Chunk IDs are preserved, which keeps `file_meta.json` valid.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use super::manager::acquire_writer_lock;
+use crate::cache::FileMetaStore;
+use crate::chunker::SemanticChunker;
+use crate::db_discovery::resolve_database_with_message;
+use crate::embed::ModelType;
+use crate::fts::FtsStore;
+use crate::search::{read_metadata, IndexMetadata};
+use crate::vectordb::VectorStore;
+
+/// Number of chunks embedded and written per batch
+const REEMBED_BATCH_SIZE: usize = 256;
+
+/// LMDB files replaced when swapping in the re-embedded store
+const LMDB_FILES: [&str; 2] = ["data.mdb", "lock.mdb"];
+
+/// Re-embed every chunk of the index for `path` with `model_type`
+pub async fn reembed(
+    path: Option<PathBuf>,
+    model_type: ModelType,
+    cancel_token: CancellationToken,
+) -> Result<()> {
+    let (db_path, project_path) = resolve_database_with_message(path.as_deref(), "re-embedding")?;
+
+    let (old_model, old_dimensions, _) = read_metadata(&db_path).ok_or_else(|| {
+        anyhow!(
+            "No usable index at {}. Run `codesearch index` first.",
+            db_path.display()
+        )
+    })?;
+
+    println!("{}", "🔁 Codesearch Re-embed".bright_cyan().bold());
+    println!("{}", "=".repeat(60));
+    println!("📂 Project: {}", project_path.display());
+    println!("💾 Database: {}", db_path.display());
+    println!(
+        "🧠 Model: {} ({} dims) -> {} ({} dims)",
+        old_model,
+        old_dimensions,
+        model_type.short_name(),
+        model_type.dimensions()
+    );
+
+    if old_model == model_type.short_name() {
+        println!(
+            "{}",
+            "Index already uses this model, nothing to do.".dimmed()
+        );
+        return Ok(());
+    }
+
+    let _lock = acquire_writer_lock(&db_path).ok_or_else(|| {
+        anyhow!("Database is locked by another process (serve/mcp/index). Stop it and retry.")
+    })?;
+
+    let staging_path = staging_path_for(&db_path, "reembed");
+    if staging_path.exists() {
+        std::fs::remove_dir_all(&staging_path)?;
+    }
+
+    let start = Instant::now();
+    let result = build_staging(&db_path, &staging_path, model_type, &cancel_token);
+    let total_chunks = match result {
+        Ok(Some(total_chunks)) => total_chunks,
+        Ok(None) => {
+            let _ = std::fs::remove_dir_all(&staging_path);
+            println!("\n{}", "⚠️  Re-embed cancelled, index unchanged".yellow());
+            return Ok(());
+        }
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging_path);
+            return Err(e);
+        }
+    };
+
+    swap_in_staging(&db_path, &staging_path)?;
+    update_metadata(&db_path, model_type, &old_model, old_dimensions)?;
+
+    println!(
+        "{}",
+        format!(
+            "✅ Re-embedded {} chunks with {} in {:?}",
+            total_chunks,
+            model_type.short_name(),
+            start.elapsed()
+        )
+        .green()
+    );
+
+    Ok(())
+}
+
+/// Sibling directory the new store is built in before it replaces the old one
+pub(super) fn staging_path_for(db_path: &Path, suffix: &str) -> PathBuf {
+    let name = db_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".codesearch.db".to_string());
+    db_path.with_file_name(format!("{}.{}", name, suffix))
+}
+
+/// Build the re-embedded vector store and FTS index in `staging_path`
+///
+/// Returns `Ok(None)` if cancelled.
+fn build_staging(
+    db_path: &Path,
+    staging_path: &Path,
+    model_type: ModelType,
+    cancel_token: &CancellationToken,
+) -> Result<Option<usize>> {
+    let metadata =
+        IndexMetadata::load(db_path)?.ok_or_else(|| anyhow!("metadata.json disappeared"))?;
+    let old_dimensions = metadata
+        .dimensions
+        .ok_or_else(|| anyhow!("metadata.json records no dimensions"))?;
+    let source = VectorStore::open_readonly(db_path, old_dimensions)?;
+    let ids = source.chunk_ids()?;
+
+    let mut embedding_service = metadata.embedding_service(staging_path, model_type)?;
+
+    let mut target = VectorStore::new_with_metric(
+        staging_path,
+        embedding_service.dimensions(),
+        source.metric(),
+    )?;
+    let mut fts_store = FtsStore::new_with_writer(staging_path)?;
+    // The code-only text isn't stored, so indexes built with --strip-comments re-strip each chunk
+    let mut stripper = metadata
+        .strip_comments()
+        .then(|| SemanticChunker::new(100, 2000, 10));
+
+    let pb = ProgressBar::new(ids.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} chunks")
+            .unwrap()
+            .progress_chars("█▓▒░ "),
+    );
+
+    let mut total_chunks = 0;
+    for batch in ids.chunks(REEMBED_BATCH_SIZE) {
+        if crate::constants::check_shutdown(cancel_token) {
+            pb.finish_and_clear();
+            return Ok(None);
+        }
+
+        let mut metas = Vec::with_capacity(batch.len());
+        for &id in batch {
+            if let Some(meta) = source.get_chunk(id)? {
+                metas.push((id, meta));
+            }
+        }
+
+        let chunks = metas
+            .iter()
+            .map(|(_, meta)| {
+                let mut chunk = meta.to_chunk();
+                if let Some(stripper) = stripper.as_mut() {
+                    stripper.strip_chunk(&mut chunk);
+                }
+                chunk
+            })
+            .collect();
+        let embedded = match embedding_service.embed_chunks(chunks) {
+            Ok(embedded) => embedded,
+            Err(_) if crate::constants::is_shutdown_requested() => {
+                pb.finish_and_clear();
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+
+        for (id, meta) in &metas {
+            if let Err(e) = fts_store.add_chunk(
+                *id,
+                &meta.content,
+                &meta.path,
+                meta.signature.as_deref(),
+                &meta.kind,
+            ) {
+                tracing::warn!("FTS add_chunk failed for chunk {}: {}", id, e);
+            }
+        }
+
+        total_chunks += metas.len();
+        let items = metas
+            .into_iter()
+            .zip(embedded)
+            .map(|((id, meta), ec)| (id, meta, ec.embedding))
+            .collect();
+        target.insert_with_ids(items)?;
+
+        pb.inc(batch.len() as u64);
+    }
+    pb.finish_and_clear();
+
+    drop(embedding_service);
+    fts_store.commit()?;
+    drop(fts_store);
+    target.build_index()?;
+
+    Ok(Some(total_chunks))
+}
+
+/// Replace the LMDB files and FTS index in `db_path` with the staged ones
+fn swap_in_staging(db_path: &Path, staging_path: &Path) -> Result<()> {
+    swap_lmdb_files(db_path, staging_path)?;
+    swap_dir(&staging_path.join("fts"), &db_path.join("fts"))?;
+
+    std::fs::remove_dir_all(staging_path)?;
+    Ok(())
+}
+
+/// Replace the LMDB files in `db_path` with the ones built in `staging_path`
+///
+/// Each staged file is renamed over the old one, which replaces it atomically,
+/// so a crash mid-swap never leaves the database without a `data.mdb`.
+pub(super) fn swap_lmdb_files(db_path: &Path, staging_path: &Path) -> Result<()> {
+    for name in LMDB_FILES {
+        let old = db_path.join(name);
+        let staged = staging_path.join(name);
+        if staged.exists() {
+            std::fs::rename(&staged, &old)?;
+        } else if old.exists() {
+            std::fs::remove_file(&old)?;
+        }
+    }
+    Ok(())
+}
+
+/// Move the directory `staged` to `target`, replacing what is there
+///
+/// A directory can't be renamed over a non-empty one, so the old one is moved
+/// aside first and only deleted once the new one is in place; if the second
+/// rename fails, the old directory is put back.
+pub(super) fn swap_dir(staged: &Path, target: &Path) -> Result<()> {
+    let mut aside = target.as_os_str().to_owned();
+    aside.push(".old");
+    let aside = PathBuf::from(aside);
+    if aside.exists() {
+        std::fs::remove_dir_all(&aside)?;
+    }
+
+    let had_target = target.exists();
+    if had_target {
+        std::fs::rename(target, &aside)?;
+    }
+    if let Err(e) = std::fs::rename(staged, target) {
+        if had_target {
+            std::fs::rename(&aside, target)?;
+        }
+        return Err(e.into());
+    }
+    if had_target {
+        std::fs::remove_dir_all(&aside)?;
+    }
+    Ok(())
+}
+
+/// Record the new model in `metadata.json` and `file_meta.json`
+fn update_metadata(
+    db_path: &Path,
+    model_type: ModelType,
+    old_model: &str,
+    old_dimensions: usize,
+) -> Result<()> {
+    let metadata_path = db_path.join("metadata.json");
+    let mut metadata: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&metadata_path)?)?;
+    metadata["model_short_name"] = model_type.short_name().into();
+    metadata["model_name"] = model_type.name().into();
+    metadata["dimensions"] = model_type.dimensions().into();
+    std::fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)?;
+
+    // Chunk IDs are unchanged, so file metadata only needs the new model recorded.
+    // Load with the old model so the store isn't invalidated on read.
+    let mut file_meta_store = FileMetaStore::load_or_create(
+        db_path,
+        ModelType::parse(old_model)
+            .map(|m| m.name())
+            .unwrap_or(old_model),
+        old_dimensions,
+    )?;
+    file_meta_store.model_name = model_type.name().to_string();
+    file_meta_store.dimensions = model_type.dimensions();
+    file_meta_store.save(db_path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staging_path_is_sibling() {
+        let staging = staging_path_for(Path::new("/repo/.codesearch.db"), "reembed");
+        assert_eq!(staging, PathBuf::from("/repo/.codesearch.db.reembed"));
+    }
+
+    #[test]
+    fn test_swaps_replace_old_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_path = temp.path().join(".codesearch.db");
+        let staging_path = staging_path_for(&db_path, "reembed");
+        for (dir, content) in [(&db_path, "old"), (&staging_path, "new")] {
+            std::fs::create_dir_all(dir.join("fts")).unwrap();
+            std::fs::write(dir.join("data.mdb"), content).unwrap();
+            std::fs::write(dir.join("fts").join("meta.json"), content).unwrap();
+        }
+        // A stale lock file with no staged replacement is removed
+        std::fs::write(db_path.join("lock.mdb"), "old").unwrap();
+
+        swap_lmdb_files(&db_path, &staging_path).unwrap();
+        swap_dir(&staging_path.join("fts"), &db_path.join("fts")).unwrap();
+
+        let read = |path: PathBuf| std::fs::read_to_string(path).unwrap();
+        assert_eq!(read(db_path.join("data.mdb")), "new");
+        assert_eq!(read(db_path.join("fts").join("meta.json")), "new");
+        assert!(!db_path.join("lock.mdb").exists());
+        assert!(!db_path.join("fts.old").exists());
+        assert!(!staging_path.join("data.mdb").exists());
+    }
+}
@@ -25,6 +25,7 @@ pub use embed::{CacheStats, EmbeddedChunk, EmbeddingService, ModelType};
 pub use error::{CodeSearchError, Result as CsResult};
 pub use file::{FileInfo, FileWalker, Language, WalkStats};
 pub use fts::{FtsResult, FtsStore};
+pub use search::{SearchOptions, SearchProfile, SearchTiming, Searcher};
 pub use utils::{
     group_chunks_by_path, group_chunks_by_path_with_capacity, group_embedded_chunks_by_path,
 };
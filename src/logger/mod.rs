@@ -9,6 +9,10 @@
 //! Daily rotation creates files named `codesearch.log.YYYY-MM-DD`.
 //! Cleanup removes files older than `retention_days` and enforces `max_files`.
 
+mod query_log;
+
+pub use query_log::{QueryLog, QueryLogEntry};
+
 use anyhow::Result;
 use chrono::{NaiveDate, Utc};
 use std::fs;
@@ -110,7 +114,12 @@ pub fn ensure_log_dir(log_dir: &Path) -> Result<()> {
 /// Returns `None` if the filename doesn't match the expected pattern.
 fn parse_log_date(file_name: &str) -> Option<NaiveDate> {
     // Pattern: "codesearch.log.YYYY-MM-DD"
-    let suffix = file_name.strip_prefix(&format!("{}.", LOG_FILE_NAME))?;
+    parse_dated_file(file_name, LOG_FILE_NAME)
+}
+
+/// Extract the date from a `<prefix>.YYYY-MM-DD` filename
+fn parse_dated_file(file_name: &str, prefix: &str) -> Option<NaiveDate> {
+    let suffix = file_name.strip_prefix(prefix)?.strip_prefix('.')?;
     NaiveDate::parse_from_str(suffix, "%Y-%m-%d").ok()
 }
 
@@ -120,6 +129,11 @@ fn parse_log_date(file_name: &str) -> Option<NaiveDate> {
 /// 1. Files older than `retention_days` are always removed.
 /// 2. If more than `max_files` remain, the oldest are removed.
 pub fn cleanup_old_logs(log_dir: &Path, config: &LogRotationConfig) -> Result<()> {
+    cleanup_dated_files(log_dir, LOG_FILE_NAME, config)
+}
+
+/// [`cleanup_old_logs`] for any daily-rotated `<prefix>.YYYY-MM-DD` files in `log_dir`
+fn cleanup_dated_files(log_dir: &Path, prefix: &str, config: &LogRotationConfig) -> Result<()> {
     if !log_dir.exists() {
         return Ok(());
     }
@@ -138,7 +152,7 @@ pub fn cleanup_old_logs(log_dir: &Path, config: &LogRotationConfig) -> Result<()
         }
 
         if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-            if let Some(date) = parse_log_date(file_name) {
+            if let Some(date) = parse_dated_file(file_name, prefix) {
                 dated_files.push((date, path));
             }
         }
@@ -0,0 +1,192 @@
+//! Opt-in query log for tuning retrieval
+//!
+//! With `CODESEARCH_QUERY_LOG` set, `serve` and `mcp` append one JSON line per
+//! search to `.codesearch.db/queries.log.YYYY-MM-DD`. Files rotate daily and are
+//! pruned with the same retention settings as the main log.
+
+use anyhow::Result;
+use chrono::{NaiveDate, SecondsFormat, Utc};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{cleanup_dated_files, LogRotationConfig};
+use crate::constants::QUERY_LOG_FILE_NAME;
+
+/// One logged search
+#[derive(Debug, Serialize)]
+pub struct QueryLogEntry<'a> {
+    /// RFC 3339 UTC time the search finished
+    pub timestamp: String,
+    /// Where the search came from (`"http"` or `"mcp"`)
+    pub source: &'static str,
+    pub query: &'a str,
+    /// Number of results returned
+    pub results: usize,
+    pub took_ms: u64,
+    /// Score of the best result (absent when nothing was returned)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_score: Option<f32>,
+}
+
+impl<'a> QueryLogEntry<'a> {
+    /// Entry for a search that just finished, taking the top score from `scores`
+    pub fn new(
+        source: &'static str,
+        query: &'a str,
+        scores: impl IntoIterator<Item = f32>,
+        took: Duration,
+    ) -> Self {
+        let mut results = 0;
+        let mut top_score: Option<f32> = None;
+        for score in scores {
+            results += 1;
+            top_score = Some(top_score.map_or(score, |top| top.max(score)));
+        }
+        Self {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            source,
+            query,
+            results,
+            took_ms: took.as_millis() as u64,
+            top_score,
+        }
+    }
+}
+
+/// Appends [`QueryLogEntry`] lines to the daily query log of one database
+#[derive(Debug)]
+pub struct QueryLog {
+    dir: PathBuf,
+    config: LogRotationConfig,
+    /// Day of the file last written; old files are pruned when it changes.
+    /// Held while appending so concurrent searches never interleave lines.
+    current_day: Mutex<Option<NaiveDate>>,
+}
+
+impl QueryLog {
+    /// Query log for the database at `db_path`, or `None` unless `CODESEARCH_QUERY_LOG` is set
+    pub fn from_env(db_path: &Path) -> Option<Self> {
+        std::env::var("CODESEARCH_QUERY_LOG")
+            .is_ok()
+            .then(|| Self::new(db_path, LogRotationConfig::from_env()))
+    }
+
+    pub fn new(db_path: &Path, config: LogRotationConfig) -> Self {
+        Self {
+            dir: db_path.to_path_buf(),
+            config,
+            current_day: Mutex::new(None),
+        }
+    }
+
+    /// Append `entry`; failures are logged and otherwise ignored so they never fail a search
+    pub fn record(&self, entry: &QueryLogEntry) {
+        if let Err(e) = self.append(entry) {
+            tracing::warn!("Failed to write query log: {}", e);
+        }
+    }
+
+    fn append(&self, entry: &QueryLogEntry) -> Result<()> {
+        let today = Utc::now().date_naive();
+        let mut current_day = self
+            .current_day
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if *current_day != Some(today) {
+            if let Err(e) = cleanup_dated_files(&self.dir, QUERY_LOG_FILE_NAME, &self.config) {
+                tracing::warn!("Failed to clean up old query logs: {}", e);
+            }
+            *current_day = Some(today);
+        }
+
+        let path = self.dir.join(format!(
+            "{}.{}",
+            QUERY_LOG_FILE_NAME,
+            today.format("%Y-%m-%d")
+        ));
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_query_log_appends_json_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = LogRotationConfig {
+            max_files: 5,
+            retention_days: 5,
+        };
+        let log = QueryLog::new(temp_dir.path(), config);
+
+        let took = Duration::from_millis(12);
+        log.record(&QueryLogEntry::new(
+            "http",
+            "parse config",
+            [0.4, 0.9],
+            took,
+        ));
+        log.record(&QueryLogEntry::new("mcp", "nothing here", [], took));
+
+        let today = Utc::now().date_naive().format("%Y-%m-%d");
+        let path = temp_dir
+            .path()
+            .join(format!("{}.{}", QUERY_LOG_FILE_NAME, today));
+        let content = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["source"], "http");
+        assert_eq!(lines[0]["query"], "parse config");
+        assert_eq!(lines[0]["results"], 2);
+        assert_eq!(lines[0]["took_ms"], 12);
+        assert!((lines[0]["top_score"].as_f64().unwrap() - 0.9).abs() < 1e-6);
+        assert_eq!(lines[1]["results"], 0);
+        assert!(lines[1].get("top_score").is_none());
+    }
+
+    #[test]
+    fn test_query_log_prunes_old_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let old_date = Utc::now().date_naive() - chrono::Duration::days(10);
+        let old_path = temp_dir.path().join(format!(
+            "{}.{}",
+            QUERY_LOG_FILE_NAME,
+            old_date.format("%Y-%m-%d")
+        ));
+        std::fs::write(&old_path, "{}\n").unwrap();
+        // Other dated files in the database directory are not ours to remove
+        let main_log = temp_dir.path().join(format!(
+            "{}.{}",
+            crate::constants::LOG_FILE_NAME,
+            old_date.format("%Y-%m-%d")
+        ));
+        std::fs::write(&main_log, "").unwrap();
+
+        let config = LogRotationConfig {
+            max_files: 5,
+            retention_days: 5,
+        };
+        let log = QueryLog::new(temp_dir.path(), config);
+        log.record(&QueryLogEntry::new("http", "q", [1.0], Duration::ZERO));
+
+        assert!(!old_path.exists());
+        assert!(main_log.exists());
+    }
+}
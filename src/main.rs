@@ -6,6 +6,8 @@ mod constants;
 mod daemon;
 mod db_discovery;
 mod embed;
+#[allow(dead_code)] // Library error API, partly unused by the CLI binary
+mod error;
 mod file;
 mod fts;
 mod index;
@@ -29,7 +31,7 @@ async fn main() -> Result<()> {
     // Parse CLI to get loglevel (need this before tracing init)
     let args: Vec<String> = std::env::args().collect();
     let is_quiet = args.iter().any(|a| a == "-q" || a == "--quiet");
-    let is_json = args.iter().any(|a| a == "--json");
+    let is_json = args.iter().any(|a| a == "--json" || a == "--jsonl");
 
     // Parse loglevel from args (default: info)
     let loglevel = args
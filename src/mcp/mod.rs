@@ -8,15 +8,34 @@ pub mod types;
 use anyhow::Result;
 use rmcp::{
     handler::server::router::tool::ToolRouter,
+    handler::server::tool::cached_schema_for_type,
     handler::server::wrapper::Parameters,
     model::{CallToolResult, Content, ServerCapabilities, ServerInfo},
     tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler,
 };
+use serde::Serialize;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tokio_util::sync::CancellationToken;
 
+use crate::chunker::ChunkKind;
+use crate::constants::{
+    clamp_request_limit, FILTERED_OVERFETCH_FACTOR, MAX_FILTER_WIDENING_FACTOR,
+};
 use crate::db_discovery::{find_best_database, find_databases};
+use crate::embed::{EmbeddingService, ModelType};
+use crate::fts::FtsStore;
+use crate::index::{IndexManager, SharedStores};
+use crate::logger::{QueryLog, QueryLogEntry};
+use crate::rerank::{rrf_fusion, rrf_fusion_with_exact, EXACT_MATCH_RRF_K};
+use crate::search::{
+    adapt_rrf_k, boost_kind, detect_identifiers, detect_structural_intent, path_matches_filters,
+    signature_declares, IndexMetadata,
+};
+use crate::vectordb::{ChunkMetadata, VectorStore};
+
+// Re-export types
+pub use types::*;
 
 /// Normalize a path for comparison: strip UNC prefix, ./ prefix, convert backslashes to forward slashes
 fn normalize_path_for_compare(path: &str) -> String {
@@ -24,16 +43,13 @@ fn normalize_path_for_compare(path: &str) -> String {
         .trim_start_matches(r"\\?\")
         .replace('\\', "/")
 }
-use crate::embed::{EmbeddingService, ModelType};
-use crate::file::Language;
-use crate::fts::FtsStore;
-use crate::index::{IndexManager, SharedStores};
-use crate::rerank::{rrf_fusion, rrf_fusion_with_exact, EXACT_MATCH_RRF_K};
-use crate::search::{adapt_rrf_k, boost_kind, detect_identifiers, detect_structural_intent};
-use crate::vectordb::VectorStore;
 
-// Re-export types
-pub use types::*;
+/// Return `value` as structured content (with its JSON text for clients that ignore it)
+fn structured<T: Serialize>(value: &T) -> Result<CallToolResult, McpError> {
+    serde_json::to_value(value)
+        .map(CallToolResult::structured)
+        .map_err(|e| McpError::internal_error(e.to_string(), None))
+}
 
 /// Codesearch MCP service
 pub struct CodesearchService {
@@ -42,10 +58,14 @@ pub struct CodesearchService {
     project_path: PathBuf,
     model_type: ModelType,
     dimensions: usize,
+    // How the index was built, for embedding queries the same way
+    metadata: IndexMetadata,
     // Lazily initialized on first search
     embedding_service: Mutex<Option<EmbeddingService>>,
     // Shared stores for concurrent access (optional - only set when running with IndexManager)
     shared_stores: Option<Arc<SharedStores>>,
+    // Set when `CODESEARCH_QUERY_LOG` is enabled
+    query_log: Option<QueryLog>,
 }
 
 impl std::fmt::Debug for CodesearchService {
@@ -89,23 +109,15 @@ impl CodesearchService {
         let project_path = db_info.project_path;
 
         // Read model metadata from database
-        let metadata_path = db_path.join("metadata.json");
-        let (model_type, dimensions) = if metadata_path.exists() {
-            let content = std::fs::read_to_string(&metadata_path)?;
-            let json: serde_json::Value = serde_json::from_str(&content)?;
-            let model_name = json
-                .get("model_short_name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("minilm-l6");
-            let dims = json
-                .get("dimensions")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(384) as usize;
-            let mt = ModelType::parse(model_name).unwrap_or_default();
-            (mt, dims)
-        } else {
-            (ModelType::default(), 384)
-        };
+        let metadata = IndexMetadata::load_or_default(&db_path)?;
+        let model_type = metadata
+            .model_short_name
+            .as_deref()
+            .and_then(ModelType::parse)
+            .unwrap_or_default();
+        let dimensions = metadata.dimensions.unwrap_or(384);
+        crate::search::check_model_dimensions(&db_path, &metadata, model_type)?;
+        let query_log = QueryLog::from_env(&db_path);
 
         Ok(Self {
             tool_router: Self::tool_router(),
@@ -113,8 +125,10 @@ impl CodesearchService {
             project_path,
             model_type,
             dimensions,
+            metadata,
             embedding_service: Mutex::new(None),
             shared_stores,
+            query_log,
         })
     }
 
@@ -123,11 +137,10 @@ impl CodesearchService {
         let mut guard = self.embedding_service.lock().unwrap();
         if guard.is_none() {
             tracing::info!("Loading ONNX embedding model (first use)...");
-            let cache_dir = crate::constants::get_global_models_cache_dir()?;
-            *guard = Some(EmbeddingService::with_cache_dir(
-                self.model_type,
-                Some(&cache_dir),
-            )?);
+            *guard = Some(
+                self.metadata
+                    .embedding_service(&self.db_path, self.model_type)?,
+            );
             tracing::info!("ONNX embedding model loaded");
         }
         Ok(guard)
@@ -159,25 +172,34 @@ impl CodesearchService {
     }
 
     #[tool(
-        description = "Search code semantically using natural language. Returns compact metadata by default (path, line numbers, kind, signature, score). Use the read tool with the returned line numbers to view actual code. Set compact=false only when you need full content inline. Use filter_path to narrow results to a specific directory."
+        description = "Search code semantically using natural language. Returns compact metadata by default (path, line numbers, kind, signature, score). Use the read tool with the returned line numbers to view actual code. Set compact=false only when you need full content inline. Use filter_path (a path or list of paths) to narrow results to specific directories and exclude_paths to drop directories like target/ or vendor/. Use kinds (e.g. [\"Method\"]) to keep only certain chunk kinds. Response: {results: [SearchResultItem], message?} where SearchResultItem is {id, path, start_line, end_line, kind, score, signature?, language?, content?, context_prev?, context_next?}.",
+        output_schema = cached_schema_for_type::<SemanticSearchResponse>()
     )]
     async fn semantic_search(
         &self,
         Parameters(request): Parameters<SemanticSearchRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let limit = request.limit.unwrap_or(10);
+        let (limit, limit_note) = match clamp_request_limit(request.limit.unwrap_or(10)) {
+            Ok(clamped) => clamped,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
         let compact = request.compact.unwrap_or(true);
+        if let Err(e) = validate_kinds(&request.kinds) {
+            return Ok(CallToolResult::error(vec![Content::text(e)]));
+        }
+        let start = std::time::Instant::now();
 
         tracing::debug!(
-            "MCP semantic_search: query='{}', limit={}, compact={}",
+            "MCP semantic_search: query='{}', limit={}, compact={}, kinds={:?}",
             request.query,
             limit,
-            compact
+            compact,
+            request.kinds
         );
 
         // Ensure database exists
         if let Err(e) = self.ensure_database_exists() {
-            return Ok(CallToolResult::success(vec![Content::text(e)]));
+            return Ok(CallToolResult::error(vec![Content::text(e)]));
         }
 
         // Get embedding service and embed query
@@ -188,7 +210,7 @@ impl CodesearchService {
                 Ok(g) => g,
                 Err(e) => {
                     tracing::error!("MCP: Failed to get embedding service: {:?}", e);
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
                         "Error initializing embedding service: {}",
                         e
                     ))]));
@@ -201,7 +223,7 @@ impl CodesearchService {
                 Ok(e) => e,
                 Err(e) => {
                     tracing::error!("MCP: Failed to embed query: {:?}", e);
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
                         "Error embedding query: {}",
                         e
                     ))]));
@@ -222,7 +244,7 @@ impl CodesearchService {
                 Ok(r) => r,
                 Err(e) => {
                     tracing::error!("MCP: Search failed (shared store): {:?}", e);
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
                         "Error searching: {}",
                         e
                     ))]));
@@ -235,7 +257,7 @@ impl CodesearchService {
                 Ok(s) => s,
                 Err(e) => {
                     tracing::error!("MCP: Failed to open vector store: {:?}", e);
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
                         "Error opening database: {}. The database may be corrupted or not indexed yet.",
                         e
                     ))]));
@@ -245,7 +267,7 @@ impl CodesearchService {
                 Ok(r) => r,
                 Err(e) => {
                     tracing::error!("MCP: Search failed: {:?}", e);
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
                         "Error searching: {}",
                         e
                     ))]));
@@ -338,12 +360,8 @@ impl CodesearchService {
         // Apply language boost (improvement 2)
         if let Some((_, _, Some(primary_lang))) = crate::search::read_metadata(&self.db_path) {
             for result in &mut results {
-                let file_lang = format!(
-                    "{:?}",
-                    Language::from_path(std::path::Path::new(&result.path))
-                );
-                if file_lang.to_lowercase() == primary_lang.to_lowercase() {
-                    result.score *= 1.2;
+                if result.language_name().eq_ignore_ascii_case(&primary_lang) {
+                    result.score *= 1.0 + crate::search::DEFAULT_LANGUAGE_BOOST;
                 }
             }
             results.sort_by(|a, b| {
@@ -360,44 +378,71 @@ impl CodesearchService {
 
         tracing::debug!("MCP: Final {} results after hybrid search", results.len());
 
-        if results.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(
-                "No results found for the query. Try rephrasing your query or using broader terms.",
-            )]));
-        }
-
-        // Convert to response format, applying compact mode and filter_path
+        // Convert to response format, applying path and kind filters before compact mode
+        let filter_paths: Vec<String> = request
+            .filter_path
+            .clone()
+            .map(OneOrMany::into_vec)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|fp| fp.trim_end_matches('/').to_string())
+            .collect();
         let items: Vec<SearchResultItem> = results
             .into_iter()
+            .filter(|r| path_matches_filters(&r.path, &filter_paths, &request.exclude_paths))
             .filter(|r| {
-                // Apply filter_path if specified
-                if let Some(ref fp) = request.filter_path {
-                    let normalized_path = r.path.trim_start_matches("./");
-                    let normalized_filter = fp.trim_start_matches("./").trim_end_matches('/');
-                    normalized_path.starts_with(normalized_filter)
-                } else {
-                    true
-                }
+                request.kinds.is_empty()
+                    || request
+                        .kinds
+                        .iter()
+                        .any(|k| k.eq_ignore_ascii_case(&r.kind))
             })
             .map(|r| SearchResultItem {
+                id: r.id,
                 path: r.path,
                 start_line: r.start_line,
                 end_line: r.end_line,
                 kind: r.kind,
                 score: r.score,
                 signature: r.signature,
+                language: r.language,
                 content: if compact { None } else { Some(r.content) },
                 context_prev: if compact { None } else { r.context_prev },
                 context_next: if compact { None } else { r.context_next },
             })
             .collect();
 
-        let json = serde_json::to_string(&items).unwrap_or_else(|_| "[]".to_string());
-        Ok(CallToolResult::success(vec![Content::text(json)]))
+        if let Some(query_log) = &self.query_log {
+            query_log.record(&QueryLogEntry::new(
+                "mcp",
+                &request.query,
+                items.iter().map(|item| item.score),
+                start.elapsed(),
+            ));
+        }
+
+        let message = if items.is_empty() && !request.kinds.is_empty() {
+            Some(format!(
+                "No results of kinds {:?} found for the query. Try broader terms or fewer kinds.",
+                request.kinds
+            ))
+        } else if items.is_empty() {
+            Some(
+                "No results found for the query. Try rephrasing your query or using broader terms."
+                    .to_string(),
+            )
+        } else {
+            limit_note
+        };
+        structured(&SemanticSearchResponse {
+            results: items,
+            message,
+        })
     }
 
     #[tool(
-        description = "Get all indexed chunks from a specific file. Returns compact metadata by default (path, line numbers, kind, signature). Useful for understanding file structure before using the read tool for specific sections."
+        description = "Get all indexed chunks from a specific file. Returns compact metadata by default (path, line numbers, kind, signature). Useful for understanding file structure before using the read tool for specific sections. Response: {chunks: [SearchResultItem], message?}, ordered by start_line.",
+        output_schema = cached_schema_for_type::<FileChunksResponse>()
     )]
     async fn get_file_chunks(
         &self,
@@ -406,241 +451,248 @@ impl CodesearchService {
         let compact = request.compact.unwrap_or(true);
         // Ensure database exists
         if let Err(e) = self.ensure_database_exists() {
-            return Ok(CallToolResult::success(vec![Content::text(e)]));
+            return Ok(CallToolResult::error(vec![Content::text(e)]));
         }
 
         // Get chunks using shared stores if available
         let file_chunks = if let Some(ref stores) = self.shared_stores {
             let store = stores.vector_store.read().await;
-
-            // Collect chunks for the requested file using LMDB iteration
-            // (avoids missing chunks with high IDs after delete+insert cycles)
-            let mut file_chunks: Vec<SearchResultItem> = Vec::new();
-            let all = match store.all_chunks() {
-                Ok(c) => c,
-                Err(e) => {
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Error reading chunks: {}",
-                        e
-                    ))]));
-                }
-            };
-            for (_id, chunk) in all {
-                // Normalize paths for comparison: strip UNC, normalize slashes
-                let chunk_norm = normalize_path_for_compare(&chunk.path);
-                let project_norm = normalize_path_for_compare(&self.project_path.to_string_lossy());
-                let req_norm = normalize_path_for_compare(&request.path);
-
-                // Make chunk path relative by stripping project path prefix
-                let chunk_rel = if chunk_norm.starts_with(&project_norm) {
-                    chunk_norm[project_norm.len()..]
-                        .trim_start_matches('/')
-                        .to_string()
-                } else {
-                    chunk_norm.clone()
-                };
-
-                // Match: exact, ends_with (for subdirectory repos), or raw paths
-                if chunk_rel == req_norm
-                    || chunk_rel.ends_with(&format!("/{}", req_norm))
-                    || req_norm.ends_with(&format!("/{}", chunk_rel))
-                    || chunk.path == request.path
-                {
-                    file_chunks.push(SearchResultItem {
-                        path: chunk.path,
-                        start_line: chunk.start_line,
-                        end_line: chunk.end_line,
-                        kind: chunk.kind,
-                        score: 1.0,
-                        signature: chunk.signature,
-                        content: if compact { None } else { Some(chunk.content) },
-                        context_prev: if compact { None } else { chunk.context_prev },
-                        context_next: if compact { None } else { chunk.context_next },
-                    });
-                }
-            }
-            file_chunks
+            collect_file_chunks(&store, &self.project_path, &request.path, compact)
         } else {
             // Fallback: open a new store (standalone mode)
-            let store = match VectorStore::new(&self.db_path, self.dimensions) {
-                Ok(s) => s,
-                Err(e) => {
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Error opening database: {}",
-                        e
-                    ))]));
+            match VectorStore::new(&self.db_path, self.dimensions) {
+                Ok(store) => {
+                    collect_file_chunks(&store, &self.project_path, &request.path, compact)
                 }
-            };
-
-            // Collect chunks for the requested file using LMDB iteration
-            // (avoids missing chunks with high IDs after delete+insert cycles)
-            let mut file_chunks: Vec<SearchResultItem> = Vec::new();
-            let all = match store.all_chunks() {
-                Ok(c) => c,
                 Err(e) => {
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Error reading chunks: {}",
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Error opening database: {}",
                         e
                     ))]));
                 }
-            };
-            for (_id, chunk) in all {
-                // Normalize paths for comparison: strip UNC, normalize slashes
-                let chunk_norm = normalize_path_for_compare(&chunk.path);
-                let project_norm = normalize_path_for_compare(&self.project_path.to_string_lossy());
-                let req_norm = normalize_path_for_compare(&request.path);
-
-                // Make chunk path relative by stripping project path prefix
-                let chunk_rel = if chunk_norm.starts_with(&project_norm) {
-                    chunk_norm[project_norm.len()..]
-                        .trim_start_matches('/')
-                        .to_string()
-                } else {
-                    chunk_norm.clone()
-                };
-
-                // Match: exact, ends_with (for subdirectory repos), or raw paths
-                if chunk_rel == req_norm
-                    || chunk_rel.ends_with(&format!("/{}", req_norm))
-                    || req_norm.ends_with(&format!("/{}", chunk_rel))
-                    || chunk.path == request.path
-                {
-                    file_chunks.push(SearchResultItem {
-                        path: chunk.path,
-                        start_line: chunk.start_line,
-                        end_line: chunk.end_line,
-                        kind: chunk.kind,
-                        score: 1.0,
-                        signature: chunk.signature,
-                        content: if compact { None } else { Some(chunk.content) },
-                        context_prev: if compact { None } else { chunk.context_prev },
-                        context_next: if compact { None } else { chunk.context_next },
-                    });
-                }
             }
-            file_chunks
+        };
+        let file_chunks = match file_chunks {
+            Ok(c) => c,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error reading chunks: {}",
+                    e
+                ))]));
+            }
         };
 
         // Sort by start line
         let mut file_chunks = file_chunks;
         file_chunks.sort_by_key(|c| c.start_line);
 
-        if file_chunks.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
+        let message = file_chunks.is_empty().then(|| {
+            format!(
                 "No chunks found for file: {}. The file may not be indexed or the path may be incorrect.",
                 request.path
-            ))]));
+            )
+        });
+        structured(&FileChunksResponse {
+            chunks: file_chunks,
+            message,
+        })
+    }
+
+    #[tool(
+        description = "Get a single indexed chunk by its ID, including full content and surrounding context. Use this to re-fetch a specific hit from an earlier semantic_search or get_file_chunks response without re-running the query. Response: one SearchResultItem with content.",
+        output_schema = cached_schema_for_type::<SearchResultItem>()
+    )]
+    async fn get_chunk(
+        &self,
+        Parameters(request): Parameters<GetChunkRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        tracing::debug!("MCP get_chunk: id={}", request.id);
+
+        // Ensure database exists
+        if let Err(e) = self.ensure_database_exists() {
+            return Ok(CallToolResult::error(vec![Content::text(e)]));
         }
 
-        let json = serde_json::to_string(&file_chunks).unwrap_or_else(|_| "[]".to_string());
-        Ok(CallToolResult::success(vec![Content::text(json)]))
+        let chunk = if let Some(ref stores) = self.shared_stores {
+            let store = stores.vector_store.read().await;
+            store.get_chunk_as_result(request.id)
+        } else {
+            // Standalone mode — open a new store
+            match VectorStore::new(&self.db_path, self.dimensions) {
+                Ok(store) => store.get_chunk_as_result(request.id),
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Error opening database: {}",
+                        e
+                    ))]));
+                }
+            }
+        };
+
+        let chunk = match chunk {
+            Ok(Some(c)) => c,
+            Ok(None) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "No chunk found with id {}. The index may have been rebuilt since the id was returned; re-run semantic_search.",
+                    request.id
+                ))]));
+            }
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error reading chunk: {}",
+                    e
+                ))]));
+            }
+        };
+
+        let item = SearchResultItem {
+            id: chunk.id,
+            path: chunk.path,
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            kind: chunk.kind,
+            score: 1.0,
+            signature: chunk.signature,
+            language: chunk.language,
+            content: Some(chunk.content),
+            context_prev: chunk.context_prev,
+            context_next: chunk.context_next,
+        };
+
+        structured(&item)
     }
 
     #[tool(
-        description = "Find all references/usages of a symbol (function, class, method, variable) across the codebase. USE THIS INSTEAD OF GREP when you need to find where a symbol is used — for refactoring, impact analysis, or understanding call sites. Returns compact list of file paths, line numbers, and containing function signatures."
+        description = "Find all references/usages of a symbol (function, class, method, variable) across the codebase. USE THIS INSTEAD OF GREP when you need to find where a symbol is used — for refactoring, impact analysis, or understanding call sites. Returns compact list of file paths, line numbers, and containing function signatures. Narrow noisy symbols with kinds (e.g. [\"Function\", \"Method\"]) or definition_only=true to get just the declaration. Response: {references: [ReferenceItem], message?} where ReferenceItem is {path, line, kind, signature?, score}.",
+        output_schema = cached_schema_for_type::<FindReferencesResponse>()
     )]
     async fn find_references(
         &self,
         Parameters(request): Parameters<FindReferencesRequest>,
     ) -> Result<CallToolResult, McpError> {
-        let limit = request.limit.unwrap_or(20);
+        let (limit, limit_note) = match clamp_request_limit(request.limit.unwrap_or(20)) {
+            Ok(clamped) => clamped,
+            Err(e) => return Ok(CallToolResult::error(vec![Content::text(e)])),
+        };
+        if let Err(e) = validate_kinds(&request.kinds) {
+            return Ok(CallToolResult::error(vec![Content::text(e)]));
+        }
 
         tracing::debug!(
-            "MCP find_references: symbol='{}', limit={}",
+            "MCP find_references: symbol='{}', limit={}, kinds={:?}, definition_only={:?}",
             request.symbol,
-            limit
+            limit,
+            request.kinds,
+            request.definition_only
         );
 
         // Ensure database exists
         if let Err(e) = self.ensure_database_exists() {
-            return Ok(CallToolResult::success(vec![Content::text(e)]));
+            return Ok(CallToolResult::error(vec![Content::text(e)]));
         }
 
         // Open FTS store for full-text search on the symbol name
         let fts_store = match FtsStore::new(&self.db_path) {
             Ok(s) => s,
             Err(e) => {
-                return Ok(CallToolResult::success(vec![Content::text(format!(
-                    "Error opening FTS store: {}. Try re-indexing with 'codesearch index --force'.",
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Error opening FTS store: {}. Try rebuilding it with 'codesearch rebuild-fts'.",
                     e
                 ))]));
             }
         };
 
-        // Search FTS for the symbol — returns chunk_id + score
-        let fts_results = match fts_store.search(&request.symbol, limit * 2, None) {
-            Ok(r) => r,
-            Err(e) => {
-                return Ok(CallToolResult::success(vec![Content::text(format!(
-                    "Error searching for references: {}",
-                    e
-                ))]));
-            }
-        };
-
-        if fts_results.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "No references found for '{}'. The symbol may not be indexed or try a different name.",
-                request.symbol
-            ))]));
-        }
-
         // Resolve chunk metadata from VectorStore using chunk_ids
-        let items: Vec<ReferenceItem> = if let Some(ref stores) = self.shared_stores {
-            let store = stores.vector_store.read().await;
-            fts_results
-                .iter()
-                .filter_map(|fts_result| {
-                    if let Ok(Some(chunk)) = store.get_chunk(fts_result.chunk_id) {
-                        Some(ReferenceItem {
-                            path: chunk.path,
-                            line: chunk.start_line,
-                            kind: chunk.kind,
-                            signature: chunk.signature,
-                            score: fts_result.score,
-                        })
-                    } else {
-                        None
-                    }
-                })
-                .take(limit)
-                .collect()
+        let shared_store;
+        let standalone_store;
+        let store: &VectorStore = if let Some(ref stores) = self.shared_stores {
+            shared_store = stores.vector_store.read().await;
+            &shared_store
         } else {
             // Standalone mode — open a new store
-            let store = match VectorStore::new(&self.db_path, self.dimensions) {
+            standalone_store = match VectorStore::new(&self.db_path, self.dimensions) {
                 Ok(s) => s,
                 Err(e) => {
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
                         "Error opening database: {}",
                         e
                     ))]));
                 }
             };
-            fts_results
-                .iter()
-                .filter_map(|fts_result| {
-                    if let Ok(Some(chunk)) = store.get_chunk(fts_result.chunk_id) {
-                        Some(ReferenceItem {
-                            path: chunk.path,
-                            line: chunk.start_line,
-                            kind: chunk.kind,
-                            signature: chunk.signature,
-                            score: fts_result.score,
-                        })
-                    } else {
-                        None
-                    }
-                })
-                .take(limit)
-                .collect()
+            &standalone_store
         };
 
-        let json = serde_json::to_string(&items).unwrap_or_else(|_| "[]".to_string());
-        Ok(CallToolResult::success(vec![Content::text(json)]))
+        // Search FTS for the symbol — returns chunk_id + score. Filters drop hits after
+        // resolution, so fetch more candidates when they are set, and keep widening
+        // until `limit` hits pass or the matches run out.
+        let filtered = !request.kinds.is_empty() || request.definition_only.unwrap_or(false);
+        let max_fetch = limit.saturating_mul(MAX_FILTER_WIDENING_FACTOR);
+        let mut fetch = if filtered {
+            limit * FILTERED_OVERFETCH_FACTOR
+        } else {
+            limit * 2
+        };
+        let items: Vec<ReferenceItem> = loop {
+            let fts_results = match fts_store.search(&request.symbol, fetch, None) {
+                Ok(r) => r,
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Error searching for references: {}",
+                        e
+                    ))]));
+                }
+            };
+
+            if fts_results.is_empty() {
+                return structured(&FindReferencesResponse {
+                    references: Vec::new(),
+                    message: Some(format!(
+                        "No references found for '{}'. The symbol may not be indexed or try a different name.",
+                        request.symbol
+                    )),
+                });
+            }
+
+            let items = resolve_references(store, &fts_results, &request, limit);
+            if items.len() == limit || fts_results.len() < fetch || fetch >= max_fetch {
+                break items;
+            }
+            fetch = fetch
+                .saturating_mul(FILTERED_OVERFETCH_FACTOR)
+                .min(max_fetch);
+        };
+
+        let message = if items.is_empty() && filtered {
+            let mut filters = Vec::new();
+            if !request.kinds.is_empty() {
+                filters.push("kinds");
+            }
+            if request.definition_only.unwrap_or(false) {
+                filters.push("definition_only");
+            }
+            Some(format!(
+                "No references to '{}' match the {} filter{}.",
+                request.symbol,
+                filters.join("/"),
+                if filters.len() > 1 { "s" } else { "" }
+            ))
+        } else if items.is_empty() {
+            Some(format!(
+                "No references found for '{}'. The index may be out of date; try re-indexing.",
+                request.symbol
+            ))
+        } else {
+            limit_note
+        };
+        structured(&FindReferencesResponse {
+            references: items,
+            message,
+        })
     }
 
     #[tool(
-        description = "Get the status of the semantic search index including model info and statistics. Check this before searching to verify the index is ready."
+        description = "Get the status of the semantic search index including model info and statistics. Check this before searching to verify the index is ready.",
+        output_schema = cached_schema_for_type::<IndexStatusResponse>()
     )]
     async fn index_status(&self) -> Result<CallToolResult, McpError> {
         let indexed = self.db_path.exists();
@@ -653,21 +705,22 @@ impl CodesearchService {
                 model: "none".to_string(),
                 dimensions: 0,
                 max_chunk_id: 0,
+                context_lines: None,
                 db_path: self.db_path.display().to_string(),
                 project_path: self.project_path.display().to_string(),
                 error_message: Some(
                     "No index found. Run 'codesearch index' first to create the index.".to_string(),
                 ),
+                chunk_cache: None,
             };
-            let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
-            return Ok(CallToolResult::success(vec![Content::text(json)]));
+            return structured(&response);
         }
 
         // Get stats using shared stores if available
-        let stats = if let Some(ref stores) = self.shared_stores {
+        let (stats, chunk_cache) = if let Some(ref stores) = self.shared_stores {
             let store = stores.vector_store.read().await;
             match store.stats() {
-                Ok(s) => s,
+                Ok(s) => (s, Some(store.chunk_cache_stats())),
                 Err(e) => {
                     let response = IndexStatusResponse {
                         indexed: false,
@@ -676,13 +729,13 @@ impl CodesearchService {
                         model: self.model_type.short_name().to_string(),
                         dimensions: 0,
                         max_chunk_id: 0,
+                        context_lines: None,
                         db_path: self.db_path.display().to_string(),
                         project_path: self.project_path.display().to_string(),
                         error_message: Some(format!("Error getting stats: {}", e)),
+                        chunk_cache: None,
                     };
-                    let json =
-                        serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
-                    return Ok(CallToolResult::success(vec![Content::text(json)]));
+                    return structured(&response);
                 }
             }
         } else {
@@ -697,18 +750,18 @@ impl CodesearchService {
                         model: self.model_type.short_name().to_string(),
                         dimensions: 0,
                         max_chunk_id: 0,
+                        context_lines: None,
                         db_path: self.db_path.display().to_string(),
                         project_path: self.project_path.display().to_string(),
                         error_message: Some(format!("Error getting stats: {}", e)),
+                        chunk_cache: None,
                     };
-                    let json =
-                        serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
-                    return Ok(CallToolResult::success(vec![Content::text(json)]));
+                    return structured(&response);
                 }
             };
 
             match store.stats() {
-                Ok(s) => s,
+                Ok(s) => (s, None),
                 Err(e) => {
                     let response = IndexStatusResponse {
                         indexed: false,
@@ -717,13 +770,13 @@ impl CodesearchService {
                         model: self.model_type.short_name().to_string(),
                         dimensions: 0,
                         max_chunk_id: 0,
+                        context_lines: None,
                         db_path: self.db_path.display().to_string(),
                         project_path: self.project_path.display().to_string(),
                         error_message: Some(format!("Error getting stats: {}", e)),
+                        chunk_cache: None,
                     };
-                    let json =
-                        serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
-                    return Ok(CallToolResult::success(vec![Content::text(json)]));
+                    return structured(&response);
                 }
             }
         };
@@ -735,17 +788,21 @@ impl CodesearchService {
             model: self.model_type.short_name().to_string(),
             dimensions: stats.dimensions,
             max_chunk_id: stats.max_chunk_id,
+            context_lines: IndexMetadata::load_or_default(&self.db_path)
+                .ok()
+                .map(|metadata| metadata.context_lines()),
             db_path: self.db_path.display().to_string(),
             project_path: self.project_path.display().to_string(),
             error_message: None,
+            chunk_cache,
         };
 
-        let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(json)]))
+        structured(&response)
     }
 
     #[tool(
-        description = "Find all available codesearch databases in the current directory, parent directories, and globally tracked repositories. Use this to discover which databases are available for searching."
+        description = "Find all available codesearch databases in the current directory, parent directories, and globally tracked repositories. Use this to discover which databases are available for searching.",
+        output_schema = cached_schema_for_type::<FindDatabasesResponse>()
     )]
     async fn find_databases(&self) -> Result<CallToolResult, McpError> {
         let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
@@ -757,23 +814,11 @@ impl CodesearchService {
             // Get stats for this database
             let (total_chunks, total_files, model) = if db_info.db_path.exists() {
                 // Try to read model from metadata
-                let metadata_path = db_info.db_path.join("metadata.json");
-                let model_name = if metadata_path.exists() {
-                    if let Ok(content) = std::fs::read_to_string(&metadata_path) {
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                            json.get("model_short_name")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown")
-                                .to_string()
-                        } else {
-                            "unknown".to_string()
-                        }
-                    } else {
-                        "unknown".to_string()
-                    }
-                } else {
-                    "unknown".to_string()
-                };
+                let model_name = IndexMetadata::load(&db_info.db_path)
+                    .ok()
+                    .flatten()
+                    .and_then(|metadata| metadata.model_short_name)
+                    .unwrap_or_else(|| "unknown".to_string());
 
                 // Try to get stats - need to infer dimensions from model name
                 let dims = match model_name.as_str() {
@@ -827,8 +872,7 @@ impl CodesearchService {
             current_directory: current_dir.display().to_string(),
         };
 
-        let json = serde_json::to_string(&response).unwrap_or_else(|_| "{}".to_string());
-        Ok(CallToolResult::success(vec![Content::text(json)]))
+        structured(&response)
     }
 }
 
@@ -870,32 +914,43 @@ AVAILABLE TOOLS:
    Use this AFTER find_databases() to verify the database is accessible.
    Returns: Index status, stats, model info, and any error messages.
 
-3. semantic_search(query, limit=10, compact=true, filter_path=null)
+3. semantic_search(query, limit=10, compact=true, filter_path=null, exclude_paths=[], kinds=[])
    Search the codebase using natural language queries.
    By default returns COMPACT results (path, line numbers, kind, signature, score only).
    Set compact=false to include full code content (use sparingly - high token cost).
-   Use filter_path to narrow results to a specific directory (e.g., "src/api/").
+   Use filter_path to narrow results to a directory (e.g., "src/api/") or any of several
+   (e.g., ["src/api/", "src/handlers/"]).
+   Use exclude_paths to drop results under directories (e.g., ["target/", "vendor/"]).
+   Use kinds to keep only some chunk kinds (e.g., ["Method"] for "retry logic, but only methods").
    Query examples:
      - "where do we handle user authentication?"
      - "how is error logging implemented?"
      - "functions that process payment data"
-   Returns: Array of matches with metadata. Use read tool to fetch actual code.
+   Returns: {{results: [...], message?}} with match metadata. Use read tool to fetch actual code.
 
-4. find_references(symbol, limit=50)
+4. find_references(symbol, limit=50, kinds=[], definition_only=false)
    Find all usages/call sites of a function, method, class, or type across the codebase.
+   Use kinds (e.g., ["Function", "Method"]) to drop noise when the name is a common word,
+   and definition_only=true to get only the chunk that declares the symbol.
    ⚠️  USE THIS instead of grep when you need to find where a symbol is used.
    Essential for refactoring — shows all locations that need to change.
    Examples:
      - find_references("authenticate") - Find all calls to authenticate()
      - find_references("UserService") - Find all usages of UserService
      - find_references("handleRequest") - Find all call sites
-   Returns: Compact list of file paths, line numbers, kind, and score.
+   Returns: {{references: [...], message?}} with file paths, line numbers, kind, and score.
 
 5. get_file_chunks(path, compact=true)
    Get all indexed chunks from a specific file.
    Useful for understanding the structure of a file (functions, classes, methods).
    By default returns COMPACT metadata only. Set compact=false for full content.
-   Returns: Chunks with metadata. Use read tool to fetch actual code.
+   Returns: {{chunks: [...], message?}} with metadata. Use read tool to fetch actual code.
+
+6. get_chunk(id)
+   Get a single chunk by the id returned from semantic_search or get_file_chunks.
+   Always returns full content and surrounding context.
+   Use this to re-fetch a specific earlier hit without re-running the query.
+   Returns: One chunk with metadata and content.
 
 TOKEN-EFFICIENT WORKFLOW (IMPORTANT):
 
@@ -1010,6 +1065,148 @@ Dimensions: {dims}
     }
 }
 
+/// Reject kind names that no chunk can have, listing the valid ones
+fn validate_kinds(kinds: &[String]) -> Result<(), String> {
+    match kinds.iter().find(|k| ChunkKind::parse(k).is_none()) {
+        Some(unknown) => Err(format!(
+            "Unknown kind '{}'. Valid kinds: {}",
+            unknown,
+            ChunkKind::ALL
+                .iter()
+                .map(|k| format!("{:?}", k))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Resolve FTS hits to reference items, applying the request's `kinds` and
+/// `definition_only` filters before taking `limit`
+fn resolve_references(
+    store: &VectorStore,
+    fts_results: &[crate::fts::FtsResult],
+    request: &FindReferencesRequest,
+    limit: usize,
+) -> Vec<ReferenceItem> {
+    let definition_only = request.definition_only.unwrap_or(false);
+    fts_results
+        .iter()
+        .filter_map(|fts_result| {
+            let chunk = store.get_chunk(fts_result.chunk_id).ok()??;
+            if !request.kinds.is_empty()
+                && !request
+                    .kinds
+                    .iter()
+                    .any(|k| k.eq_ignore_ascii_case(&chunk.kind))
+            {
+                return None;
+            }
+            if definition_only
+                && !chunk
+                    .signature
+                    .as_deref()
+                    .is_some_and(|sig| signature_declares(sig, &request.symbol))
+            {
+                return None;
+            }
+            Some(ReferenceItem {
+                path: chunk.path,
+                line: chunk.start_line,
+                kind: chunk.kind,
+                signature: chunk.signature,
+                score: fts_result.score,
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Chunk IDs scanned per LMDB read transaction when collecting a file's chunks
+const FILE_CHUNKS_PAGE_SIZE: u32 = 4096;
+
+/// Collect the chunks of the file at `request_path`
+///
+/// Paths match exactly, by suffix (for subdirectory repos) or as given raw. With
+/// a path index only its keys are scanned to find the file, and the file's
+/// chunks are looked up by ID. Older databases without one are scanned page by
+/// page, so memory stays bounded by the page size rather than the index size.
+fn collect_file_chunks(
+    store: &VectorStore,
+    project_path: &std::path::Path,
+    request_path: &str,
+    compact: bool,
+) -> Result<Vec<SearchResultItem>> {
+    // Normalize paths for comparison: strip UNC, normalize slashes
+    let project_norm = normalize_path_for_compare(&project_path.to_string_lossy());
+    let req_norm = normalize_path_for_compare(request_path);
+    let path_matches = |path: &str| {
+        let chunk_norm = normalize_path_for_compare(path);
+
+        // Make chunk path relative by stripping project path prefix
+        let chunk_rel = if chunk_norm.starts_with(&project_norm) {
+            chunk_norm[project_norm.len()..]
+                .trim_start_matches('/')
+                .to_string()
+        } else {
+            chunk_norm.clone()
+        };
+
+        chunk_rel == req_norm
+            || chunk_rel.ends_with(&format!("/{}", req_norm))
+            || req_norm.ends_with(&format!("/{}", chunk_rel))
+            || path == request_path
+    };
+    let item = |id: u32, chunk: ChunkMetadata| SearchResultItem {
+        id,
+        path: chunk.path,
+        start_line: chunk.start_line,
+        end_line: chunk.end_line,
+        kind: chunk.kind,
+        score: 1.0,
+        signature: chunk.signature,
+        language: chunk.language,
+        content: if compact { None } else { Some(chunk.content) },
+        context_prev: if compact { None } else { chunk.context_prev },
+        context_next: if compact { None } else { chunk.context_next },
+    };
+
+    let mut file_chunks = Vec::new();
+    if store.has_path_index() {
+        for path in store.indexed_paths()? {
+            if !path_matches(&path) {
+                continue;
+            }
+            for id in store.chunks_for_path(&path)? {
+                if let Some(chunk) = store.get_chunk(id)? {
+                    file_chunks.push(item(id, chunk));
+                }
+            }
+        }
+        return Ok(file_chunks);
+    }
+
+    // Walk the full ID range rather than 0..total_chunks: IDs have gaps after
+    // delete+insert cycles
+    let Some(max_id) = store.max_chunk_id()? else {
+        return Ok(file_chunks);
+    };
+    let mut start = 0u32;
+    while start <= max_id {
+        let end = start.saturating_add(FILE_CHUNKS_PAGE_SIZE);
+        for (id, chunk) in store.get_chunks_range(start, end)? {
+            if path_matches(&chunk.path) {
+                file_chunks.push(item(id, chunk));
+            }
+        }
+        if end == u32::MAX {
+            break;
+        }
+        start = end;
+    }
+    Ok(file_chunks)
+}
+
 // === Server Entry Point ===
 
 /// Run the MCP server using stdio transport with file watching for live index updates.
@@ -1046,16 +1243,9 @@ pub async fn run_mcp_server(path: Option<PathBuf>, cancel_token: CancellationTok
     tracing::info!("💾 Database: {}", db_path.display());
 
     // Read model metadata to get dimensions
-    let metadata_path = db_path.join("metadata.json");
-    let dimensions = if metadata_path.exists() {
-        let content = std::fs::read_to_string(&metadata_path)?;
-        let json: serde_json::Value = serde_json::from_str(&content)?;
-        json.get("dimensions")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(384) as usize
-    } else {
-        384
-    };
+    let dimensions = IndexMetadata::load_or_default(&db_path)?
+        .dimensions
+        .unwrap_or(384);
 
     // Always open in readonly mode — the `serve` daemon is responsible for
     // indexing and file watching. Running refresh + FSEvents watcher inside
@@ -1081,12 +1271,12 @@ pub async fn run_mcp_server(path: Option<PathBuf>, cancel_token: CancellationTok
     tracing::info!("🔥 Pre-warming ONNX embedding model...");
     {
         let model_type = service.model_type;
-        let cache_dir = crate::constants::get_global_models_cache_dir()?;
-        let embedding = tokio::task::spawn_blocking(move || {
-            EmbeddingService::with_cache_dir(model_type, Some(&cache_dir))
-        })
-        .await
-        .map_err(|e| anyhow::anyhow!("Model pre-warm task panicked: {}", e))?;
+        let metadata = service.metadata.clone();
+        let db_path = service.db_path.clone();
+        let embedding =
+            tokio::task::spawn_blocking(move || metadata.embedding_service(&db_path, model_type))
+                .await
+                .map_err(|e| anyhow::anyhow!("Model pre-warm task panicked: {}", e))?;
 
         match embedding {
             Ok(svc) => {
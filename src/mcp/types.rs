@@ -3,13 +3,15 @@
 use rmcp::schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::vectordb::ChunkCacheStats;
+
 /// Request for semantic search
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct SemanticSearchRequest {
     /// The search query (natural language or code snippet)
     pub query: String,
 
-    /// Maximum number of results to return (default: 10)
+    /// Maximum number of results to return (default: 10, capped at 200)
     pub limit: Option<usize>,
 
     /// Return compact results (metadata only) to save tokens (default: true).
@@ -18,8 +20,36 @@ pub struct SemanticSearchRequest {
     /// Use compact=true (default) and then read specific files with line offsets for the code you need.
     pub compact: Option<bool>,
 
-    /// Only return results from files under this path prefix (e.g., "src/api/")
-    pub filter_path: Option<String>,
+    /// Only return results from files under this path prefix (e.g., "src/api/"),
+    /// or under any of a list of prefixes (e.g., ["src/api/", "src/handlers/"])
+    pub filter_path: Option<OneOrMany>,
+
+    /// Exclude results from files under any of these path prefixes (e.g., ["target/", "vendor/"]).
+    /// Excludes take precedence over filter_path.
+    #[serde(default)]
+    pub exclude_paths: Vec<String>,
+
+    /// Only return results of these chunk kinds (e.g., ["Function", "Method"], case-insensitive).
+    /// Empty means all kinds.
+    #[serde(default)]
+    pub kinds: Vec<String>,
+}
+
+/// A single string or a list of strings (`"a"` or `["a", "b"]`)
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    pub fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(value) => vec![value],
+            Self::Many(values) => values,
+        }
+    }
 }
 
 /// Request to get file chunks
@@ -42,30 +72,80 @@ pub struct FindReferencesRequest {
     /// The symbol name to find references for (e.g., "authenticate", "User", "Config")
     pub symbol: String,
 
-    /// Maximum number of references to return (default: 20)
+    /// Maximum number of references to return (default: 20, capped at 200)
     pub limit: Option<usize>,
+
+    /// Only return references in chunks of these kinds (e.g., ["Function", "Method"], case-insensitive).
+    /// Empty means all kinds.
+    #[serde(default)]
+    pub kinds: Vec<String>,
+
+    /// Only return the chunk(s) whose signature declares the symbol (default: false)
+    pub definition_only: Option<bool>,
 }
 
-/// Search result item - returned by semantic_search and get_file_chunks
-#[derive(Debug, Serialize)]
+/// Request to fetch a single chunk by its ID.
+/// Chunk IDs are returned in the `id` field of semantic_search and get_file_chunks results.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetChunkRequest {
+    /// The chunk ID from a previous semantic_search or get_file_chunks result
+    pub id: u32,
+}
+
+/// Search result item - returned by semantic_search, get_file_chunks and get_chunk
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct SearchResultItem {
+    /// Chunk ID (can be passed to get_chunk to re-fetch this result)
+    pub id: u32,
+    /// File path of the chunk
     pub path: String,
+    /// First line of the chunk (1-based)
     pub start_line: usize,
+    /// Last line of the chunk (inclusive)
     pub end_line: usize,
+    /// Chunk kind (e.g., "Function", "Struct", "Method")
     pub kind: String,
+    /// Relevance score (1.0 for chunks fetched by path or ID)
     pub score: f32,
+    /// Signature of the function/type (if available)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
+    /// Language of the source file (e.g., "Rust"); absent for chunks from older indexes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Full chunk content (omitted in compact mode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    /// Lines before the chunk (omitted in compact mode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_prev: Option<String>,
+    /// Lines after the chunk (omitted in compact mode)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_next: Option<String>,
 }
 
+/// semantic_search response
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SemanticSearchResponse {
+    /// Matches, best first
+    pub results: Vec<SearchResultItem>,
+    /// Hint for the caller when there are no results or `limit` was capped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// get_file_chunks response
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FileChunksResponse {
+    /// Chunks of the file, ordered by start_line
+    pub chunks: Vec<SearchResultItem>,
+    /// Hint for the caller when the file has no chunks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
 /// Reference/call site item - returned by find_references
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ReferenceItem {
     /// File path containing the reference
     pub path: String,
@@ -80,8 +160,18 @@ pub struct ReferenceItem {
     pub score: f32,
 }
 
+/// find_references response
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FindReferencesResponse {
+    /// References, most relevant first
+    pub references: Vec<ReferenceItem>,
+    /// Hint for the caller when nothing was found or `limit` was capped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
 /// Index status response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct IndexStatusResponse {
     pub indexed: bool,
     pub total_chunks: usize,
@@ -89,14 +179,20 @@ pub struct IndexStatusResponse {
     pub model: String,
     pub dimensions: usize,
     pub max_chunk_id: u32,
+    /// Lines of surrounding code stored with each chunk as `context_prev`/`context_next`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_lines: Option<usize>,
     pub db_path: String,
     pub project_path: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// Chunk metadata cache counters of the server's long-lived store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_cache: Option<ChunkCacheStats>,
 }
 
 /// Database info response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct DatabaseInfoResponse {
     pub database_path: String,
     pub project_path: String,
@@ -108,7 +204,7 @@ pub struct DatabaseInfoResponse {
 }
 
 /// Find databases response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct FindDatabasesResponse {
     pub databases: Vec<DatabaseInfoResponse>,
     pub message: String,
@@ -122,12 +218,14 @@ mod tests {
     #[test]
     fn test_search_result_item_serialization_compact() {
         let item = SearchResultItem {
+            id: 7,
             path: "src/main.rs".to_string(),
             start_line: 1,
             end_line: 10,
             kind: "Function".to_string(),
             score: 0.95,
             signature: Some("fn main()".to_string()),
+            language: None,
             content: None,
             context_prev: None,
             context_next: None,
@@ -137,21 +235,25 @@ mod tests {
         assert!(json.contains("src/main.rs"));
         assert!(json.contains("Function"));
         assert!(json.contains("fn main()"));
+        assert!(json.contains("\"id\":7"));
         // None fields should be omitted
         assert!(!json.contains("content"));
         assert!(!json.contains("context_prev"));
         assert!(!json.contains("context_next"));
+        assert!(!json.contains("language"));
     }
 
     #[test]
     fn test_search_result_item_serialization_full() {
         let item = SearchResultItem {
+            id: 42,
             path: "src/lib.rs".to_string(),
             start_line: 5,
             end_line: 20,
             kind: "Struct".to_string(),
             score: 0.85,
             signature: None,
+            language: Some("Rust".to_string()),
             content: Some("pub struct Foo {}".to_string()),
             context_prev: Some("// before".to_string()),
             context_next: Some("// after".to_string()),
@@ -161,6 +263,7 @@ mod tests {
         assert!(json.contains("content"));
         assert!(json.contains("context_prev"));
         assert!(json.contains("context_next"));
+        assert!(json.contains("\"language\":\"Rust\""));
         assert!(!json.contains("signature"));
     }
 
@@ -205,9 +308,11 @@ mod tests {
             model: "bge-small".to_string(),
             dimensions: 384,
             max_chunk_id: 999,
+            context_lines: Some(3),
             db_path: "/tmp/db".to_string(),
             project_path: "/tmp/project".to_string(),
             error_message: None,
+            chunk_cache: None,
         };
 
         let json = serde_json::to_string(&resp).unwrap();
@@ -225,9 +330,11 @@ mod tests {
             model: "unknown".to_string(),
             dimensions: 0,
             max_chunk_id: 0,
+            context_lines: None,
             db_path: "".to_string(),
             project_path: "".to_string(),
             error_message: Some("Database not found".to_string()),
+            chunk_cache: None,
         };
 
         let json = serde_json::to_string(&resp).unwrap();
@@ -268,6 +375,38 @@ mod tests {
         assert_eq!(parsed["message"], "No databases found");
     }
 
+    #[test]
+    fn test_output_schemas_are_objects() {
+        use rmcp::handler::server::tool::schema_for_type;
+
+        let schema = schema_for_type::<SemanticSearchResponse>();
+        assert_eq!(schema["type"], "object");
+        assert!(schema["properties"]["results"].is_object());
+        assert_eq!(
+            schema_for_type::<FindReferencesResponse>()["type"],
+            "object"
+        );
+        assert_eq!(schema_for_type::<FileChunksResponse>()["type"], "object");
+        assert_eq!(schema_for_type::<IndexStatusResponse>()["type"], "object");
+    }
+
+    #[test]
+    fn test_empty_response_carries_message() {
+        let resp = FindReferencesResponse {
+            references: vec![],
+            message: Some("No references found".to_string()),
+        };
+        let parsed = serde_json::to_value(&resp).unwrap();
+        assert!(parsed["references"].as_array().unwrap().is_empty());
+        assert_eq!(parsed["message"], "No references found");
+
+        let resp = SemanticSearchResponse {
+            results: vec![],
+            message: None,
+        };
+        assert!(!serde_json::to_string(&resp).unwrap().contains("message"));
+    }
+
     #[test]
     fn test_semantic_search_request_deserialization() {
         let json = r#"{"query": "find authentication", "limit": 5, "compact": true}"#;
@@ -276,6 +415,36 @@ mod tests {
         assert_eq!(req.limit, Some(5));
         assert_eq!(req.compact, Some(true));
         assert_eq!(req.filter_path, None);
+        assert!(req.exclude_paths.is_empty());
+        assert!(req.kinds.is_empty());
+    }
+
+    #[test]
+    fn test_semantic_search_request_exclude_paths() {
+        let json = r#"{"query": "test", "exclude_paths": ["target/", "vendor/"]}"#;
+        let req: SemanticSearchRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.exclude_paths, vec!["target/", "vendor/"]);
+    }
+
+    #[test]
+    fn test_semantic_search_request_filter_path_one_or_many() {
+        let json = r#"{"query": "test", "filter_path": "src/api/"}"#;
+        let req: SemanticSearchRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.filter_path.unwrap().into_vec(), vec!["src/api/"]);
+
+        let json = r#"{"query": "test", "filter_path": ["src/api/", "src/handlers/"]}"#;
+        let req: SemanticSearchRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            req.filter_path.unwrap().into_vec(),
+            vec!["src/api/", "src/handlers/"]
+        );
+    }
+
+    #[test]
+    fn test_semantic_search_request_kinds() {
+        let json = r#"{"query": "retry logic", "kinds": ["Method", "function"]}"#;
+        let req: SemanticSearchRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.kinds, vec!["Method", "function"]);
     }
 
     #[test]
@@ -309,5 +478,29 @@ mod tests {
         let req: FindReferencesRequest = serde_json::from_str(json).unwrap();
         assert_eq!(req.symbol, "foo");
         assert_eq!(req.limit, None);
+        assert!(req.kinds.is_empty());
+        assert_eq!(req.definition_only, None);
+    }
+
+    #[test]
+    fn test_find_references_request_filters() {
+        let json =
+            r#"{"symbol": "parse", "kinds": ["Function", "method"], "definition_only": true}"#;
+        let req: FindReferencesRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.kinds, vec!["Function", "method"]);
+        assert_eq!(req.definition_only, Some(true));
+    }
+
+    #[test]
+    fn test_get_chunk_request_deserialization() {
+        let json = r#"{"id": 1234}"#;
+        let req: GetChunkRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.id, 1234);
+    }
+
+    #[test]
+    fn test_get_chunk_request_rejects_negative_id() {
+        let json = r#"{"id": -1}"#;
+        assert!(serde_json::from_str::<GetChunkRequest>(json).is_err());
     }
 }
@@ -33,6 +33,30 @@ pub fn print_warn(args: std::fmt::Arguments<'_>) {
     }
 }
 
+/// Shorten `text` to at most `max_len` bytes followed by "...", cutting on a char boundary
+///
+/// Slicing at a raw byte index panics when it lands inside a multibyte character.
+pub fn truncate_with_ellipsis(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...", &text[..end])
+}
+
+/// A byte count in MB, or GB from 1 GB up
+pub fn format_size(bytes: u64) -> String {
+    let mb = bytes as f64 / (1024.0 * 1024.0);
+    if mb >= 1024.0 {
+        format!("{:.1} GB", mb / 1024.0)
+    } else {
+        format!("{:.1} MB", mb)
+    }
+}
+
 /// Print a message only if not in quiet mode
 #[macro_export]
 macro_rules! info_print {
@@ -65,6 +89,33 @@ mod tests {
         assert!(!is_quiet());
     }
 
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        assert_eq!(truncate_with_ellipsis("short", 10), "short");
+        assert_eq!(truncate_with_ellipsis("exactly", 7), "exactly");
+        assert_eq!(truncate_with_ellipsis("truncated", 5), "trunc...");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0.0 MB");
+        assert_eq!(format_size(90 * 1024 * 1024 + 512 * 1024), "90.5 MB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GB");
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis_multibyte() {
+        // 'é' is 2 bytes, so byte 5 falls inside the third one
+        assert_eq!(truncate_with_ellipsis("éééé", 5), "éé...");
+        // '🦀' is 4 bytes; a limit inside the first one keeps nothing of it
+        assert_eq!(truncate_with_ellipsis("🦀🦀", 3), "...");
+        let comment = "// 日本語のコメント ".repeat(20);
+        for max_len in 0..comment.len() {
+            let truncated = truncate_with_ellipsis(&comment, max_len);
+            assert!(truncated.len() <= max_len + 3);
+        }
+    }
+
     #[test]
     fn test_quiet_mode_default_is_false() {
         // Reset to known state
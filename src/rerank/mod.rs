@@ -10,7 +10,7 @@ use std::collections::HashMap;
 use crate::fts::FtsResult;
 use crate::vectordb::SearchResult;
 
-pub use neural::NeuralReranker;
+pub use neural::{NeuralReranker, RerankerType};
 
 /// Default RRF k parameter (per osgrep reference)
 pub const DEFAULT_RRF_K: f32 = 20.0;
@@ -34,6 +34,8 @@ pub struct FusedResult {
     pub vector_rank: Option<usize>,
     /// FTS rank (1-indexed, None if not in FTS results)
     pub fts_rank: Option<usize>,
+    /// Exact identifier match rank (1-indexed, None without an exact match)
+    pub exact_rank: Option<usize>,
 }
 
 /// Reciprocal Rank Fusion (RRF) for combining search results
@@ -90,6 +92,7 @@ pub fn rrf_fusion(
                 fts_score,
                 vector_rank,
                 fts_rank,
+                exact_rank: None,
             },
         )
         .collect();
@@ -116,6 +119,7 @@ pub fn vector_only(vector_results: &[SearchResult]) -> Vec<FusedResult> {
             fts_score: None,
             vector_rank: Some(rank + 1),
             fts_rank: None,
+            exact_rank: None,
         })
         .collect()
 }
@@ -225,6 +229,7 @@ pub fn rrf_fusion_with_exact(
                     fts_score: combined_fts_score,
                     vector_rank,
                     fts_rank: fts_rank.or(exact_rank),
+                    exact_rank,
                 }
             },
         )
@@ -260,6 +265,7 @@ mod tests {
             context: None,
             docstring: None,
             hash: String::new(),
+            language: None,
         }
     }
 
@@ -432,6 +438,8 @@ mod tests {
 
         assert!(fused_with_exact[0].rrf_score > fused_without_exact[0].rrf_score,
             "Exact match should boost the score");
+        assert_eq!(fused_with_exact[0].exact_rank, Some(1));
+        assert_eq!(fused_without_exact[0].exact_rank, None);
     }
 
     #[test]
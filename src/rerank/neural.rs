@@ -1,59 +1,142 @@
 //! Neural reranking using cross-encoder models
 //!
-//! Provides second-pass reranking using fastembed's TextRerank
-//! with the Jina Reranker v1 Turbo model for improved accuracy.
+//! Provides second-pass reranking using fastembed's TextRerank. Jina Reranker
+//! v1 Turbo is the default; other cross-encoders, or a local ONNX export, can be
+//! selected with `RerankerType`.
 
 use crate::info_print;
-use anyhow::Result;
-use fastembed::{RerankInitOptions, RerankerModel, TextRerank};
+use anyhow::{anyhow, Context, Result};
+use fastembed::{
+    RerankInitOptions, RerankInitOptionsUserDefined, RerankerModel, TextRerank, TokenizerFiles,
+    UserDefinedRerankingModel,
+};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 /// Score blending weights (per osgrep pattern)
 /// 57.5% rerank + 42.5% RRF
 pub const RERANK_WEIGHT: f32 = 0.575;
 pub const RRF_WEIGHT: f32 = 0.425;
 
+/// Available cross-encoder reranker models
+///
+/// Cross-encoders score (query, document) pairs directly, so unlike embedding
+/// models they have no dimensions to match against the index.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RerankerType {
+    /// Jina Reranker v1 Turbo EN - small and fast
+    #[default]
+    JinaV1TurboEn,
+    /// Jina Reranker v2 Base Multilingual - slower, more accurate, multilingual
+    JinaV2BaseMultilingual,
+    /// BGE Reranker Base - English and Chinese
+    BgeBase,
+    /// BGE Reranker v2 M3 - large multilingual model, best accuracy
+    BgeV2M3,
+    /// A local ONNX cross-encoder: a directory holding `model.onnx` and its tokenizer files
+    Local(PathBuf),
+}
+
+impl RerankerType {
+    /// The fastembed model, or `None` for a local model
+    pub fn to_fastembed_model(&self) -> Option<RerankerModel> {
+        match self {
+            Self::JinaV1TurboEn => Some(RerankerModel::JINARerankerV1TurboEn),
+            Self::JinaV2BaseMultilingual => Some(RerankerModel::JINARerankerV2BaseMultiligual),
+            Self::BgeBase => Some(RerankerModel::BGERerankerBase),
+            Self::BgeV2M3 => Some(RerankerModel::BGERerankerV2M3),
+            Self::Local(_) => None,
+        }
+    }
+
+    /// Get a short identifier for the model (as accepted by `--rerank-model`)
+    pub fn short_name(&self) -> String {
+        match self {
+            Self::JinaV1TurboEn => "jina-v1-turbo".to_string(),
+            Self::JinaV2BaseMultilingual => "jina-v2-multilingual".to_string(),
+            Self::BgeBase => "bge-base".to_string(),
+            Self::BgeV2M3 => "bge-v2-m3".to_string(),
+            Self::Local(dir) => dir.display().to_string(),
+        }
+    }
+
+    /// Parse a reranker from a short name or a path to a local model directory
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "jina-v1-turbo" | "jina" => Some(Self::JinaV1TurboEn),
+            "jina-v2-multilingual" => Some(Self::JinaV2BaseMultilingual),
+            "bge-base" => Some(Self::BgeBase),
+            "bge-v2-m3" => Some(Self::BgeV2M3),
+            _ if Path::new(s).is_dir() => Some(Self::Local(PathBuf::from(s))),
+            _ => None,
+        }
+    }
+}
+
 /// Neural reranker using cross-encoder model
-#[allow(dead_code)] // model_name used for diagnostics
 pub struct NeuralReranker {
     reranker: TextRerank,
-    model_name: String,
+    kind: RerankerType,
 }
 
 impl NeuralReranker {
     /// Create a new neural reranker with the default Jina model
+    #[allow(dead_code)] // Library API, the CLI always passes a RerankerType
     pub fn new() -> Result<Self> {
-        Self::with_model(RerankerModel::JINARerankerV1TurboEn)
+        Self::with_model(RerankerType::default())
     }
 
     /// Create a neural reranker with a specific model
-    pub fn with_model(model: RerankerModel) -> Result<Self> {
-        let model_name = model.to_string();
-        info_print!("Loading reranker model: {}", model_name);
-
-        let mut options = RerankInitOptions::default();
-        options.model_name = model;
-        options.show_download_progress = false;
+    ///
+    /// Downloaded models are cached in the global models cache, next to the
+    /// embedding models.
+    pub fn with_model(kind: RerankerType) -> Result<Self> {
+        info_print!("Loading reranker model: {}", kind.short_name());
+        // Shares the ONNX Runtime thread pool configured for embedding
+        crate::embed::init_runtime();
 
-        let reranker = TextRerank::try_new(options)?;
+        let reranker = match (&kind, kind.to_fastembed_model()) {
+            (_, Some(model)) => {
+                let mut options = RerankInitOptions::default();
+                options.model_name = model;
+                options.show_download_progress = false;
+                options.cache_dir = crate::constants::get_global_models_cache_dir()?;
+                TextRerank::try_new(options)?
+            }
+            (RerankerType::Local(dir), None) => load_local(dir)?,
+            (_, None) => return Err(anyhow!("No model for reranker {:?}", kind)),
+        };
 
         info_print!("Reranker model loaded successfully!");
 
-        Ok(Self {
-            reranker,
-            model_name,
-        })
+        Ok(Self { reranker, kind })
+    }
+
+    /// The model this reranker was loaded with
+    pub fn kind(&self) -> &RerankerType {
+        &self.kind
     }
 
     /// Get the model name
     #[allow(dead_code)] // Reserved for diagnostics
-    pub fn model_name(&self) -> &str {
-        &self.model_name
+    pub fn model_name(&self) -> String {
+        self.kind.short_name()
     }
 
     /// Rerank documents given a query
     ///
     /// Returns Vec of (original_index, rerank_score) sorted by score descending
     pub fn rerank(&mut self, query: &str, documents: &[String]) -> Result<Vec<(usize, f32)>> {
+        self.rerank_batched(query, documents, rerank_batch_size())
+    }
+
+    /// [`Self::rerank`] with an explicit batch size (1 scores one document per run)
+    fn rerank_batched(
+        &mut self,
+        query: &str,
+        documents: &[String],
+        batch_size: usize,
+    ) -> Result<Vec<(usize, f32)>> {
         if documents.is_empty() {
             return Ok(vec![]);
         }
@@ -61,11 +144,24 @@ impl NeuralReranker {
         // Convert to &str references for fastembed API
         let doc_refs: Vec<&str> = documents.iter().map(|s| s.as_str()).collect();
 
-        // Rerank using the cross-encoder
+        // All (query, document) pairs go through the cross-encoder in batches of
+        // `batch_size`, one ONNX run per batch
+        let start = Instant::now();
         let results = self.reranker.rerank(
-            query, doc_refs, false, // Don't return documents (we have them)
-            None,  // Use default batch size
+            query,
+            doc_refs,
+            false, // Don't return documents (we have them)
+            Some(batch_size),
         )?;
+        let elapsed = start.elapsed();
+        tracing::debug!(
+            "Reranked {} documents in {} batch(es) of {} in {:?} ({:.1}ms/doc)",
+            documents.len(),
+            documents.len().div_ceil(batch_size),
+            batch_size,
+            elapsed,
+            elapsed.as_secs_f64() * 1000.0 / documents.len() as f64
+        );
 
         // Convert to (index, score) pairs
         Ok(results.into_iter().map(|r| (r.index, r.score)).collect())
@@ -121,6 +217,46 @@ impl NeuralReranker {
     }
 }
 
+/// Documents scored per cross-encoder run, from `CODESEARCH_RERANK_BATCH`
+fn rerank_batch_size() -> usize {
+    std::env::var("CODESEARCH_RERANK_BATCH")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(crate::constants::DEFAULT_RERANK_BATCH_SIZE)
+}
+
+/// Load a cross-encoder exported to ONNX from `dir`
+///
+/// Expects `model.onnx` (or `onnx/model.onnx`) plus the Hugging Face tokenizer
+/// files, as produced by `optimum-cli export onnx`.
+fn load_local(dir: &Path) -> Result<TextRerank> {
+    let read = |name: &str| {
+        std::fs::read(dir.join(name))
+            .with_context(|| format!("Missing {} in {}", name, dir.display()))
+    };
+
+    let onnx_path = ["model.onnx", "onnx/model.onnx"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| anyhow!("No model.onnx found in {}", dir.display()))?;
+    let onnx_file = std::fs::read(&onnx_path)?;
+
+    let tokenizer_files = TokenizerFiles {
+        tokenizer_file: read("tokenizer.json")?,
+        config_file: read("config.json")?,
+        special_tokens_map_file: read("special_tokens_map.json")?,
+        tokenizer_config_file: read("tokenizer_config.json")?,
+    };
+
+    TextRerank::try_new_from_user_defined(
+        UserDefinedRerankingModel::new(onnx_file, tokenizer_files),
+        RerankInitOptionsUserDefined::default(),
+    )
+    .map_err(|e| anyhow!("Failed to load reranker from {}: {}", dir.display(), e))
+}
+
 /// Sigmoid function to normalize scores to [0, 1]
 fn sigmoid(x: f32) -> f32 {
     1.0 / (1.0 + (-x).exp())
@@ -137,6 +273,26 @@ mod tests {
         assert!(sigmoid(-10.0) < 0.01);
     }
 
+    #[test]
+    fn test_reranker_type_parse() {
+        assert_eq!(
+            RerankerType::parse("jina"),
+            Some(RerankerType::JinaV1TurboEn)
+        );
+        assert_eq!(
+            RerankerType::parse("BGE-V2-M3"),
+            Some(RerankerType::BgeV2M3)
+        );
+        assert_eq!(RerankerType::parse("no-such-reranker"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        assert_eq!(
+            RerankerType::parse(&path),
+            Some(RerankerType::Local(dir.path().to_path_buf()))
+        );
+    }
+
     #[test]
     #[ignore] // Requires model download
     fn test_reranker_creation() {
@@ -167,4 +323,47 @@ mod tests {
             assert!(results[i].1 >= results[i + 1].1);
         }
     }
+
+    /// Batched vs one-document-per-run timing, as for `--rerank-top 50`:
+    /// `cargo test --release rerank_batched_vs_single -- --ignored --nocapture`
+    #[test]
+    #[ignore] // Requires model download
+    fn test_rerank_batched_vs_single() {
+        let mut reranker = NeuralReranker::new().unwrap();
+        let query = "How do I authenticate users?";
+        let documents: Vec<String> = (0..50)
+            .map(|i| {
+                format!(
+                    "fn handler_{}(request: &Request) -> Response {{ todo!() }}",
+                    i
+                )
+            })
+            .collect();
+        // Load and warm up the session before timing
+        reranker.rerank_batched(query, &documents[..1], 1).unwrap();
+
+        let start = Instant::now();
+        let single = reranker.rerank_batched(query, &documents, 1).unwrap();
+        let single_time = start.elapsed();
+        let batch_size = crate::constants::DEFAULT_RERANK_BATCH_SIZE;
+        let start = Instant::now();
+        let batched = reranker
+            .rerank_batched(query, &documents, batch_size)
+            .unwrap();
+        let batched_time = start.elapsed();
+        println!(
+            "50 documents: one per run {:?}, batches of {} {:?} ({:.1}x)",
+            single_time,
+            batch_size,
+            batched_time,
+            single_time.as_secs_f64() / batched_time.as_secs_f64()
+        );
+
+        // Batching changes throughput, not scores
+        assert_eq!(single.len(), batched.len());
+        for ((i, a), (j, b)) in single.iter().zip(&batched) {
+            assert_eq!(i, j);
+            assert!((a - b).abs() < 1e-3, "{} vs {}", a, b);
+        }
+    }
 }
@@ -0,0 +1,152 @@
+//! `codesearch search --interactive`: a query loop over one open `Searcher`
+//!
+//! The index and embedding model are loaded once, so every query after the first
+//! only pays for embedding and retrieval. Lines starting with `:` change options
+//! for the following queries.
+
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use super::profile::report_profile;
+use super::query::with_query_filters;
+use super::{prepare_search, print_degraded_banner, print_results, SearchOptions, Searcher};
+
+const HELP: &str = "\
+Type a query, or a command:
+  :limit N         return at most N results
+  :filter PATH...  only show results under any of the PATHs (`:filter` alone clears it)
+  kind:K path:P lang:L sig:S name:N  filter one query (see the README)
+  :help            show this help
+  :quit            exit (or Ctrl-D)";
+
+/// What to do with one line of input
+#[derive(Debug, PartialEq)]
+enum Input {
+    Query(String),
+    /// An option changed, or help was printed
+    Handled,
+    Quit,
+}
+
+/// Read queries from stdin and print results for each until EOF or `:quit`
+pub async fn interactive(path: Option<PathBuf>, mut options: SearchOptions) -> Result<()> {
+    let Some((db_path, project_path)) = prepare_search(path, &options)? else {
+        return Ok(());
+    };
+    let searcher = Searcher::open_for(&db_path, &options)?;
+    print_degraded_banner(&searcher);
+
+    let stdin = std::io::stdin();
+    let prompt = stdin.is_terminal();
+    if prompt {
+        println!(
+            "{}",
+            "🔍 Interactive search (:help for commands)".bright_cyan()
+        );
+    }
+
+    let mut lines = stdin.lock().lines();
+    loop {
+        if prompt {
+            print!("{} ", ">".bright_cyan());
+            std::io::stdout().flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+
+        match parse_input(&line, &mut options) {
+            Ok(Input::Query(query)) => {
+                let (query, options) = match with_query_filters(&query, &options) {
+                    Ok(split) => split,
+                    Err(e) => {
+                        eprintln!("{}", e.to_string().yellow());
+                        continue;
+                    }
+                };
+                // A failed query (say, a dimension mismatch) is reported, not fatal
+                let (results, timing, explain) = match searcher.search_explained(&query, &options) {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        eprintln!("{}", format!("⚠️  Search failed: {:#}", e).yellow());
+                        continue;
+                    }
+                };
+                let start = Instant::now();
+                print_results(&query, results, &timing, &explain, &project_path, &options)?;
+                report_profile(&timing, start.elapsed(), &options)?;
+            }
+            Ok(Input::Handled) => {}
+            Ok(Input::Quit) => break,
+            Err(e) => eprintln!("{}", e.to_string().yellow()),
+        }
+    }
+    Ok(())
+}
+
+/// Apply a `:command` to `options`, or return the line as a query
+fn parse_input(line: &str, options: &mut SearchOptions) -> Result<Input> {
+    let line = line.trim();
+    let Some(command) = line.strip_prefix(':') else {
+        return Ok(if line.is_empty() {
+            Input::Handled
+        } else {
+            Input::Query(line.to_string())
+        });
+    };
+
+    let (name, arg) = command
+        .split_once(char::is_whitespace)
+        .map(|(name, arg)| (name, arg.trim()))
+        .unwrap_or((command, ""));
+    match name {
+        "limit" | "l" => {
+            options.max_results = arg
+                .parse()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| anyhow!("Usage: :limit N (N > 0)"))?;
+        }
+        "filter" | "f" => {
+            options.filter_paths = arg.split_whitespace().map(str::to_string).collect();
+        }
+        "help" | "h" | "?" => println!("{}", HELP),
+        "quit" | "q" | "exit" => return Ok(Input::Quit),
+        _ => return Err(anyhow!("Unknown command :{} (try :help)", name)),
+    }
+    Ok(Input::Handled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input_commands() {
+        let mut options = SearchOptions::default();
+
+        assert_eq!(
+            parse_input("  error handling ", &mut options).unwrap(),
+            Input::Query("error handling".to_string())
+        );
+        assert_eq!(parse_input("", &mut options).unwrap(), Input::Handled);
+
+        parse_input(":limit 20", &mut options).unwrap();
+        assert_eq!(options.max_results, 20);
+        assert!(parse_input(":limit 0", &mut options).is_err());
+        assert_eq!(options.max_results, 20);
+
+        parse_input(":filter src/", &mut options).unwrap();
+        assert_eq!(options.filter_paths, vec!["src/"]);
+        parse_input(":filter src/api/ src/handlers/", &mut options).unwrap();
+        assert_eq!(options.filter_paths, vec!["src/api/", "src/handlers/"]);
+        parse_input(":filter", &mut options).unwrap();
+        assert!(options.filter_paths.is_empty());
+
+        assert_eq!(parse_input(":q", &mut options).unwrap(), Input::Quit);
+        assert!(parse_input(":bogus", &mut options).is_err());
+    }
+}
@@ -1,18 +1,36 @@
 use anyhow::Result;
 use colored::Colorize;
 use rayon::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 use crate::cache::FileMetaStore;
 use crate::chunker::SemanticChunker;
 use crate::embed::{EmbeddingService, ModelType};
-use crate::file::FileWalker;
-use crate::fts::FtsStore;
-use crate::rerank::{rrf_fusion, vector_only, FusedResult, NeuralReranker, DEFAULT_RRF_K};
+use crate::error::{CodeSearchError, Result as CsResult};
+use crate::file::{FileInfo, FileWalker, ProjectConfig};
+use crate::fts::{FtsResult, FtsStore};
+use crate::info_print;
+use crate::rerank::{
+    rrf_fusion, vector_only, FusedResult, NeuralReranker, RerankerType, DEFAULT_RRF_K,
+};
 use crate::vectordb::VectorStore;
 
+mod interactive;
+mod open;
+mod profile;
+mod query;
+mod similar;
+mod watch;
+
+pub use interactive::interactive;
+pub use profile::SearchProfile;
+use query::with_query_filters;
+pub use similar::similar;
+pub use watch::search_watch;
+
 /// Configuration options for search operations
 #[derive(Debug, Clone)]
 pub struct SearchOptions {
@@ -20,28 +38,95 @@ pub struct SearchOptions {
     pub max_results: usize,
     /// Maximum number of results per file
     pub per_file: Option<usize>,
+    /// Largest share (0-1] of `max_results` any one file may fill; other files backfill the rest
+    pub max_file_ratio: Option<f32>,
+    /// Merge chunks of the same definition (split parts of a long function) into one result
+    pub group_by_symbol: bool,
     /// Number of content lines to show
     pub content_lines: usize,
+    /// Lines of context to re-read from the file on disk around each match (grep `-C`)
+    pub context: Option<usize>,
     /// Whether to show scores
     pub show_scores: bool,
     /// Compact output mode
     pub compact: bool,
+    /// Print `path:line:1: text` lines like grep/ripgrep (for editor quickfix lists)
+    pub grep: bool,
     /// Sync database before search
     pub sync: bool,
+    /// Print the files a sync would re-index or remove, then exit without searching
+    pub sync_dry_run: bool,
+    /// Sync automatically when the pre-search staleness check finds changed files
+    /// (also enabled by the `CODESEARCH_AUTO_SYNC` environment variable)
+    pub auto_sync: bool,
+    /// Use this database directory directly instead of discovering one from the path
+    pub db_path: Option<PathBuf>,
     /// JSON output mode
     pub json: bool,
-    /// Optional path filter
-    pub filter_path: Option<String>,
+    /// JSON Lines output mode (one result per line)
+    pub jsonl: bool,
+    /// Print only the number of results
+    pub count: bool,
+    /// Don't cap results at `max_results` (counts every fused match that passes filters)
+    pub count_uncapped: bool,
+    /// Only keep results under any of these path prefixes (empty = no restriction)
+    pub filter_paths: Vec<String>,
+    /// Path prefixes to exclude (take precedence over `filter_paths`)
+    pub exclude_paths: Vec<String>,
+    /// Only return test chunks (`ChunkKind::Test`)
+    pub tests_only: bool,
+    /// Exclude test chunks (`ChunkKind::Test`)
+    pub exclude_tests: bool,
+    /// Only keep these chunk kinds, by stored name (`kind:` in the query; empty = all)
+    pub filter_kinds: Vec<String>,
+    /// Only keep these languages, by stored name (`lang:` in the query; empty = all)
+    pub filter_languages: Vec<String>,
+    /// Only keep results whose signature contains one of these (`sig:` in the query)
+    pub filter_signatures: Vec<String>,
+    /// Only keep results whose signature declares one of these symbols (`name:` in the query)
+    pub filter_names: Vec<String>,
     /// Optional model override
     pub model_override: Option<String>,
     /// Vector-only mode (skip FTS)
     pub vector_only: bool,
+    /// Full-text-only mode: rank with BM25 alone, without the embedding model
+    pub fts_only: bool,
     /// RRF fusion constant
     pub rrf_k: Option<usize>,
+    /// Retry identifiers with fuzzy FTS matching when exact matching finds nothing
+    pub fuzzy: bool,
+    /// Match identifiers case-sensitively in the exact-match branch (`getUser` != `getuser`)
+    pub case_sensitive: bool,
+    /// Embed query variants (`fn foo`, `struct Foo`, ...) alongside the query itself
+    pub expand_query: bool,
     /// Enable neural reranking
     pub rerank: bool,
+    /// Rerank only when the top results are ambiguous (close scores, no high-confidence hits)
+    pub auto_rerank: bool,
     /// Number of results to rerank
     pub rerank_top: Option<usize>,
+    /// Cross-encoder used for reranking (None = default Jina v1 Turbo)
+    pub rerank_model: Option<RerankerType>,
+    /// Upper bound on candidates retrieved per query variant (None = `CODESEARCH_MAX_RETRIEVAL` or uncapped)
+    pub retrieval_limit: Option<usize>,
+    /// Min-max normalize final scores into 0-1 (relative to this query's results)
+    pub normalize_scores: bool,
+    /// Drop results whose final score is below this threshold (applied before truncation)
+    pub min_score: Option<f32>,
+    /// Highlight matched query terms in output (and emit `highlights` in JSON)
+    pub highlight: bool,
+    /// Emit the signature's line and column in JSON results (for editor integrations)
+    pub positions: bool,
+    /// After printing, open the N-th result (1-based, ranked order) in `$EDITOR`
+    pub open: Option<usize>,
+    /// Show why each result ranked where it did (ranks, scores, boosts)
+    pub explain: bool,
+    /// Collect a per-stage timing breakdown into `SearchTiming::profile`
+    pub profile: bool,
+    /// Score boost for results in the project's primary language (0.0 disables)
+    pub language_boost: f32,
+    /// Score boost for results whose kind matches the query's structural intent (0.0 disables)
+    pub kind_boost: f32,
 }
 
 impl Default for SearchOptions {
@@ -49,17 +134,50 @@ impl Default for SearchOptions {
         Self {
             max_results: 10,
             per_file: None,
+            max_file_ratio: None,
+            group_by_symbol: false,
             content_lines: 3,
+            context: None,
             show_scores: false,
             compact: false,
+            grep: false,
             sync: false,
+            sync_dry_run: false,
+            auto_sync: false,
+            db_path: None,
             json: false,
-            filter_path: None,
+            jsonl: false,
+            count: false,
+            count_uncapped: false,
+            filter_paths: Vec::new(),
+            exclude_paths: Vec::new(),
+            tests_only: false,
+            exclude_tests: false,
+            filter_kinds: Vec::new(),
+            filter_languages: Vec::new(),
+            filter_signatures: Vec::new(),
+            filter_names: Vec::new(),
             model_override: None,
             vector_only: false,
+            fts_only: false,
+            case_sensitive: false,
             rrf_k: None,
+            fuzzy: false,
+            expand_query: true,
             rerank: false,
             rerank_top: None,
+            auto_rerank: false,
+            rerank_model: None,
+            retrieval_limit: None,
+            normalize_scores: false,
+            min_score: None,
+            highlight: true,
+            positions: false,
+            open: None,
+            explain: false,
+            profile: false,
+            language_boost: DEFAULT_LANGUAGE_BOOST,
+            kind_boost: DEFAULT_KIND_BOOST,
         }
     }
 }
@@ -83,10 +201,144 @@ struct JsonResult {
     score: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
     signature: Option<String>,
+    /// Language of the source file (absent for chunks from older indexes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     context_prev: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     context_next: Option<String>,
+    /// Byte ranges `[start, end)` in `content` that match query terms
+    #[serde(skip_serializing_if = "Option::is_none")]
+    highlights: Option<Vec<(usize, usize)>>,
+    /// Lines around the match read from the current file (with `--context`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    live_context: Option<JsonLiveContext>,
+    /// Where the signature starts (with `--positions`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position: Option<JsonPosition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explain: Option<Explain>,
+}
+
+/// Why a result ranked where it did (`--explain`)
+///
+/// Ranks are 1-based positions in each retrieval list before fusion; boosts are
+/// the multipliers applied to the fused score afterwards.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Explain {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_rank: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_score: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fts_rank: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fts_score: Option<f32>,
+    /// Rank among exact identifier matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exact_rank: Option<usize>,
+    pub rrf_score: f32,
+    /// Primary-language boost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_boost: Option<f32>,
+    /// Boost for matching the query's structural intent ("struct", "fn", ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind_boost: Option<f32>,
+    /// Blended score after neural reranking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank_score: Option<f32>,
+}
+
+impl From<&FusedResult> for Explain {
+    fn from(fused: &FusedResult) -> Self {
+        Self {
+            vector_rank: fused.vector_rank,
+            vector_score: fused.vector_score,
+            fts_rank: fused.fts_rank,
+            fts_score: fused.fts_score,
+            exact_rank: fused.exact_rank,
+            rrf_score: fused.rrf_score,
+            ..Default::default()
+        }
+    }
+}
+
+impl Explain {
+    /// One-line summary for terminal output
+    fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        match (self.vector_rank, self.vector_score) {
+            (Some(rank), Some(score)) => parts.push(format!("vector #{} ({:.3})", rank, score)),
+            _ => parts.push("vector -".to_string()),
+        }
+        match (self.fts_rank, self.fts_score) {
+            (Some(rank), Some(score)) => parts.push(format!("fts #{} ({:.2})", rank, score)),
+            _ => parts.push("fts -".to_string()),
+        }
+        if let Some(rank) = self.exact_rank {
+            parts.push(format!("exact #{}", rank));
+        }
+        parts.push(format!("rrf {:.4}", self.rrf_score));
+        if let Some(boost) = self.language_boost {
+            parts.push(format!("lang ×{:.2}", boost));
+        }
+        if let Some(boost) = self.kind_boost {
+            parts.push(format!("kind ×{:.2}", boost));
+        }
+        if let Some(score) = self.rerank_score {
+            parts.push(format!("rerank {:.3}", score));
+        }
+        parts.join(" • ")
+    }
+}
+
+/// Start of a result's signature, in editor coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+struct JsonPosition {
+    /// Line number, counted like the result's `start_line`
+    line: usize,
+    /// 0-based offset into the line in UTF-16 code units (LSP `character`)
+    character: usize,
+    /// 1-based column in characters
+    column: usize,
+}
+
+/// Locate the signature in a chunk's `content` (which starts at `start_line`)
+///
+/// Falls back to the first non-blank character when there is no signature or
+/// it doesn't appear verbatim (signatures can be normalized by the chunker).
+fn signature_position(
+    content: &str,
+    signature: Option<&str>,
+    start_line: usize,
+) -> Option<JsonPosition> {
+    let needle = signature
+        .and_then(|s| s.lines().next())
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let find = |locate: &dyn Fn(&str) -> Option<usize>| {
+        content.lines().enumerate().find_map(|(i, line)| {
+            locate(line).map(|byte| {
+                let prefix = &line[..byte];
+                JsonPosition {
+                    line: start_line + i,
+                    character: prefix.encode_utf16().count(),
+                    column: prefix.chars().count() + 1,
+                }
+            })
+        })
+    };
+    needle
+        .and_then(|needle| find(&|line: &str| line.find(needle)))
+        .or_else(|| find(&|line: &str| line.find(|c: char| !c.is_whitespace())))
+}
+
+#[derive(Serialize)]
+struct JsonLiveContext {
+    /// 1-based line number of the first line in `content`
+    start_line: usize,
+    content: String,
 }
 
 #[derive(Serialize)]
@@ -98,6 +350,101 @@ struct JsonTiming {
     rerank_ms: Option<u64>,
 }
 
+/// Trailing metadata line for `--jsonl` output (only emitted with `--scores`)
+#[derive(Serialize)]
+struct JsonlSummary {
+    query: String,
+    total_results: usize,
+    timing: JsonTiming,
+}
+
+/// JSON output for `--count`
+#[derive(Serialize)]
+struct JsonCount {
+    query: String,
+    count: usize,
+}
+
+impl JsonResult {
+    fn from_result(r: &crate::vectordb::SearchResult) -> Self {
+        Self {
+            path: r.path.clone(),
+            start_line: r.start_line,
+            end_line: r.end_line,
+            kind: r.kind.clone(),
+            content: r.content.clone(),
+            score: r.score,
+            signature: r.signature.clone(),
+            language: r.language.clone(),
+            context_prev: r.context_prev.clone(),
+            context_next: r.context_next.clone(),
+            highlights: None,
+            live_context: None,
+            position: None,
+            explain: None,
+        }
+    }
+
+    /// Attach the signature position (no-op unless `enabled`)
+    fn with_position(mut self, enabled: bool) -> Self {
+        if enabled {
+            self.position =
+                signature_position(&self.content, self.signature.as_deref(), self.start_line);
+        }
+        self
+    }
+
+    /// Attach ranking diagnostics (no-op without `--explain`)
+    fn with_explain(mut self, explain: Option<&Explain>) -> Self {
+        self.explain = explain.cloned();
+        self
+    }
+
+    /// Attach `context` lines re-read from disk (no-op when `None` or unreadable)
+    fn with_live_context(mut self, root: &Path, context: Option<usize>) -> Self {
+        if let Some(context) = context {
+            self.live_context =
+                read_live_context(root, &self.path, self.start_line, self.end_line, context).map(
+                    |lines| JsonLiveContext {
+                        start_line: lines
+                            .first()
+                            .map(|l| l.number)
+                            .unwrap_or(self.start_line + 1),
+                        content: lines
+                            .into_iter()
+                            .map(|l| l.text)
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    },
+                );
+        }
+        self
+    }
+
+    /// Attach byte ranges of matched query terms (no-op without terms)
+    fn with_highlights(mut self, terms: &[String]) -> Self {
+        if !terms.is_empty() {
+            self.highlights = Some(find_highlights(&self.content, terms));
+        }
+        self
+    }
+}
+
+impl JsonTiming {
+    fn from_timing(timing: &SearchTiming, rerank: bool) -> Self {
+        Self {
+            total_ms: timing.total().as_millis() as u64,
+            embed_ms: timing.embed.as_millis() as u64,
+            search_ms: timing.search.as_millis() as u64,
+            rerank_ms: if rerank {
+                Some(timing.rerank.as_millis() as u64)
+            } else {
+                None
+            },
+        }
+    }
+}
+
 /// Get the database path and project path for a given project directory
 /// Uses automatic database discovery to find indexes in parent/global directories
 fn get_db_path(path: Option<PathBuf>) -> Result<(PathBuf, PathBuf)> {
@@ -105,21 +452,273 @@ fn get_db_path(path: Option<PathBuf>) -> Result<(PathBuf, PathBuf)> {
     resolve_database_with_message(path.as_deref(), "searching")
 }
 
+/// What `metadata.json` records about how an index was built
+///
+/// Every field is optional in the file: indexes written by older versions lack
+/// the newer ones, and the accessors return what such an index was built with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexMetadata {
+    pub model_short_name: Option<String>,
+    /// Full model name, as `file_meta.json` records it
+    pub model_name: Option<String>,
+    pub dimensions: Option<usize>,
+    pub context_lines: Option<usize>,
+    pub strip_comments: Option<bool>,
+    pub normalized: Option<bool>,
+    pub metric: Option<String>,
+    pub primary_language: Option<String>,
+    pub indexed_at: Option<String>,
+    /// `--include` globs the index was limited to
+    pub include: Option<Vec<String>>,
+}
+
+impl IndexMetadata {
+    /// Path of `metadata.json` in the database at `db_path`
+    pub fn path(db_path: &Path) -> PathBuf {
+        db_path.join("metadata.json")
+    }
+
+    /// Load the metadata of the index at `db_path`
+    ///
+    /// Returns `Ok(None)` when the database has no metadata yet. An unreadable
+    /// or malformed file is an error rather than an index with default settings.
+    pub fn load(db_path: &Path) -> CsResult<Option<Self>> {
+        let path = Self::path(db_path);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(CodeSearchError::io(&path, e.to_string())),
+        };
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| CodeSearchError::parse(&path, e.to_string()))
+    }
+
+    /// Load the metadata of the index at `db_path`, or an empty one if it has none yet
+    pub fn load_or_default(db_path: &Path) -> CsResult<Self> {
+        Ok(Self::load(db_path)?.unwrap_or_default())
+    }
+
+    /// Write this metadata to the database at `db_path`
+    pub fn save(&self, db_path: &Path) -> Result<()> {
+        std::fs::write(Self::path(db_path), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Context lines stored around each chunk
+    ///
+    /// Indexes that predate the setting were chunked with the default.
+    pub fn context_lines(&self) -> usize {
+        self.context_lines
+            .unwrap_or(crate::chunker::DEFAULT_CONTEXT_LINES)
+    }
+
+    /// Whether code was embedded with comments and string literals stripped
+    ///
+    /// Indexes that predate the setting embedded the raw chunk text.
+    pub fn strip_comments(&self) -> bool {
+        self.strip_comments.unwrap_or(false)
+    }
+
+    /// Whether the stored embeddings are L2-normalized
+    ///
+    /// Indexes that predate the setting are treated as normalized: the bundled
+    /// models already produced unit-length vectors.
+    pub fn normalized(&self) -> bool {
+        self.normalized.unwrap_or(true)
+    }
+
+    /// `--include` globs the index was built with (empty: every indexable file)
+    pub fn include(&self) -> &[String] {
+        self.include.as_deref().unwrap_or_default()
+    }
+
+    /// Distance metric of the stored vectors
+    ///
+    /// Indexes that predate the setting use cosine similarity.
+    pub fn metric(&self) -> crate::vectordb::Metric {
+        self.metric
+            .as_deref()
+            .and_then(crate::vectordb::Metric::parse)
+            .unwrap_or_default()
+    }
+
+    /// Model name and dimensions the index's `file_meta.json` is keyed by
+    pub fn file_meta_key(&self) -> (&str, usize) {
+        let default = ModelType::default();
+        (
+            self.model_name.as_deref().unwrap_or(default.name()),
+            self.dimensions.unwrap_or(default.dimensions()),
+        )
+    }
+
+    /// An embedding service for the index at `db_path`, which this metadata describes
+    ///
+    /// Loads `model_type` from the shared models cache and embeds the way the
+    /// index's vectors were made, so new chunks and queries match them. Query
+    /// embeddings are cached in the database across runs.
+    pub fn embedding_service(
+        &self,
+        db_path: &Path,
+        model_type: ModelType,
+    ) -> Result<EmbeddingService> {
+        let cache_dir = crate::constants::get_global_models_cache_dir()?;
+        Ok(
+            EmbeddingService::with_cache_dir(model_type, Some(&cache_dir))?
+                .with_normalization(self.normalized())
+                .with_query_cache(db_path),
+        )
+    }
+
+    /// The model to keep embedding this index with
+    ///
+    /// The recorded model always wins; `default` (the user config's `model`)
+    /// only picks the model of an index that doesn't record one yet.
+    pub fn model_or(&self, default: Option<ModelType>) -> CsResult<ModelType> {
+        Ok(self.model()?.or(default).unwrap_or_default())
+    }
+
+    /// The embedding model the index was built with
+    ///
+    /// Returns `Ok(None)` when no model is recorded yet. Errors if the stored
+    /// model is unknown or its dimensions don't match the stored index dimensions,
+    /// since searching with the wrong model produces meaningless scores.
+    pub fn model(&self) -> CsResult<Option<ModelType>> {
+        let (Some(model_name), Some(dims)) = (&self.model_short_name, self.dimensions) else {
+            return Ok(None);
+        };
+
+        let model_type = ModelType::parse(model_name).ok_or_else(|| {
+            CodeSearchError::model_not_found(
+                model_name,
+                format!("Unknown model '{}' in metadata.json", model_name),
+            )
+        })?;
+
+        if model_type.dimensions() != dims {
+            return Err(CodeSearchError::dimension_mismatch(
+                dims,
+                model_type.dimensions(),
+                format!(
+                    "Index dimensions ({}) don't match model {} ({} dims). Re-index with `codesearch index --force`.",
+                    dims,
+                    model_type.name(),
+                    model_type.dimensions()
+                ),
+            ));
+        }
+
+        Ok(Some(model_type))
+    }
+}
+
 /// Read model metadata from database
 pub fn read_metadata(db_path: &Path) -> Option<(String, usize, Option<String>)> {
-    let metadata_path = db_path.join("metadata.json");
-    if let Ok(content) = std::fs::read_to_string(&metadata_path) {
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-            let model = json.get("model_short_name")?.as_str()?.to_string();
-            let dims = json.get("dimensions")?.as_u64()? as usize;
-            let primary_language = json
-                .get("primary_language")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
-            return Some((model, dims, primary_language));
+    let metadata = IndexMetadata::load(db_path).ok().flatten()?;
+    Some((
+        metadata.model_short_name?,
+        metadata.dimensions?,
+        metadata.primary_language,
+    ))
+}
+
+/// Fail with an actionable error when `model_type` can't query the index at `db_path`
+///
+/// Embeddings from a model with different dimensions than the stored vectors
+/// can't be compared with them, so searching would error deep inside the
+/// store or return nonsense. Passes when the index has no metadata yet.
+pub fn check_model_dimensions(
+    db_path: &Path,
+    metadata: &IndexMetadata,
+    model_type: ModelType,
+) -> CsResult<()> {
+    let (Some(indexed_model), Some(dims)) = (&metadata.model_short_name, metadata.dimensions)
+    else {
+        return Ok(());
+    };
+    if model_type.dimensions() == dims {
+        return Ok(());
+    }
+
+    Err(CodeSearchError::dimension_mismatch(
+        dims,
+        model_type.dimensions(),
+        format!(
+            "Model {} produces {}-dim embeddings, but the index at {} was built with {} ({} dims).\n\
+             Drop --model to search with the indexed model, or re-index with `codesearch index --force --model {}`.",
+            model_type.short_name(),
+            model_type.dimensions(),
+            db_path.display(),
+            indexed_model,
+            dims,
+            model_type.short_name()
+        ),
+    ))
+}
+
+/// Shortest token treated as an exact-match identifier, unless
+/// `CODESEARCH_MIN_IDENTIFIER_LEN` says otherwise
+///
+/// Names like `db` or `Id` appear in almost every file, so boosting their exact
+/// matches floods the results instead of pinpointing a symbol.
+pub const DEFAULT_MIN_IDENTIFIER_LEN: usize = 3;
+
+/// Minimum identifier length: `CODESEARCH_MIN_IDENTIFIER_LEN`, else the default
+fn min_identifier_len() -> usize {
+    std::env::var("CODESEARCH_MIN_IDENTIFIER_LEN")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(DEFAULT_MIN_IDENTIFIER_LEN)
+}
+
+/// Query words that are never treated as identifiers, however they are capitalized
+///
+/// Sentence-initial words such as "Find" or "Show" look like PascalCase type
+/// names but only phrase the question. The list is kept to natural-language
+/// filler and question verbs such as "Get"; words that commonly name types or
+/// methods (`List`, `Display`, `Any`, `Search`) stay identifiers.
+/// `CODESEARCH_QUERY_STOPWORDS` adjusts the list: comma-separated words are
+/// added, and `-word` removes a default (for a codebase with a real `Get` type,
+/// say). Matching ignores case.
+pub const DEFAULT_QUERY_STOPWORDS: &[&str] = &[
+    "All", "An", "Are", "Can", "Describe", "Do", "Does", "Each", "Explain", "Find", "Get", "Give",
+    "How", "Is", "Locate", "Look", "Show", "The", "This", "What", "When", "Where", "Which", "Who",
+    "Why",
+];
+
+/// The stopword list for a `CODESEARCH_QUERY_STOPWORDS` value, lowercased
+fn stopwords_from(overrides: Option<&str>) -> Vec<String> {
+    let mut words: Vec<String> = DEFAULT_QUERY_STOPWORDS
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect();
+    for entry in overrides.unwrap_or("").split(',').map(str::trim) {
+        if let Some(removed) = entry.strip_prefix('-') {
+            let removed = removed.trim().to_lowercase();
+            words.retain(|w| *w != removed);
+        } else if !entry.is_empty() {
+            words.push(entry.to_lowercase());
         }
     }
-    None
+    words
+}
+
+/// Whether `CODESEARCH_VERBOSE` asks for diagnostics on stderr
+fn verbose() -> bool {
+    std::env::var("CODESEARCH_VERBOSE").is_ok_and(|v| flag_enabled(&v))
+}
+
+/// A boolean environment value: anything but empty, `0`, `false` or `off`
+fn flag_enabled(value: &str) -> bool {
+    !matches!(
+        value.trim().to_lowercase().as_str(),
+        "" | "0" | "false" | "off"
+    )
+}
+
+/// Query stopwords: [`DEFAULT_QUERY_STOPWORDS`] adjusted by `CODESEARCH_QUERY_STOPWORDS`
+fn query_stopwords() -> Vec<String> {
+    stopwords_from(std::env::var("CODESEARCH_QUERY_STOPWORDS").ok().as_deref())
 }
 
 /// Detect if query contains likely code identifiers
@@ -128,16 +727,28 @@ pub fn read_metadata(db_path: &Path) -> Option<(String, usize, Option<String>)>
 /// - PascalCase (Class, Struct, Interface)
 /// - snake_case (function, method)
 /// - camelCase (property, variable)
+///
+/// Tokens shorter than `CODESEARCH_MIN_IDENTIFIER_LEN` characters (default
+/// [`DEFAULT_MIN_IDENTIFIER_LEN`]) and query stopwords (see
+/// [`DEFAULT_QUERY_STOPWORDS`]) are skipped.
 pub fn detect_identifiers(query: &str) -> Vec<String> {
+    detect_identifiers_min_len(query, min_identifier_len())
+}
+
+/// [`detect_identifiers`] with an explicit minimum token length (in characters)
+pub fn detect_identifiers_min_len(query: &str, min_len: usize) -> Vec<String> {
+    let stopwords = query_stopwords();
     let mut identifiers = Vec::new();
     for token in query.split_whitespace() {
+        if token.chars().count() < min_len || is_stopword(token, &stopwords) {
+            continue;
+        }
         let is_pascal = token
             .chars()
             .next()
             .map(|c| c.is_uppercase())
             .unwrap_or(false)
-            && token.chars().any(|c| c.is_lowercase())
-            && !["Find", "Show", "Get", "Where", "How", "What", "All"].contains(&token);
+            && token.chars().any(|c| c.is_lowercase());
         let is_snake =
             token.contains('_') && token.chars().all(|c| c.is_alphanumeric() || c == '_');
         let is_camel = token
@@ -171,46 +782,149 @@ pub fn detect_structural_intent(query: &str) -> Option<crate::chunker::ChunkKind
     // This indicates the user is looking for a specific type/function, not just any of that kind
     let has_identifier = contains_identifier(query);
 
-    eprintln!(
-        "🔍 detect_structural_intent: query='{}', has_identifier={}",
-        query, has_identifier
+    tracing::debug!(
+        "detect_structural_intent: query='{}', has_identifier={}",
+        query,
+        has_identifier
     );
 
     if !has_identifier {
         return None; // No specific identifier - don't apply kind boost
     }
 
-    let kind = if query_lower.contains("class ") {
+    // Whole words only: "latest" isn't "test", and a keyword may end the query
+    let words: Vec<&str> = query_lower
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .collect();
+    let has_word = |keyword: &str| words.contains(&keyword);
+
+    let kind = if has_word("class") {
         Some(ChunkKind::Class)
-    } else if query_lower.contains("struct ") {
+    } else if has_word("struct") {
         Some(ChunkKind::Struct)
-    } else if query_lower.contains("function ") || query_lower.contains("fn ") {
+    } else if has_word("function") || has_word("fn") {
         Some(ChunkKind::Function)
-    } else if query_lower.contains("method ") {
+    } else if has_word("method") {
         Some(ChunkKind::Method)
-    } else if query_lower.contains("enum ") {
+    } else if has_word("enum") {
         Some(ChunkKind::Enum)
-    } else if query_lower.contains("interface ") {
+    } else if has_word("interface") {
         Some(ChunkKind::Interface)
-    } else if query_lower.contains("trait ") {
+    } else if has_word("trait") {
         Some(ChunkKind::Trait)
+    } else if has_word("test") {
+        Some(ChunkKind::Test)
     } else {
         None
     };
 
-    eprintln!("🔍 detect_structural_intent: kind={:?}", kind);
+    tracing::debug!("detect_structural_intent: kind={:?}", kind);
     kind
 }
 
+/// Tolerant identifier detection for `--fuzzy` mode
+///
+/// Same as `detect_identifiers`, but short queries (one or two words) also treat
+/// bare words of at least 4 characters as identifiers, so typos and partial
+/// names like `authenticat` still get exact-style boosting.
+pub fn detect_identifiers_tolerant(query: &str) -> Vec<String> {
+    let mut identifiers = detect_identifiers(query);
+
+    let tokens: Vec<&str> = query.split_whitespace().collect();
+    if tokens.len() <= 2 {
+        for token in tokens {
+            let is_word =
+                token.len() >= 4 && token.chars().all(|c| c.is_alphanumeric() || c == '_');
+            if is_word && !identifiers.iter().any(|i| i == token) {
+                identifiers.push(token.to_string());
+            }
+        }
+    }
+    identifiers
+}
+
+/// Extract double-quoted phrases from a query (e.g. `"connection pool timeout"`)
+///
+/// Unterminated quotes and empty phrases are ignored.
+pub fn extract_quoted_phrases(query: &str) -> Vec<String> {
+    let parts: Vec<&str> = query.split('"').collect();
+    parts
+        .iter()
+        .enumerate()
+        // Odd parts are inside quotes; the last part is only quoted if unterminated
+        .filter(|(i, _)| i % 2 == 1 && *i < parts.len() - 1)
+        .map(|(_, phrase)| phrase.trim().to_string())
+        .filter(|phrase| !phrase.is_empty())
+        .collect()
+}
+
+/// BM25 search merged with exact phrase matches for any quoted phrases in the query
+///
+/// Phrase matches are ranked first so they win in RRF fusion.
+fn fts_search_with_phrases(
+    fts_store: &FtsStore,
+    query: &str,
+    limit: usize,
+    structural_intent: Option<crate::chunker::ChunkKind>,
+) -> Result<Vec<FtsResult>> {
+    let fts_results = fts_store.search(query, limit, structural_intent)?;
+
+    let phrases = extract_quoted_phrases(query);
+    if phrases.is_empty() {
+        return Ok(fts_results);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut merged = Vec::with_capacity(fts_results.len());
+    for phrase in &phrases {
+        for result in fts_store.search_phrase(phrase, limit)? {
+            if seen.insert(result.chunk_id) {
+                merged.push(result);
+            }
+        }
+    }
+    for result in fts_results {
+        if seen.insert(result.chunk_id) {
+            merged.push(result);
+        }
+    }
+    Ok(merged)
+}
+
+/// Edit distance for fuzzy identifier matching (longer names tolerate more typos)
+fn fuzzy_distance(identifier: &str) -> u8 {
+    if identifier.chars().count() >= 8 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Whether `token` is one of the (lowercased) `stopwords`
+fn is_stopword(token: &str, stopwords: &[String]) -> bool {
+    let token = token.to_lowercase();
+    stopwords.iter().any(|w| *w == token)
+}
+
 /// Checks if query contains a PascalCase or snake_case identifier
 /// indicating a specific type/function name is being searched for
 ///
-/// Simple heuristic without regex dependency:
+/// Query stopwords ("Find", "Show", ...) don't count, so "Find all enums"
+/// has no identifier. Simple heuristic without regex dependency:
 /// - PascalCase: contains uppercase letter followed by lowercase/digit
 /// - snake_case: contains underscore with lowercase letters around it
 /// - camelCase: contains lowercase letter followed by uppercase letter
 fn contains_identifier(query: &str) -> bool {
-    let chars: Vec<char> = query.chars().collect();
+    let stopwords = query_stopwords();
+    query
+        .split_whitespace()
+        .filter(|token| !is_stopword(token, &stopwords))
+        .any(token_is_identifier)
+}
+
+/// [`contains_identifier`] for a single whitespace-free token
+fn token_is_identifier(token: &str) -> bool {
+    let chars: Vec<char> = token.chars().collect();
 
     // Look for PascalCase: uppercase letter followed by lowercase letter or digit
     for i in 0..chars.len().saturating_sub(1) {
@@ -237,17 +951,31 @@ fn contains_identifier(query: &str) -> bool {
     false
 }
 
-/// Boosts results that match a specific ChunkKind by a factor
+/// Default score boost for results whose kind matches the query's structural intent (15%)
+pub const DEFAULT_KIND_BOOST: f32 = 0.15;
+
+/// Default score boost for results in the project's primary language (20%)
+pub const DEFAULT_LANGUAGE_BOOST: f32 = 0.2;
+
+/// Boosts results that match a specific ChunkKind by [`DEFAULT_KIND_BOOST`]
 pub fn boost_kind(
     results: &mut Vec<crate::vectordb::SearchResult>,
     target_kind: crate::chunker::ChunkKind,
 ) {
-    let boost_factor = 0.15; // 15% boost for matching kind
-                             // Convert ChunkKind to string for comparison
+    boost_kind_by(results, target_kind, DEFAULT_KIND_BOOST);
+}
+
+/// Multiplies the score of results of `target_kind` by `1.0 + boost` and re-sorts
+pub fn boost_kind_by(
+    results: &mut [crate::vectordb::SearchResult],
+    target_kind: crate::chunker::ChunkKind,
+    boost: f32,
+) {
+    // Convert ChunkKind to string for comparison
     let target_kind_str = format!("{:?}", target_kind);
     for result in results.iter_mut() {
         if result.kind == target_kind_str {
-            result.score *= 1.0 + boost_factor;
+            result.score *= 1.0 + boost;
         }
     }
     // Re-sort after boosting
@@ -378,7 +1106,7 @@ fn expand_query(query: &str) -> Vec<String> {
 
     // OPTIMIZATION: Log variant count for monitoring (when verbose)
     // This helps track the effectiveness of query variant reduction
-    if std::env::var("CODESEARCH_VERBOSE").is_ok() && variants.len() > 1 {
+    if verbose() && variants.len() > 1 {
         eprintln!(
             "[optimization] Query expansion: {} -> {} variants (original + {} expansions)",
             original_query,
@@ -390,6 +1118,296 @@ fn expand_query(query: &str) -> Vec<String> {
     variants
 }
 
+/// The queries to embed: the expanded variants, or just the query when expansion is off
+fn query_variants(query: &str, expand: bool) -> Vec<String> {
+    if expand {
+        expand_query(query)
+    } else {
+        vec![query.to_string()]
+    }
+}
+
+/// Check whether a result path passes the include/exclude path filters.
+///
+/// Paths are compared after stripping a leading `./`. A path is rejected if it
+/// starts with any exclude prefix (excludes win over includes), otherwise it must
+/// start with one of `filter_paths` when any are given.
+pub fn path_matches_filters(path: &str, filter_paths: &[String], exclude_paths: &[String]) -> bool {
+    let path_normalized = path.trim_start_matches("./");
+
+    if exclude_paths
+        .iter()
+        .any(|ex| path_normalized.starts_with(ex.trim_start_matches("./")))
+    {
+        return false;
+    }
+
+    filter_paths.is_empty()
+        || filter_paths
+            .iter()
+            .any(|filter| path_normalized.starts_with(filter.trim_start_matches("./")))
+}
+
+/// Check whether a result kind passes the `--tests-only` / `--no-tests` filter.
+pub fn kind_matches_test_filter(kind: &str, tests_only: bool, exclude_tests: bool) -> bool {
+    let is_test = kind == "Test";
+    if tests_only {
+        is_test
+    } else if exclude_tests {
+        !is_test
+    } else {
+        true
+    }
+}
+
+/// Check whether a result passes the field filters of a structured query
+///
+/// Each non-empty filter list must have a match; within a list any value matches.
+fn matches_query_filters(result: &crate::vectordb::SearchResult, options: &SearchOptions) -> bool {
+    matches_query_fields(
+        &result.kind,
+        || result.language_name(),
+        result.signature.as_deref(),
+        options,
+    )
+}
+
+/// [`matches_query_filters`] on the individual fields of a result or stored chunk
+fn matches_query_fields(
+    kind: &str,
+    language_name: impl FnOnce() -> String,
+    signature: Option<&str>,
+    options: &SearchOptions,
+) -> bool {
+    let signature = signature.unwrap_or("");
+    (options.filter_kinds.is_empty() || options.filter_kinds.iter().any(|k| k == kind))
+        && (options.filter_languages.is_empty()
+            || options.filter_languages.contains(&language_name()))
+        && (options.filter_signatures.is_empty()
+            || options
+                .filter_signatures
+                .iter()
+                .any(|s| signature.contains(s.as_str())))
+        && (options.filter_names.is_empty()
+            || options
+                .filter_names
+                .iter()
+                .any(|name| signature_declares(signature, name)))
+}
+
+/// Check whether a stored chunk passes the path, test and structured query filters
+///
+/// Used as the vector search predicate, so filtered searches retrieve enough
+/// matching chunks instead of over-fetching and discarding most of them.
+fn chunk_matches_filters(meta: &crate::vectordb::ChunkMetadata, options: &SearchOptions) -> bool {
+    path_matches_filters(&meta.path, &options.filter_paths, &options.exclude_paths)
+        && kind_matches_test_filter(&meta.kind, options.tests_only, options.exclude_tests)
+        && matches_query_fields(
+            &meta.kind,
+            || meta.language_name(),
+            meta.signature.as_deref(),
+            options,
+        )
+}
+
+/// Keywords whose next identifier is the declared name (`fn load`, `class Config`)
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "class",
+    "def",
+    "defmodule",
+    "enum",
+    "fn",
+    "fun",
+    "func",
+    "function",
+    "impl",
+    "interface",
+    "macro_rules",
+    "mod",
+    "module",
+    "namespace",
+    "object",
+    "record",
+    "struct",
+    "trait",
+    "type",
+    "union",
+];
+
+/// Binding keywords: they declare the next identifier unless it is a type, as in
+/// C's `static int count` or Java's `static Config load()`
+const BINDING_KEYWORDS: &[&str] = &["const", "let", "static", "val", "var"];
+
+/// Modifiers that can come between a keyword and the declared name
+const DECLARATION_MODIFIERS: &[&str] = &[
+    "abstract",
+    "async",
+    "data",
+    "declare",
+    "default",
+    "export",
+    "extern",
+    "final",
+    "inline",
+    "internal",
+    "open",
+    "override",
+    "partial",
+    "private",
+    "protected",
+    "pub",
+    "public",
+    "readonly",
+    "sealed",
+    "unsafe",
+    "virtual",
+];
+
+/// A top-level piece of a signature, as seen by [`declared_name`]
+#[derive(Debug, PartialEq)]
+enum SignatureToken {
+    Ident(String),
+    /// A bracketed group (parameters, generics, receiver), by its opening bracket
+    Group(char),
+    Punct(char),
+}
+
+/// Split the head of `signature` into top-level tokens
+///
+/// Stops at the body or return type: `{`, `;`, `=`, `->`, or `):` for Python
+/// and TypeScript annotations. Bracketed groups collapse into one token, so
+/// their contents (parameters, generic bounds) never count as names.
+fn signature_tokens(signature: &str) -> Vec<SignatureToken> {
+    let chars: Vec<char> = signature.chars().collect();
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut angle = 0usize;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let top = depth == 0 && angle == 0;
+        match c {
+            '(' | '[' => {
+                if top {
+                    tokens.push(SignatureToken::Group(c));
+                }
+                depth += 1;
+            }
+            ')' | ']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 && angle == 0 && c == ')' && chars.get(i + 1) == Some(&':') {
+                    break;
+                }
+            }
+            // Generics follow a name (`Vec<T>`, `template<`); anything else is an operator
+            '<' if angle > 0 || matches!(tokens.last(), Some(SignatureToken::Ident(_))) => {
+                if top {
+                    tokens.push(SignatureToken::Group('<'));
+                }
+                angle += 1;
+            }
+            '>' if angle > 0 && !matches!(chars.get(i.wrapping_sub(1)), Some('-' | '=')) => {
+                angle -= 1;
+            }
+            '{' | ';' | '=' if top => break,
+            '-' if top && chars.get(i + 1) == Some(&'>') => break,
+            _ if is_ident(c) => {
+                let start = i;
+                while i < chars.len() && is_ident(chars[i]) {
+                    i += 1;
+                }
+                if top {
+                    tokens.push(SignatureToken::Ident(chars[start..i].iter().collect()));
+                }
+                continue;
+            }
+            _ if top && !c.is_whitespace() => tokens.push(SignatureToken::Punct(c)),
+            _ => {}
+        }
+        i += 1;
+    }
+    tokens
+}
+
+/// The name a signature declares, in any of the supported languages
+///
+/// After a declaration keyword it is the next identifier (past modifiers and a
+/// Go receiver); `impl Trait for Type` declares `Type`. Without a keyword, as
+/// in Java, C# and C++ (`public Config load()`), it is the identifier right
+/// before the parameter list, else the last one (`private Config config;`).
+fn declared_name(signature: &str) -> Option<String> {
+    use SignatureToken::Ident;
+
+    let tokens = signature_tokens(signature);
+    let is_keyword = |s: &str| DECLARATION_KEYWORDS.contains(&s) || BINDING_KEYWORDS.contains(&s);
+    let is_modifier = |s: &str| DECLARATION_MODIFIERS.contains(&s);
+    // The first identifier at or after `from` that isn't a modifier
+    let next_name = |from: usize| {
+        (from..tokens.len()).find_map(|j| match &tokens[j] {
+            Ident(s) if !is_modifier(s) => Some((j, s.as_str())),
+            _ => None,
+        })
+    };
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let Ident(keyword) = &tokens[i] else {
+            i += 1;
+            continue;
+        };
+        if !is_keyword(keyword) {
+            i += 1;
+            continue;
+        }
+        let from = if keyword == "impl" {
+            (i..tokens.len())
+                .find(|&f| matches!(&tokens[f], Ident(s) if s == "for"))
+                .map_or(i + 1, |f| f + 1)
+        } else {
+            i + 1
+        };
+        let Some((j, name)) = next_name(from) else {
+            break;
+        };
+        if is_keyword(name) {
+            // `const fn`
+            i = j;
+            continue;
+        }
+        if BINDING_KEYWORDS.contains(&keyword.as_str())
+            && (tokens[j..].contains(&SignatureToken::Group('('))
+                || keyword == "static" && matches!(tokens.get(j + 1), Some(Ident(_))))
+        {
+            // A type before the name (`static Config load(`): look further on
+            i = j + 1;
+            continue;
+        }
+        return Some(name.to_string());
+    }
+
+    let before_params = tokens.windows(2).find_map(|pair| match pair {
+        [Ident(name), SignatureToken::Group('(')] if !is_modifier(name) => Some(name),
+        _ => None,
+    });
+    before_params
+        .or_else(|| {
+            tokens.iter().rev().find_map(|t| match t {
+                Ident(s) if !is_modifier(s) && !is_keyword(s) => Some(s),
+                _ => None,
+            })
+        })
+        .cloned()
+}
+
+/// Check whether `signature` declares `symbol` rather than merely mentioning it
+///
+/// The symbol must be the declared name (see [`declared_name`]): parameter,
+/// return, field and generic bound types mention it without declaring it.
+pub fn signature_declares(signature: &str, symbol: &str) -> bool {
+    !symbol.is_empty() && declared_name(signature).is_some_and(|name| name == symbol)
+}
+
 /// Detect query type and adapt RRF-k accordingly
 /// Returns (vector_k, fts_k) based on query characteristics
 pub fn adapt_rrf_k(query: &str) -> (f64, f64) {
@@ -408,238 +1426,732 @@ pub fn adapt_rrf_k(query: &str) -> (f64, f64) {
     }
 }
 
-/// Search the codebase
-pub async fn search(query: &str, path: Option<PathBuf>, options: SearchOptions) -> Result<()> {
-    let (db_path, _project_path) = get_db_path(path)?;
-
-    if !db_path.exists() {
-        println!("{}", "❌ No database found!".red());
-        println!("   Run {} first", "codesearch index".bright_cyan());
-        println!();
-        println!(
-            "{}",
-            "💡 Tip: codesearch can find databases in parent directories. Use 'codesearch list' to see all indexed projects.".dimmed()
-        );
-        return Ok(());
+/// Resolve the embedding model, dimensions and primary language for a database.
+///
+/// An explicit `model_override` wins over the model recorded in `metadata.json`
+/// (warning: it may not match the indexed data).
+fn resolve_model(
+    metadata: &IndexMetadata,
+    model_override: Option<&str>,
+) -> (ModelType, usize, Option<String>) {
+    if let Some(model_name) = model_override {
+        // User specified a model - use it (warning: may not match indexed data!)
+        let mt = ModelType::parse(model_name).unwrap_or_default();
+        (mt, mt.dimensions(), None)
+    } else if let (Some(model_name), Some(dims)) = (&metadata.model_short_name, metadata.dimensions)
+    {
+        // Use model from metadata
+        if let Some(mt) = ModelType::parse(model_name) {
+            (mt, dims, metadata.primary_language.clone())
+        } else {
+            // Model name not recognized, fall back to default
+            eprintln!(
+                "{}",
+                "⚠️  Unknown model in metadata, using default".yellow()
+            );
+            (ModelType::default(), 384, None)
+        }
+    } else {
+        // No metadata, fall back to default
+        (ModelType::default(), 384, None)
     }
+}
 
-    // Read model metadata from database FIRST (needed for sync)
-    let (model_type, dimensions, primary_language) =
-        if let Some(ref model_name) = options.model_override {
-            // User specified a model - use it (warning: may not match indexed data!)
-            let mt = ModelType::parse(model_name).unwrap_or_default();
-            (mt, mt.dimensions(), None)
-        } else if let Some((model_name, dims, lang)) = read_metadata(&db_path) {
-            // Use model from metadata
-            if let Some(mt) = ModelType::parse(&model_name) {
-                (mt, dims, lang)
-            } else {
-                // Model name not recognized, fall back to default
-                eprintln!(
-                    "{}",
-                    "⚠️  Unknown model in metadata, using default".yellow()
+/// Per-variant retrieval cap: `--retrieval-limit`, else `CODESEARCH_MAX_RETRIEVAL`
+fn retrieval_cap(options: &SearchOptions) -> Option<usize> {
+    options.retrieval_limit.or_else(|| {
+        std::env::var("CODESEARCH_MAX_RETRIEVAL")
+            .ok()
+            .and_then(|s| s.parse().ok())
+    })
+}
+
+/// Pair each query variant with its search outcome, keeping the ones that succeeded
+///
+/// A variant whose vector search fails (a transient LMDB read error, say) is
+/// logged and dropped so the query still answers from the others. Only when
+/// every variant fails is the first error returned.
+fn successful_variants<T>(
+    variants: &[String],
+    outcomes: Vec<CsResult<T>>,
+) -> CsResult<Vec<(String, T)>> {
+    let mut succeeded = Vec::with_capacity(outcomes.len());
+    let mut first_error = None;
+    for (variant, outcome) in variants.iter().zip(outcomes) {
+        match outcome {
+            Ok(value) => succeeded.push((variant.clone(), value)),
+            Err(e) => {
+                tracing::warn!(
+                    "Vector search failed for query variant {:?}: {}",
+                    variant,
+                    e
                 );
-                (ModelType::default(), 384, None)
+                first_error.get_or_insert(e);
             }
+        }
+    }
+    match first_error {
+        Some(e) if succeeded.is_empty() => Err(e),
+        _ => Ok(succeeded),
+    }
+}
+
+/// Bound the adaptive retrieval limit by `cap` (at least one candidate)
+fn cap_retrieval_limit(limit: usize, cap: Option<usize>) -> usize {
+    match cap {
+        Some(cap) if limit > cap => {
+            let cap = cap.max(1);
+            tracing::debug!(
+                "Retrieval limit capped: {} -> {} per query variant",
+                limit,
+                cap
+            );
+            cap
+        }
+        _ => limit,
+    }
+}
+
+/// Min-max normalize scores in place: the best result gets 1.0, the worst 0.0
+///
+/// Scores are only comparable within one result set; if all scores are equal they
+/// all become 1.0.
+fn normalize_scores(results: &mut [crate::vectordb::SearchResult]) {
+    let (min, max) = results
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), r| {
+            (min.min(r.score), max.max(r.score))
+        });
+    let range = max - min;
+    for result in results.iter_mut() {
+        result.score = if range > f32::EPSILON {
+            (result.score - min) / range
         } else {
-            // No metadata, fall back to default
-            (ModelType::default(), 384, None)
+            1.0
         };
+    }
+}
 
-    // Perform incremental sync if requested (after we know the model)
-    if options.sync {
-        println!("{}", "🔄 Syncing database...".yellow());
-        sync_database(&db_path, model_type)?;
+/// Vector distance below which a hit counts as high confidence
+const HIGH_CONFIDENCE_THRESHOLD: f32 = 0.15;
+
+/// With `auto_rerank`, results count as ambiguous when the best score leads the
+/// runner-up by less than this fraction of the best score
+const AUTO_RERANK_MIN_LEAD: f32 = 0.1;
+
+/// Whether the ranking of `results` (best first) is close enough to be worth reranking
+///
+/// Fused scores vary in scale with the RRF constant and boosts, so the lead of
+/// the top result is compared relative to its own score.
+fn is_ambiguous(results: &[crate::vectordb::SearchResult]) -> bool {
+    match results {
+        [first, second, ..] => {
+            first.score <= 0.0 || (first.score - second.score) / first.score < AUTO_RERANK_MIN_LEAD
+        }
+        _ => false,
     }
+}
 
-    // Load database
-    let start = Instant::now();
-    let store = VectorStore::new(&db_path, dimensions)?;
-    let load_duration = start.elapsed();
+/// Drop results beyond `ratio` of `max_results` from any one file (at least one per file)
+///
+/// Results keep their order, so lower-ranked results from other files move up
+/// into the freed slots when the list is truncated to `max_results`.
+fn cap_file_share(
+    results: &mut Vec<crate::vectordb::SearchResult>,
+    max_results: usize,
+    ratio: f32,
+) {
+    let cap = ((max_results as f32 * ratio).ceil() as usize).max(1);
+    let mut per_file: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    results.retain(|r| {
+        let count = per_file.entry(r.path.clone()).or_insert(0);
+        *count += 1;
+        *count <= cap
+    });
+}
 
-    // Initialize embedding service with the correct model
-    let start = Instant::now();
-    let cache_dir = crate::constants::get_global_models_cache_dir()?;
-    let mut embedding_service = EmbeddingService::with_cache_dir(model_type, Some(&cache_dir))?;
-    let model_load_duration = start.elapsed();
+/// Merge results from the same definition into one result spanning all of them
+///
+/// Chunks share a definition when they have the same path, signature and
+/// context breadcrumbs below the file level (the parts of a split function or
+/// class). The merged result takes the place, id and score of its best-ranked
+/// part, covers the parts' whole line range, and joins their content in line
+/// order without the `// [Part i/n]` headers. Lines between parts that did not
+/// match are left blank so line numbers stay right. File-level chunks (imports,
+/// gaps between definitions) are never merged.
+fn group_by_symbol(
+    results: Vec<crate::vectordb::SearchResult>,
+) -> Vec<crate::vectordb::SearchResult> {
+    let mut groups: Vec<Vec<crate::vectordb::SearchResult>> = Vec::new();
+    let mut index: std::collections::HashMap<(String, String, String), usize> =
+        std::collections::HashMap::new();
+    for result in results {
+        let key = match (&result.signature, &result.context) {
+            (Some(signature), Some(context)) if context.contains(" > ") => {
+                Some((result.path.clone(), context.clone(), signature.clone()))
+            }
+            _ => None,
+        };
+        match key.map(|key| *index.entry(key).or_insert(groups.len())) {
+            Some(i) if i < groups.len() => groups[i].push(result),
+            _ => groups.push(vec![result]),
+        }
+    }
+    groups.into_iter().map(merge_symbol_parts).collect()
+}
 
-    // Expand query with variants for better matching
-    let query_variants = expand_query(query);
+/// Coalesce the parts of one definition (best-ranked first) into a single result
+fn merge_symbol_parts(
+    mut parts: Vec<crate::vectordb::SearchResult>,
+) -> crate::vectordb::SearchResult {
+    if parts.len() == 1 {
+        return parts.remove(0);
+    }
+    let mut merged = parts[0].clone();
+    merged.score = parts.iter().map(|r| r.score).fold(f32::MIN, f32::max);
+    parts.sort_by_key(|r| r.start_line);
+
+    let mut lines: Vec<&str> = Vec::new();
+    let mut next_line = parts[0].start_line;
+    for part in &parts {
+        let body = match part.content.split_once('\n') {
+            Some((first, rest)) if first.starts_with("// [Part ") => rest,
+            _ => part.content.as_str(),
+        };
+        for (offset, line) in body.lines().enumerate() {
+            let line_no = part.start_line + offset;
+            if line_no < next_line {
+                continue;
+            }
+            lines.resize(lines.len() + (line_no - next_line), "");
+            lines.push(line);
+            next_line = line_no + 1;
+        }
+    }
+    merged.content = lines.join("\n");
+
+    let first = &parts[0];
+    let last = &parts[parts.len() - 1];
+    merged.start_line = first.start_line;
+    merged.end_line = parts
+        .iter()
+        .map(|r| r.end_line)
+        .max()
+        .unwrap_or(merged.end_line);
+    merged.context_prev = first.context_prev.clone();
+    merged.context_next = last.context_next.clone();
+    if let Some(docstring) = parts.iter().find_map(|r| r.docstring.clone()) {
+        merged.docstring = Some(docstring);
+    }
+    merged
+}
 
-    // Embed all query variants in a single batch (OPTIMIZATION: batched ONNX calls)
-    let start = Instant::now();
-    let all_query_embeddings = embedding_service.embed_queries_batch(&query_variants)?;
+/// Drop results scoring below `min_score`, returning how many were removed
+fn apply_min_score(results: &mut Vec<crate::vectordb::SearchResult>, min_score: f32) -> usize {
+    let before = results.len();
+    results.retain(|r| r.score >= min_score);
+    before - results.len()
+}
 
-    let embed_duration = start.elapsed();
+/// Timing breakdown of a single search
+#[derive(Debug, Clone, Default)]
+pub struct SearchTiming {
+    /// Time spent opening the vector store (when the `Searcher` was opened)
+    pub load: Duration,
+    /// Time spent loading the embedding model (when the `Searcher` was opened)
+    pub model_load: Duration,
+    /// Time spent embedding the query variants
+    pub embed: Duration,
+    /// Time spent on retrieval, fusion and boosting
+    pub search: Duration,
+    /// Time spent on neural reranking
+    pub rerank: Duration,
+    /// Per-stage breakdown (only with `SearchOptions::profile`)
+    pub profile: Option<SearchProfile>,
+}
 
-    // Search - hybrid by default, vector-only if requested
-    let start = Instant::now();
+impl SearchTiming {
+    /// Total time, including the one-off cost of opening the searcher
+    pub fn total(&self) -> Duration {
+        self.load + self.model_load + self.embed + self.search + self.rerank
+    }
+}
 
-    // Adaptive retrieval limit based on query type and max_results
-    // For semantic queries, we need more candidates for good RRF fusion
-    // For exact identifier queries, fewer candidates may suffice
-    let has_identifiers = !detect_identifiers(query).is_empty();
-    let retrieval_limit = if options.vector_only {
-        options.max_results
-    } else if has_identifiers {
-        // Identifier queries: fetch fewer results as exact matches are prioritized
-        std::cmp::max(options.max_results * 3, 100)
-    } else {
-        // Semantic queries: need more candidates for good fusion
-        std::cmp::max(options.max_results * 5, 200)
-    };
+/// Reusable searcher that keeps the vector store, embedding model and FTS index loaded.
+///
+/// The CLI [`search`] opens a `Searcher` per invocation. Library users issuing many
+/// queries should open one once and call [`Searcher::search`] repeatedly.
+///
+/// Methods return [`CodeSearchError`] so callers can match on failure modes such as
+/// `DatabaseNotFound`, `DimensionMismatch`, `ModelNotFound` and `Corrupt`.
+pub struct Searcher {
+    db_path: PathBuf,
+    model_type: ModelType,
+    primary_language: Option<String>,
+    /// The index's `metadata.json`, as read when the searcher was opened
+    metadata: IndexMetadata,
+    store: VectorStore,
+    /// None when the FTS index is unavailable (vector-only fallback)
+    fts_store: Option<FtsStore>,
+    /// None in full-text-only mode (`--fts-only`, or the model failed to load)
+    embedding_service: Option<Mutex<EmbeddingService>>,
+    /// Lazily loaded on the first search with `rerank` enabled
+    reranker: Mutex<Option<NeuralReranker>>,
+    load_duration: Duration,
+    model_load_duration: Duration,
+}
 
-    // Search with all query variants in parallel and combine results
-    // OPTIMIZATION: Use efficient deduplication with top-N tracking
-    use std::collections::BinaryHeap;
+impl Searcher {
+    /// Open a searcher for the database at `db_path`, using the model it was indexed with
+    #[allow(dead_code)] // Library API, unused by the CLI binary
+    pub fn open(db_path: &Path) -> CsResult<Self> {
+        Self::open_with_model(db_path, None)
+    }
 
-    let vector_search_results: Vec<Vec<crate::vectordb::SearchResult>> = all_query_embeddings
-        .par_iter()
-        .map(|query_emb| store.search(query_emb, retrieval_limit))
-        .collect::<Result<Vec<_>>>()?;
+    /// Open a searcher for the database at `db_path`, optionally overriding the model
+    ///
+    /// If the embedding model can't be loaded (download failed, corrupt cache)
+    /// but the full-text index opens, the searcher warns and falls back to
+    /// full-text-only ranking instead of failing.
+    pub fn open_with_model(db_path: &Path, model_override: Option<&str>) -> CsResult<Self> {
+        Self::open_inner(db_path, model_override, false)
+    }
 
-    // OPTIMIZATION: Deduplicate with top-N tracking using BinaryHeap
-    // This avoids collecting all results and then truncating
-    struct HeapEntry {
-        id: u32,
-        score: f32,
-        distance: f32,
+    /// Open a searcher that ranks with full-text search only, without loading
+    /// the embedding model (`search --fts-only`)
+    pub fn open_fts_only(db_path: &Path) -> CsResult<Self> {
+        Self::open_inner(db_path, None, true)
     }
 
-    impl PartialEq for HeapEntry {
-        fn eq(&self, other: &Self) -> bool {
-            self.id == other.id
+    /// Open for `options`: full-text only with `fts_only`, else with the indexed or overridden model
+    pub(crate) fn open_for(db_path: &Path, options: &SearchOptions) -> CsResult<Self> {
+        if options.fts_only {
+            Self::open_fts_only(db_path)
+        } else {
+            Self::open_with_model(db_path, options.model_override.as_deref())
         }
     }
 
-    impl Eq for HeapEntry {}
+    fn open_inner(db_path: &Path, model_override: Option<&str>, fts_only: bool) -> CsResult<Self> {
+        if !db_path.exists() {
+            return Err(CodeSearchError::database_not_found(db_path));
+        }
 
-    impl PartialOrd for HeapEntry {
-        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-            Some(self.cmp(other))
+        let metadata = IndexMetadata::load_or_default(db_path)?;
+        let (model_type, dimensions, primary_language) = resolve_model(&metadata, model_override);
+        if !fts_only {
+            check_model_dimensions(db_path, &metadata, model_type)?;
         }
-    }
 
-    impl Ord for HeapEntry {
-        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-            // Max-heap based on score
-            self.score
-                .partial_cmp(&other.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
+        // Load database
+        let start = Instant::now();
+        let store = VectorStore::new(db_path, dimensions)?;
+        let load_duration = start.elapsed();
+
+        // Opening would create an empty index, which --fts-only can't rank with
+        if fts_only && !db_path.join("fts").join("meta.json").exists() {
+            return Err(CodeSearchError::search(format!(
+                "No full-text index in {}; run `codesearch rebuild-fts` to use --fts-only",
+                db_path.display()
+            )));
         }
+
+        // FTS is optional: searches fall back to vector-only when it can't be opened
+        let fts_store = match FtsStore::new(db_path) {
+            Ok(fts_store) => Some(fts_store),
+            Err(e) if fts_only => {
+                return Err(CodeSearchError::search(format!(
+                    "Full-text index in {} can't be opened ({}); run `codesearch rebuild-fts` to use --fts-only",
+                    db_path.display(),
+                    e
+                )));
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Full-text index unavailable, searching vectors only: {}. Run `codesearch rebuild-fts` to restore it.",
+                    e
+                );
+                None
+            }
+        };
+
+        // Initialize embedding service with the correct model
+        let start = Instant::now();
+        let embedding_service = if fts_only {
+            None
+        } else {
+            match metadata.embedding_service(db_path, model_type) {
+                Ok(service) => Some(service),
+                // Without a model, full-text search alone can still answer
+                Err(e) if fts_store.is_some() => {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "⚠️  Could not load embedding model {} ({:#}); falling back to full-text search",
+                            model_type.short_name(),
+                            e
+                        )
+                        .yellow()
+                    );
+                    None
+                }
+                Err(e) => {
+                    return Err(CodeSearchError::model_not_found(
+                        model_type.short_name(),
+                        format!("{:#}", e),
+                    ))
+                }
+            }
+        };
+        let model_load_duration = start.elapsed();
+
+        Ok(Self {
+            db_path: db_path.to_path_buf(),
+            model_type,
+            primary_language,
+            metadata,
+            store,
+            fts_store,
+            embedding_service: embedding_service.map(Mutex::new),
+            reranker: Mutex::new(None),
+            load_duration,
+            model_load_duration,
+        })
     }
 
-    // Track top results per chunk ID AND keep one full result per ID
-    let mut top_by_id: std::collections::HashMap<u32, HeapEntry> = std::collections::HashMap::new();
-    let mut full_results_by_id: std::collections::HashMap<u32, crate::vectordb::SearchResult> =
-        std::collections::HashMap::new();
+    /// Path of the database this searcher was opened on
+    #[allow(dead_code)] // Library API, unused by the CLI binary
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
 
-    for results in vector_search_results {
-        for result in results {
-            top_by_id
-                .entry(result.id)
-                .and_modify(|e| {
-                    if result.score > e.score {
-                        e.score = result.score;
-                        e.distance = result.distance;
-                        // Update the stored full result
-                        full_results_by_id.insert(result.id, result.clone());
-                    }
-                })
-                .or_insert_with(|| {
-                    let entry = HeapEntry {
-                        id: result.id,
-                        score: result.score,
-                        distance: result.distance,
-                    };
-                    full_results_by_id.insert(result.id, result.clone());
-                    entry
-                });
-        }
+    /// Embedding model used for queries
+    #[allow(dead_code)] // Library API, unused by the CLI binary
+    pub fn model_type(&self) -> ModelType {
+        self.model_type
     }
 
-    // Convert to heap and extract top N
-    let mut heap: BinaryHeap<HeapEntry> = top_by_id.into_values().collect();
-    let mut vector_results: Vec<crate::vectordb::SearchResult> =
-        Vec::with_capacity(retrieval_limit);
+    /// Whether searches rank with full-text search only (no embedding model loaded)
+    pub fn is_fts_only(&self) -> bool {
+        self.embedding_service.is_none()
+    }
 
-    while let Some(entry) = heap.pop() {
-        if vector_results.len() >= retrieval_limit {
-            break;
-        }
-        if let Some(mut result) = full_results_by_id.get(&entry.id).cloned() {
-            result.score = entry.score;
-            result.distance = entry.distance;
-            vector_results.push(result);
+    /// Re-index files changed on disk since the last sync, reusing the loaded model
+    ///
+    /// Returns the number of files synced (0 when the index was up to date).
+    /// Later searches see the new chunks.
+    pub(crate) fn sync_changes(&self) -> Result<usize> {
+        let plan = plan_sync(&self.db_path, self.model_type)?;
+        if plan.is_empty() {
+            return Ok(0);
         }
+
+        let synced = match &self.embedding_service {
+            Some(service) => {
+                let mut service = service
+                    .lock()
+                    .map_err(|e| CodeSearchError::search(format!("Mutex poisoned: {}", e)))?;
+                apply_sync_plan(&self.db_path, plan, &mut service)?
+            }
+            // Full-text-only searchers still need the model to embed new chunks
+            None => {
+                let mut service = self
+                    .metadata
+                    .embedding_service(&self.db_path, self.model_type)?;
+                apply_sync_plan(&self.db_path, plan, &mut service)?
+            }
+        };
+        // Chunk IDs freed by the sync can be reused for different chunks
+        self.store.invalidate_chunk_cache();
+        Ok(synced)
     }
 
-    // Sort by score descending
-    vector_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+    /// Run a search and return the ranked results instead of printing them.
+    ///
+    /// Only retrieval options are used; output options such as `json`, `compact`
+    /// and `content_lines` are ignored.
+    #[allow(dead_code)] // Library API, unused by the CLI binary
+    pub fn search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> CsResult<Vec<crate::vectordb::SearchResult>> {
+        self.search_timed(query, options)
+            .map(|(results, _)| results)
+    }
 
-    // OPTIMIZATION: Early termination for high-confidence exact matches
-    // If top results have very high confidence (very low distance), skip FTS search
-    // This saves ~30-50ms per search for queries with clear matches
-    const HIGH_CONFIDENCE_THRESHOLD: f32 = 0.15; // Distance < 0.15 = very high confidence
-    const EARLY_TERMINATION_TOP_N: usize = 5; // Check top 5 results
+    /// Like [`Searcher::search`], also returning a timing breakdown
+    pub fn search_timed(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> CsResult<(Vec<crate::vectordb::SearchResult>, SearchTiming)> {
+        self.search_explained(query, options)
+            .map(|(results, timing, _)| (results, timing))
+    }
 
-    let should_use_vector_only = !options.vector_only && {
-        // Check if top N results all have high confidence
-        let top_results: Vec<_> = vector_results
-            .iter()
-            .take(EARLY_TERMINATION_TOP_N.min(vector_results.len()))
+    /// Like [`Searcher::search_timed`], also returning ranking diagnostics by chunk ID
+    ///
+    /// The map is empty unless `options.explain` is set.
+    pub fn search_explained(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> CsResult<(
+        Vec<crate::vectordb::SearchResult>,
+        SearchTiming,
+        std::collections::HashMap<u32, Explain>,
+    )> {
+        let mut profile = options.profile.then(SearchProfile::default);
+        let mut mark = Instant::now();
+
+        // Expand query with variants for better matching (unless disabled)
+        let query_variants = query_variants(query, options.expand_query);
+        SearchProfile::lap(&mut profile, "expand", &mut mark);
+
+        // Embed all query variants in a single batch (OPTIMIZATION: batched ONNX calls).
+        // Full-text-only mode has no embeddings, so only FTS results get fused below.
+        let start = Instant::now();
+        let all_query_embeddings = match &self.embedding_service {
+            Some(service) if !options.fts_only => service
+                .lock()
+                .map_err(|e| CodeSearchError::search(format!("Mutex poisoned: {}", e)))?
+                .embed_queries_batch(&query_variants)
+                .map_err(|e| CodeSearchError::embedding(format!("{:#}", e)))?,
+            _ => Vec::new(),
+        };
+
+        let embed_duration = start.elapsed();
+        SearchProfile::lap(&mut profile, "embed", &mut mark);
+
+        // Search - hybrid by default, vector-only if requested
+        let start = Instant::now();
+
+        // Adaptive retrieval limit based on query type and max_results
+        // For semantic queries, we need more candidates for good RRF fusion
+        // For exact identifier queries, fewer candidates may suffice
+        let has_identifiers = !detect_identifiers(query).is_empty();
+        let retrieval_limit = if options.vector_only {
+            options.max_results
+        } else if has_identifiers {
+            // Identifier queries: fetch fewer results as exact matches are prioritized
+            std::cmp::max(options.max_results * 3, 100)
+        } else {
+            // Semantic queries: need more candidates for good fusion
+            std::cmp::max(options.max_results * 5, 200)
+        };
+        let retrieval_limit = cap_retrieval_limit(retrieval_limit, retrieval_cap(options));
+
+        // Search with all query variants in parallel and combine results
+        // OPTIMIZATION: Use efficient deduplication with top-N tracking
+        use std::collections::BinaryHeap;
+
+        let outcomes: Vec<CsResult<_>> = all_query_embeddings
+            .par_iter()
+            .map(|query_emb| {
+                let start = Instant::now();
+                self.store
+                    .search_with_filter(
+                        query_emb,
+                        retrieval_limit,
+                        retrieval_cap(options),
+                        |meta| chunk_matches_filters(meta, options),
+                    )
+                    .map(|results| (results, start.elapsed()))
+            })
             .collect();
+        let (searched_variants, vector_search_results): (
+            Vec<(String, Duration)>,
+            Vec<Vec<crate::vectordb::SearchResult>>,
+        ) = successful_variants(&query_variants, outcomes)?
+            .into_iter()
+            .map(|(variant, (results, took))| ((variant, took), results))
+            .unzip();
+        SearchProfile::lap(&mut profile, "vector search", &mut mark);
+        if let Some(profile) = profile.as_mut() {
+            profile.variants = searched_variants;
+        }
 
-        let all_high_confidence = top_results
-            .iter()
-            .all(|r| r.distance < HIGH_CONFIDENCE_THRESHOLD);
+        // OPTIMIZATION: Deduplicate with top-N tracking using BinaryHeap
+        // This avoids collecting all results and then truncating
+        struct HeapEntry {
+            id: u32,
+            score: f32,
+            distance: f32,
+        }
 
-        // Also ensure we have at least one result
-        !top_results.is_empty() && all_high_confidence
-    };
+        impl PartialEq for HeapEntry {
+            fn eq(&self, other: &Self) -> bool {
+                self.id == other.id
+            }
+        }
 
-    // Use vector-only mode if early termination conditions are met
-    let vector_only_mode = options.vector_only || should_use_vector_only;
+        impl Eq for HeapEntry {}
 
-    // OPTIMIZATION: Log early termination for monitoring
-    if should_use_vector_only && !options.vector_only {
-        eprintln!(
-            "{}",
-            "⚡ Early termination: High-confidence results found, skipping FTS search".green()
-        );
-    }
+        impl PartialOrd for HeapEntry {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for HeapEntry {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                // Max-heap based on score
+                self.score
+                    .partial_cmp(&other.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }
+        }
+
+        // Track top results per chunk ID AND keep one full result per ID
+        let mut top_by_id: std::collections::HashMap<u32, HeapEntry> =
+            std::collections::HashMap::new();
+        let mut full_results_by_id: std::collections::HashMap<u32, crate::vectordb::SearchResult> =
+            std::collections::HashMap::new();
+
+        for results in vector_search_results {
+            for result in results {
+                top_by_id
+                    .entry(result.id)
+                    .and_modify(|e| {
+                        if result.score > e.score {
+                            e.score = result.score;
+                            e.distance = result.distance;
+                            // Update the stored full result
+                            full_results_by_id.insert(result.id, result.clone());
+                        }
+                    })
+                    .or_insert_with(|| {
+                        let entry = HeapEntry {
+                            id: result.id,
+                            score: result.score,
+                            distance: result.distance,
+                        };
+                        full_results_by_id.insert(result.id, result.clone());
+                        entry
+                    });
+            }
+        }
+
+        // Convert to heap and extract top N
+        let mut heap: BinaryHeap<HeapEntry> = top_by_id.into_values().collect();
+        let mut vector_results: Vec<crate::vectordb::SearchResult> =
+            Vec::with_capacity(retrieval_limit);
+
+        while let Some(entry) = heap.pop() {
+            if vector_results.len() >= retrieval_limit {
+                break;
+            }
+            if let Some(mut result) = full_results_by_id.get(&entry.id).cloned() {
+                result.score = entry.score;
+                result.distance = entry.distance;
+                vector_results.push(result);
+            }
+        }
+
+        // Sort by score descending
+        vector_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        SearchProfile::lap(&mut profile, "merge variants", &mut mark);
+
+        // OPTIMIZATION: Early termination for high-confidence exact matches
+        // If top results have very high confidence (very low distance), skip FTS search
+        // This saves ~30-50ms per search for queries with clear matches
+        const EARLY_TERMINATION_TOP_N: usize = 5; // Check top 5 results
+
+        let should_use_vector_only = !options.vector_only && {
+            // Check if top N results all have high confidence
+            let top_results: Vec<_> = vector_results
+                .iter()
+                .take(EARLY_TERMINATION_TOP_N.min(vector_results.len()))
+                .collect();
+
+            // The threshold is a cosine distance; other metrics have no fixed scale
+            let all_high_confidence = self.store.metric() == crate::vectordb::Metric::Cosine
+                && top_results
+                    .iter()
+                    .all(|r| r.distance < HIGH_CONFIDENCE_THRESHOLD);
+
+            // Also ensure we have at least one result
+            !top_results.is_empty() && all_high_confidence
+        };
+
+        // Use vector-only mode if early termination conditions are met
+        let vector_only_mode = options.vector_only || should_use_vector_only;
+
+        // OPTIMIZATION: Log early termination for monitoring
+        if should_use_vector_only && !options.vector_only {
+            eprintln!(
+                "{}",
+                "⚡ Early termination: High-confidence results found, skipping FTS search".green()
+            );
+        }
+
+        let fused_results: Vec<FusedResult> = if vector_only_mode {
+            // Vector-only mode
+            vector_only(&vector_results)
+        } else {
+            // Hybrid search with RRF fusion
+            match self.fts_store {
+                Some(ref fts_store) => {
+                    // Detect identifiers for exact match boosting
+                    let identifiers = if options.fuzzy {
+                        detect_identifiers_tolerant(query)
+                    } else {
+                        detect_identifiers(query)
+                    };
+                    // Detect structural intent for kind field boosting
+                    let structural_intent = detect_structural_intent(query);
+
+                    if identifiers.is_empty() {
+                        // No identifiers - standard hybrid search
+                        let fts_results = fts_search_with_phrases(
+                            fts_store,
+                            query,
+                            retrieval_limit,
+                            structural_intent,
+                        )?;
+                        let k = options.rrf_k.unwrap_or(DEFAULT_RRF_K as usize) as f32;
+                        rrf_fusion(&vector_results, &fts_results, k)
+                    } else {
+                        // Has identifiers - use exact match boosting
+                        let fts_results = fts_search_with_phrases(
+                            fts_store,
+                            query,
+                            retrieval_limit,
+                            structural_intent,
+                        )?;
+
+                        // Search for each identifier and combine exact results
+                        let mut all_exact_results = Vec::new();
+                        let mut seen_exact_ids = std::collections::HashSet::new();
+
+                        for identifier in &identifiers {
+                            let exact = if options.case_sensitive {
+                                fts_store.search_exact_cs(
+                                    identifier,
+                                    retrieval_limit,
+                                    structural_intent,
+                                )
+                            } else {
+                                fts_store.search_exact(
+                                    identifier,
+                                    retrieval_limit,
+                                    structural_intent,
+                                )
+                            };
+                            let mut exact_matches = exact.unwrap_or_default();
+
+                            // Tolerant mode: retry near-miss identifiers (typos, partial names)
+                            if exact_matches.is_empty() && options.fuzzy {
+                                exact_matches = fts_store
+                                    .search_fuzzy(
+                                        identifier,
+                                        fuzzy_distance(identifier),
+                                        retrieval_limit,
+                                    )
+                                    .unwrap_or_default();
+                            }
 
-    let fused_results: Vec<FusedResult> = if vector_only_mode {
-        // Vector-only mode
-        vector_only(&vector_results)
-    } else {
-        // Hybrid search with RRF fusion
-        match FtsStore::new(&db_path) {
-            Ok(fts_store) => {
-                // Detect identifiers for exact match boosting
-                let identifiers = detect_identifiers(query);
-                // Detect structural intent for kind field boosting
-                let structural_intent = detect_structural_intent(query);
-
-                if identifiers.is_empty() {
-                    // No identifiers - standard hybrid search
-                    let fts_results =
-                        fts_store.search(query, retrieval_limit, structural_intent)?;
-                    let k = options.rrf_k.unwrap_or(DEFAULT_RRF_K as usize) as f32;
-                    rrf_fusion(&vector_results, &fts_results, k)
-                } else {
-                    // Has identifiers - use exact match boosting
-                    let fts_results =
-                        fts_store.search(query, retrieval_limit, structural_intent)?;
-
-                    // Search for each identifier and combine exact results
-                    let mut all_exact_results = Vec::new();
-                    let mut seen_exact_ids = std::collections::HashSet::new();
-
-                    for identifier in &identifiers {
-                        if let Ok(exact_matches) =
-                            fts_store.search_exact(identifier, retrieval_limit, structural_intent)
-                        {
                             for exact_match in exact_matches {
                                 // Deduplicate exact results by chunk ID
                                 if seen_exact_ids.insert(exact_match.chunk_id) {
@@ -647,163 +2159,242 @@ pub async fn search(query: &str, path: Option<PathBuf>, options: SearchOptions)
                                 }
                             }
                         }
-                    }
 
-                    // Use adaptive RRF-k based on query type
-                    let (vector_k, fts_k) = adapt_rrf_k(query);
-                    let k = options.rrf_k.unwrap_or(DEFAULT_RRF_K as usize) as f32;
-                    // Use the smaller of user-specified k and adaptive k (more conservative)
-                    let vector_k_adaptive = vector_k.min(k as f64) as f32;
-                    let fts_k_adaptive = fts_k.min(k as f64) as f32;
-
-                    use crate::rerank::{rrf_fusion_with_exact, EXACT_MATCH_RRF_K};
-                    rrf_fusion_with_exact(
-                        &vector_results,
-                        &fts_results,
-                        &all_exact_results,
-                        vector_k_adaptive,
-                        fts_k_adaptive,
-                        EXACT_MATCH_RRF_K,
-                    )
+                        // Use adaptive RRF-k based on query type
+                        let (vector_k, fts_k) = adapt_rrf_k(query);
+                        let k = options.rrf_k.unwrap_or(DEFAULT_RRF_K as usize) as f32;
+                        // Use the smaller of user-specified k and adaptive k (more conservative)
+                        let vector_k_adaptive = vector_k.min(k as f64) as f32;
+                        let fts_k_adaptive = fts_k.min(k as f64) as f32;
+
+                        use crate::rerank::{rrf_fusion_with_exact, EXACT_MATCH_RRF_K};
+                        rrf_fusion_with_exact(
+                            &vector_results,
+                            &fts_results,
+                            &all_exact_results,
+                            vector_k_adaptive,
+                            fts_k_adaptive,
+                            EXACT_MATCH_RRF_K,
+                        )
+                    }
+                }
+                None => {
+                    // FTS not available, fall back to vector-only
+                    eprintln!(
+                        "{}",
+                        "⚠️  FTS index not found, using vector-only search".yellow()
+                    );
+                    vector_only(&vector_results)
                 }
             }
-            Err(_) => {
-                // FTS not available, fall back to vector-only
-                eprintln!(
-                    "{}",
-                    "⚠️  FTS index not found, using vector-only search".yellow()
-                );
-                vector_only(&vector_results)
-            }
-        }
-    };
+        };
 
-    // Map fused results back to full SearchResult
-    let mut results: Vec<crate::vectordb::SearchResult> = Vec::new();
-    let chunk_id_to_result: std::collections::HashMap<u32, &crate::vectordb::SearchResult> =
-        vector_results.iter().map(|r| (r.id, r)).collect();
+        SearchProfile::lap(&mut profile, "fts + fusion", &mut mark);
 
-    // OPTIMIZATION: Apply path filter BEFORE expensive operations (reranking, boosting)
-    // This avoids processing results that will be filtered out anyway
-    let should_filter_by_path = options.filter_path.is_some();
-    let filter_path_normalized = options
-        .filter_path
-        .as_ref()
-        .map(|f| f.trim_start_matches("./").to_string());
-
-    // Take top rerank_top results for reranking (or max_results if not reranking)
-    // OPTIMIZATION: Take extra results when path filtering is active to ensure we have enough after filtering
-    let take_multiplier = if should_filter_by_path { 3 } else { 1 };
-    let take_count = if options.rerank {
-        options
-            .rerank_top
-            .unwrap_or(options.max_results)
-            .min(fused_results.len())
-    } else {
-        options.max_results * take_multiplier
-    };
+        let mut explain: std::collections::HashMap<u32, Explain> = if options.explain {
+            fused_results
+                .iter()
+                .map(|fused| (fused.chunk_id, Explain::from(fused)))
+                .collect()
+        } else {
+            std::collections::HashMap::new()
+        };
+
+        // Map fused results back to full SearchResult
+        let mut results: Vec<crate::vectordb::SearchResult> = Vec::new();
+        let chunk_id_to_result: std::collections::HashMap<u32, &crate::vectordb::SearchResult> =
+            vector_results.iter().map(|r| (r.id, r)).collect();
+
+        // OPTIMIZATION: Apply path filter BEFORE expensive operations (reranking, boosting)
+        // This avoids processing results that will be filtered out anyway
+        let should_filter_by_path =
+            !options.filter_paths.is_empty() || !options.exclude_paths.is_empty();
+
+        let should_filter_by_kind = options.tests_only
+            || options.exclude_tests
+            || !options.filter_kinds.is_empty()
+            || !options.filter_languages.is_empty()
+            || !options.filter_signatures.is_empty()
+            || !options.filter_names.is_empty();
+
+        // Take top rerank_top results for reranking (or max_results if not reranking),
+        // counting only candidates that pass the filters. Vector candidates were
+        // filtered during retrieval; full-text ones are checked below.
+        // Take extra when --max-file-ratio will drop some of them afterwards.
+        let take_multiplier = if options.max_file_ratio.is_some() {
+            crate::constants::FILTERED_OVERFETCH_FACTOR
+        } else {
+            1
+        };
+        let take_count = if options.rerank {
+            options
+                .rerank_top
+                .unwrap_or(options.max_results)
+                .min(fused_results.len())
+        } else if options.count_uncapped {
+            fused_results.len()
+        } else if options.auto_rerank {
+            // Enough candidates to rerank, or to filter if reranking is skipped
+            options
+                .rerank_top
+                .unwrap_or(options.max_results)
+                .max(options.max_results * take_multiplier)
+        } else {
+            options.max_results * take_multiplier
+        };
 
-    for fused in fused_results.iter().take(take_count) {
-        if let Some(result) = chunk_id_to_result.get(&fused.chunk_id) {
-            // OPTIMIZATION: Skip early if path filter doesn't match
-            if should_filter_by_path {
-                if let Some(ref filter) = filter_path_normalized {
-                    let path_normalized = result.path.trim_start_matches("./");
-                    if !path_normalized.starts_with(filter) {
+        let mut candidates_processed = 0;
+        for fused in &fused_results {
+            if results.len() >= take_count {
+                break;
+            }
+            candidates_processed += 1;
+            if let Some(result) = chunk_id_to_result.get(&fused.chunk_id) {
+                // OPTIMIZATION: Skip early if path filter doesn't match
+                if should_filter_by_path
+                    && !path_matches_filters(
+                        &result.path,
+                        &options.filter_paths,
+                        &options.exclude_paths,
+                    )
+                {
+                    continue;
+                }
+                if !kind_matches_test_filter(
+                    &result.kind,
+                    options.tests_only,
+                    options.exclude_tests,
+                ) || !matches_query_filters(result, options)
+                {
+                    continue;
+                }
+
+                // Update score to RRF score
+                let mut r = (*result).clone();
+                r.score = fused.rrf_score;
+                results.push(r);
+            } else {
+                // Result only from FTS, need to fetch from store
+                if let Ok(Some(mut result)) = self.store.get_chunk_as_result(fused.chunk_id) {
+                    // OPTIMIZATION: Skip early if path filter doesn't match
+                    if should_filter_by_path
+                        && !path_matches_filters(
+                            &result.path,
+                            &options.filter_paths,
+                            &options.exclude_paths,
+                        )
+                    {
                         continue;
                     }
+                    if !kind_matches_test_filter(
+                        &result.kind,
+                        options.tests_only,
+                        options.exclude_tests,
+                    ) || !matches_query_filters(&result, options)
+                    {
+                        continue;
+                    }
+
+                    result.score = fused.rrf_score;
+                    results.push(result);
                 }
             }
+        }
 
-            // Update score to RRF score
-            let mut r = (*result).clone();
-            r.score = fused.rrf_score;
-            results.push(r);
-        } else {
-            // Result only from FTS, need to fetch from store
-            if let Ok(Some(mut result)) = store.get_chunk_as_result(fused.chunk_id) {
-                // OPTIMIZATION: Skip early if path filter doesn't match
-                if should_filter_by_path {
-                    if let Some(ref filter) = filter_path_normalized {
-                        let path_normalized = result.path.trim_start_matches("./");
-                        if !path_normalized.starts_with(filter) {
-                            continue;
-                        }
+        // Log path filtering optimization (verbose mode)
+        if should_filter_by_path {
+            let results_after_filtering = results.len();
+            let filtered_out = candidates_processed.saturating_sub(results_after_filtering);
+            eprintln!(
+                "{}",
+                format!(
+                    "🔍 Path filter {:?} (excluding {:?}): {} candidates → {} results ({} filtered out)",
+                    options.filter_paths,
+                    options.exclude_paths,
+                    candidates_processed,
+                    results_after_filtering,
+                    filtered_out
+                )
+                .blue()
+            );
+        }
+
+        SearchProfile::lap(&mut profile, "candidates", &mut mark);
+
+        // Language awareness: Boost results from primary language
+        let lang_boost = options.language_boost;
+        if let Some(lang) = self.primary_language.as_ref().filter(|_| lang_boost > 0.0) {
+            for result in results.iter_mut() {
+                if result.language_name() == *lang {
+                    result.score *= 1.0 + lang_boost;
+                    if let Some(e) = explain.get_mut(&result.id) {
+                        e.language_boost = Some(1.0 + lang_boost);
                     }
                 }
-
-                result.score = fused.rrf_score;
-                results.push(result);
             }
+            // Re-sort after boosting
+            results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
         }
-    }
 
-    // Log path filtering optimization (verbose mode)
-    if should_filter_by_path {
-        let candidates_processed = take_count;
-        let results_after_filtering = results.len();
-        let filtered_out = candidates_processed.saturating_sub(results_after_filtering);
-        eprintln!(
-            "{}",
-            format!(
-                "🔍 Path filter '{}': {} candidates → {} results ({} filtered out)",
-                filter_path_normalized.as_ref().unwrap_or(&"".to_string()),
-                candidates_processed,
-                results_after_filtering,
-                filtered_out
-            )
-            .blue()
-        );
-    }
-
-    // Language awareness: Boost results from primary language
-    // Extract language from file path (since SearchResult doesn't have language field)
-    if let Some(ref lang) = primary_language {
-        use crate::file::Language;
-        let lang_boost = 0.2; // Boost results from primary language by 20%
-        for result in results.iter_mut() {
-            // Detect language from file path
-            let file_lang = format!(
-                "{:?}",
-                Language::from_path(std::path::Path::new(&result.path))
-            );
-            if file_lang == *lang {
-                result.score *= 1.0 + lang_boost;
+        // ChunkKind-Aware Ranking: Boost results matching structural intent
+        if let Some(intent) = detect_structural_intent(query).filter(|_| options.kind_boost > 0.0) {
+            boost_kind_by(&mut results, intent, options.kind_boost);
+            let intent_kind = format!("{:?}", intent);
+            for result in results.iter().filter(|r| r.kind == intent_kind) {
+                if let Some(e) = explain.get_mut(&result.id) {
+                    e.kind_boost = Some(1.0 + options.kind_boost);
+                }
             }
         }
-        // Re-sort after boosting
-        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-    }
 
-    // ChunkKind-Aware Ranking: Boost results matching structural intent
-    if let Some(intent) = detect_structural_intent(query) {
-        boost_kind(&mut results, intent);
-    }
-
-    // Negative Result Check: Report when no exact matches found for identifier queries
-    let identifiers = detect_identifiers(query);
-    if !identifiers.is_empty() && results.is_empty() {
-        eprintln!(
-            "{}",
-            format!(
-                "❓ No exact matches found for identifiers: {}",
-                identifiers.join(", ")
-            )
-            .yellow()
-        );
-        eprintln!("{}", "  Try using broader search terms or running `codesearch index --sync` if the codebase changed.".dimmed());
-    }
-
-    let search_duration = start.elapsed();
+        // Negative Result Check: Report when no exact matches found for identifier queries
+        let identifiers = detect_identifiers(query);
+        if !identifiers.is_empty() && results.is_empty() {
+            eprintln!(
+                "{}",
+                format!(
+                    "❓ No exact matches found for identifiers: {}",
+                    identifiers.join(", ")
+                )
+                .yellow()
+            );
+            eprintln!("{}", "  Try using broader search terms or running `codesearch index --sync` if the codebase changed.".dimmed());
+        }
 
-    // Neural reranking (if enabled)
-    let mut rerank_duration = Duration::ZERO;
-    if options.rerank && !results.is_empty() {
-        let start = Instant::now();
+        let search_duration = start.elapsed();
+        SearchProfile::lap(&mut profile, "boost", &mut mark);
+
+        // Neural reranking (if enabled). Auto mode skips it when the vector hits were
+        // high confidence (early termination) or the top result clearly leads.
+        let rerank = options.rerank
+            || (options.auto_rerank && !should_use_vector_only && is_ambiguous(&results));
+        if options.auto_rerank && !rerank && !results.is_empty() {
+            info_print!(
+                "{}",
+                "⏭️  Auto-rerank: top results are unambiguous, skipping reranking".dimmed()
+            );
+        }
+        let mut rerank_duration = Duration::ZERO;
+        if rerank && !results.is_empty() {
+            let start = Instant::now();
+
+            // Initialize the neural reranker on first use (or when a different model is asked for)
+            let kind = options.rerank_model.clone().unwrap_or_default();
+            let mut reranker_guard = self
+                .reranker
+                .lock()
+                .map_err(|e| CodeSearchError::search(format!("Mutex poisoned: {}", e)))?;
+            if reranker_guard.as_ref().map(|r| r.kind()) != Some(&kind) {
+                *reranker_guard = None;
+                match NeuralReranker::with_model(kind) {
+                    Ok(reranker) => *reranker_guard = Some(reranker),
+                    Err(e) => {
+                        eprintln!("{}", format!("⚠️  Could not load reranker: {}", e).yellow());
+                    }
+                }
+            }
 
-        // Initialize neural reranker (Jina Reranker v1 Turbo)
-        match NeuralReranker::new() {
-            Ok(mut reranker) => {
+            if let Some(reranker) = reranker_guard.as_mut() {
                 // Prepare documents for reranking
                 let documents: Vec<String> = results.iter().map(|r| r.content.clone()).collect();
                 let rrf_scores: Vec<f32> = results.iter().map(|r| r.score).collect();
@@ -817,69 +2408,290 @@ pub async fn search(query: &str, path: Option<PathBuf>, options: SearchOptions)
                         for (idx, score) in reranked {
                             let mut result = results[idx].clone();
                             result.score = score;
+                            if let Some(e) = explain.get_mut(&result.id) {
+                                e.rerank_score = Some(score);
+                            }
                             reordered.push(result);
                         }
                         results = reordered;
-                        println!("{}", "✅ Neural reranking applied".green());
+                        info_print!("{}", "✅ Neural reranking applied".green());
                     }
                     Err(e) => {
                         eprintln!("{}", format!("⚠️  Reranking failed: {}", e).yellow());
                     }
                 }
             }
-            Err(e) => {
-                eprintln!("{}", format!("⚠️  Could not load reranker: {}", e).yellow());
+            drop(reranker_guard);
+
+            rerank_duration = start.elapsed();
+            SearchProfile::lap(&mut profile, "rerank", &mut mark);
+        }
+
+        // Filter by path if specified
+        if should_filter_by_path {
+            results.retain(|r| {
+                path_matches_filters(&r.path, &options.filter_paths, &options.exclude_paths)
+            });
+        }
+
+        // Filter by test/non-test kind and structured query fields if specified
+        if should_filter_by_kind {
+            results.retain(|r| {
+                kind_matches_test_filter(&r.kind, options.tests_only, options.exclude_tests)
+                    && matches_query_filters(r, options)
+            });
+        }
+
+        // Map fused/boosted scores into 0-1 before the final cut
+        if options.normalize_scores {
+            normalize_scores(&mut results);
+        }
+
+        // Threshold first, so max_results counts only confident matches
+        if let Some(min_score) = options.min_score {
+            let dropped = apply_min_score(&mut results, min_score);
+            if verbose() && dropped > 0 {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "🔍 Min score {}: {} results below threshold filtered out",
+                        min_score, dropped
+                    )
+                    .dimmed()
+                );
             }
         }
 
-        rerank_duration = start.elapsed();
+        // Coalesce split definitions before the cut, so max_results counts symbols
+        if options.group_by_symbol {
+            results = group_by_symbol(results);
+        }
+
+        // Truncate to max_results after reranking and filtering
+        if !options.count_uncapped {
+            if let Some(ratio) = options.max_file_ratio {
+                cap_file_share(&mut results, options.max_results, ratio);
+            }
+            results.truncate(options.max_results);
+        }
+        SearchProfile::lap(&mut profile, "filter", &mut mark);
+
+        let timing = SearchTiming {
+            load: self.load_duration,
+            model_load: self.model_load_duration,
+            embed: embed_duration,
+            search: search_duration,
+            rerank: rerank_duration,
+            profile,
+        };
+
+        if !explain.is_empty() {
+            let returned: std::collections::HashSet<u32> = results.iter().map(|r| r.id).collect();
+            explain.retain(|id, _| returned.contains(id));
+        }
+
+        Ok((results, timing, explain))
     }
+}
 
-    // Filter by path if specified
-    if let Some(ref filter) = options.filter_path {
-        let filter_normalized = filter.trim_start_matches("./");
-        results.retain(|r| {
-            let path_normalized = r.path.trim_start_matches("./");
-            path_normalized.starts_with(filter_normalized)
-        });
+/// Search the codebase
+pub async fn search(query: &str, path: Option<PathBuf>, options: SearchOptions) -> Result<()> {
+    // `kind:function path:src/ ...` tokens become filters; the rest is searched
+    let (query, options) = with_query_filters(query, &options)?;
+    let query = query.as_str();
+
+    let Some((db_path, project_path)) = prepare_search(path, &options)? else {
+        return Ok(());
+    };
+
+    let searcher = Searcher::open_for(&db_path, &options)?;
+    print_degraded_banner(&searcher);
+    let (results, timing, explain) = searcher.search_explained(query, &options)?;
+    let total = results.len();
+    let to_open = options
+        .open
+        .map(|n| (n, n.checked_sub(1).and_then(|i| results.get(i)).cloned()));
+    let start = Instant::now();
+    print_results(query, results, &timing, &explain, &project_path, &options)?;
+    profile::report_profile(&timing, start.elapsed(), &options)?;
+
+    match to_open {
+        Some((_, Some(result))) => open::open_result(&result, &project_path),
+        Some((n, None)) if total > 0 => {
+            eprintln!(
+                "{}",
+                format!("⚠️  No result #{} to open ({} results)", n, total).yellow()
+            );
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Tell the user (on stderr, so JSON stays clean) that results are full-text only
+fn print_degraded_banner(searcher: &Searcher) {
+    if searcher.is_fts_only() {
+        eprintln!(
+            "{}",
+            "⚠️  Degraded mode: full-text search only, without semantic ranking".yellow()
+        );
+    }
+}
+
+/// Resolve the database and run the pre-search sync or staleness check
+///
+/// Returns the database and project paths, or `None` when there is nothing to
+/// search (no database, or `--sync-dry-run` already printed its plan).
+fn prepare_search(
+    path: Option<PathBuf>,
+    options: &SearchOptions,
+) -> Result<Option<(PathBuf, PathBuf)>> {
+    let (db_path, project_path) = match &options.db_path {
+        Some(db) => crate::db_discovery::resolve_explicit_database(db)?,
+        None => get_db_path(path)?,
+    };
+
+    if !db_path.exists() {
+        println!("{}", "❌ No database found!".red());
+        println!("   Run {} first", "codesearch index".bright_cyan());
+        println!();
+        println!(
+            "{}",
+            "💡 Tip: codesearch can find databases in parent directories. Use 'codesearch list' to see all indexed projects.".dimmed()
+        );
+        return Ok(None);
     }
 
-    // Truncate to max_results after reranking and filtering
-    results.truncate(options.max_results);
+    if options.sync_dry_run {
+        let (model_type, _, _) = resolve_model(
+            &IndexMetadata::load_or_default(&db_path)?,
+            options.model_override.as_deref(),
+        );
+        let plan = plan_sync(&db_path, model_type)?;
+        print_sync_plan(&plan);
+        return Ok(None);
+    }
+
+    // Perform incremental sync if requested (needs the model before the searcher opens)
+    if options.sync {
+        let (model_type, _, _) = resolve_model(
+            &IndexMetadata::load_or_default(&db_path)?,
+            options.model_override.as_deref(),
+        );
+        println!("{}", "🔄 Syncing database...".yellow());
+        sync_database(&db_path, model_type)?;
+    } else if let Some(sample) = sample_staleness(&db_path) {
+        let auto_sync = options.auto_sync
+            || std::env::var("CODESEARCH_AUTO_SYNC").is_ok_and(|v| v != "0" && !v.is_empty());
+        if auto_sync {
+            info_print!(
+                "{}",
+                format!(
+                    "🔄 {} of {} sampled files changed since indexing, syncing...",
+                    sample.stale, sample.checked
+                )
+                .yellow()
+            );
+            let (model_type, _, _) = resolve_model(
+                &IndexMetadata::load_or_default(&db_path)?,
+                options.model_override.as_deref(),
+            );
+            sync_database(&db_path, model_type)?;
+        } else {
+            info_print!(
+                "{}",
+                format!(
+                    "⚠️  Index may be stale: {} of {} sampled files changed since indexing. \
+                     Run with --sync (or --auto-sync) to update.",
+                    sample.stale, sample.checked
+                )
+                .yellow()
+            );
+        }
+    }
+
+    Ok(Some((db_path, project_path)))
+}
+
+/// Print search results in the format selected by `options` (JSON, grep, text, ...)
+fn print_results(
+    query: &str,
+    results: Vec<crate::vectordb::SearchResult>,
+    timing: &SearchTiming,
+    explain: &std::collections::HashMap<u32, Explain>,
+    project_path: &Path,
+    options: &SearchOptions,
+) -> Result<()> {
+    // Count-only mode: skip all result formatting
+    if options.count {
+        if options.json || options.jsonl {
+            let output = JsonCount {
+                query: query.to_string(),
+                count: results.len(),
+            };
+            println!("{}", serde_json::to_string(&output)?);
+        } else {
+            println!("{}", results.len());
+        }
+        return Ok(());
+    }
+
+    // Query terms to highlight (empty when highlighting is disabled)
+    let terms = if options.highlight {
+        highlight_terms(query)
+    } else {
+        Vec::new()
+    };
 
     // Output results
+    if options.jsonl {
+        // One JSON object per line, flushed as each result is written
+        use std::io::Write;
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        for r in &results {
+            writeln!(
+                out,
+                "{}",
+                serde_json::to_string(
+                    &JsonResult::from_result(r)
+                        .with_highlights(&terms)
+                        .with_live_context(project_path, options.context)
+                        .with_position(options.positions)
+                        .with_explain(explain.get(&r.id))
+                )?
+            )?;
+            out.flush()?;
+        }
+
+        if options.show_scores {
+            let summary = JsonlSummary {
+                query: query.to_string(),
+                total_results: results.len(),
+                timing: JsonTiming::from_timing(timing, options.rerank || options.auto_rerank),
+            };
+            writeln!(out, "{}", serde_json::to_string(&summary)?)?;
+        }
+        return Ok(());
+    }
+
     if options.json {
         let json_results: Vec<JsonResult> = results
             .iter()
-            .map(|r| JsonResult {
-                path: r.path.clone(),
-                start_line: r.start_line,
-                end_line: r.end_line,
-                kind: r.kind.clone(),
-                content: r.content.clone(),
-                score: r.score,
-                signature: r.signature.clone(),
-                context_prev: r.context_prev.clone(),
-                context_next: r.context_next.clone(),
+            .map(|r| {
+                JsonResult::from_result(r)
+                    .with_highlights(&terms)
+                    .with_live_context(project_path, options.context)
+                    .with_position(options.positions)
+                    .with_explain(explain.get(&r.id))
             })
             .collect();
 
         let timing = if options.show_scores {
-            Some(JsonTiming {
-                total_ms: (load_duration
-                    + model_load_duration
-                    + embed_duration
-                    + search_duration
-                    + rerank_duration)
-                    .as_millis() as u64,
-                embed_ms: embed_duration.as_millis() as u64,
-                search_ms: search_duration.as_millis() as u64,
-                rerank_ms: if options.rerank {
-                    Some(rerank_duration.as_millis() as u64)
-                } else {
-                    None
-                },
-            })
+            Some(JsonTiming::from_timing(
+                &timing,
+                options.rerank || options.auto_rerank,
+            ))
         } else {
             None
         };
@@ -894,6 +2706,13 @@ pub async fn search(query: &str, path: Option<PathBuf>, options: SearchOptions)
         return Ok(());
     }
 
+    if options.grep {
+        for result in &results {
+            println!("{}", grep_line(result));
+        }
+        return Ok(());
+    }
+
     if options.compact {
         // Show only file paths (like grep -l)
         let mut seen_files = std::collections::HashSet::new();
@@ -915,21 +2734,14 @@ pub async fn search(query: &str, path: Option<PathBuf>, options: SearchOptions)
 
     if options.show_scores {
         println!("Timing:");
-        println!("   Database load: {:?}", load_duration);
-        println!("   Model load:    {:?}", model_load_duration);
-        println!("   Query embed:   {:?}", embed_duration);
-        println!("   Search:        {:?}", search_duration);
-        if options.rerank {
-            println!("   Reranking:     {:?}", rerank_duration);
+        println!("   Database load: {:?}", timing.load);
+        println!("   Model load:    {:?}", timing.model_load);
+        println!("   Query embed:   {:?}", timing.embed);
+        println!("   Search:        {:?}", timing.search);
+        if options.rerank || options.auto_rerank {
+            println!("   Reranking:     {:?}", timing.rerank);
         }
-        println!(
-            "   Total:         {:?}",
-            load_duration
-                + model_load_duration
-                + embed_duration
-                + search_duration
-                + rerank_duration
-        );
+        println!("   Total:         {:?}", timing.total());
         println!();
     }
 
@@ -946,6 +2758,8 @@ pub async fn search(query: &str, path: Option<PathBuf>, options: SearchOptions)
         return Ok(());
     }
 
+    let live_context = options.context.map(|n| (project_path, n));
+
     // Group results by file if per_file > 0
     if let Some(per_file) = options.per_file {
         if per_file > 0 && per_file < options.max_results {
@@ -975,59 +2789,188 @@ pub async fn search(query: &str, path: Option<PathBuf>, options: SearchOptions)
                         idx == 0,
                         options.content_lines > 0,
                         options.show_scores,
+                        &terms,
+                        live_context,
+                        explain.get(&result.id),
                     )?;
                 }
             }
         } else {
             // Show all results
             for result in &results {
-                print_result(result, true, options.content_lines > 0, options.show_scores)?;
+                print_result(
+                    result,
+                    true,
+                    options.content_lines > 0,
+                    options.show_scores,
+                    &terms,
+                    live_context,
+                    explain.get(&result.id),
+                )?;
             }
         }
     } else {
         // Show all results
         for result in &results {
-            print_result(result, true, options.content_lines > 0, options.show_scores)?;
+            print_result(
+                result,
+                true,
+                options.content_lines > 0,
+                options.show_scores,
+                &terms,
+                live_context,
+                explain.get(&result.id),
+            )?;
         }
     }
 
     Ok(())
 }
 
-/// Sync database by re-indexing changed files
-fn sync_database(db_path: &Path, model_type: ModelType) -> Result<()> {
+/// Files an incremental sync would touch
+struct SyncPlan {
+    project_config: Option<ProjectConfig>,
+    file_meta: FileMetaStore,
+    /// New or modified files, with the chunk IDs they currently own
+    changed: Vec<(FileInfo, Vec<u32>)>,
+    /// Indexed files that no longer exist on disk, with their chunk IDs
+    deleted: Vec<(String, Vec<u32>)>,
+}
+
+impl SyncPlan {
+    fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// Indexed files stat'ed by the staleness check before each search
+const STALENESS_SAMPLE_SIZE: usize = 200;
+
+/// Cheap pre-search staleness check: stat a bounded sample of indexed files
+///
+/// Returns the sample when any checked file was modified or deleted since it was
+/// indexed, `None` when the sample is clean or there is no file metadata.
+fn sample_staleness(db_path: &Path) -> Option<crate::cache::StalenessSample> {
+    let file_meta = FileMetaStore::load(db_path).ok().flatten()?;
+    let sample = file_meta.sample_staleness(STALENESS_SAMPLE_SIZE);
+    (sample.stale > 0).then_some(sample)
+}
+
+/// Detect changed and deleted files without modifying the database
+fn plan_sync(db_path: &Path, model_type: ModelType) -> Result<SyncPlan> {
     let project_path = db_path.parent().unwrap_or(std::path::Path::new("."));
 
     // Load file metadata store
-    let mut file_meta =
+    let file_meta =
         FileMetaStore::load_or_create(db_path, model_type.short_name(), model_type.dimensions())?;
 
     // Walk the file system
-    let walker = FileWalker::new(project_path.to_path_buf());
+    let metadata = IndexMetadata::load_or_default(db_path)?;
+    let mut walker =
+        FileWalker::new(project_path.to_path_buf()).with_include_globs(metadata.include());
+    let project_config = ProjectConfig::load(project_path).ok().flatten();
+    if let Some(config) = &project_config {
+        walker = walker.with_config(config);
+    }
     let (files, _stats) = walker.walk()?;
 
-    // Initialize services
-    let cache_dir = crate::constants::get_global_models_cache_dir()?;
-    let mut embedding_service = EmbeddingService::with_cache_dir(model_type, Some(&cache_dir))?;
-    let mut chunker = SemanticChunker::new(100, 2000, 10);
-    let mut store = VectorStore::new(db_path, model_type.dimensions())?;
-
-    let mut changes = 0;
-
-    // Check for changed files
-    for file in &files {
+    let mut changed = Vec::new();
+    for file in files {
         let (needs_reindex, old_chunk_ids) = file_meta.check_file(&file.path)?;
+        if needs_reindex {
+            changed.push((file, old_chunk_ids));
+        }
+    }
+    let deleted = file_meta.find_deleted_files();
+
+    Ok(SyncPlan {
+        project_config,
+        file_meta,
+        changed,
+        deleted,
+    })
+}
 
-        if !needs_reindex {
-            continue;
+/// Print what a sync would do (for `--sync-dry-run`)
+fn print_sync_plan(plan: &SyncPlan) {
+    println!(
+        "{}",
+        format!(
+            "🔍 Sync dry run: {} changed, {} deleted (database not modified)",
+            plan.changed.len(),
+            plan.deleted.len()
+        )
+        .yellow()
+    );
+    for (file, old_chunk_ids) in &plan.changed {
+        if old_chunk_ids.is_empty() {
+            println!("  📝 {} (new)", file.path.display());
+        } else {
+            println!(
+                "  📝 {} (replaces {} chunks)",
+                file.path.display(),
+                old_chunk_ids.len()
+            );
         }
+    }
+    for (path, chunk_ids) in &plan.deleted {
+        println!("  🗑️  {} (deleted, {} chunks)", path, chunk_ids.len());
+    }
+    if plan.is_empty() {
+        println!("  ✅ Already up to date");
+    }
+}
 
-        changes += 1;
+/// Sync database by re-indexing changed files
+fn sync_database(db_path: &Path, model_type: ModelType) -> Result<()> {
+    let plan = plan_sync(db_path, model_type)?;
+    if plan.is_empty() {
+        println!("  ✅ Already up to date");
+        return Ok(());
+    }
+
+    let mut embedding_service =
+        IndexMetadata::load_or_default(db_path)?.embedding_service(db_path, model_type)?;
+    apply_sync_plan(db_path, plan, &mut embedding_service)?;
+    Ok(())
+}
+
+/// Re-index the changed files and drop the deleted ones of `plan`
+///
+/// Returns the number of files synced.
+fn apply_sync_plan(
+    db_path: &Path,
+    plan: SyncPlan,
+    embedding_service: &mut EmbeddingService,
+) -> Result<usize> {
+    let SyncPlan {
+        project_config,
+        mut file_meta,
+        changed,
+        deleted,
+    } = plan;
+
+    let metadata = IndexMetadata::load_or_default(db_path)?;
+    let mut chunker = SemanticChunker::new(100, 2000, 10)
+        .with_context_lines(metadata.context_lines())
+        .with_strip_comments(metadata.strip_comments());
+    if let Some(limits) = project_config
+        .as_ref()
+        .and_then(|c| c.language_chunk_limits(chunker.default_limits()).ok())
+    {
+        chunker = chunker.with_language_limits(limits);
+    }
+    let mut store = VectorStore::new(db_path, embedding_service.dimensions())?;
+
+    let changes = changed.len() + deleted.len();
+
+    // Re-index changed files
+    for (file, old_chunk_ids) in &changed {
         println!("  📝 {}", file.path.display());
 
         // Delete old chunks
         if !old_chunk_ids.is_empty() {
-            store.delete_chunks(&old_chunk_ids)?;
+            store.delete_chunks(old_chunk_ids)?;
         }
 
         // Read and chunk file
@@ -1049,28 +2992,140 @@ fn sync_database(db_path: &Path, model_type: ModelType) -> Result<()> {
         file_meta.update_file(&file.path, chunk_ids)?;
     }
 
-    // Check for deleted files
-    let deleted_files = file_meta.find_deleted_files();
-    for (path, chunk_ids) in &deleted_files {
-        changes += 1;
+    // Remove deleted files, a removed directory at a time
+    crate::index::delete_removed_files(&mut store, &deleted)?;
+    for (path, _) in &deleted {
         println!("  🗑️  {} (deleted)", path);
-        if !chunk_ids.is_empty() {
-            store.delete_chunks(chunk_ids)?;
-        }
         file_meta.remove_file(std::path::Path::new(path));
     }
 
-    // Rebuild index if changes were made
-    if changes > 0 {
-        println!("  🔨 Rebuilding index...");
-        store.build_index()?;
-        file_meta.save(db_path)?;
-        println!("  ✅ {} file(s) synced", changes);
-    } else {
-        println!("  ✅ Already up to date");
+    // Rebuild index
+    println!("  🔨 Rebuilding index...");
+    store.build_index()?;
+    file_meta.save(db_path)?;
+    println!("  ✅ {} file(s) synced", changes);
+
+    Ok(changes)
+}
+
+/// Extract query terms worth highlighting (words of 3+ chars, ASCII-lowercased
+/// like the text [`find_highlights`] searches)
+fn highlight_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| word.chars().count() >= 3)
+        .map(|word| word.to_ascii_lowercase())
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Find case-insensitive (ASCII) matches of `terms` in `text`
+///
+/// Returns sorted, non-overlapping byte ranges `(start, end)`.
+fn find_highlights(text: &str, terms: &[String]) -> Vec<(usize, usize)> {
+    // ASCII lowercasing keeps byte offsets aligned with the original text
+    let haystack = text.to_ascii_lowercase();
+    let mut ranges = Vec::new();
+    for term in terms {
+        let needle = term.to_ascii_lowercase();
+        if needle.is_empty() {
+            continue;
+        }
+        for (start, _) in haystack.match_indices(&needle) {
+            ranges.push((start, start + needle.len()));
+        }
     }
 
-    Ok(())
+    // Merge overlapping ranges
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Render a line with matched terms highlighted and the rest dimmed
+fn highlight_line(line: &str, terms: &[String]) -> String {
+    let ranges = find_highlights(line, terms);
+    if ranges.is_empty() {
+        return line.dimmed().to_string();
+    }
+
+    let mut out = String::with_capacity(line.len() + ranges.len() * 16);
+    let mut pos = 0;
+    for (start, end) in ranges {
+        out.push_str(&line[pos..start].dimmed().to_string());
+        out.push_str(&line[start..end].bold().yellow().to_string());
+        pos = end;
+    }
+    out.push_str(&line[pos..].dimmed().to_string());
+    out
+}
+
+/// A line read from the file on disk for `--context`
+#[derive(Debug, PartialEq)]
+struct LiveLine {
+    /// 1-based line number
+    number: usize,
+    text: String,
+    /// Whether the line is inside the matched chunk
+    in_match: bool,
+}
+
+/// Format a result as a `path:line:col: text` line, as emitted by grep/ripgrep
+///
+/// The text is the chunk's first non-blank line; the line number is 1-based.
+fn grep_line(result: &crate::vectordb::SearchResult) -> String {
+    let text = result
+        .content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or("");
+    format!("{}:{}:1: {}", result.path, result.start_line + 1, text)
+}
+
+/// Re-read `context` lines around a chunk from the current file on disk
+///
+/// `start_line`/`end_line` are the chunk's 0-based, end-exclusive line range.
+/// Relative paths are resolved against `root`. Returns `None` if the file
+/// can no longer be read.
+fn read_live_context(
+    root: &Path,
+    path: &str,
+    start_line: usize,
+    end_line: usize,
+    context: usize,
+) -> Option<Vec<LiveLine>> {
+    let file_path = Path::new(path);
+    let file_path = if file_path.is_absolute() {
+        file_path.to_path_buf()
+    } else {
+        root.join(file_path)
+    };
+    let source = std::fs::read_to_string(file_path).ok()?;
+
+    let first = start_line.saturating_sub(context);
+    let last = end_line.max(start_line + 1).saturating_add(context);
+    Some(
+        source
+            .lines()
+            .enumerate()
+            .skip(first)
+            .take(last - first)
+            .map(|(i, text)| LiveLine {
+                number: i + 1,
+                text: text.to_string(),
+                in_match: i >= start_line && i < end_line,
+            })
+            .collect(),
+    )
 }
 
 fn print_result(
@@ -1078,6 +3133,9 @@ fn print_result(
     show_file: bool,
     show_content: bool,
     show_scores: bool,
+    terms: &[String],
+    live_context: Option<(&Path, usize)>,
+    explain: Option<&Explain>,
 ) -> Result<()> {
     if show_file {
         println!("{}", "─".repeat(60));
@@ -1118,13 +3176,38 @@ fn print_result(
         );
     }
 
+    if let Some(explain) = explain {
+        println!("   {} {}", "Why:".dimmed(), explain.summary().dimmed());
+    }
+
     // Show context if available
     if let Some(ctx) = &result.context {
         println!("   Context: {}", ctx.dimmed());
     }
 
-    // Show content if requested
-    if show_content {
+    // Show live context from disk if requested, falling back to the indexed content
+    let live_lines = live_context.and_then(|(root, n)| {
+        read_live_context(root, &result.path, result.start_line, result.end_line, n)
+    });
+    if let Some(lines) = live_lines {
+        let width = lines
+            .last()
+            .map(|l| l.number.to_string().len())
+            .unwrap_or(1);
+        println!();
+        for line in &lines {
+            let number = format!("{:>width$}", line.number, width = width);
+            if line.in_match {
+                println!(
+                    "   {} │ {}",
+                    number.bright_yellow(),
+                    highlight_line(&line.text, terms)
+                );
+            } else {
+                println!("   {} │ {}", number.dimmed(), line.text.bright_black());
+            }
+        }
+    } else if show_content {
         // Show context before (if available)
         if let Some(ctx_prev) = &result.context_prev {
             println!("\n   {}:", "Context (before)".dimmed());
@@ -1135,7 +3218,7 @@ fn print_result(
 
         println!("\n   {}:", "Content".bright_yellow());
         for line in result.content.lines().take(10) {
-            println!("   │ {}", line.dimmed());
+            println!("   │ {}", highlight_line(line, terms));
         }
         if result.content.lines().count() > 10 {
             println!("   │ {}", "...".dimmed());
@@ -1152,13 +3235,9 @@ fn print_result(
         // Show a snippet
         let snippet: String = result.content.lines().take(3).collect::<Vec<_>>().join(" ");
 
-        let snippet = if snippet.len() > 100 {
-            format!("{}...", &snippet[..100])
-        } else {
-            snippet
-        };
+        let snippet = crate::output::truncate_with_ellipsis(&snippet, 100);
 
-        println!("   {}", snippet.dimmed());
+        println!("   {}", highlight_line(&snippet, terms));
     }
 
     println!();
@@ -1171,6 +3250,27 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    /// A Function chunk result at `path` from line `start`, scored `score`
+    fn result(path: &str, start: usize, score: f32) -> crate::vectordb::SearchResult {
+        crate::vectordb::SearchResult {
+            id: 0,
+            score,
+            path: path.into(),
+            content: String::new(),
+            start_line: start,
+            end_line: start + 1,
+            kind: "Function".into(),
+            signature: None,
+            context_prev: None,
+            context_next: None,
+            distance: 1.0 - score,
+            context: None,
+            docstring: None,
+            hash: String::new(),
+            language: None,
+        }
+    }
+
     // --- detect_identifiers ---
 
     #[test]
@@ -1218,6 +3318,61 @@ mod tests {
         assert!(ids.is_empty(), "Plain lowercase words are not identifiers");
     }
 
+    #[test]
+    fn test_detect_identifiers_min_len_boundaries() {
+        // Below the default minimum: two-letter names in every casing style
+        assert!(detect_identifiers_min_len("Db iD a_ lookup", 3).is_empty());
+        // Exactly at the minimum counts
+        assert_eq!(
+            detect_identifiers_min_len("Url a_b getX", 3),
+            vec!["Url", "a_b", "getX"]
+        );
+        assert_eq!(detect_identifiers_min_len("Url getX", 4), vec!["getX"]);
+        // Length is in characters, not bytes
+        assert_eq!(detect_identifiers_min_len("Über", 4), vec!["Über"]);
+        // 0 turns the threshold off
+        assert_eq!(detect_identifiers_min_len("Db", 0), vec!["Db"]);
+    }
+
+    #[test]
+    fn test_detect_identifiers_tolerant_short_query() {
+        let ids = detect_identifiers_tolerant("authenticat");
+        assert_eq!(ids, vec!["authenticat"]);
+
+        let ids = detect_identifiers_tolerant("UserServ lookup");
+        assert_eq!(ids, vec!["UserServ", "lookup"]);
+    }
+
+    #[test]
+    fn test_detect_identifiers_tolerant_long_query_unchanged() {
+        let query = "search for authentication handlers";
+        assert_eq!(
+            detect_identifiers_tolerant(query),
+            detect_identifiers(query)
+        );
+    }
+
+    #[test]
+    fn test_extract_quoted_phrases() {
+        assert_eq!(
+            extract_quoted_phrases(r#"where is "connection pool timeout" set"#),
+            vec!["connection pool timeout"]
+        );
+        assert_eq!(
+            extract_quoted_phrases(r#""read file" and "write file""#),
+            vec!["read file", "write file"]
+        );
+        assert!(extract_quoted_phrases("no quotes here").is_empty());
+        assert!(extract_quoted_phrases(r#"unterminated "phrase"#).is_empty());
+        assert!(extract_quoted_phrases(r#"empty "" phrase"#).is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_distance() {
+        assert_eq!(fuzzy_distance("auth"), 1);
+        assert_eq!(fuzzy_distance("authenticat"), 2);
+    }
+
     // --- detect_structural_intent ---
 
     #[test]
@@ -1263,9 +3418,30 @@ mod tests {
     }
 
     #[test]
-    fn test_detect_structural_intent_trait() {
-        let kind = detect_structural_intent("trait Chunker");
-        assert_eq!(kind, Some(crate::chunker::ChunkKind::Trait));
+    fn test_detect_structural_intent_trait() {
+        let kind = detect_structural_intent("trait Chunker");
+        assert_eq!(kind, Some(crate::chunker::ChunkKind::Trait));
+    }
+
+    #[test]
+    fn test_detect_structural_intent_test() {
+        let kind = detect_structural_intent("test parse_config");
+        assert_eq!(kind, Some(crate::chunker::ChunkKind::Test));
+    }
+
+    #[test]
+    fn test_detect_structural_intent_matches_whole_words() {
+        use crate::chunker::ChunkKind;
+        assert_eq!(
+            detect_structural_intent("parse_config test"),
+            Some(ChunkKind::Test)
+        );
+        assert_eq!(detect_structural_intent("latest parse_config"), None);
+        assert_eq!(detect_structural_intent("subclass UserService"), None);
+        assert_eq!(
+            detect_structural_intent("UserService (class)"),
+            Some(ChunkKind::Class)
+        );
     }
 
     #[test]
@@ -1280,6 +3456,34 @@ mod tests {
         assert_eq!(kind, None);
     }
 
+    #[test]
+    fn test_detect_structural_intent_writes_nothing_to_stderr() {
+        // The test harness captures stderr in-process, so re-run this test in a
+        // child process with capture off and check what actually reaches stderr
+        const CHILD: &str = "CODESEARCH_TEST_STDERR_CHILD";
+        if std::env::var_os(CHILD).is_some() {
+            crate::output::set_quiet(true);
+            detect_structural_intent("find struct UserService");
+            detect_structural_intent("how does authentication work");
+            return;
+        }
+
+        let module = module_path!().split_once("::").map_or("", |(_, m)| m);
+        let name = format!(
+            "{}::test_detect_structural_intent_writes_nothing_to_stderr",
+            module
+        );
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([name.as_str(), "--exact", "--nocapture", "--test-threads=1"])
+            .env(CHILD, "1")
+            .env_remove("RUST_LOG")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.trim().is_empty(), "unexpected stderr: {}", stderr);
+    }
+
     // --- contains_identifier ---
 
     #[test]
@@ -1323,6 +3527,134 @@ mod tests {
         assert!(!contains_identifier("hello_"));
     }
 
+    #[test]
+    fn test_query_stopwords_are_not_identifiers() {
+        // Sentence-initial words no longer look like PascalCase types
+        assert!(!contains_identifier("Show all enums"));
+        assert_eq!(detect_structural_intent("Find every enum"), None);
+        assert!(contains_identifier("Find the Config"));
+        assert_eq!(
+            detect_structural_intent("Where is struct Config"),
+            Some(crate::chunker::ChunkKind::Struct)
+        );
+        assert_eq!(
+            detect_identifiers_min_len("Which Parser handles Request", 3),
+            vec!["Parser", "Request"]
+        );
+
+        assert!(detect_identifiers_min_len("Get all handlers", 3).is_empty());
+
+        // Words that commonly name types or methods are still identifiers
+        for word in ["Display", "List", "Any", "Search"] {
+            assert_eq!(
+                detect_identifiers_min_len(&format!("Where is {} implemented", word), 3),
+                vec![word]
+            );
+        }
+
+        // CODESEARCH_QUERY_STOPWORDS adds words and removes defaults with `-`
+        let words = stopwords_from(Some("Zeige, -Get ,  ,Finde"));
+        assert!(words.contains(&"zeige".to_string()));
+        assert!(words.contains(&"finde".to_string()));
+        assert!(!words.contains(&"get".to_string()));
+        assert!(is_stopword("ZEIGE", &words));
+        assert!(!is_stopword("Get", &words));
+        assert_eq!(stopwords_from(None).len(), DEFAULT_QUERY_STOPWORDS.len());
+    }
+
+    // --- signature_declares ---
+
+    #[test]
+    fn test_signature_declares_definitions() {
+        assert!(signature_declares(
+            "pub fn search(&self, query: &str)",
+            "search"
+        ));
+        assert!(signature_declares("pub struct Searcher<'a>", "Searcher"));
+        assert!(signature_declares(
+            "def authenticate(self, user):",
+            "authenticate"
+        ));
+        assert!(signature_declares(
+            "func (s *Server) Handle(w http.ResponseWriter)",
+            "Handle"
+        ));
+        assert!(signature_declares(
+            "class UserService extends Base {",
+            "UserService"
+        ));
+    }
+
+    #[test]
+    fn test_signature_declares_ignores_mentions() {
+        // Parameter and return types mention the symbol without declaring it
+        assert!(!signature_declares(
+            "fn run(config: Config) -> Result<()>",
+            "Config"
+        ));
+        assert!(!signature_declares("fn load() -> Config", "Config"));
+        assert!(!signature_declares("def f(x) -> User:", "User"));
+        assert!(!signature_declares("func (s *Server) Handle()", "Server"));
+        // Whole identifiers only
+        assert!(!signature_declares("fn search_all()", "search"));
+        assert!(!signature_declares("fn search()", ""));
+    }
+
+    #[test]
+    fn test_signature_declares_the_name_not_its_types() {
+        // Java/C#/C++: the return type comes before the name
+        assert!(!signature_declares("public Config load()", "Config"));
+        assert!(signature_declares("public Config load()", "load"));
+        assert!(!signature_declares(
+            "static Config load(String path)",
+            "Config"
+        ));
+        assert!(signature_declares("Config Settings::load() const", "load"));
+        assert!(signature_declares(
+            "public async Task<Config> LoadAsync()",
+            "LoadAsync"
+        ));
+        assert!(signature_declares("private Config config;", "config"));
+        assert!(!signature_declares("private Config config;", "Config"));
+        // Go: the return type comes after the parameters
+        assert!(!signature_declares("func load() Config", "Config"));
+        assert!(signature_declares("type Config struct", "Config"));
+        // Generic bounds and impl targets
+        assert!(!signature_declares(
+            "fn parse<T: Config>(input: &str) -> T",
+            "Config"
+        ));
+        assert!(!signature_declares(
+            "impl<T: Config> Loader for Cache<T>",
+            "Config"
+        ));
+        assert!(!signature_declares("impl Loader for Cache", "Loader"));
+        assert!(signature_declares("impl Loader for Cache", "Cache"));
+        assert!(signature_declares("impl<T> Cache<T>", "Cache"));
+        // Bindings and keyword runs
+        assert!(signature_declares("pub const fn new() -> Self", "new"));
+        assert!(signature_declares(
+            "pub static CONFIG: Lazy<Config>",
+            "CONFIG"
+        ));
+        assert!(!signature_declares(
+            "pub static CONFIG: Lazy<Config>",
+            "Config"
+        ));
+        assert!(signature_declares(
+            "const handler = async (req) =>",
+            "handler"
+        ));
+        assert!(signature_declares("static int count = 0;", "count"));
+        assert!(signature_declares("export default class App", "App"));
+        assert!(signature_declares("macro_rules! bail", "bail"));
+        assert!(signature_declares("var count int", "count"));
+        assert!(signature_declares(
+            "const std::string& name() const",
+            "name"
+        ));
+    }
+
     // --- boost_kind ---
 
     #[test]
@@ -1332,14 +3664,14 @@ mod tests {
                 id: 1, score: 0.8,
                 path: "a.rs".into(), content: "fn foo()".into(),
                 start_line: 1, end_line: 5, kind: "Function".into(),
-                signature: None, context_prev: None, context_next: None,
+                signature: None, context_prev: None, context_next: None, language: None,
                 distance: 0.2, context: None, docstring: None, hash: String::new(),
             },
             crate::vectordb::SearchResult {
                 id: 2, score: 0.9,
                 path: "b.rs".into(), content: "struct Bar".into(),
                 start_line: 1, end_line: 5, kind: "Struct".into(),
-                signature: None, context_prev: None, context_next: None,
+                signature: None, context_prev: None, context_next: None, language: None,
                 distance: 0.1, context: None, docstring: None, hash: String::new(),
             },
         ];
@@ -1360,14 +3692,14 @@ mod tests {
                 id: 1, score: 0.7,
                 path: "a.rs".into(), content: "fn foo()".into(),
                 start_line: 1, end_line: 5, kind: "Function".into(),
-                signature: None, context_prev: None, context_next: None,
+                signature: None, context_prev: None, context_next: None, language: None,
                 distance: 0.3, context: None, docstring: None, hash: String::new(),
             },
             crate::vectordb::SearchResult {
                 id: 2, score: 0.75,
                 path: "b.rs".into(), content: "struct Bar".into(),
                 start_line: 1, end_line: 5, kind: "Struct".into(),
-                signature: None, context_prev: None, context_next: None,
+                signature: None, context_prev: None, context_next: None, language: None,
                 distance: 0.25, context: None, docstring: None, hash: String::new(),
             },
         ];
@@ -1383,6 +3715,24 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_boost_kind_by_custom_and_disabled() {
+        let kinded = |id, score, kind: &str| crate::vectordb::SearchResult {
+            id,
+            kind: kind.into(),
+            ..result("a.rs", 1, score)
+        };
+        let mut results = vec![kinded(1, 0.5, "Struct"), kinded(2, 0.4, "Function")];
+
+        boost_kind_by(&mut results, crate::chunker::ChunkKind::Function, 0.0);
+        assert_eq!(results[0].id, 1);
+        assert!((results[1].score - 0.4).abs() < 1e-6);
+
+        boost_kind_by(&mut results, crate::chunker::ChunkKind::Function, 0.5);
+        assert_eq!(results[0].id, 2);
+        assert!((results[0].score - 0.6).abs() < 1e-6);
+    }
+
     // --- adapt_rrf_k ---
 
     #[test]
@@ -1462,8 +3812,235 @@ mod tests {
         assert!(read_metadata(dir.path()).is_none());
     }
 
+    // --- IndexMetadata ---
+
+    /// Metadata of the index at `dir`, with defaults when it has none
+    fn indexed(dir: &Path) -> IndexMetadata {
+        IndexMetadata::load_or_default(dir).unwrap()
+    }
+
+    #[test]
+    fn test_index_metadata_context_lines() {
+        let dir = tempdir().unwrap();
+        // No metadata and metadata without the field both mean the default
+        assert_eq!(
+            indexed(dir.path()).context_lines(),
+            crate::chunker::DEFAULT_CONTEXT_LINES
+        );
+        let metadata = serde_json::json!({ "model_short_name": "bge-small", "dimensions": 384 });
+        std::fs::write(dir.path().join("metadata.json"), metadata.to_string()).unwrap();
+        assert_eq!(
+            indexed(dir.path()).context_lines(),
+            crate::chunker::DEFAULT_CONTEXT_LINES
+        );
+
+        let metadata = serde_json::json!({
+            "model_short_name": "bge-small",
+            "dimensions": 384,
+            "context_lines": 8
+        });
+        std::fs::write(dir.path().join("metadata.json"), metadata.to_string()).unwrap();
+        assert_eq!(indexed(dir.path()).context_lines(), 8);
+    }
+
+    #[test]
+    fn test_index_metadata_strip_comments() {
+        let dir = tempdir().unwrap();
+        assert!(!indexed(dir.path()).strip_comments());
+        let metadata = serde_json::json!({ "model_short_name": "bge-small", "dimensions": 384 });
+        std::fs::write(dir.path().join("metadata.json"), metadata.to_string()).unwrap();
+        assert!(!indexed(dir.path()).strip_comments());
+
+        let metadata = serde_json::json!({
+            "model_short_name": "bge-small",
+            "dimensions": 384,
+            "strip_comments": true
+        });
+        std::fs::write(dir.path().join("metadata.json"), metadata.to_string()).unwrap();
+        assert!(indexed(dir.path()).strip_comments());
+    }
+
+    #[test]
+    fn test_index_metadata_roundtrip_and_errors() {
+        let dir = tempdir().unwrap();
+        assert!(IndexMetadata::load(dir.path()).unwrap().is_none());
+
+        let metadata = IndexMetadata {
+            model_short_name: Some("bge-small".into()),
+            dimensions: Some(384),
+            metric: Some("euclidean".into()),
+            normalized: Some(false),
+            include: Some(vec!["src/**".into()]),
+            ..Default::default()
+        };
+        assert!(IndexMetadata::default().include().is_empty());
+        metadata.save(dir.path()).unwrap();
+        let loaded = IndexMetadata::load(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.include(), ["src/**"]);
+        assert_eq!(loaded.metric(), crate::vectordb::Metric::Euclidean);
+        assert!(!loaded.normalized());
+        assert_eq!(loaded.model().unwrap(), Some(ModelType::BGESmallENV15));
+
+        // A malformed file is reported, not mistaken for an index with defaults
+        std::fs::write(dir.path().join("metadata.json"), "not json").unwrap();
+        assert!(matches!(
+            IndexMetadata::load(dir.path()),
+            Err(CodeSearchError::Parse { .. })
+        ));
+    }
+
+    // --- IndexMetadata::model ---
+
+    #[test]
+    fn test_indexed_model_wins_over_config_default() {
+        let dir = tempdir().unwrap();
+        let config_model = Some(ModelType::BGEBaseENV15);
+        // A new index takes the configured model
+        assert_eq!(
+            indexed(dir.path()).model_or(config_model).unwrap(),
+            ModelType::BGEBaseENV15
+        );
+
+        // An existing index keeps the model it was built with
+        let metadata = serde_json::json!({ "model_short_name": "bge-small", "dimensions": 384 });
+        std::fs::write(dir.path().join("metadata.json"), metadata.to_string()).unwrap();
+        assert_eq!(
+            indexed(dir.path()).model_or(config_model).unwrap(),
+            ModelType::BGESmallENV15
+        );
+    }
+
+    #[test]
+    fn test_indexed_model_missing_metadata() {
+        let dir = tempdir().unwrap();
+        assert!(indexed(dir.path()).model().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_indexed_model_valid() {
+        let dir = tempdir().unwrap();
+        let metadata = serde_json::json!({ "model_short_name": "bge-base", "dimensions": 768 });
+        std::fs::write(
+            dir.path().join("metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+
+        let model = indexed(dir.path()).model().unwrap().unwrap();
+        assert_eq!(model.dimensions(), 768);
+    }
+
+    #[test]
+    fn test_check_model_dimensions() {
+        let dir = tempdir().unwrap();
+        // No metadata yet: nothing to compare against
+        assert!(
+            check_model_dimensions(dir.path(), &indexed(dir.path()), ModelType::BGEBaseENV15)
+                .is_ok()
+        );
+
+        std::fs::write(
+            dir.path().join("metadata.json"),
+            r#"{"model_short_name": "minilm-l6-q", "dimensions": 384}"#,
+        )
+        .unwrap();
+        assert!(
+            check_model_dimensions(dir.path(), &indexed(dir.path()), ModelType::BGESmallENV15)
+                .is_ok()
+        );
+
+        let err = check_model_dimensions(dir.path(), &indexed(dir.path()), ModelType::BGEBaseENV15)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("768"));
+        assert!(err.contains("minilm-l6-q (384 dims)"));
+        assert!(err.contains("Drop --model"));
+        assert!(matches!(
+            check_model_dimensions(dir.path(), &indexed(dir.path()), ModelType::BGEBaseENV15),
+            Err(CodeSearchError::DimensionMismatch {
+                expected: 384,
+                actual: 768,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_indexed_model_dimension_mismatch() {
+        let dir = tempdir().unwrap();
+        let metadata = serde_json::json!({ "model_short_name": "bge-base", "dimensions": 384 });
+        std::fs::write(
+            dir.path().join("metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+
+        assert!(indexed(dir.path()).model().is_err());
+    }
+
+    // --- plan_sync ---
+
+    #[test]
+    fn test_read_live_context_reads_around_chunk() {
+        let dir = tempdir().unwrap();
+        let lines: Vec<String> = (1..=10).map(|i| format!("line {}", i)).collect();
+        std::fs::write(dir.path().join("a.rs"), lines.join("\n")).unwrap();
+
+        // Chunk covers lines 5-6 (0-based 4..6)
+        let live = read_live_context(dir.path(), "a.rs", 4, 6, 2).unwrap();
+        let numbers: Vec<usize> = live.iter().map(|l| l.number).collect();
+        assert_eq!(numbers, vec![3, 4, 5, 6, 7, 8]);
+        assert_eq!(live[2].text, "line 5");
+        assert!(live[2].in_match && live[3].in_match);
+        assert!(!live[1].in_match && !live[4].in_match);
+
+        // Clamped at both ends of the file
+        let live = read_live_context(dir.path(), "a.rs", 0, 1, 20).unwrap();
+        assert_eq!(live.len(), 10);
+
+        assert!(read_live_context(dir.path(), "missing.rs", 0, 1, 2).is_none());
+    }
+
+    #[test]
+    fn test_plan_sync_reports_changed_and_deleted_files() {
+        let project = tempdir().unwrap();
+        let db_path = project.path().join(".codesearch.db");
+        std::fs::create_dir_all(&db_path).unwrap();
+        let model_type = ModelType::default();
+
+        let gone = project.path().join("gone.rs");
+        std::fs::write(&gone, "fn gone() {}\n").unwrap();
+        let mut file_meta = FileMetaStore::load_or_create(
+            &db_path,
+            model_type.short_name(),
+            model_type.dimensions(),
+        )
+        .unwrap();
+        file_meta.update_file(&gone, vec![1, 2]).unwrap();
+        file_meta.save(&db_path).unwrap();
+        std::fs::remove_file(&gone).unwrap();
+
+        std::fs::write(project.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+        let plan = plan_sync(&db_path, model_type).unwrap();
+        assert_eq!(plan.changed.len(), 1);
+        assert!(plan.changed[0].0.path.ends_with("main.rs"));
+        assert!(plan.changed[0].1.is_empty());
+        assert_eq!(plan.deleted.len(), 1);
+        assert_eq!(plan.deleted[0].1, vec![1, 2]);
+        assert!(!plan.is_empty());
+    }
+
     // --- expand_query ---
 
+    #[test]
+    fn test_query_variants_without_expansion() {
+        let variants = query_variants("handle_file_modified", false);
+        assert_eq!(variants, vec!["handle_file_modified".to_string()]);
+        assert!(query_variants("handle_file_modified", true).len() > 1);
+        assert!(SearchOptions::default().expand_query);
+    }
+
     #[test]
     fn test_expand_query_short_query_not_expanded() {
         let variants = expand_query("foo");
@@ -1512,6 +4089,476 @@ mod tests {
         assert!(variants.contains(&"fn search".to_string()));
     }
 
+    // --- path_matches_filters ---
+
+    #[test]
+    fn test_kind_matches_test_filter() {
+        assert!(kind_matches_test_filter("Function", false, false));
+        assert!(kind_matches_test_filter("Test", false, false));
+        assert!(kind_matches_test_filter("Test", true, false));
+        assert!(!kind_matches_test_filter("Function", true, false));
+        assert!(!kind_matches_test_filter("Test", false, true));
+        assert!(kind_matches_test_filter("Function", false, true));
+    }
+
+    #[test]
+    fn test_path_matches_filters_no_filters() {
+        assert!(path_matches_filters("src/main.rs", &[], &[]));
+    }
+
+    #[test]
+    fn test_path_matches_filters_include() {
+        let src = vec!["src/".to_string()];
+        assert!(path_matches_filters("./src/api/mod.rs", &src, &[]));
+        let src = vec!["./src/".to_string()];
+        assert!(!path_matches_filters("tests/api.rs", &src, &[]));
+    }
+
+    #[test]
+    fn test_path_matches_filters_include_any() {
+        let filters = vec!["src/api/".to_string(), "src/handlers/".to_string()];
+        assert!(path_matches_filters("src/api/user.rs", &filters, &[]));
+        assert!(path_matches_filters(
+            "./src/handlers/auth.rs",
+            &filters,
+            &[]
+        ));
+        assert!(!path_matches_filters("src/db/pool.rs", &filters, &[]));
+    }
+
+    #[test]
+    fn test_path_matches_filters_exclude() {
+        let ex = vec!["target/".to_string(), "./vendor/".to_string()];
+        assert!(!path_matches_filters("target/debug/main.rs", &[], &ex));
+        assert!(!path_matches_filters("./vendor/lib.rs", &[], &ex));
+        assert!(path_matches_filters("src/lib.rs", &[], &ex));
+    }
+
+    #[test]
+    fn test_path_matches_filters_exclude_wins_over_include() {
+        let src = vec!["src/".to_string()];
+        let ex = vec!["src/gen/".to_string()];
+        assert!(!path_matches_filters("src/gen/proto.rs", &src, &ex));
+        assert!(path_matches_filters("src/api.rs", &src, &ex));
+    }
+
+    // --- JSON output ---
+
+    #[test]
+    fn test_json_result_from_result() {
+        let r = crate::vectordb::SearchResult {
+            id: 3,
+            content: "fn foo() {}".into(),
+            end_line: 6,
+            signature: Some("fn foo()".into()),
+            ..result("src/lib.rs", 4, 0.5)
+        };
+        let line = serde_json::to_string(&JsonResult::from_result(&r)).unwrap();
+        assert!(!line.contains('\n'), "JSONL records must fit on one line");
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["path"], "src/lib.rs");
+        assert_eq!(parsed["start_line"], 4);
+        assert_eq!(parsed["signature"], "fn foo()");
+    }
+
+    #[test]
+    fn test_grep_line() {
+        let r = crate::vectordb::SearchResult {
+            content: "\n    fn foo() {\n    }".into(),
+            end_line: 7,
+            ..result("src/lib.rs", 4, 0.5)
+        };
+        assert_eq!(grep_line(&r), "src/lib.rs:5:1: fn foo() {");
+    }
+
+    // --- positions ---
+
+    #[test]
+    fn test_signature_position() {
+        let content = "/// Doc\npub fn parse(input: &str) -> Ast {\n    todo!()\n}";
+        let pos = signature_position(content, Some("pub fn parse(input: &str) -> Ast"), 10);
+        assert_eq!(
+            pos,
+            Some(JsonPosition {
+                line: 11,
+                character: 0,
+                column: 1
+            })
+        );
+
+        // Columns count UTF-16 units for LSP and characters for everyone else
+        let content = "let café = 1; let 𝒳 = 2; fn target() {}";
+        let pos = signature_position(content, Some("fn target()"), 0).unwrap();
+        assert_eq!(pos.character, 26);
+        assert_eq!(pos.column, 26);
+
+        // No signature (or a normalized one): first non-blank character
+        let content = "\n    struct Point {\n    x: f64,\n}";
+        let expected = Some(JsonPosition {
+            line: 4,
+            character: 4,
+            column: 5,
+        });
+        assert_eq!(signature_position(content, None, 3), expected);
+        assert_eq!(
+            signature_position(content, Some("struct  Point"), 3),
+            expected
+        );
+        assert_eq!(signature_position("", None, 0), None);
+    }
+
+    #[test]
+    fn test_flag_enabled() {
+        for on in ["1", "true", "yes", " TRUE "] {
+            assert!(flag_enabled(on), "{:?}", on);
+        }
+        for off in ["", "0", "false", "Off", " false "] {
+            assert!(!flag_enabled(off), "{:?}", off);
+        }
+    }
+
+    // --- highlighting ---
+
+    #[test]
+    fn test_highlight_terms() {
+        assert_eq!(
+            highlight_terms("where do we Parse the config?"),
+            vec!["config", "parse", "the", "where"]
+        );
+        assert!(highlight_terms("a b").is_empty());
+    }
+
+    #[test]
+    fn test_find_highlights_case_insensitive_and_merged() {
+        let terms = vec!["parse".to_string(), "parser".to_string()];
+        assert_eq!(
+            find_highlights("let p = Parser::new();", &terms),
+            vec![(8, 14)]
+        );
+        assert!(find_highlights("nothing here", &terms).is_empty());
+    }
+
+    #[test]
+    fn test_find_highlights_multibyte() {
+        let terms = vec!["café".to_string()];
+        let text = "// CAFé café";
+        let ranges = find_highlights(text, &terms);
+        assert_eq!(ranges.len(), 2);
+        for (start, end) in ranges {
+            assert!(text.is_char_boundary(start) && text.is_char_boundary(end));
+        }
+    }
+
+    #[test]
+    fn test_highlights_non_ascii_query_terms() {
+        // A capitalized non-ASCII letter must survive on both sides to match
+        let terms = highlight_terms("Übersetzung laden");
+        assert_eq!(terms, vec!["laden", "Übersetzung"]);
+        assert_eq!(
+            find_highlights("fn Übersetzung() {}", &terms),
+            vec![(3, 15)]
+        );
+    }
+
+    #[test]
+    fn test_json_result_with_highlights() {
+        let r = crate::vectordb::SearchResult {
+            content: "fn parse_config() {}".into(),
+            end_line: 1,
+            ..result("src/lib.rs", 1, 0.5)
+        };
+        let terms = vec!["config".to_string()];
+        let json =
+            serde_json::to_value(JsonResult::from_result(&r).with_highlights(&terms)).unwrap();
+        assert_eq!(json["highlights"], serde_json::json!([[9, 15]]));
+
+        let json = serde_json::to_value(JsonResult::from_result(&r).with_highlights(&[])).unwrap();
+        assert!(json.get("highlights").is_none());
+    }
+
+    #[test]
+    fn test_jsonl_summary_serialization() {
+        let summary = JsonlSummary {
+            query: "auth".to_string(),
+            total_results: 2,
+            timing: JsonTiming::from_timing(&SearchTiming::default(), false),
+        };
+        let parsed: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&summary).unwrap()).unwrap();
+        assert_eq!(parsed["total_results"], 2);
+        assert!(parsed["timing"].get("rerank_ms").is_none());
+        assert!(parsed.get("path").is_none());
+    }
+
+    // --- Searcher ---
+
+    #[test]
+    fn test_searcher_open_missing_database() {
+        let dir = tempdir().unwrap();
+        let result = Searcher::open(&dir.path().join("missing.db"));
+        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(CodeSearchError::DatabaseNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_searcher_fts_only() {
+        use crate::chunker::{Chunk, ChunkKind};
+        use crate::embed::EmbeddedChunk;
+
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join(".codesearch.db");
+        let dims = ModelType::default().dimensions();
+        let chunks = [
+            ("fn parse_config(path: &Path) -> Config {}", "config.rs"),
+            ("fn render_page(page: &Page) -> Html {}", "render.rs"),
+        ];
+        let ids = {
+            let mut store = VectorStore::new(&db_path, dims).unwrap();
+            let embedded = chunks
+                .iter()
+                .map(|(content, path)| {
+                    let mut chunk = Chunk::new(
+                        content.to_string(),
+                        0,
+                        1,
+                        ChunkKind::Function,
+                        path.to_string(),
+                    );
+                    chunk.signature = Some(content.trim_end_matches(" {}").to_string());
+                    EmbeddedChunk::new(chunk, vec![0.1; dims])
+                })
+                .collect();
+            let ids = store.insert_chunks_with_ids(embedded).unwrap();
+            store.build_index().unwrap();
+            ids
+        };
+
+        // No full-text index yet: --fts-only has nothing to rank with, and
+        // rebuilding the full-text index (not re-embedding) fixes that
+        let err = Searcher::open_fts_only(&db_path).err().unwrap();
+        assert!(
+            err.to_string().contains("codesearch rebuild-fts"),
+            "{}",
+            err
+        );
+
+        let mut fts_store = FtsStore::new_with_writer(&db_path).unwrap();
+        for (id, (content, path)) in ids.into_iter().zip(chunks) {
+            fts_store
+                .add_chunk(id, content, path, None, "Function")
+                .unwrap();
+        }
+        fts_store.commit().unwrap();
+        drop(fts_store);
+
+        let searcher = Searcher::open_fts_only(&db_path).unwrap();
+        assert!(searcher.is_fts_only());
+        let options = SearchOptions {
+            fts_only: true,
+            ..Default::default()
+        };
+        let results = searcher.search("render page", &options).unwrap();
+        assert_eq!(results.first().map(|r| r.path.as_str()), Some("render.rs"));
+    }
+
+    #[test]
+    fn test_search_timing_total() {
+        let timing = SearchTiming {
+            load: Duration::from_millis(1),
+            model_load: Duration::from_millis(2),
+            embed: Duration::from_millis(3),
+            search: Duration::from_millis(4),
+            rerank: Duration::from_millis(5),
+            profile: None,
+        };
+        assert_eq!(timing.total(), Duration::from_millis(15));
+    }
+
+    // --- Explain ---
+
+    #[test]
+    fn test_explain_from_fused_result() {
+        let fused = FusedResult {
+            chunk_id: 7,
+            rrf_score: 0.25,
+            vector_score: Some(0.9),
+            fts_score: None,
+            vector_rank: Some(2),
+            fts_rank: None,
+            exact_rank: Some(1),
+        };
+        let mut explain = Explain::from(&fused);
+        explain.kind_boost = Some(1.0 + DEFAULT_KIND_BOOST);
+
+        let summary = explain.summary();
+        assert!(summary.contains("vector #2 (0.900)"));
+        assert!(summary.contains("fts -"));
+        assert!(summary.contains("exact #1"));
+        assert!(summary.contains("kind ×1.15"));
+        assert!(!summary.contains("lang"));
+
+        let json = serde_json::to_value(&explain).unwrap();
+        assert_eq!(json["vector_rank"], 2);
+        assert!(json.get("fts_rank").is_none());
+    }
+
+    // --- retrieval cap ---
+
+    #[test]
+    fn test_cap_retrieval_limit() {
+        assert_eq!(cap_retrieval_limit(200, None), 200);
+        assert_eq!(cap_retrieval_limit(200, Some(50)), 50);
+        assert_eq!(cap_retrieval_limit(30, Some(50)), 30);
+        assert_eq!(cap_retrieval_limit(200, Some(0)), 1);
+    }
+
+    #[test]
+    fn test_successful_variants_keeps_partial_results() {
+        let variants: Vec<String> = ["parse config", "parse", "config"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+
+        // The failing middle variant is dropped, the others still answer
+        let outcomes = vec![
+            Ok(vec![1, 2]),
+            Err(CodeSearchError::search("MDB_BAD_TXN")),
+            Ok(vec![3]),
+        ];
+        let kept = successful_variants(&variants, outcomes).unwrap();
+        assert_eq!(
+            kept,
+            vec![
+                ("parse config".to_string(), vec![1, 2]),
+                ("config".to_string(), vec![3]),
+            ]
+        );
+
+        // Every variant failing is still an error
+        let outcomes: Vec<CsResult<Vec<u32>>> = vec![
+            Err(CodeSearchError::search("first")),
+            Err(CodeSearchError::search("second")),
+        ];
+        let err = successful_variants(&variants, outcomes).unwrap_err();
+        assert!(err.to_string().contains("first"));
+
+        // No embeddings at all (full-text only) is not a failure
+        assert!(successful_variants::<u32>(&variants, Vec::new())
+            .unwrap()
+            .is_empty());
+    }
+
+    // --- score normalization ---
+
+    #[test]
+    fn test_normalize_scores() {
+        let scored = |score| result("src/lib.rs", 0, score);
+
+        let mut results = vec![scored(0.05), scored(0.03), scored(0.01)];
+        normalize_scores(&mut results);
+        let scores: Vec<f32> = results.iter().map(|r| r.score).collect();
+        assert_eq!(scores, vec![1.0, 0.5, 0.0]);
+
+        let mut tied = vec![scored(0.02), scored(0.02)];
+        normalize_scores(&mut tied);
+        assert!(tied.iter().all(|r| r.score == 1.0));
+
+        normalize_scores(&mut []);
+    }
+
+    #[test]
+    fn test_apply_min_score() {
+        let scored = |score| result("src/lib.rs", 0, score);
+
+        let mut results = vec![scored(1.0), scored(0.5), scored(0.2)];
+        assert_eq!(apply_min_score(&mut results, 0.5), 1);
+        assert_eq!(results.len(), 2);
+        assert_eq!(apply_min_score(&mut results, 0.0), 0);
+    }
+
+    #[test]
+    fn test_cap_file_share_backfills_from_other_files() {
+        let at = |path| result(path, 0, 0.0);
+        let paths = |results: &[crate::vectordb::SearchResult]| {
+            results.iter().map(|r| r.path.clone()).collect::<Vec<_>>()
+        };
+
+        let mut results: Vec<_> = ["a", "a", "a", "a", "b", "a", "c", "b"]
+            .into_iter()
+            .map(at)
+            .collect();
+        // At most 2 of 4 results (50%) per file
+        cap_file_share(&mut results, 4, 0.5);
+        results.truncate(4);
+        assert_eq!(paths(&results), vec!["a", "a", "b", "c"]);
+
+        // A tiny ratio still lets each file contribute one result
+        let mut results: Vec<_> = ["a", "a", "b"].into_iter().map(at).collect();
+        cap_file_share(&mut results, 10, 0.01);
+        assert_eq!(paths(&results), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_group_by_symbol_merges_split_parts() {
+        let part = |id, score, start: usize, content: &str| crate::vectordb::SearchResult {
+            id,
+            content: content.into(),
+            end_line: start + content.lines().count() - 1,
+            signature: Some("fn run()".into()),
+            context: Some("File: src/lib.rs > Function: run".into()),
+            ..result("src/lib.rs", start, score)
+        };
+        let mut other = part(3, 0.5, 40, "// [Part 1/1] fn run()\nfn other() {}");
+        other.path = "src/main.rs".into();
+        let mut gap = part(4, 0.4, 0, "use std::fs;");
+        gap.context = Some("File: src/lib.rs".into());
+        let results = vec![
+            part(1, 0.9, 12, "// [Part 2/3] fn run()\n    b();\n    c();"),
+            other,
+            part(
+                2,
+                0.7,
+                10,
+                "// [Part 1/3] fn run()\nfn run() {\n    a();\n    b();",
+            ),
+            part(5, 0.6, 20, "// [Part 3/3] fn run()\n}"),
+            gap.clone(),
+            gap,
+        ];
+
+        let grouped = group_by_symbol(results);
+        let ids: Vec<u32> = grouped.iter().map(|r| r.id).collect();
+        // The merged result keeps its best part's place; file-level chunks stay apart
+        assert_eq!(ids, vec![1, 3, 4, 4]);
+        let run = &grouped[0];
+        assert_eq!(run.score, 0.9);
+        assert_eq!((run.start_line, run.end_line), (10, 21));
+        // Overlap is dropped, and the unmatched lines 14-19 are left blank
+        let lines: Vec<&str> = run.content.lines().collect();
+        assert_eq!(
+            lines[..4],
+            ["fn run() {", "    a();", "    b();", "    c();"]
+        );
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[10], "}");
+    }
+
+    #[test]
+    fn test_is_ambiguous() {
+        let scored = |score| result("src/lib.rs", 0, score);
+
+        // Clear leader (RRF-scale scores)
+        assert!(!is_ambiguous(&[scored(0.032), scored(0.016)]));
+        // Within 10% of the top score
+        assert!(is_ambiguous(&[scored(0.032), scored(0.030), scored(0.01)]));
+        // Nothing to reorder
+        assert!(!is_ambiguous(&[scored(0.5)]));
+        assert!(!is_ambiguous(&[]));
+    }
+
     // --- SearchOptions ---
 
     #[test]
@@ -1524,7 +4571,15 @@ mod tests {
         assert!(!opts.compact);
         assert!(!opts.sync);
         assert!(!opts.json);
-        assert!(opts.filter_path.is_none());
+        assert!(!opts.jsonl);
+        assert!(opts.filter_paths.is_empty());
+        assert!(opts.exclude_paths.is_empty());
+        assert!(!opts.tests_only);
+        assert!(!opts.exclude_tests);
+        assert!(!opts.count);
+        assert!(!opts.count_uncapped);
+        assert!(!opts.fuzzy);
+        assert!(opts.highlight);
         assert!(opts.model_override.is_none());
         assert!(!opts.vector_only);
         assert!(opts.rrf_k.is_none());
@@ -0,0 +1,126 @@
+//! `codesearch search --open [N]`: open a result in `$VISUAL` / `$EDITOR`
+//!
+//! Editors disagree on how to jump to a line, so the command line is picked
+//! from the editor's program name: VS Code and forks take `-g file:LINE`,
+//! Sublime, Zed and Helix take `file:LINE`, JetBrains IDEs take
+//! `--line LINE file`, and everything else (vi, nano, emacs, micro, kak, ...)
+//! gets `+LINE file`.
+
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+use super::signature_position;
+use crate::vectordb::SearchResult;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const FALLBACK_EDITOR: &str = "vi";
+
+/// Program and arguments that open `file` at 1-based `line` in `editor`
+///
+/// `editor` is the raw `$EDITOR` value and may carry its own arguments
+/// (`code --wait`); they are kept before the file.
+pub fn editor_command(editor: &str, file: &Path, line: usize) -> Option<(String, Vec<String>)> {
+    let mut words = editor.split_whitespace();
+    let program = words.next()?.to_string();
+    let mut args: Vec<String> = words.map(str::to_string).collect();
+
+    let name = Path::new(&program)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(&program)
+        .to_lowercase();
+    let file = file.display().to_string();
+    match name.as_str() {
+        "code" | "code-insiders" | "codium" | "vscodium" | "cursor" | "windsurf" => {
+            args.push("-g".to_string());
+            args.push(format!("{}:{}", file, line));
+        }
+        "subl" | "sublime_text" | "zed" | "hx" | "helix" => {
+            args.push(format!("{}:{}", file, line));
+        }
+        "idea" | "idea64" | "pycharm" | "goland" | "clion" | "webstorm" | "rustrover"
+        | "phpstorm" | "rider" => {
+            args.push("--line".to_string());
+            args.push(line.to_string());
+            args.push(file);
+        }
+        _ => {
+            args.push(format!("+{}", line));
+            args.push(file);
+        }
+    }
+    Some((program, args))
+}
+
+/// Open `result` in the user's editor at its signature line and wait for it to exit
+///
+/// Relative result paths are resolved against `root`.
+pub fn open_result(result: &SearchResult, root: &Path) -> Result<()> {
+    let path = Path::new(&result.path);
+    let file = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    // 1-based; the signature line when it can be found, else the chunk's first line
+    let line = signature_position(
+        &result.content,
+        result.signature.as_deref(),
+        result.start_line,
+    )
+    .map_or(result.start_line, |pos| pos.line)
+        + 1;
+
+    let editor = std::env::var("VISUAL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| FALLBACK_EDITOR.to_string());
+    let (program, args) =
+        editor_command(&editor, &file, line).ok_or_else(|| anyhow!("$EDITOR is empty"))?;
+
+    let status = Command::new(&program)
+        .args(&args)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", program))?;
+    if !status.success() {
+        return Err(anyhow!("Editor '{}' exited with {}", program, status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(editor: &str) -> (String, Vec<String>) {
+        editor_command(editor, Path::new("src/main.rs"), 42).unwrap()
+    }
+
+    #[test]
+    fn test_editor_command_line_jump_syntax() {
+        assert_eq!(
+            command("nvim"),
+            ("nvim".into(), vec!["+42".into(), "src/main.rs".into()])
+        );
+        assert_eq!(
+            command("/usr/bin/emacsclient -t"),
+            (
+                "/usr/bin/emacsclient".into(),
+                vec!["-t".into(), "+42".into(), "src/main.rs".into()]
+            )
+        );
+        assert_eq!(
+            command("code --wait"),
+            (
+                "code".into(),
+                vec!["--wait".into(), "-g".into(), "src/main.rs:42".into()]
+            )
+        );
+        assert_eq!(command("hx").1, vec!["src/main.rs:42"]);
+        assert_eq!(command("idea").1, vec!["--line", "42", "src/main.rs"]);
+        assert!(editor_command("  ", Path::new("a.rs"), 1).is_none());
+    }
+}
@@ -0,0 +1,170 @@
+//! `codesearch search --profile`: per-stage timing of one search
+//!
+//! The searcher records how long each pipeline stage took (and each query
+//! variant's vector retrieval) into a [`SearchProfile`]; the CLI adds the output
+//! stage and prints the report to stderr, so it never mixes with results.
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use super::{SearchOptions, SearchTiming};
+
+/// Per-stage timing breakdown, collected only when `SearchOptions::profile` is set
+#[derive(Debug, Clone, Default)]
+pub struct SearchProfile {
+    /// Wall time of each stage, in pipeline order
+    pub stages: Vec<(&'static str, Duration)>,
+    /// Vector retrieval time of each query variant
+    ///
+    /// Variants are searched in parallel, so these overlap rather than add up.
+    /// Embedding has no per-variant entry: all variants are embedded in one batch.
+    pub variants: Vec<(String, Duration)>,
+}
+
+impl SearchProfile {
+    /// Record the time since `mark` as `stage` and restart `mark` (no-op when not profiling)
+    pub(super) fn lap(profile: &mut Option<Self>, stage: &'static str, mark: &mut Instant) {
+        if let Some(profile) = profile {
+            let now = Instant::now();
+            profile.stages.push((stage, now - *mark));
+            *mark = now;
+        }
+    }
+
+    /// Stages including the one-off cost of opening the searcher
+    fn rows(&self, timing: &SearchTiming) -> Vec<(&'static str, Duration)> {
+        let mut rows = vec![
+            ("open store", timing.load),
+            ("load model", timing.model_load),
+        ];
+        rows.extend(self.stages.iter().copied());
+        rows
+    }
+}
+
+#[derive(Serialize)]
+struct JsonProfile {
+    total_ms: f64,
+    stages: Vec<JsonStage>,
+    variants: Vec<JsonVariant>,
+}
+
+#[derive(Serialize)]
+struct JsonStage {
+    stage: &'static str,
+    ms: f64,
+}
+
+#[derive(Serialize)]
+struct JsonVariant {
+    query: String,
+    search_ms: f64,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+impl JsonProfile {
+    fn new(profile: &SearchProfile, timing: &SearchTiming) -> Self {
+        let rows = profile.rows(timing);
+        Self {
+            total_ms: millis(rows.iter().map(|(_, d)| *d).sum()),
+            stages: rows
+                .into_iter()
+                .map(|(stage, d)| JsonStage {
+                    stage,
+                    ms: millis(d),
+                })
+                .collect(),
+            variants: profile
+                .variants
+                .iter()
+                .map(|(query, d)| JsonVariant {
+                    query: query.clone(),
+                    search_ms: millis(*d),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Print the profile (plus the `output` stage) to stderr: one JSON line with
+/// `--json`/`--jsonl`, a table otherwise. No-op when the search wasn't profiled.
+pub(super) fn report_profile(
+    timing: &SearchTiming,
+    output: Duration,
+    options: &SearchOptions,
+) -> Result<()> {
+    let Some(profile) = &timing.profile else {
+        return Ok(());
+    };
+    let mut profile = profile.clone();
+    profile.stages.push(("output", output));
+
+    if options.json || options.jsonl {
+        eprintln!(
+            "{}",
+            serde_json::to_string(&JsonProfile::new(&profile, timing))?
+        );
+        return Ok(());
+    }
+
+    let rows = profile.rows(timing);
+    let total: Duration = rows.iter().map(|(_, d)| *d).sum();
+    eprintln!("\n{}", "⏱️  Profile".bright_cyan().bold());
+    for (stage, duration) in &rows {
+        eprintln!("   {:<16} {:>10.2}ms", stage, millis(*duration));
+        if *stage == "vector search" {
+            for (query, duration) in &profile.variants {
+                eprintln!(
+                    "     {:<14} {:>10.2}ms",
+                    format!("\"{}\"", query).dimmed(),
+                    millis(*duration)
+                );
+            }
+        }
+    }
+    eprintln!("   {:<16} {:>10.2}ms", "total".bold(), millis(total));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lap_records_only_when_profiling() {
+        let mut mark = Instant::now();
+        let mut off: Option<SearchProfile> = None;
+        SearchProfile::lap(&mut off, "embed", &mut mark);
+        assert!(off.is_none());
+
+        let mut on = Some(SearchProfile::default());
+        SearchProfile::lap(&mut on, "embed", &mut mark);
+        SearchProfile::lap(&mut on, "fusion", &mut mark);
+        let stages: Vec<_> = on.unwrap().stages.iter().map(|(s, _)| *s).collect();
+        assert_eq!(stages, vec!["embed", "fusion"]);
+    }
+
+    #[test]
+    fn test_json_profile_includes_open_cost() {
+        let profile = SearchProfile {
+            stages: vec![("embed", Duration::from_millis(3))],
+            variants: vec![("parse config".to_string(), Duration::from_millis(2))],
+        };
+        let timing = SearchTiming {
+            load: Duration::from_millis(1),
+            model_load: Duration::from_millis(2),
+            ..Default::default()
+        };
+
+        let json = JsonProfile::new(&profile, &timing);
+        assert_eq!(json.total_ms, 6.0);
+        let stages: Vec<_> = json.stages.iter().map(|s| s.stage).collect();
+        assert_eq!(stages, vec!["open store", "load model", "embed"]);
+        assert_eq!(json.variants[0].search_ms, 2.0);
+    }
+}
@@ -0,0 +1,202 @@
+//! Field filters written into the query text (`kind:function name:parse`)
+//!
+//! `kind:`, `path:`, `lang:`, `sig:` and `name:` tokens are pulled out of the
+//! query before embedding and become result filters; everything else, including
+//! `key:value` tokens with an unknown key or value, stays in the free text.
+
+use anyhow::{anyhow, Result};
+
+use super::SearchOptions;
+use crate::chunker::ChunkKind;
+use crate::file::Language;
+
+/// A query split into free text and field filters
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryFilters {
+    /// Free text left for semantic and full-text search
+    pub text: String,
+    /// `kind:` values, as stored chunk kind names (`Function`)
+    pub kinds: Vec<String>,
+    /// `path:` prefixes
+    pub paths: Vec<String>,
+    /// `lang:` values, as stored language names (`Rust`)
+    pub languages: Vec<String>,
+    /// `sig:` substrings the signature must contain
+    pub signatures: Vec<String>,
+    /// `name:` symbols the signature must declare (also kept in `text`)
+    pub names: Vec<String>,
+}
+
+impl QueryFilters {
+    /// Add the filters to `options`, next to any given on the command line
+    ///
+    /// `path:` prefixes narrow `--filter-path` instead of adding to it: results
+    /// must match both. Fails if no path can satisfy both.
+    pub fn apply(&self, options: &mut SearchOptions) -> Result<()> {
+        if !self.paths.is_empty() {
+            options.filter_paths = intersect_path_prefixes(&options.filter_paths, &self.paths);
+            if options.filter_paths.is_empty() {
+                return Err(anyhow!(
+                    "`path:` filters in the query don't overlap --filter-path"
+                ));
+            }
+        }
+        options.filter_kinds.extend(self.kinds.iter().cloned());
+        options
+            .filter_languages
+            .extend(self.languages.iter().cloned());
+        options
+            .filter_signatures
+            .extend(self.signatures.iter().cloned());
+        options.filter_names.extend(self.names.iter().cloned());
+        Ok(())
+    }
+}
+
+/// Prefixes matching paths that start with one of `given` and one of `inline`
+///
+/// For each overlapping pair the longer prefix is kept; no `given` means no
+/// restriction.
+fn intersect_path_prefixes(given: &[String], inline: &[String]) -> Vec<String> {
+    if given.is_empty() {
+        return inline.to_vec();
+    }
+    let mut prefixes: Vec<String> = Vec::new();
+    for a in given {
+        for b in inline {
+            let (a_norm, b_norm) = (a.trim_start_matches("./"), b.trim_start_matches("./"));
+            let narrower = if b_norm.starts_with(a_norm) {
+                b
+            } else if a_norm.starts_with(b_norm) {
+                a
+            } else {
+                continue;
+            };
+            if !prefixes.contains(narrower) {
+                prefixes.push(narrower.clone());
+            }
+        }
+    }
+    prefixes
+}
+
+/// Split `kind:`, `path:`, `lang:`, `sig:` and `name:` tokens out of `query`
+pub fn parse_query_filters(query: &str) -> QueryFilters {
+    let mut filters = QueryFilters::default();
+    let mut text = Vec::new();
+
+    for token in query.split_whitespace() {
+        let Some((key, value)) = token.split_once(':').filter(|(_, v)| !v.is_empty()) else {
+            text.push(token);
+            continue;
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "kind" => match ChunkKind::parse(value) {
+                Some(kind) => filters.kinds.push(format!("{:?}", kind)),
+                None => text.push(token),
+            },
+            "lang" | "language" => match Language::from_name(value) {
+                Some(lang) => filters.languages.push(format!("{:?}", lang)),
+                None => text.push(token),
+            },
+            "path" => filters.paths.push(value.to_string()),
+            "sig" | "signature" => filters.signatures.push(value.to_string()),
+            "name" => {
+                filters.names.push(value.to_string());
+                text.push(value);
+            }
+            _ => text.push(token),
+        }
+    }
+
+    filters.text = text.join(" ");
+    filters
+}
+
+/// Split the field filters out of `query` and add them to a copy of `options`
+///
+/// Returns the free text to search for; a query of only filters is an error.
+pub fn with_query_filters(query: &str, options: &SearchOptions) -> Result<(String, SearchOptions)> {
+    let filters = parse_query_filters(query);
+    if filters.text.is_empty() {
+        return Err(anyhow!(
+            "Query has only field filters; add some text to search for"
+        ));
+    }
+    let mut options = options.clone();
+    filters.apply(&mut options)?;
+    Ok((filters.text, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_filters() {
+        let f =
+            parse_query_filters("kind:function name:parse path:src/ lang:rust sig:Result tokens");
+        assert_eq!(f.text, "parse tokens");
+        assert_eq!(f.kinds, vec!["Function"]);
+        assert_eq!(f.names, vec!["parse"]);
+        assert_eq!(f.paths, vec!["src/"]);
+        assert_eq!(f.languages, vec!["Rust"]);
+        assert_eq!(f.signatures, vec!["Result"]);
+
+        // Case-insensitive keys and kind/language names
+        let f = parse_query_filters("Kind:STRUCT Lang:C++ config");
+        assert_eq!(f.kinds, vec!["Struct"]);
+        assert_eq!(f.languages, vec!["Cpp"]);
+        assert_eq!(f.text, "config");
+    }
+
+    #[test]
+    fn test_parse_query_filters_falls_through() {
+        let query = "http://localhost:8080 kind:lambda lang:cobol std::fs path: retry";
+        assert_eq!(
+            parse_query_filters(query),
+            QueryFilters {
+                text: query.to_string(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_filters_apply() {
+        let mut options = SearchOptions::default();
+        parse_query_filters("path:src/ kind:method errors")
+            .apply(&mut options)
+            .unwrap();
+        assert_eq!(options.filter_paths, vec!["src/"]);
+        assert_eq!(options.filter_kinds, vec!["Method"]);
+        assert!(options.filter_languages.is_empty());
+
+        let (text, options) =
+            with_query_filters("sig:Result name:load", &SearchOptions::default()).unwrap();
+        assert_eq!(text, "load");
+        assert_eq!(options.filter_signatures, vec!["Result"]);
+        assert!(with_query_filters("kind:function path:src/", &options).is_err());
+    }
+
+    #[test]
+    fn test_query_path_filters_narrow_filter_path() {
+        let given = |paths: &[&str]| SearchOptions {
+            filter_paths: paths.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        };
+
+        // Inside --filter-path: the inline prefix is the narrower one
+        let (_, options) =
+            with_query_filters("path:src/search/ errors", &given(&["src/"])).unwrap();
+        assert_eq!(options.filter_paths, vec!["src/search/"]);
+
+        // Wider than --filter-path: the command-line prefix still applies
+        let (_, options) =
+            with_query_filters("path:./src/ errors", &given(&["src/index/", "lib/"])).unwrap();
+        assert_eq!(options.filter_paths, vec!["src/index/"]);
+
+        // Disjoint prefixes can match nothing
+        assert!(with_query_filters("path:lib/ errors", &given(&["src/"])).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! `codesearch similar <file:line>`: find code that looks like a given location
+//!
+//! The chunk enclosing the location is found through the vector store's path
+//! index, and its stored embedding is used as the query vector, so no text query
+//! (or model load) is needed.
+
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use super::{get_db_path, print_result, read_metadata, JsonOutput, JsonResult};
+use crate::vectordb::{ChunkMetadata, VectorStore};
+
+/// Print the chunks most similar to the chunk enclosing `location` (`path:line`)
+pub async fn similar(
+    location: &str,
+    path: Option<PathBuf>,
+    max_results: usize,
+    content: bool,
+    json: bool,
+) -> Result<()> {
+    let (file, line) = parse_location(location)?;
+    let file = file
+        .canonicalize()
+        .with_context(|| format!("Cannot read {}", file.display()))?;
+
+    let (db_path, _project_path) = get_db_path(path)?;
+    let (_, dimensions, _) = read_metadata(&db_path).ok_or_else(|| {
+        anyhow!(
+            "No usable index at {}. Run `codesearch index` first.",
+            db_path.display()
+        )
+    })?;
+    let store = VectorStore::new(&db_path, dimensions)?;
+
+    let file_str = file.to_string_lossy().to_string();
+    let mut chunks = Vec::new();
+    for id in store.chunks_for_path(&file_str)? {
+        if let Some(meta) = store.get_chunk(id)? {
+            chunks.push((id, meta));
+        }
+    }
+    if chunks.is_empty() {
+        return Err(anyhow!(
+            "{} is not indexed. Run `codesearch index` to add it.",
+            file.display()
+        ));
+    }
+
+    let (source_id, source) = enclosing_chunk(&chunks, line).ok_or_else(|| {
+        anyhow!(
+            "No indexed chunk covers line {} of {}",
+            line,
+            file.display()
+        )
+    })?;
+    let vector = store.get_vector(source_id)?.ok_or_else(|| {
+        anyhow!("No embedding stored for that chunk. Run `codesearch index` to rebuild the index.")
+    })?;
+
+    // One extra result: the source chunk is its own nearest neighbour
+    let results: Vec<_> = store
+        .search(&vector, max_results + 1)?
+        .into_iter()
+        .filter(|r| r.id != source_id)
+        .take(max_results)
+        .collect();
+
+    if json {
+        let output = JsonOutput {
+            query: location.to_string(),
+            results: results.iter().map(JsonResult::from_result).collect(),
+            timing: None,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} {}:{}-{} • {}",
+        "🔎 Similar to".bright_cyan().bold(),
+        source.path,
+        source.start_line + 1,
+        source.end_line,
+        source.signature.as_deref().unwrap_or(&source.kind)
+    );
+    if results.is_empty() {
+        println!("{}", "No similar code found.".yellow());
+        return Ok(());
+    }
+    for result in &results {
+        print_result(result, true, content, true, &[], None, None)?;
+    }
+    Ok(())
+}
+
+/// Split `path:line` into the path and its 1-based line number
+fn parse_location(location: &str) -> Result<(PathBuf, usize)> {
+    let (path, line) = location
+        .rsplit_once(':')
+        .ok_or_else(|| anyhow!("Expected <file>:<line>, got '{}'", location))?;
+    let line: usize = line
+        .parse()
+        .ok()
+        .filter(|&l| l > 0)
+        .ok_or_else(|| anyhow!("Invalid line number '{}' in '{}'", line, location))?;
+    if path.is_empty() {
+        return Err(anyhow!("Expected <file>:<line>, got '{}'", location));
+    }
+    Ok((Path::new(path).to_path_buf(), line))
+}
+
+/// The smallest chunk containing 1-based `line` (chunks may nest, e.g. a method in an impl)
+fn enclosing_chunk(chunks: &[(u32, ChunkMetadata)], line: usize) -> Option<(u32, &ChunkMetadata)> {
+    // Chunk line ranges are 0-based with an exclusive end
+    let line = line - 1;
+    chunks
+        .iter()
+        .filter(|(_, c)| c.start_line <= line && line < c.end_line.max(c.start_line + 1))
+        .min_by_key(|(_, c)| c.end_line - c.start_line)
+        .map(|(id, c)| (*id, c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(start_line: usize, end_line: usize) -> ChunkMetadata {
+        ChunkMetadata {
+            content: String::new(),
+            path: "a.rs".to_string(),
+            start_line,
+            end_line,
+            kind: "Function".to_string(),
+            signature: None,
+            docstring: None,
+            context: None,
+            hash: String::new(),
+            context_prev: None,
+            context_next: None,
+            searchable_text: String::new(),
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_location() {
+        let (path, line) = parse_location("src/main.rs:42").unwrap();
+        assert_eq!(path, PathBuf::from("src/main.rs"));
+        assert_eq!(line, 42);
+
+        assert!(parse_location("src/main.rs").is_err());
+        assert!(parse_location("src/main.rs:0").is_err());
+        assert!(parse_location(":3").is_err());
+    }
+
+    #[test]
+    fn test_enclosing_chunk_prefers_innermost() {
+        // An impl block spanning lines 1-20 with a method on lines 5-8 (1-based)
+        let chunks = vec![(1, meta(0, 20)), (2, meta(4, 8))];
+
+        assert_eq!(enclosing_chunk(&chunks, 6).map(|(id, _)| id), Some(2));
+        assert_eq!(enclosing_chunk(&chunks, 2).map(|(id, _)| id), Some(1));
+        assert_eq!(enclosing_chunk(&chunks, 21).map(|(id, _)| id), None);
+    }
+}
@@ -0,0 +1,101 @@
+//! `codesearch search <query> --watch`: re-run a query whenever the code changes
+//!
+//! The searcher (vector store, FTS index and embedding model) is opened once.
+//! Each burst of file changes is synced into the index with that same model,
+//! then the screen is cleared and the results are printed again. Bursts that
+//! only touch files the index doesn't track (ignored paths, editor swap files)
+//! leave the results as they are.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    prepare_search, print_degraded_banner, print_results, profile, with_query_filters,
+    SearchOptions, Searcher,
+};
+use crate::file::ProjectConfig;
+use crate::watch::{FileWatcher, WatchSettings};
+
+/// How often the wait for file changes checks for Ctrl-C
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Search for `query`, then search again after every change to the indexed files
+///
+/// Runs until `cancel_token` fires (Ctrl-C). With `--json`/`--jsonl` each run
+/// is appended instead of clearing the screen, so the output can be piped.
+pub async fn search_watch(
+    query: &str,
+    path: Option<PathBuf>,
+    options: SearchOptions,
+    cancel_token: CancellationToken,
+) -> Result<()> {
+    let (query, options) = with_query_filters(query, &options)?;
+    let query = query.as_str();
+
+    let Some((db_path, project_path)) = prepare_search(path, &options)? else {
+        return Ok(());
+    };
+    let searcher = Searcher::open_for(&db_path, &options)?;
+
+    let config = ProjectConfig::load(&project_path).ok().flatten();
+    let settings = WatchSettings::resolve(config.as_ref().map(|c| &c.watch));
+    let mut watcher = FileWatcher::new(project_path.clone());
+    watcher.start(settings.debounce_ms)?;
+
+    let redraw = !(options.json || options.jsonl) && std::io::stdout().is_terminal();
+    loop {
+        if redraw {
+            // Clear the screen and move the cursor home
+            print!("\x1B[2J\x1B[1;1H");
+        }
+        print_degraded_banner(&searcher);
+        // A failed run is reported and retried after the next change
+        match searcher.search_explained(query, &options) {
+            Ok((results, timing, explain)) => {
+                let start = Instant::now();
+                print_results(query, results, &timing, &explain, &project_path, &options)?;
+                profile::report_profile(&timing, start.elapsed(), &options)?;
+            }
+            Err(e) => eprintln!("{}", format!("⚠️  Search failed: {:#}", e).yellow()),
+        }
+        if !(options.json || options.jsonl) {
+            println!(
+                "\n{}",
+                format!(
+                    "👀 Watching {} for changes, last run {} (Ctrl-C to stop)",
+                    project_path.display(),
+                    chrono::Local::now().format("%H:%M:%S")
+                )
+                .dimmed()
+            );
+        }
+        std::io::stdout().flush()?;
+
+        // Wait for a burst of changes that actually alters the index
+        loop {
+            if crate::constants::check_shutdown(&cancel_token) {
+                watcher.stop();
+                return Ok(());
+            }
+            let events =
+                watcher.wait_for_burst(CANCEL_POLL_INTERVAL, settings.settle(), settings.max_batch);
+            if events.is_empty() {
+                continue;
+            }
+            match searcher.sync_changes() {
+                Ok(0) => continue,
+                Ok(_) => break,
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        format!("⚠️  Could not sync changes: {:#}", e).yellow()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Shared bearer-token authentication for the `serve` and daemon HTTP servers
+//!
+//! Off by default, for local use. When a token is configured
+//! (`CODESEARCH_API_TOKEN`, or `api_token` in the daemon config) every route
+//! requires `Authorization: Bearer <token>` and answers `401 Unauthorized`
+//! otherwise.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use std::sync::Arc;
+
+/// Environment variable holding the shared API token
+pub const API_TOKEN_ENV: &str = "CODESEARCH_API_TOKEN";
+
+/// The token from `CODESEARCH_API_TOKEN`, if set and not blank
+pub fn api_token_from_env() -> Option<String> {
+    std::env::var(API_TOKEN_ENV)
+        .ok()
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Require `token` as a bearer token on every route of `router`; `None` leaves it open
+pub fn require_token(router: Router, token: Option<String>) -> Router {
+    match token {
+        Some(token) => router.layer(middleware::from_fn_with_state(
+            Arc::<str>::from(token),
+            check_token,
+        )),
+        None => router,
+    }
+}
+
+async fn check_token(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    let header = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if is_authorized(header, &token) {
+        return next.run(request).await;
+    }
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        "Missing or invalid bearer token",
+    )
+        .into_response()
+}
+
+/// Whether an `Authorization` header value carries `token`
+fn is_authorized(header: Option<&str>, token: &str) -> bool {
+    let Some(presented) = header.and_then(|h| {
+        let (scheme, credentials) = h.trim().split_once(' ')?;
+        scheme
+            .eq_ignore_ascii_case("bearer")
+            .then(|| credentials.trim())
+    }) else {
+        return false;
+    };
+    constant_time_eq(presented.as_bytes(), token.as_bytes())
+}
+
+/// Compare without returning early, so response timing doesn't leak the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get};
+    use tower::ServiceExt;
+
+    fn get_root(authorization: Option<&str>) -> Request {
+        let mut builder = axum::http::Request::builder().uri("/");
+        if let Some(value) = authorization {
+            builder = builder.header(header::AUTHORIZATION, value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_require_token_rejects_requests_without_it() {
+        let app = require_token(
+            Router::new().route("/", get(|| async { "ok" })),
+            Some("s3cret".to_string()),
+        );
+
+        let response = app.clone().oneshot(get_root(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app
+            .clone()
+            .oneshot(get_root(Some("Bearer wrong")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.oneshot(get_root(Some("Bearer s3cret"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        assert!(is_authorized(Some("Bearer s3cret"), "s3cret"));
+        assert!(is_authorized(Some("bearer  s3cret "), "s3cret"));
+        assert!(!is_authorized(Some("Bearer wrong"), "s3cret"));
+        assert!(!is_authorized(Some("Bearer s3cre"), "s3cret"));
+        assert!(!is_authorized(Some("Basic s3cret"), "s3cret"));
+        assert!(!is_authorized(Some("s3cret"), "s3cret"));
+        assert!(!is_authorized(None, "s3cret"));
+    }
+}
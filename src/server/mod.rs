@@ -1,3 +1,5 @@
+pub mod auth;
+
 use anyhow::Result;
 use axum::{
     extract::{Json, State},
@@ -9,18 +11,22 @@ use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 use crate::cache::FileMetaStore;
 use crate::chunker::SemanticChunker;
 use crate::db_discovery::find_best_database;
-use crate::embed::{EmbeddingService, ModelType};
-use crate::file::FileWalker;
-use crate::output::set_quiet;
+use crate::embed::EmbeddingService;
+use crate::file::{FileWalker, ProjectConfig};
+use crate::logger::{QueryLog, QueryLogEntry};
+use crate::output::{set_quiet, truncate_with_ellipsis};
+use crate::search::IndexMetadata;
 use crate::vectordb::VectorStore;
-use crate::watch::{FileEvent, FileWatcher};
+use crate::watch::{FileEvent, FileWatcher, WatchSettings};
 
 /// Shared server state
 struct ServerState {
@@ -30,6 +36,12 @@ struct ServerState {
     file_meta: RwLock<FileMetaStore>,
     root: PathBuf,
     db_path: PathBuf,
+    /// False until the initial index of an empty database finishes; searches get 503 until then
+    ready: AtomicBool,
+    /// Set when `CODESEARCH_QUERY_LOG` is enabled
+    query_log: Option<QueryLog>,
+    /// Serving with `--read-only`: the store is opened read-only and never watched or written
+    read_only: bool,
 }
 
 /// Search request body
@@ -46,12 +58,35 @@ fn default_limit() -> usize {
     25
 }
 
+/// Batch search request body
+#[derive(Debug, Deserialize)]
+struct BatchSearchRequest {
+    queries: Vec<String>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+    #[serde(default)]
+    path: Option<String>,
+}
+
 /// Search response
 #[derive(Debug, Serialize)]
 struct SearchResponse {
     results: Vec<SearchResult>,
     query: String,
     took_ms: u64,
+    /// Set when the requested `limit` was clamped to `CODESEARCH_MAX_LIMIT`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+/// Batch search response (`took_ms` of each entry excludes the shared embedding step)
+#[derive(Debug, Serialize)]
+struct BatchSearchResponse {
+    results: Vec<SearchResponse>,
+    total_ms: u64,
+    /// Set when the requested `limit` was clamped to `CODESEARCH_MAX_LIMIT`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -62,6 +97,8 @@ struct SearchResult {
     end_line: usize,
     kind: String,
     score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
 }
 
 /// Health check response
@@ -81,6 +118,7 @@ struct StatusResponse {
     indexed: bool,
     model: String,
     dimensions: usize,
+    read_only: bool,
 }
 
 /// Run the background server with live file watching
@@ -90,7 +128,10 @@ struct StatusResponse {
 /// 2. Built-in file watching with native notify crate
 /// 3. Two-level change detection (mtime + hash)
 /// 4. Tracks chunk IDs for efficient incremental updates
-pub async fn serve(port: u16, path: Option<PathBuf>) -> Result<()> {
+///
+/// With `read_only`, the index is served as-is: no refresh, no writer lock and
+/// no file watcher, so several servers can share one index.
+pub async fn serve(port: u16, path: Option<PathBuf>, read_only: bool) -> Result<()> {
     // Find the best database to use
     let db_info = find_best_database(path.as_deref())?;
 
@@ -110,6 +151,12 @@ pub async fn serve(port: u16, path: Option<PathBuf>) -> Result<()> {
     println!("📂 Root: {}", root.display());
     println!("💾 Database: {}", db_path.display());
     println!("🌐 Port: {}", port);
+    if read_only {
+        println!("🔒 Read-only: no index refresh or file watching");
+    }
+    if auth::api_token_from_env().is_some() {
+        println!("🔑 Auth: bearer token required ({})", auth::API_TOKEN_ENV);
+    }
 
     if db_info.is_global {
         println!("   {}", "(Global index)".dimmed());
@@ -117,96 +164,193 @@ pub async fn serve(port: u16, path: Option<PathBuf>) -> Result<()> {
         println!("   {}", "(Parent directory index)".dimmed());
     }
 
+    // Use the model the index was built with (errors on dimension mismatch)
+    let metadata = IndexMetadata::load_or_default(&db_path)?;
+    let model_type = metadata.model()?.unwrap_or_default();
+    println!(
+        "🧠 Model: {} ({} dims)",
+        model_type.name(),
+        model_type.dimensions()
+    );
+
     // STEP 1: Perform incremental index refresh
-    println!("\n🔍 Performing incremental index refresh...");
-    crate::index::index_quiet(
-        Some(root.clone()),
-        false,
-        tokio_util::sync::CancellationToken::new(),
-    )
-    .await?;
-    println!("✅ Index refresh completed");
+    if !read_only {
+        println!("\n🔍 Performing incremental index refresh...");
+        crate::index::index_quiet(
+            Some(root.clone()),
+            false,
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await?;
+        println!("✅ Index refresh completed");
+    }
 
     // Initialize embedding service
-    let model_type = ModelType::default();
     println!("\n🔄 Loading embedding model...");
-    let cache_dir = crate::constants::get_global_models_cache_dir()?;
-    let embedding_service = EmbeddingService::with_cache_dir(model_type, Some(&cache_dir))?;
+    let mut embedding_service = metadata.embedding_service(&db_path, model_type)?;
     let dimensions = embedding_service.dimensions();
 
     // Load or create file metadata store
     let file_meta = FileMetaStore::load_or_create(&db_path, model_type.short_name(), dimensions)?;
 
     // Open or create vector store
-    let store = VectorStore::new(&db_path, dimensions)?;
+    let store = if read_only {
+        VectorStore::open_readonly(&db_path, dimensions)?
+    } else {
+        VectorStore::new(&db_path, dimensions)?
+    };
     let stats = store.stats()?;
 
-    // If database is empty, do initial index
-    if stats.total_chunks == 0 {
+    // An empty database is indexed in the background; the server reports `starting` until then
+    let empty = stats.total_chunks == 0;
+    if empty && read_only {
+        return Err(anyhow::anyhow!(
+            "Database at {} is empty; run 'codesearch index' before serving it read-only",
+            db_path.display()
+        ));
+    }
+    if empty {
         println!(
             "\n{}",
-            "📦 Database empty, performing initial index...".yellow()
+            "📦 Database empty, performing initial index in the background...".yellow()
         );
-        let (store, file_meta) = initial_index(root.clone(), db_path.clone(), model_type).await?;
-
-        let state = Arc::new(ServerState {
-            store: RwLock::new(store),
-            embedding_service: Mutex::new(EmbeddingService::with_cache_dir(
-                model_type,
-                Some(&crate::constants::get_global_models_cache_dir()?),
-            )?),
-            chunker: Mutex::new(SemanticChunker::new(100, 2000, 10)),
-            file_meta: RwLock::new(file_meta),
-            root: root.clone(),
-            db_path: db_path.clone(),
-        });
-
-        // STEP 2: Start background file watcher
-        start_server(state, port, root).await
     } else {
         println!(
             "✅ Database loaded: {} chunks from {} files",
             stats.total_chunks, stats.total_files
         );
+    }
 
-        let state = Arc::new(ServerState {
-            store: RwLock::new(store),
-            embedding_service: Mutex::new(embedding_service),
-            chunker: Mutex::new(SemanticChunker::new(100, 2000, 10)),
-            file_meta: RwLock::new(file_meta),
-            root: root.clone(),
-            db_path,
-        });
-
-        // STEP 2: Start background file watcher
-        start_server(state, port, root).await
+    // Initialize the inference session now rather than inside the first request
+    match embedding_service.warm_up() {
+        Ok(took) => println!("🔥 Model warmed up in {:?}", took),
+        Err(e) => eprintln!("{}", format!("⚠️  Model warm-up failed: {}", e).yellow()),
     }
+
+    let state = Arc::new(ServerState {
+        store: RwLock::new(store),
+        embedding_service: Mutex::new(embedding_service),
+        chunker: Mutex::new(index_chunker(&root, &metadata)),
+        file_meta: RwLock::new(file_meta),
+        root: root.clone(),
+        query_log: QueryLog::from_env(&db_path),
+        db_path,
+        ready: AtomicBool::new(!empty),
+        read_only,
+    });
+
+    // STEP 2: Start background file watcher
+    start_server(state, port, root).await
 }
 
-async fn initial_index(
-    root: PathBuf,
-    db_path: PathBuf,
-    model_type: ModelType,
-) -> Result<(VectorStore, FileMetaStore)> {
-    // Clear existing database if any
-    if db_path.exists() {
-        std::fs::remove_dir_all(&db_path)?;
+/// Keep the index fresh in the foreground: watch files and re-index changes,
+/// without starting the HTTP server. Stops cleanly on Ctrl-C.
+pub async fn watch(path: Option<PathBuf>, cancel_token: CancellationToken) -> Result<()> {
+    let Some(db_info) = find_best_database(path.as_deref())? else {
+        return Err(anyhow::anyhow!(
+            "No database found in current directory, parent directories, or globally tracked repositories. \
+             Run 'codesearch index' first to index the codebase."
+        ));
+    };
+    let db_path = db_info.db_path;
+    let root = db_info.project_path;
+
+    println!("{}", "👀 Codesearch Watch".bright_cyan().bold());
+    println!("{}", "=".repeat(60));
+    println!("📂 Root: {}", root.display());
+    println!("💾 Database: {}", db_path.display());
+
+    let metadata = IndexMetadata::load_or_default(&db_path)?;
+    let model_type = metadata.model()?.unwrap_or_default();
+    println!(
+        "🧠 Model: {} ({} dims)",
+        model_type.name(),
+        model_type.dimensions()
+    );
+
+    // Catch up on changes made while nothing was watching
+    println!("\n🔍 Performing incremental index refresh...");
+    crate::index::index_quiet(Some(root.clone()), false, cancel_token.clone()).await?;
+    if crate::constants::check_shutdown(&cancel_token) {
+        return Ok(());
     }
+    println!("✅ Index refresh completed");
+
+    println!("\n🔄 Loading embedding model...");
+    let embedding_service = metadata.embedding_service(&db_path, model_type)?;
+    let dimensions = embedding_service.dimensions();
+
+    let file_meta = FileMetaStore::load_or_create(&db_path, model_type.short_name(), dimensions)?;
+    let store = VectorStore::new(&db_path, dimensions)?;
+
+    let state = ServerState {
+        store: RwLock::new(store),
+        embedding_service: Mutex::new(embedding_service),
+        chunker: Mutex::new(index_chunker(&root, &metadata)),
+        file_meta: RwLock::new(file_meta),
+        root: root.clone(),
+        db_path,
+        ready: AtomicBool::new(true),
+        query_log: None,
+        read_only: false,
+    };
+
+    let settings = watch_settings(&root);
+    let mut watcher = FileWatcher::new(root).with_include_globs(metadata.include())?;
+    watcher.start(settings.debounce_ms)?;
+    println!(
+        "\n{}",
+        "👀 Watching for file changes... (Ctrl-C to stop)".dimmed()
+    );
+
+    while !crate::constants::check_shutdown(&cancel_token) {
+        let events = watcher.wait_for_burst(
+            Duration::from_secs(1),
+            settings.settle(),
+            settings.max_batch,
+        );
+        if events.is_empty() {
+            continue;
+        }
+        apply_file_events(&state, events, &settings).await?;
+    }
+
+    watcher.stop();
+    state.file_meta.read().await.save(&state.db_path)?;
+    println!("\n{}", "✅ Watch stopped, index saved".green());
 
+    Ok(())
+}
+
+/// Index every file into the (empty) store of a server that isn't ready yet
+///
+/// Chunking and embedding run without holding the store lock, so `/health` and
+/// `/status` stay responsive while this runs.
+async fn initial_index(state: &ServerState) -> Result<()> {
     // File discovery
-    let walker = FileWalker::new(root.clone());
+    let metadata = IndexMetadata::load_or_default(&state.db_path)?;
+    let mut walker = FileWalker::new(state.root.clone()).with_include_globs(metadata.include());
+    let project_config = ProjectConfig::load(&state.root).ok().flatten();
+    if let Some(config) = &project_config {
+        walker = walker.with_config(config);
+    }
     let (files, _stats) = walker.walk()?;
     println!("  Found {} files", files.len());
 
     if files.is_empty() {
-        let store = VectorStore::new(&db_path, model_type.dimensions())?;
-        let file_meta =
-            FileMetaStore::new(model_type.short_name().to_string(), model_type.dimensions());
-        return Ok((store, file_meta));
+        return Ok(());
     }
 
     // Chunking
-    let mut chunker = SemanticChunker::new(100, 2000, 10);
+    let mut chunker = SemanticChunker::new(100, 2000, 10)
+        .with_context_lines(metadata.context_lines())
+        .with_strip_comments(metadata.strip_comments());
+    if let Some(limits) = project_config
+        .as_ref()
+        .and_then(|c| c.language_chunk_limits(chunker.default_limits()).ok())
+    {
+        chunker = chunker.with_language_limits(limits);
+    }
     let mut all_chunks = Vec::new();
     let mut file_chunks: HashMap<String, Vec<crate::chunker::Chunk>> = HashMap::new();
 
@@ -223,19 +367,22 @@ async fn initial_index(
     println!("  Created {} chunks", all_chunks.len());
 
     // Embedding
-    let cache_dir = crate::constants::get_global_models_cache_dir()?;
-    let mut embedding_service = EmbeddingService::with_cache_dir(model_type, Some(&cache_dir))?;
-    let embedded_chunks = embedding_service.embed_chunks(all_chunks)?;
+    let embedded_chunks = state
+        .embedding_service
+        .lock()
+        .map_err(|e| anyhow::anyhow!("Embedding service mutex poisoned: {}", e))?
+        .embed_chunks(all_chunks)?;
     println!("  Generated {} embeddings", embedded_chunks.len());
 
     // Storage
-    let mut store = VectorStore::new(&db_path, model_type.dimensions())?;
+    let mut store = state.store.write().await;
     let chunk_ids = store.insert_chunks_with_ids(embedded_chunks)?;
     store.build_index()?;
+    drop(store);
 
     // Build file metadata
-    let mut file_meta =
-        FileMetaStore::new(model_type.short_name().to_string(), model_type.dimensions());
+    let mut file_meta = state.file_meta.write().await;
+    *file_meta = FileMetaStore::new(file_meta.model_name.clone(), file_meta.dimensions);
 
     let mut chunk_id_iter = chunk_ids.iter();
     for file in &files {
@@ -246,18 +393,37 @@ async fn initial_index(
         }
     }
     file_meta.mark_full_index();
-    file_meta.save(&db_path)?;
+    file_meta.save(&state.db_path)?;
 
     println!("  ✅ Initial index complete");
 
-    Ok((store, file_meta))
+    Ok(())
 }
 
 async fn start_server(state: Arc<ServerState>, port: u16, root: PathBuf) -> Result<()> {
-    // Start file watcher in background
+    // Finish the initial index (if any), then start the file watcher, in the background
+    let read_only = state.read_only;
     let watcher_state = state.clone();
     let watcher_root = root.clone();
     tokio::spawn(async move {
+        if watcher_state.read_only {
+            return;
+        }
+        if !watcher_state.ready.load(Ordering::SeqCst) {
+            // Serve what is indexed either way; the watcher picks up changes from here
+            if let Err(e) = initial_index(&watcher_state).await {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "⚠️  Initial index failed: {}. Serving the existing index; re-run `codesearch index` to complete it.",
+                        e
+                    )
+                    .yellow()
+                );
+            }
+            watcher_state.ready.store(true, Ordering::SeqCst);
+            println!("{}", "✅ Server ready, accepting searches".green());
+        }
         if let Err(e) = run_file_watcher(watcher_state, watcher_root).await {
             eprintln!("File watcher error: {}", e);
         }
@@ -268,13 +434,18 @@ async fn start_server(state: Arc<ServerState>, port: u16, root: PathBuf) -> Resu
         .route("/health", get(health_handler))
         .route("/status", get(status_handler))
         .route("/search", post(search_handler))
+        .route("/search/batch", post(batch_search_handler))
         .with_state(state);
+    let app = auth::require_token(app, auth::api_token_from_env());
 
     let addr = format!("127.0.0.1:{}", port);
     println!("\n{}", "🌐 Server ready!".bright_green().bold());
     println!("  Health: http://{}/health", addr);
     println!("  Search: POST http://{}/search", addr);
-    println!("\n{}", "👀 Watching for file changes...".dimmed());
+    println!("  Batch:  POST http://{}/search/batch", addr);
+    if !read_only {
+        println!("\n{}", "👀 Watching for file changes...".dimmed());
+    }
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     axum::serve(listener, app).await?;
@@ -282,55 +453,126 @@ async fn start_server(state: Arc<ServerState>, port: u16, root: PathBuf) -> Resu
     Ok(())
 }
 
+/// A chunker matching the index's settings and the project's `[chunk_limits]`,
+/// so re-chunked files split like the rest of the index
+fn index_chunker(root: &Path, metadata: &IndexMetadata) -> SemanticChunker {
+    let chunker = SemanticChunker::new(100, 2000, 10)
+        .with_context_lines(metadata.context_lines())
+        .with_strip_comments(metadata.strip_comments());
+    match ProjectConfig::load(root)
+        .ok()
+        .flatten()
+        .and_then(|c| c.language_chunk_limits(chunker.default_limits()).ok())
+    {
+        Some(limits) => chunker.with_language_limits(limits),
+        None => chunker,
+    }
+}
+
+/// Watcher settings for a project root (`[watch]` in `.codesearch.toml`, then env)
+fn watch_settings(root: &Path) -> WatchSettings {
+    let config = ProjectConfig::load(root).ok().flatten();
+    let settings = WatchSettings::resolve(config.as_ref().map(|c| &c.watch));
+    tracing::info!(
+        "File watcher: {}ms debounce, batches of up to {} events",
+        settings.debounce_ms,
+        settings.max_batch
+    );
+    settings
+}
+
 async fn run_file_watcher(state: Arc<ServerState>, root: PathBuf) -> Result<()> {
-    let mut watcher = FileWatcher::new(root);
-    watcher.start(300)?; // 300ms debounce
+    let settings = watch_settings(&root);
+    let metadata = IndexMetadata::load_or_default(&state.db_path)?;
+    let mut watcher = FileWatcher::new(root).with_include_globs(metadata.include())?;
+    watcher.start(settings.debounce_ms)?;
 
     loop {
-        let events = watcher.wait_for_events(Duration::from_secs(1));
+        let events = watcher.wait_for_burst(
+            Duration::from_secs(1),
+            settings.settle(),
+            settings.max_batch,
+        );
 
         if events.is_empty() {
             continue;
         }
 
-        println!("\n📁 {} file change(s) detected", events.len());
+        apply_file_events(&state, events, &settings).await?;
+    }
+}
+
+/// Re-index modified files, drop deleted ones, then rebuild the index and save metadata
+async fn apply_file_events(
+    state: &ServerState,
+    events: Vec<FileEvent>,
+    settings: &WatchSettings,
+) -> Result<()> {
+    let start = std::time::Instant::now();
+    let deleted = events
+        .iter()
+        .filter(|e| matches!(e, FileEvent::Deleted(_)))
+        .count();
+    println!(
+        "\n📁 {} file change(s) detected ({} modified, {} deleted)",
+        events.len(),
+        events.len() - deleted,
+        deleted
+    );
+    if settings.max_batch > 0 && events.len() >= settings.max_batch {
+        println!(
+            "{}",
+            format!(
+                "   Batch limit of {} reached; later changes follow in the next batch",
+                settings.max_batch
+            )
+            .dimmed()
+        );
+    }
+    let batch_size = events.len();
 
-        // Enable quiet mode during FSW indexing to suppress verbose output
-        set_quiet(true);
+    // Enable quiet mode during FSW indexing to suppress verbose output
+    set_quiet(true);
 
-        for event in events {
-            match event {
-                FileEvent::Modified(path) => {
-                    if let Err(e) = handle_file_modified(&state, &path).await {
-                        eprintln!("  ❌ Error processing {}: {}", path.display(), e);
-                    }
+    for event in events {
+        match event {
+            FileEvent::Modified(path) => {
+                if let Err(e) = handle_file_modified(state, &path).await {
+                    eprintln!("  ❌ Error processing {}: {}", path.display(), e);
                 }
-                FileEvent::Deleted(path) => {
-                    if let Err(e) = handle_file_deleted(&state, &path).await {
-                        eprintln!("  ❌ Error processing deletion {}: {}", path.display(), e);
-                    }
-                }
-                FileEvent::Renamed(from, to) => {
-                    // Treat as delete + create
-                    let _ = handle_file_deleted(&state, &from).await;
-                    let _ = handle_file_modified(&state, &to).await;
+            }
+            FileEvent::Deleted(path) => {
+                if let Err(e) = handle_file_deleted(state, &path).await {
+                    eprintln!("  ❌ Error processing deletion {}: {}", path.display(), e);
                 }
             }
+            FileEvent::Renamed(from, to) => {
+                // Treat as delete + create
+                let _ = handle_file_deleted(state, &from).await;
+                let _ = handle_file_modified(state, &to).await;
+            }
         }
+    }
 
-        // Rebuild index after changes
-        let mut store = state.store.write().await;
-        if !store.is_indexed() {
-            store.build_index()?;
-        }
+    // Rebuild index after changes
+    let mut store = state.store.write().await;
+    if !store.is_indexed() {
+        store.build_index()?;
+    }
 
-        // Save metadata
-        let file_meta = state.file_meta.read().await;
-        file_meta.save(&state.db_path)?;
+    // Save metadata
+    let file_meta = state.file_meta.read().await;
+    file_meta.save(&state.db_path)?;
 
-        // Disable quiet mode after FSW indexing is complete
-        set_quiet(false);
-    }
+    // Disable quiet mode after FSW indexing is complete
+    set_quiet(false);
+    println!(
+        "   ✅ Applied {} change(s) in {:.1}s",
+        batch_size,
+        start.elapsed().as_secs_f64()
+    );
+
+    Ok(())
 }
 
 async fn handle_file_modified(state: &ServerState, path: &PathBuf) -> Result<()> {
@@ -410,48 +652,16 @@ async fn handle_file_deleted(state: &ServerState, path: &Path) -> Result<()> {
         // Path not found as a tracked file — might be a directory deletion.
         // On Windows, rm -rf of a directory may only produce a Remove event
         // for the directory itself, not for individual files within it.
-        let path_prefix = path.to_string_lossy().to_string();
-
-        // DEBUG: Log path prefix and first few tracked files
-        println!("  🐛 DEBUG: Deleted path prefix = {:?}", path_prefix);
-        let tracked_count = file_meta.tracked_files().count();
-        println!("  🐛 DEBUG: Total tracked files = {}", tracked_count);
-        let first_files: Vec<_> = file_meta.tracked_files().take(3).cloned().collect();
-        for (i, f) in first_files.iter().enumerate() {
-            println!("  🐛 DEBUG: Tracked file[{}] = {}", i, f);
-        }
-
-        let files_to_remove: Vec<String> = file_meta
-            .tracked_files()
-            .filter(|f| {
-                let starts = f.starts_with(&path_prefix);
-                if !starts && f.contains("test_fsw_project") {
-                    println!("  🐛 DEBUG: '{}' does NOT start with '{}'", f, path_prefix);
-                }
-                starts
-            })
-            .cloned()
-            .collect();
-
+        let files_to_remove = file_meta.remove_dir(path);
         if !files_to_remove.is_empty() {
+            let mut store = state.store.write().await;
+            let chunk_ids = crate::index::delete_removed_files(&mut store, &files_to_remove)?;
             println!(
-                "  🗑️  Directory deleted: {} ({} files)",
+                "  🗑️  Directory deleted: {} ({} files, {} chunks)",
                 path.display(),
-                files_to_remove.len()
+                files_to_remove.len(),
+                chunk_ids.len()
             );
-            let mut store = state.store.write().await;
-            for file_path in files_to_remove {
-                if let Some(meta) = file_meta.remove_file(Path::new(&file_path)) {
-                    if !meta.chunk_ids.is_empty() {
-                        println!(
-                            "    🗑️  {}: {} chunks removed",
-                            file_path,
-                            meta.chunk_ids.len()
-                        );
-                        store.delete_chunks(&meta.chunk_ids)?;
-                    }
-                }
-            }
         }
     }
 
@@ -472,8 +682,13 @@ async fn health_handler(State(state): State<Arc<ServerState>>) -> Json<HealthRes
 
     let file_meta = state.file_meta.read().await;
 
+    let status = if state.ready.load(Ordering::SeqCst) {
+        "ready"
+    } else {
+        "starting"
+    };
     Json(HealthResponse {
-        status: "ready".to_string(),
+        status: status.to_string(),
         indexed_files: stats.total_files,
         indexed_chunks: stats.total_chunks,
         model: file_meta.model_name.clone(),
@@ -498,40 +713,202 @@ async fn status_handler(State(state): State<Arc<ServerState>>) -> Json<StatusRes
         indexed: stats.indexed,
         model: file_meta.model_name.clone(),
         dimensions: file_meta.dimensions,
+        read_only: state.read_only,
     })
 }
 
+/// Reject searches with 503 while the initial index is still being built
+fn ensure_ready(state: &ServerState) -> Result<(), (StatusCode, String)> {
+    if state.ready.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    Err((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Initial index in progress, retry in a few seconds (GET /health reports \"ready\" when done)"
+            .to_string(),
+    ))
+}
+
+/// Deadline for one search request (`CODESEARCH_SEARCH_TIMEOUT_MS`, 0 or invalid = default)
+fn search_timeout() -> Duration {
+    let ms = std::env::var("CODESEARCH_SEARCH_TIMEOUT_MS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|&ms| ms > 0)
+        .unwrap_or(crate::constants::DEFAULT_SEARCH_TIMEOUT_MS);
+    Duration::from_millis(ms)
+}
+
+/// Validate and clamp a request `limit` (400 for zero)
+fn request_limit(limit: usize) -> Result<(usize, Option<String>), (StatusCode, String)> {
+    crate::constants::clamp_request_limit(limit).map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+fn timed_out(deadline: Duration) -> (StatusCode, String) {
+    (
+        StatusCode::GATEWAY_TIMEOUT,
+        format!("Search timed out after {} ms", deadline.as_millis()),
+    )
+}
+
+/// Run blocking embedding/search work on the blocking pool, answering 504 past the deadline
+///
+/// A running ONNX or ANN call can't be interrupted, so `work` gets a flag that is set
+/// on timeout and should be checked between steps; its result is discarded either way.
+async fn with_deadline<T, F>(work: F) -> Result<T, (StatusCode, String)>
+where
+    T: Send + 'static,
+    F: FnOnce(&AtomicBool) -> Result<T, (StatusCode, String)> + Send + 'static,
+{
+    let deadline = search_timeout();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let flag = cancelled.clone();
+    let task = tokio::task::spawn_blocking(move || work(&flag));
+
+    match tokio::time::timeout(deadline, task).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Search task failed: {}", e),
+        )),
+        Err(_) => {
+            cancelled.store(true, Ordering::SeqCst);
+            Err(timed_out(deadline))
+        }
+    }
+}
+
 async fn search_handler(
     State(state): State<Arc<ServerState>>,
     Json(req): Json<SearchRequest>,
 ) -> Result<Json<SearchResponse>, (StatusCode, String)> {
+    ensure_ready(&state)?;
     let start = std::time::Instant::now();
 
-    // Embed query
-    let query_embedding = {
-        let mut embedding_service = state.embedding_service.lock().map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Mutex poisoned: {}", e),
-            )
-        })?;
-        embedding_service
-            .embed_query(&req.query)
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-    };
+    let SearchRequest { query, limit, path } = req;
+    let (limit, note) = request_limit(limit)?;
+    let log_state = state.clone();
+    let (query, search_results) = with_deadline(move |cancelled| {
+        // Embed query
+        let query_embedding = {
+            let mut embedding_service = state.embedding_service.lock().map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Mutex poisoned: {}", e),
+                )
+            })?;
+            embedding_service
+                .embed_query(&query)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        };
+        if cancelled.load(Ordering::SeqCst) {
+            return Err(timed_out(search_timeout()));
+        }
 
-    // Search
-    let store = state.store.read().await;
-    let results = store
-        .search(&query_embedding, req.limit)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        // Search
+        let store = state.store.blocking_read();
+        let results = store
+            .search(&query_embedding, limit)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        // Convert to response format
+        Ok((
+            query,
+            to_response_results(&state.root, results, path.as_deref()),
+        ))
+    })
+    .await?;
+
+    let took = start.elapsed();
+    if let Some(query_log) = &log_state.query_log {
+        query_log.record(&QueryLogEntry::new(
+            "http",
+            &query,
+            search_results.iter().map(|r| r.score),
+            took,
+        ));
+    }
+    let took_ms = took.as_millis() as u64;
+
+    Ok(Json(SearchResponse {
+        results: search_results,
+        query,
+        took_ms,
+        note,
+    }))
+}
+
+async fn batch_search_handler(
+    State(state): State<Arc<ServerState>>,
+    Json(req): Json<BatchSearchRequest>,
+) -> Result<Json<BatchSearchResponse>, (StatusCode, String)> {
+    ensure_ready(&state)?;
+    let start = std::time::Instant::now();
+
+    if req.queries.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "queries must not be empty".to_string(),
+        ));
+    }
+    let (limit, note) = request_limit(req.limit)?;
+
+    let responses = with_deadline(move |cancelled| {
+        // Embed all queries in one batched call
+        let query_embeddings = {
+            let mut embedding_service = state.embedding_service.lock().map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Mutex poisoned: {}", e),
+                )
+            })?;
+            embedding_service
+                .embed_queries_batch(&req.queries)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        };
+
+        // Search each query, stopping early once the request has timed out
+        let store = state.store.blocking_read();
+        let mut responses = Vec::with_capacity(req.queries.len());
+        for (query, query_embedding) in req.queries.into_iter().zip(query_embeddings) {
+            if cancelled.load(Ordering::SeqCst) {
+                return Err(timed_out(search_timeout()));
+            }
+            let query_start = std::time::Instant::now();
+            let results = store
+                .search(&query_embedding, limit)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+            responses.push(SearchResponse {
+                results: to_response_results(&state.root, results, req.path.as_deref()),
+                query,
+                took_ms: query_start.elapsed().as_millis() as u64,
+                note: None,
+            });
+        }
+        Ok(responses)
+    })
+    .await?;
+
+    Ok(Json(BatchSearchResponse {
+        results: responses,
+        total_ms: start.elapsed().as_millis() as u64,
+        note,
+    }))
+}
 
-    // Convert to response format
-    let search_results: Vec<SearchResult> = results
+/// Convert store results to the HTTP response format (root-relative paths,
+/// truncated content), applying the optional path filter
+fn to_response_results(
+    root: &Path,
+    results: Vec<crate::vectordb::SearchResult>,
+    path_filter: Option<&str>,
+) -> Vec<SearchResult> {
+    results
         .into_iter()
         .filter(|r| {
             // Filter by path if specified
-            if let Some(ref path_filter) = req.path {
+            if let Some(path_filter) = path_filter {
                 r.path.contains(path_filter)
             } else {
                 true
@@ -541,35 +918,20 @@ async fn search_handler(
             // Make path relative to root
             let rel_path = r
                 .path
-                .strip_prefix(state.root.to_str().unwrap_or(""))
+                .strip_prefix(root.to_str().unwrap_or(""))
                 .unwrap_or(&r.path)
                 .trim_start_matches('/')
                 .to_string();
 
             SearchResult {
                 path: rel_path,
-                content: truncate_content(&r.content, 200),
+                content: truncate_with_ellipsis(&r.content, 200),
                 start_line: r.start_line,
                 end_line: r.end_line,
                 kind: r.kind,
                 score: r.score,
+                language: r.language,
             }
         })
-        .collect();
-
-    let took_ms = start.elapsed().as_millis() as u64;
-
-    Ok(Json(SearchResponse {
-        results: search_results,
-        query: req.query,
-        took_ms,
-    }))
-}
-
-fn truncate_content(content: &str, max_len: usize) -> String {
-    if content.len() <= max_len {
-        content.to_string()
-    } else {
-        format!("{}...", &content[..max_len])
-    }
+        .collect()
 }
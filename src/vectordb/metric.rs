@@ -0,0 +1,237 @@
+//! Distance metric of the vector index
+//!
+//! The metric is picked when an index is created (`codesearch index --metric`)
+//! and is recorded by the LMDB database the vectors live in: `vectors` for
+//! cosine (every index built before the option existed), `vectors_dot` and
+//! `vectors_euclidean` for the others. Opening a store therefore always
+//! searches with the metric it was built with.
+
+use arroy::distances::{Cosine, DotProduct, Euclidean};
+use arroy::{Database as ArroyDatabase, ItemId, Reader, Writer};
+use heed::{Env, RoTxn, RwTxn};
+use rand::rngs::StdRng;
+use std::num::NonZeroUsize;
+
+/// How vector similarity is measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    /// Angle between the vectors, ignoring their length
+    #[default]
+    Cosine,
+    /// Dot product; vector length counts, for models trained with it
+    DotProduct,
+    /// Straight-line (L2) distance
+    Euclidean,
+}
+
+impl Metric {
+    /// All metrics, in `--metric` help order
+    pub const ALL: [Metric; 3] = [Metric::Cosine, Metric::DotProduct, Metric::Euclidean];
+
+    /// Parse a `--metric` value
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "cosine" | "cos" => Some(Self::Cosine),
+            "dot" | "dot-product" | "dotproduct" | "ip" => Some(Self::DotProduct),
+            "euclidean" | "l2" => Some(Self::Euclidean),
+            _ => None,
+        }
+    }
+
+    /// Name used on the command line and in `metadata.json`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cosine => "cosine",
+            Self::DotProduct => "dot",
+            Self::Euclidean => "euclidean",
+        }
+    }
+
+    /// LMDB database holding vectors compared with this metric
+    fn database_name(&self) -> &'static str {
+        match self {
+            Self::Cosine => "vectors",
+            Self::DotProduct => "vectors_dot",
+            Self::Euclidean => "vectors_euclidean",
+        }
+    }
+
+    /// Similarity score (higher is better) for a distance reported by arroy
+    pub fn score(&self, distance: f32) -> f32 {
+        match self {
+            Self::Cosine => 1.0 - distance,
+            // arroy ranks by the negated dot product
+            Self::DotProduct => -distance,
+            Self::Euclidean => 1.0 / (1.0 + distance),
+        }
+    }
+}
+
+impl std::fmt::Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Run `$body` with `$db` bound to the typed arroy value inside any variant of `$enum`
+macro_rules! with_metric {
+    ($value:expr, $enum:ident, $db:ident => $body:expr) => {
+        match $value {
+            $enum::Cosine($db) => $body,
+            $enum::DotProduct($db) => $body,
+            $enum::Euclidean($db) => $body,
+        }
+    };
+}
+
+/// The arroy database of a store, typed by its metric
+#[derive(Clone, Copy)]
+pub(crate) enum Vectors {
+    Cosine(ArroyDatabase<Cosine>),
+    DotProduct(ArroyDatabase<DotProduct>),
+    Euclidean(ArroyDatabase<Euclidean>),
+}
+
+impl Vectors {
+    /// Open whichever vector database exists in `env`, if any
+    pub(crate) fn open(env: &Env, rtxn: &RoTxn) -> heed::Result<Option<Self>> {
+        for metric in Metric::ALL {
+            let name = Some(metric.database_name());
+            let found = match metric {
+                Metric::Cosine => env.open_database(rtxn, name)?.map(Self::Cosine),
+                Metric::DotProduct => env.open_database(rtxn, name)?.map(Self::DotProduct),
+                Metric::Euclidean => env.open_database(rtxn, name)?.map(Self::Euclidean),
+            };
+            if found.is_some() {
+                return Ok(found);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Create the vector database for `metric`
+    pub(crate) fn create(env: &Env, wtxn: &mut RwTxn, metric: Metric) -> heed::Result<Self> {
+        let name = Some(metric.database_name());
+        Ok(match metric {
+            Metric::Cosine => Self::Cosine(env.create_database(wtxn, name)?),
+            Metric::DotProduct => Self::DotProduct(env.create_database(wtxn, name)?),
+            Metric::Euclidean => Self::Euclidean(env.create_database(wtxn, name)?),
+        })
+    }
+
+    pub(crate) fn metric(&self) -> Metric {
+        match self {
+            Self::Cosine(_) => Metric::Cosine,
+            Self::DotProduct(_) => Metric::DotProduct,
+            Self::Euclidean(_) => Metric::Euclidean,
+        }
+    }
+
+    pub(crate) fn writer(&self, dimensions: usize) -> VectorWriter {
+        match *self {
+            Self::Cosine(db) => VectorWriter::Cosine(Writer::new(db, 0, dimensions)),
+            Self::DotProduct(db) => VectorWriter::DotProduct(Writer::new(db, 0, dimensions)),
+            Self::Euclidean(db) => VectorWriter::Euclidean(Writer::new(db, 0, dimensions)),
+        }
+    }
+
+    pub(crate) fn reader<'t>(&self, rtxn: &'t RoTxn) -> arroy::Result<VectorReader<'t>> {
+        Ok(match *self {
+            Self::Cosine(db) => VectorReader::Cosine(Reader::open(rtxn, 0, db)?),
+            Self::DotProduct(db) => VectorReader::DotProduct(Reader::open(rtxn, 0, db)?),
+            Self::Euclidean(db) => VectorReader::Euclidean(Reader::open(rtxn, 0, db)?),
+        })
+    }
+
+    pub(crate) fn clear(&self, wtxn: &mut RwTxn) -> heed::Result<()> {
+        with_metric!(self, Vectors, db => db.clear(wtxn))
+    }
+}
+
+/// An arroy writer for any metric
+pub(crate) enum VectorWriter {
+    Cosine(Writer<Cosine>),
+    DotProduct(Writer<DotProduct>),
+    Euclidean(Writer<Euclidean>),
+}
+
+impl VectorWriter {
+    pub(crate) fn add_item(
+        &self,
+        wtxn: &mut RwTxn,
+        id: ItemId,
+        vector: &[f32],
+    ) -> arroy::Result<()> {
+        with_metric!(self, VectorWriter, w => w.add_item(wtxn, id, vector))
+    }
+
+    /// The vector stored for `id`, before or after the index is built
+    pub(crate) fn item_vector(&self, rtxn: &RoTxn, id: ItemId) -> arroy::Result<Option<Vec<f32>>> {
+        with_metric!(self, VectorWriter, w => w.item_vector(rtxn, id))
+    }
+
+    pub(crate) fn del_item(&self, wtxn: &mut RwTxn, id: ItemId) -> arroy::Result<bool> {
+        with_metric!(self, VectorWriter, w => w.del_item(wtxn, id))
+    }
+
+    pub(crate) fn build(&self, wtxn: &mut RwTxn, rng: &mut StdRng) -> arroy::Result<()> {
+        with_metric!(self, VectorWriter, w => w.builder(rng).build(wtxn))
+    }
+}
+
+/// An arroy reader for any metric
+pub(crate) enum VectorReader<'t> {
+    Cosine(Reader<'t, Cosine>),
+    DotProduct(Reader<'t, DotProduct>),
+    Euclidean(Reader<'t, Euclidean>),
+}
+
+impl VectorReader<'_> {
+    /// The `limit` nearest items to `vector` with their distances, exploring
+    /// `search_k` candidates when given
+    pub(crate) fn nns_by_vector(
+        &self,
+        rtxn: &RoTxn,
+        vector: &[f32],
+        limit: usize,
+        search_k: Option<NonZeroUsize>,
+    ) -> arroy::Result<Vec<(ItemId, f32)>> {
+        with_metric!(self, VectorReader, r => {
+            let mut query = r.nns(limit);
+            if let Some(search_k) = search_k {
+                query.search_k(search_k);
+            }
+            query.by_vector(rtxn, vector)
+        })
+    }
+
+    pub(crate) fn n_trees(&self) -> usize {
+        with_metric!(self, VectorReader, r => r.n_trees())
+    }
+
+    pub(crate) fn item_vector(&self, rtxn: &RoTxn, id: ItemId) -> arroy::Result<Option<Vec<f32>>> {
+        with_metric!(self, VectorReader, r => r.item_vector(rtxn, id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_parse_and_score() {
+        assert_eq!(Metric::parse("Cosine"), Some(Metric::Cosine));
+        assert_eq!(Metric::parse("dot-product"), Some(Metric::DotProduct));
+        assert_eq!(Metric::parse("l2"), Some(Metric::Euclidean));
+        assert_eq!(Metric::parse("manhattan"), None);
+        for metric in Metric::ALL {
+            assert_eq!(Metric::parse(metric.name()), Some(metric));
+        }
+
+        // Closer is always a higher score
+        for metric in Metric::ALL {
+            assert!(metric.score(0.1) > metric.score(0.5), "{}", metric);
+        }
+        assert_eq!(Metric::Euclidean.score(0.0), 1.0);
+    }
+}
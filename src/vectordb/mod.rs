@@ -1,3 +1,5 @@
+mod metric;
 mod store;
 
-pub use store::{SearchResult, StoreStats, VectorStore};
+pub use metric::Metric;
+pub use store::{ChunkCacheStats, ChunkMetadata, SearchResult, StoreStats, VectorStore};
@@ -1,17 +1,25 @@
+use crate::chunker::{Chunk, ChunkKind};
 use crate::embed::EmbeddedChunk;
+use crate::error::{CodeSearchError, Result as CsResult};
+use crate::file::Language;
 use crate::info_print;
 use anyhow::{anyhow, Result};
-use arroy::distances::Cosine;
-use arroy::{Database as ArroyDatabase, ItemId, Reader, Writer};
+use arroy::ItemId;
 use heed::byteorder::BigEndian;
 use heed::types::*;
-use heed::{Database, EnvFlags, EnvOpenOptions};
+use heed::{BoxedError, BytesDecode, BytesEncode, Database, EnvFlags, EnvOpenOptions};
+use moka::sync::Cache;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fs;
 use std::num::NonZeroUsize;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::metric::{Metric, VectorWriter, Vectors};
 
 /// Chunk metadata stored in the database
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,54 +42,160 @@ pub struct ChunkMetadata {
     /// Searchable text combining signature, name, and content for better searchability
     #[serde(default)]
     pub searchable_text: String,
+    /// Language of the source file (`Language` debug name, e.g. "Rust"); None for
+    /// chunks indexed before languages were stored
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// `ChunkMetadata` as written before chunks recorded their language
+#[derive(Deserialize)]
+struct ChunkMetadataV1 {
+    content: String,
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    kind: String,
+    signature: Option<String>,
+    docstring: Option<String>,
+    context: Option<String>,
+    hash: String,
+    context_prev: Option<String>,
+    context_next: Option<String>,
+    searchable_text: String,
+}
+
+impl From<ChunkMetadataV1> for ChunkMetadata {
+    fn from(v1: ChunkMetadataV1) -> Self {
+        Self {
+            content: v1.content,
+            path: v1.path,
+            start_line: v1.start_line,
+            end_line: v1.end_line,
+            kind: v1.kind,
+            signature: v1.signature,
+            docstring: v1.docstring,
+            context: v1.context,
+            hash: v1.hash,
+            context_prev: v1.context_prev,
+            context_next: v1.context_next,
+            searchable_text: v1.searchable_text,
+            language: None,
+        }
+    }
+}
+
+/// Bincode codec for [`ChunkMetadata`]
+///
+/// Bincode has no field names, so records written before `language` was added
+/// fail to decode as the current struct (they end one field early). Those are
+/// read as [`ChunkMetadataV1`] instead, keeping existing indexes usable.
+struct ChunkMetadataCodec;
+
+impl<'a> BytesEncode<'a> for ChunkMetadataCodec {
+    type EItem = ChunkMetadata;
+
+    fn bytes_encode(item: &'a ChunkMetadata) -> std::result::Result<Cow<'a, [u8]>, BoxedError> {
+        bincode::serialize(item).map(Cow::Owned).map_err(Into::into)
+    }
+}
+
+impl<'a> BytesDecode<'a> for ChunkMetadataCodec {
+    type DItem = ChunkMetadata;
+
+    fn bytes_decode(bytes: &'a [u8]) -> std::result::Result<ChunkMetadata, BoxedError> {
+        bincode::deserialize::<ChunkMetadata>(bytes)
+            .or_else(|_| bincode::deserialize::<ChunkMetadataV1>(bytes).map(Into::into))
+            .map_err(Into::into)
+    }
 }
 
 impl ChunkMetadata {
+    /// Language of the chunk, like [`SearchResult::language_name`]
+    pub fn language_name(&self) -> String {
+        language_name(self.language.as_deref(), &self.path)
+    }
+
     fn from_embedded_chunk(chunk: &EmbeddedChunk) -> Self {
+        Self::from_chunk(&chunk.chunk)
+    }
+
+    fn from_chunk(chunk: &Chunk) -> Self {
         // Build searchable text from signature, docstring, and content
         let searchable_text = {
             let mut parts = Vec::new();
 
             // Add signature if available (e.g., "fn handle_file_modified(path: PathBuf)")
-            if let Some(sig) = &chunk.chunk.signature {
+            if let Some(sig) = &chunk.signature {
                 parts.push(sig.clone());
             }
 
             // Add docstring if available
-            if let Some(doc) = &chunk.chunk.docstring {
+            if let Some(doc) = &chunk.docstring {
                 parts.push(doc.clone());
             }
 
             // Add kind (e.g., "Function", "Struct", "Impl")
-            parts.push(format!("{:?}", chunk.chunk.kind));
+            parts.push(format!("{:?}", chunk.kind));
 
             // Add content
-            parts.push(chunk.chunk.content.clone());
+            parts.push(chunk.content.clone());
 
             parts.join("\n")
         };
 
         Self {
-            content: chunk.chunk.content.clone(),
-            path: chunk.chunk.path.clone(),
-            start_line: chunk.chunk.start_line,
-            end_line: chunk.chunk.end_line,
-            kind: format!("{:?}", chunk.chunk.kind),
-            signature: chunk.chunk.signature.clone(),
-            docstring: chunk.chunk.docstring.clone(),
-            context: if chunk.chunk.context.is_empty() {
+            content: chunk.content.clone(),
+            path: chunk.path.clone(),
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            kind: format!("{:?}", chunk.kind),
+            signature: chunk.signature.clone(),
+            docstring: chunk.docstring.clone(),
+            context: if chunk.context.is_empty() {
                 None
             } else {
-                Some(chunk.chunk.context.join(" > "))
+                Some(chunk.context.join(" > "))
             },
-            hash: chunk.chunk.hash.clone(),
-            context_prev: chunk.chunk.context_prev.clone(),
-            context_next: chunk.chunk.context_next.clone(),
+            hash: chunk.hash.clone(),
+            context_prev: chunk.context_prev.clone(),
+            context_next: chunk.context_next.clone(),
             searchable_text,
+            language: chunk.language.map(|lang| format!("{:?}", lang)),
         }
     }
+
+    /// Rebuild the `Chunk` fields that feed into embeddings (context, signature,
+    /// docstring, content), e.g. to re-embed with a different model.
+    ///
+    /// The chunk kind is only stored by name, so it is set to `ChunkKind::Other`;
+    /// it does not affect the embedding text.
+    pub fn to_chunk(&self) -> Chunk {
+        let mut chunk = Chunk::new(
+            self.content.clone(),
+            self.start_line,
+            self.end_line,
+            ChunkKind::Other,
+            self.path.clone(),
+        );
+        chunk.context = self
+            .context
+            .as_deref()
+            .map(|c| c.split(" > ").map(str::to_string).collect())
+            .unwrap_or_default();
+        chunk.signature = self.signature.clone();
+        chunk.docstring = self.docstring.clone();
+        chunk.hash = self.hash.clone();
+        chunk.context_prev = self.context_prev.clone();
+        chunk.context_next = self.context_next.clone();
+        chunk.language = self.language.as_deref().and_then(Language::from_name);
+        chunk
+    }
 }
 
+/// Chunk key (its path, or its content hash) -> IDs of the chunks stored under it (ascending)
+type IdIndex = Database<Str, SerdeBincode<Vec<u32>>>;
+
 /// Vector database using arroy + heed (LMDB)
 ///
 /// Single-file database with:
@@ -89,13 +203,27 @@ impl ChunkMetadata {
 /// - Metadata storage via heed (LMDB)
 /// - ACID transactions
 /// - Memory-mapped for performance
+///
+/// Chunks with identical content (same `hash`, e.g. license headers or copied
+/// files) each keep their own ID and metadata but share one stored vector:
+/// only the first copy is added to arroy, and a search hit on it also returns
+/// the other copies. Deleting the copy that holds the vector hands it over to
+/// a remaining one.
 pub struct VectorStore {
     env: heed::Env,
-    vectors: ArroyDatabase<Cosine>,
-    chunks: Database<U32<BigEndian>, SerdeBincode<ChunkMetadata>>,
+    vectors: Vectors,
+    chunks: Database<U32<BigEndian>, ChunkMetadataCodec>,
+    /// None for read-only stores created before the path index existed
+    paths: Option<IdIndex>,
+    /// None for read-only stores created before content deduplication
+    hashes: Option<IdIndex>,
     next_id: u32,
     dimensions: usize,
     indexed: bool,
+    /// Recently fetched chunk metadata (None when disabled)
+    chunk_cache: Option<Cache<u32, Arc<ChunkMetadata>>>,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
 }
 
 impl VectorStore {
@@ -119,7 +247,21 @@ impl VectorStore {
     /// # Arguments
     /// * `db_path` - Path to the database directory (e.g., ".codesearch.db")
     /// * `dimensions` - Dimensionality of embeddings (e.g., 384, 768)
-    pub fn new(db_path: &Path, dimensions: usize) -> Result<Self> {
+    ///
+    /// Damaged LMDB files are reported as [`CodeSearchError::Corrupt`].
+    pub fn new(db_path: &Path, dimensions: usize) -> CsResult<Self> {
+        Self::open_rw(db_path, dimensions, None).map_err(|e| open_error(db_path, e))
+    }
+
+    /// Open or create a vector database whose vectors are compared with `metric`
+    ///
+    /// A new database is created with `metric`; an existing one must already
+    /// use it, since stored vectors can't be searched with another metric.
+    pub fn new_with_metric(db_path: &Path, dimensions: usize, metric: Metric) -> CsResult<Self> {
+        Self::open_rw(db_path, dimensions, Some(metric)).map_err(|e| open_error(db_path, e))
+    }
+
+    fn open_rw(db_path: &Path, dimensions: usize, metric: Option<Metric>) -> Result<Self> {
         info_print!("📦 Opening vector database at: {}", db_path.display());
 
         // Create database directory (LMDB expects a directory, not a file)
@@ -143,9 +285,49 @@ impl VectorStore {
         // Open or create databases
         let mut wtxn = env.write_txn()?;
 
-        let vectors: ArroyDatabase<Cosine> = env.create_database(&mut wtxn, Some("vectors"))?;
-        let chunks: Database<U32<BigEndian>, SerdeBincode<ChunkMetadata>> =
+        let vectors = match Vectors::open(&env, &wtxn)? {
+            Some(vectors) => vectors,
+            None => Vectors::create(&env, &mut wtxn, metric.unwrap_or_default())?,
+        };
+        if let Some(metric) = metric.filter(|m| *m != vectors.metric()) {
+            return Err(anyhow!(
+                "Index uses the {} metric, not {}. Re-index with `codesearch index --force --metric {}`.",
+                vectors.metric(),
+                metric,
+                metric
+            ));
+        }
+        let chunks: Database<U32<BigEndian>, ChunkMetadataCodec> =
             env.create_database(&mut wtxn, Some("chunks"))?;
+        let paths: IdIndex = env.create_database(&mut wtxn, Some("paths"))?;
+        let hashes: IdIndex = env.create_database(&mut wtxn, Some("hashes"))?;
+
+        // Databases written before the path or hash index existed get it built once here
+        let build_paths = paths.is_empty(&wtxn)? && !chunks.is_empty(&wtxn)?;
+        let build_hashes = hashes.is_empty(&wtxn)? && !chunks.is_empty(&wtxn)?;
+        if build_paths || build_hashes {
+            let mut by_path: std::collections::HashMap<String, Vec<u32>> =
+                std::collections::HashMap::new();
+            let mut by_hash: std::collections::HashMap<String, Vec<u32>> =
+                std::collections::HashMap::new();
+            for entry in chunks.iter(&wtxn)? {
+                let (id, metadata) = entry?;
+                by_path.entry(metadata.path).or_default().push(id);
+                by_hash.entry(metadata.hash).or_default().push(id);
+            }
+            if build_paths {
+                for (path, ids) in &by_path {
+                    paths.put(&mut wtxn, path, ids)?;
+                }
+                tracing::info!("Built path index for {} files", by_path.len());
+            }
+            if build_hashes {
+                for (hash, ids) in &by_hash {
+                    hashes.put(&mut wtxn, hash, ids)?;
+                }
+                tracing::info!("Built content hash index for {} chunks", by_hash.len());
+            }
+        }
 
         // Get the next ID from the maximum existing key + 1
         // Using len() is wrong after delete+insert cycles: deleted IDs create gaps
@@ -160,7 +342,7 @@ impl VectorStore {
         // Check if database is already indexed by trying to open a reader
         let indexed = if next_id > 0 {
             let rtxn = env.read_txn()?;
-            match Reader::open(&rtxn, 0, vectors) {
+            match vectors.reader(&rtxn) {
                 Ok(_) => {
                     tracing::debug!("Index detected: Reader::open succeeded");
                     true
@@ -180,9 +362,14 @@ impl VectorStore {
             env,
             vectors,
             chunks,
+            paths: Some(paths),
+            hashes: Some(hashes),
             next_id,
             dimensions,
             indexed,
+            chunk_cache: chunk_cache_from_env(),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
         })
     }
 
@@ -191,19 +378,20 @@ impl VectorStore {
     /// # Arguments
     /// * `db_path` - Path to the database directory (e.g., ".codesearch.db")
     /// * `dimensions` - Dimensionality of embeddings (e.g., 384, 768)
-    pub fn open_readonly(db_path: &Path, dimensions: usize) -> Result<Self> {
+    pub fn open_readonly(db_path: &Path, dimensions: usize) -> CsResult<Self> {
         tracing::debug!(
             "📦 Opening vector database (read-only) at: {}",
             db_path.display()
         );
 
         if !db_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Database does not exist at: {}",
-                db_path.display()
-            ));
+            return Err(CodeSearchError::database_not_found(db_path));
         }
 
+        Self::open_ro(db_path, dimensions).map_err(|e| open_error(db_path, e))
+    }
+
+    fn open_ro(db_path: &Path, dimensions: usize) -> Result<Self> {
         // Open LMDB environment in read-only mode
         let map_size_mb = std::env::var("CODESEARCH_LMDB_MAP_SIZE_MB")
             .ok()
@@ -220,12 +408,13 @@ impl VectorStore {
         // Open databases (read-only, no create)
         let rtxn = env.read_txn()?;
 
-        let vectors: ArroyDatabase<Cosine> = env
-            .open_database(&rtxn, Some("vectors"))?
-            .ok_or_else(|| anyhow::anyhow!("vectors database not found"))?;
-        let chunks: Database<U32<BigEndian>, SerdeBincode<ChunkMetadata>> = env
+        let vectors = Vectors::open(&env, &rtxn)?
+            .ok_or_else(|| CodeSearchError::corrupt(db_path, "vectors database not found"))?;
+        let chunks: Database<U32<BigEndian>, ChunkMetadataCodec> = env
             .open_database(&rtxn, Some("chunks"))?
-            .ok_or_else(|| anyhow::anyhow!("chunks database not found"))?;
+            .ok_or_else(|| CodeSearchError::corrupt(db_path, "chunks database not found"))?;
+        let paths: Option<IdIndex> = env.open_database(&rtxn, Some("paths"))?;
+        let hashes: Option<IdIndex> = env.open_database(&rtxn, Some("hashes"))?;
 
         // Get the next ID from the maximum existing key + 1
         // Using len() is wrong after delete+insert cycles: deleted IDs create gaps
@@ -236,7 +425,7 @@ impl VectorStore {
 
         // Check if database is already indexed
         let indexed = if next_id > 0 {
-            Reader::open(&rtxn, 0, vectors).is_ok()
+            vectors.reader(&rtxn).is_ok()
         } else {
             false
         };
@@ -253,9 +442,14 @@ impl VectorStore {
             env,
             vectors,
             chunks,
+            paths,
+            hashes,
             next_id,
             dimensions,
             indexed,
+            chunk_cache: chunk_cache_from_env(),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
         })
     }
 
@@ -271,7 +465,7 @@ impl VectorStore {
         eprintln!("📊 Inserting {} chunks...", chunks.len());
 
         let mut wtxn = self.env.write_txn()?;
-        let writer = Writer::new(self.vectors, 0, self.dimensions);
+        let writer = self.vectors.writer(self.dimensions);
 
         for chunk in &chunks {
             let id = self.next_id;
@@ -285,12 +479,9 @@ impl VectorStore {
                 ));
             }
 
-            // Add vector to arroy
-            writer.add_item(&mut wtxn, id, &chunk.embedding)?;
-
-            // Store metadata
+            // Store metadata, and the vector unless a copy of the content has one
             let metadata = ChunkMetadata::from_embedded_chunk(chunk);
-            self.chunks.put(&mut wtxn, &id, &metadata)?;
+            self.put_chunk(&mut wtxn, &writer, id, &metadata, &chunk.embedding)?;
 
             self.next_id += 1;
         }
@@ -315,10 +506,10 @@ impl VectorStore {
     /// Must be called after inserting chunks and before searching
     pub fn build_index(&mut self) -> Result<()> {
         let mut wtxn = self.env.write_txn()?;
-        let writer = Writer::new(self.vectors, 0, self.dimensions);
+        let writer = self.vectors.writer(self.dimensions);
 
         let mut rng = StdRng::seed_from_u64(rand::random());
-        writer.builder(&mut rng).build(&mut wtxn)?;
+        writer.build(&mut wtxn, &mut rng)?;
 
         wtxn.commit()?;
 
@@ -335,61 +526,128 @@ impl VectorStore {
     ///
     /// # Returns
     /// Vector of search results with metadata and scores
-    pub fn search(&self, query_embedding: &[f32], limit: usize) -> Result<Vec<SearchResult>> {
+    pub fn search(&self, query_embedding: &[f32], limit: usize) -> CsResult<Vec<SearchResult>> {
+        self.search_with_filter(query_embedding, limit, None, |_| true)
+    }
+
+    /// Search for the `limit` most similar chunks that satisfy `predicate`
+    ///
+    /// The predicate is checked while results are assembled, and the nearest
+    /// neighbour search is widened until `limit` matching chunks are found (or
+    /// the store runs out), so a restrictive filter still fills the limit
+    /// instead of keeping a handful of an over-fetched batch.
+    ///
+    /// The widening stops after `max_candidates` nearest neighbours (the
+    /// retrieval cap), or at `MAX_FILTER_WIDENING_FACTOR` times `limit` when
+    /// there is none.
+    pub fn search_with_filter(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        max_candidates: Option<usize>,
+        predicate: impl Fn(&ChunkMetadata) -> bool,
+    ) -> CsResult<Vec<SearchResult>> {
         if query_embedding.len() != self.dimensions {
-            return Err(anyhow!(
-                "Query embedding dimension mismatch: expected {}, got {}",
+            return Err(CodeSearchError::dimension_mismatch(
                 self.dimensions,
-                query_embedding.len()
+                query_embedding.len(),
+                format!(
+                    "Query embedding dimension mismatch: expected {}, got {}",
+                    self.dimensions,
+                    query_embedding.len()
+                ),
             ));
         }
 
         if !self.indexed {
-            return Err(anyhow!(
-                "Index not built. Call build_index() after inserting chunks."
+            return Err(CodeSearchError::index(
+                "Index not built. Call build_index() after inserting chunks.",
             ));
         }
 
-        let rtxn = self.env.read_txn()?;
-        let reader = Reader::open(&rtxn, 0, self.vectors)?;
+        self.nearest(query_embedding, limit, max_candidates, &predicate)
+            .map_err(CodeSearchError::from)
+    }
 
-        // Perform ANN search with quality boost
-        let mut query = reader.nns(limit);
+    fn nearest(
+        &self,
+        query_embedding: &[f32],
+        limit: usize,
+        max_candidates: Option<usize>,
+        predicate: &dyn Fn(&ChunkMetadata) -> bool,
+    ) -> Result<Vec<SearchResult>> {
+        let rtxn = self.env.read_txn()?;
+        let reader = self.vectors.reader(&rtxn)?;
+        let total = self.chunks.len(&rtxn)? as usize;
+        let metric = self.vectors.metric();
+
+        let ceiling = max_candidates
+            .unwrap_or_else(|| limit.saturating_mul(crate::constants::MAX_FILTER_WIDENING_FACTOR))
+            .max(limit)
+            .clamp(1, total.max(1));
+        let mut fetch = limit.clamp(1, ceiling);
+        let mut search_results = Vec::new();
+        // Neighbours checked in an earlier round, and chunks already considered
+        let mut checked = std::collections::HashSet::new();
+        let mut seen = std::collections::HashSet::new();
+        loop {
+            // Perform ANN search, exploring more candidates to improve quality
+            let search_k = NonZeroUsize::new(fetch * reader.n_trees() * 15);
+            let results = reader.nns_by_vector(&rtxn, query_embedding, fetch, search_k)?;
+            let exhausted = results.len() < fetch || fetch >= ceiling;
+
+            // Fetch metadata for each new result and the copies sharing its
+            // vector, keeping the ones the predicate accepts
+            'results: for (id, distance) in results {
+                if !checked.insert(id) {
+                    continue;
+                }
+                let Some(metadata) = self.chunk_in(&rtxn, id)? else {
+                    continue;
+                };
+                let copies = self.copies_of(&rtxn, id, &metadata.hash)?;
+                let mut candidates = vec![(id, metadata)];
+                for copy in copies {
+                    if let Some(copy_metadata) = self.chunk_in(&rtxn, copy)? {
+                        candidates.push((copy, copy_metadata));
+                    }
+                }
 
-        // Improve search quality by exploring more candidates
-        if let Some(n_trees) = NonZeroUsize::new(reader.n_trees()) {
-            if let Some(search_k) = NonZeroUsize::new(limit * n_trees.get() * 15) {
-                query.search_k(search_k);
+                for (id, metadata) in candidates {
+                    if search_results.len() == limit {
+                        break 'results;
+                    }
+                    if !seen.insert(id) || !predicate(&metadata) {
+                        continue;
+                    }
+                    search_results.push(SearchResult {
+                        id,
+                        content: metadata.content,
+                        path: metadata.path,
+                        start_line: metadata.start_line,
+                        end_line: metadata.end_line,
+                        kind: metadata.kind,
+                        signature: metadata.signature,
+                        docstring: metadata.docstring,
+                        context: metadata.context,
+                        hash: metadata.hash,
+                        distance,
+                        score: metric.score(distance), // Convert distance to similarity score
+                        context_prev: metadata.context_prev,
+                        context_next: metadata.context_next,
+                        language: metadata.language,
+                    });
+                }
             }
-        }
 
-        let results = query.by_vector(&rtxn, query_embedding)?;
-
-        // Fetch metadata for each result
-        let mut search_results = Vec::new();
-
-        for (id, distance) in results {
-            if let Some(metadata) = self.chunks.get(&rtxn, &id)? {
-                search_results.push(SearchResult {
-                    id,
-                    content: metadata.content,
-                    path: metadata.path,
-                    start_line: metadata.start_line,
-                    end_line: metadata.end_line,
-                    kind: metadata.kind,
-                    signature: metadata.signature,
-                    docstring: metadata.docstring,
-                    context: metadata.context,
-                    hash: metadata.hash,
-                    distance,
-                    score: 1.0 - distance, // Convert distance to similarity score
-                    context_prev: metadata.context_prev,
-                    context_next: metadata.context_next,
-                });
+            if search_results.len() == limit || exhausted {
+                // A wider search can find a neighbour the narrower one missed
+                search_results.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+                return Ok(search_results);
             }
+            // Too few matches among the nearest chunks: look further out
+            fetch = fetch.saturating_mul(4).min(ceiling);
         }
-
-        Ok(search_results)
     }
 
     /// Get statistics about the vector store
@@ -426,20 +684,24 @@ impl VectorStore {
         }
 
         let mut wtxn = self.env.write_txn()?;
-        let writer = Writer::new(self.vectors, 0, self.dimensions);
+        let writer = self.vectors.writer(self.dimensions);
+        let doomed: std::collections::HashSet<u32> = chunk_ids.iter().copied().collect();
 
         let mut deleted = 0;
         for &id in chunk_ids {
-            // Delete from vector database
-            if writer.del_item(&mut wtxn, id).is_ok() {
+            if self.remove_chunk(&mut wtxn, &writer, id, &doomed)? {
                 deleted += 1;
             }
-            // Delete from metadata
-            self.chunks.delete(&mut wtxn, &id)?;
         }
 
         wtxn.commit()?;
 
+        if let Some(cache) = &self.chunk_cache {
+            for id in chunk_ids {
+                cache.invalidate(id);
+            }
+        }
+
         // Mark as needing re-index
         if deleted > 0 {
             self.indexed = false;
@@ -448,6 +710,58 @@ impl VectorStore {
         Ok(deleted)
     }
 
+    /// Delete every chunk whose path starts with `prefix` (e.g. a removed `src/legacy/`)
+    ///
+    /// Uses the path index, so a whole directory is dropped in one transaction
+    /// without looking up its files one by one. The prefix is matched literally:
+    /// end it with a separator so `src/legacy/` doesn't match `src/legacy2/`.
+    ///
+    /// Returns the IDs of the deleted chunks
+    pub fn delete_chunks_by_path_prefix(&mut self, prefix: &str) -> Result<Vec<u32>> {
+        if prefix.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut wtxn = self.env.write_txn()?;
+        let mut ids = Vec::new();
+        if let Some(paths) = self.paths {
+            let mut matched = Vec::new();
+            for entry in paths.prefix_iter(&wtxn, prefix)? {
+                let (path, path_ids) = entry?;
+                matched.push(path.to_string());
+                ids.extend(path_ids);
+            }
+            for path in &matched {
+                paths.delete(&mut wtxn, path)?;
+            }
+        } else {
+            for entry in self.chunks.iter(&wtxn)? {
+                let (id, metadata) = entry?;
+                if metadata.path.starts_with(prefix) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        let writer = self.vectors.writer(self.dimensions);
+        let doomed: std::collections::HashSet<u32> = ids.iter().copied().collect();
+        for &id in &ids {
+            self.remove_chunk(&mut wtxn, &writer, id, &doomed)?;
+        }
+        wtxn.commit()?;
+
+        if let Some(cache) = &self.chunk_cache {
+            for id in &ids {
+                cache.invalidate(id);
+            }
+        }
+        if !ids.is_empty() {
+            self.indexed = false;
+        }
+
+        Ok(ids)
+    }
+
     /// Delete all chunks from a specific file
     ///
     /// Returns the IDs of deleted chunks
@@ -461,7 +775,7 @@ impl VectorStore {
 
         let start_id = self.next_id;
         let mut wtxn = self.env.write_txn()?;
-        let writer = Writer::new(self.vectors, 0, self.dimensions);
+        let writer = self.vectors.writer(self.dimensions);
 
         for chunk in &chunks {
             let id = self.next_id;
@@ -474,9 +788,8 @@ impl VectorStore {
                 ));
             }
 
-            writer.add_item(&mut wtxn, id, &chunk.embedding)?;
             let metadata = ChunkMetadata::from_embedded_chunk(chunk);
-            self.chunks.put(&mut wtxn, &id, &metadata)?;
+            self.put_chunk(&mut wtxn, &writer, id, &metadata, &chunk.embedding)?;
 
             self.next_id += 1;
         }
@@ -488,6 +801,97 @@ impl VectorStore {
         Ok(ids)
     }
 
+    /// Insert chunks whose content is already stored, sharing its vector
+    ///
+    /// Nothing is embedded: each chunk gets its own ID and metadata, so it is
+    /// tracked and deleted with its file, and searches find it through the
+    /// stored copy. Fails for a chunk with no stored copy (see [`Self::has_content`]).
+    pub fn insert_duplicate_chunks(&mut self, chunks: Vec<Chunk>) -> Result<Vec<u32>> {
+        if chunks.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let start_id = self.next_id;
+        let mut wtxn = self.env.write_txn()?;
+        let writer = self.vectors.writer(self.dimensions);
+
+        for chunk in &chunks {
+            let id = self.next_id;
+            if self
+                .vector_holder(&wtxn, &writer, &chunk.hash, id)?
+                .is_none()
+            {
+                return Err(anyhow!(
+                    "No stored copy of a chunk of {} to share a vector with",
+                    chunk.path
+                ));
+            }
+            let metadata = ChunkMetadata::from_chunk(chunk);
+            self.chunks.put(&mut wtxn, &id, &metadata)?;
+            self.index_path(&mut wtxn, &metadata.path, id)?;
+            add_id(self.hashes, &mut wtxn, &metadata.hash, id)?;
+
+            self.next_id += 1;
+        }
+
+        // The vectors are unchanged, so a built index stays usable
+        wtxn.commit()?;
+
+        let ids: Vec<u32> = (start_id..self.next_id).collect();
+        Ok(ids)
+    }
+
+    /// Whether content with this hash is stored with a vector a new copy can share
+    pub fn has_content(&self, hash: &str) -> Result<bool> {
+        let rtxn = self.env.read_txn()?;
+        let writer = self.vectors.writer(self.dimensions);
+        Ok(self
+            .vector_holder(&rtxn, &writer, hash, u32::MAX)?
+            .is_some())
+    }
+
+    /// Insert chunks under explicit IDs, storing their metadata unchanged
+    ///
+    /// Used when rebuilding a store with new embeddings: keeping the original IDs
+    /// keeps the FTS index and file metadata chunk references valid.
+    pub fn insert_with_ids(&mut self, items: Vec<(u32, ChunkMetadata, Vec<f32>)>) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let mut wtxn = self.env.write_txn()?;
+        let writer = self.vectors.writer(self.dimensions);
+
+        for (id, metadata, embedding) in &items {
+            if embedding.len() != self.dimensions {
+                return Err(anyhow!(
+                    "Embedding dimension mismatch: expected {}, got {}",
+                    self.dimensions,
+                    embedding.len()
+                ));
+            }
+
+            if let Some(old) = self.chunks.get(&wtxn, id)? {
+                self.unindex_path(&mut wtxn, &old.path, *id)?;
+                remove_id(self.hashes, &mut wtxn, &old.hash, *id)?;
+            }
+            self.put_chunk(&mut wtxn, &writer, *id, metadata, embedding)?;
+            self.next_id = self.next_id.max(id + 1);
+        }
+
+        wtxn.commit()?;
+        self.indexed = false;
+
+        // Existing IDs may have been overwritten
+        if let Some(cache) = &self.chunk_cache {
+            for (id, _, _) in &items {
+                cache.invalidate(id);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Clear all data from the database
     #[allow(dead_code)] // Reserved for database reset operations
     pub fn clear(&mut self) -> Result<()> {
@@ -498,26 +902,257 @@ impl VectorStore {
         // Clear both databases
         self.chunks.clear(&mut wtxn)?;
         self.vectors.clear(&mut wtxn)?;
+        for index in [self.paths, self.hashes].into_iter().flatten() {
+            index.clear(&mut wtxn)?;
+        }
 
         wtxn.commit()?;
 
         self.next_id = 0;
         self.indexed = false;
+        if let Some(cache) = &self.chunk_cache {
+            cache.invalidate_all();
+        }
 
         eprintln!("✅ Database cleared");
         Ok(())
     }
 
+    /// Drop every cached chunk, for when another handle on this database wrote to it
+    pub fn invalidate_chunk_cache(&self) {
+        if let Some(cache) = &self.chunk_cache {
+            cache.invalidate_all();
+        }
+    }
+
     /// Get a chunk by ID
+    ///
+    /// Served from the in-memory chunk cache when possible; misses are read from
+    /// LMDB and cached.
     pub fn get_chunk(&self, id: u32) -> Result<Option<ChunkMetadata>> {
+        if let Some(meta) = self.cached_chunk(id) {
+            return Ok(Some(meta));
+        }
+        let rtxn = self.env.read_txn()?;
+        self.load_chunk(&rtxn, id)
+    }
+
+    /// `get_chunk` within an open read transaction
+    fn chunk_in(&self, rtxn: &heed::RoTxn, id: u32) -> Result<Option<ChunkMetadata>> {
+        match self.cached_chunk(id) {
+            Some(meta) => Ok(Some(meta)),
+            None => self.load_chunk(rtxn, id),
+        }
+    }
+
+    /// A chunk from the in-memory cache, counting the hit or miss
+    fn cached_chunk(&self, id: u32) -> Option<ChunkMetadata> {
+        let cache = self.chunk_cache.as_ref()?;
+        match cache.get(&id) {
+            Some(meta) => {
+                self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                Some(meta.as_ref().clone())
+            }
+            None => {
+                self.cache_misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Read a chunk from LMDB and cache it
+    fn load_chunk(&self, rtxn: &heed::RoTxn, id: u32) -> Result<Option<ChunkMetadata>> {
+        let meta = self.chunks.get(rtxn, &id)?;
+        if let (Some(cache), Some(meta)) = (&self.chunk_cache, &meta) {
+            cache.insert(id, Arc::new(meta.clone()));
+        }
+        Ok(meta)
+    }
+
+    /// Get all chunks with IDs in `start..end`, in ascending ID order
+    ///
+    /// Reads the range with a single LMDB cursor scan; IDs with no chunk (deleted)
+    /// are skipped. Results bypass the chunk cache so bulk scans don't evict the
+    /// entries that point lookups rely on.
+    pub fn get_chunks_range(&self, start: u32, end: u32) -> Result<Vec<(u32, ChunkMetadata)>> {
         let rtxn = self.env.read_txn()?;
-        Ok(self.chunks.get(&rtxn, &id)?)
+        let mut result = Vec::new();
+        for entry in self.chunks.range(&rtxn, &(start..end))? {
+            let (id, metadata) = entry?;
+            result.push((id, metadata));
+        }
+        Ok(result)
+    }
+
+    /// Highest chunk ID in the store, or `None` if it is empty
+    ///
+    /// Reads only the last LMDB key, unlike `stats()` which scans every chunk.
+    pub fn max_chunk_id(&self) -> Result<Option<u32>> {
+        let rtxn = self.env.read_txn()?;
+        Ok(self
+            .chunks
+            .remap_data_type::<DecodeIgnore>()
+            .last(&rtxn)?
+            .map(|(id, ())| id))
+    }
+
+    /// Whether the database has a path index (older databases don't)
+    pub fn has_path_index(&self) -> bool {
+        self.paths.is_some()
+    }
+
+    /// IDs of the chunks stored for `path` (as recorded on the chunks), ascending
+    ///
+    /// Uses the path index, so the cost is proportional to the file's chunks
+    /// rather than the whole store.
+    pub fn chunks_for_path(&self, path: &str) -> Result<Vec<u32>> {
+        let rtxn = self.env.read_txn()?;
+        if let Some(paths) = self.paths {
+            return Ok(paths.get(&rtxn, path)?.unwrap_or_default());
+        }
+
+        let mut ids = Vec::new();
+        for entry in self.chunks.iter(&rtxn)? {
+            let (id, metadata) = entry?;
+            if metadata.path == path {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Every distinct chunk path in the store, in sorted order
+    pub fn indexed_paths(&self) -> Result<Vec<String>> {
+        let rtxn = self.env.read_txn()?;
+        if let Some(paths) = self.paths {
+            let mut result = Vec::new();
+            for entry in paths.remap_data_type::<DecodeIgnore>().iter(&rtxn)? {
+                let (path, ()) = entry?;
+                result.push(path.to_string());
+            }
+            return Ok(result);
+        }
+
+        let mut result = std::collections::BTreeSet::new();
+        for entry in self.chunks.iter(&rtxn)? {
+            let (_, metadata) = entry?;
+            result.insert(metadata.path);
+        }
+        Ok(result.into_iter().collect())
+    }
+
+    /// Record chunk `id` under `path` in the path index
+    fn index_path(&self, wtxn: &mut heed::RwTxn, path: &str, id: u32) -> Result<()> {
+        add_id(self.paths, wtxn, path, id)
+    }
+
+    /// Drop chunk `id` from `path` in the path index
+    fn unindex_path(&self, wtxn: &mut heed::RwTxn, path: &str, id: u32) -> Result<()> {
+        remove_id(self.paths, wtxn, path, id).map(|_| ())
+    }
+
+    /// Store chunk `id` with its metadata
+    ///
+    /// The vector is only added when no other copy of the content has one;
+    /// otherwise the chunk shares that copy's vector.
+    fn put_chunk(
+        &self,
+        wtxn: &mut heed::RwTxn,
+        writer: &VectorWriter,
+        id: u32,
+        metadata: &ChunkMetadata,
+        embedding: &[f32],
+    ) -> Result<()> {
+        self.chunks.put(wtxn, &id, metadata)?;
+        self.index_path(wtxn, &metadata.path, id)?;
+        add_id(self.hashes, wtxn, &metadata.hash, id)?;
+        if self
+            .vector_holder(wtxn, writer, &metadata.hash, id)?
+            .is_some()
+        {
+            // A vector left from an earlier chunk under this ID would be a second copy
+            writer.del_item(wtxn, id)?;
+        } else {
+            writer.add_item(wtxn, id, embedding)?;
+        }
+        Ok(())
+    }
+
+    /// Delete chunk `id` from the vectors, metadata and indexes
+    ///
+    /// When it holds the vector of content other chunks share, a copy that is
+    /// not being deleted too (not in `doomed`) takes the vector over.
+    /// Returns whether the vector database accepted the deletion.
+    fn remove_chunk(
+        &self,
+        wtxn: &mut heed::RwTxn,
+        writer: &VectorWriter,
+        id: u32,
+        doomed: &std::collections::HashSet<u32>,
+    ) -> Result<bool> {
+        let vector = writer.item_vector(wtxn, id)?;
+        let deleted = writer.del_item(wtxn, id).is_ok();
+        if let Some(old) = self.chunks.get(wtxn, &id)? {
+            self.unindex_path(wtxn, &old.path, id)?;
+            let copies = remove_id(self.hashes, wtxn, &old.hash, id)?;
+            let heir = copies.into_iter().find(|copy| !doomed.contains(copy));
+            if let (Some(vector), Some(heir)) = (vector, heir) {
+                if writer.item_vector(wtxn, heir)?.is_none() {
+                    writer.add_item(wtxn, heir, &vector)?;
+                }
+            }
+        }
+        self.chunks.delete(wtxn, &id)?;
+        Ok(deleted)
+    }
+
+    /// IDs of the other chunks with content `hash`, besides `id`
+    fn copies_of(&self, rtxn: &heed::RoTxn, id: u32, hash: &str) -> Result<Vec<u32>> {
+        let Some(hashes) = self.hashes else {
+            return Ok(vec![]);
+        };
+        let mut ids = hashes.get(rtxn, hash)?.unwrap_or_default();
+        ids.retain(|&i| i != id);
+        Ok(ids)
+    }
+
+    /// A chunk other than `id` with content `hash` that has a vector stored
+    fn vector_holder(
+        &self,
+        rtxn: &heed::RoTxn,
+        writer: &VectorWriter,
+        hash: &str,
+        id: u32,
+    ) -> Result<Option<u32>> {
+        for copy in self.copies_of(rtxn, id, hash)? {
+            if writer.item_vector(rtxn, copy)?.is_some() {
+                return Ok(Some(copy));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Hit/miss counters and size of the chunk metadata cache
+    pub fn chunk_cache_stats(&self) -> ChunkCacheStats {
+        ChunkCacheStats {
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+            entries: self
+                .chunk_cache
+                .as_ref()
+                .map(|c| c.entry_count())
+                .unwrap_or(0),
+            capacity: self
+                .chunk_cache
+                .as_ref()
+                .and_then(|c| c.policy().max_capacity())
+                .unwrap_or(0),
+        }
     }
 
     /// Get a chunk as SearchResult (for hybrid search)
     pub fn get_chunk_as_result(&self, id: u32) -> Result<Option<SearchResult>> {
-        let rtxn = self.env.read_txn()?;
-        if let Some(meta) = self.chunks.get(&rtxn, &id)? {
+        if let Some(meta) = self.get_chunk(id)? {
             Ok(Some(SearchResult {
                 id,
                 content: meta.content,
@@ -533,6 +1168,7 @@ impl VectorStore {
                 score: 0.0, // Will be set by caller
                 context_prev: meta.context_prev,
                 context_next: meta.context_next,
+                language: meta.language,
             }))
         } else {
             Ok(None)
@@ -542,6 +1178,7 @@ impl VectorStore {
     /// Iterate all chunks in the store via LMDB cursor.
     /// Returns (id, metadata) pairs for every chunk, regardless of ID gaps.
     /// This is the correct way to enumerate chunks after delete+insert cycles.
+    #[allow(dead_code)] // Public API for bulk enumeration
     pub fn all_chunks(&self) -> Result<Vec<(u32, ChunkMetadata)>> {
         let rtxn = self.env.read_txn()?;
         let mut result = Vec::new();
@@ -552,6 +1189,38 @@ impl VectorStore {
         Ok(result)
     }
 
+    /// List the IDs of all chunks in the store, in ascending order.
+    /// Only keys are read, so this is cheap even for large indexes.
+    pub fn chunk_ids(&self) -> Result<Vec<u32>> {
+        let rtxn = self.env.read_txn()?;
+        let mut ids = Vec::new();
+        for entry in self.chunks.remap_data_type::<DecodeIgnore>().iter(&rtxn)? {
+            let (id, ()) = entry?;
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+
+    /// Get the stored embedding for a chunk, or the one it shares with a copy
+    ///
+    /// Requires a built index (see `build_index`).
+    pub fn get_vector(&self, id: u32) -> Result<Option<Vec<f32>>> {
+        let rtxn = self.env.read_txn()?;
+        let reader = self.vectors.reader(&rtxn)?;
+        if let Some(vector) = reader.item_vector(&rtxn, id)? {
+            return Ok(Some(vector));
+        }
+        let Some(metadata) = self.chunks.get(&rtxn, &id)? else {
+            return Ok(None);
+        };
+        for copy in self.copies_of(&rtxn, id, &metadata.hash)? {
+            if let Some(vector) = reader.item_vector(&rtxn, copy)? {
+                return Ok(Some(vector));
+            }
+        }
+        Ok(None)
+    }
+
     /// Get the database file size in bytes
     #[allow(dead_code)] // Reserved for stats display
     pub fn db_size(&self) -> Result<u64> {
@@ -563,6 +1232,11 @@ impl VectorStore {
     pub fn is_indexed(&self) -> bool {
         self.indexed
     }
+
+    /// Distance metric the stored vectors are compared with
+    pub fn metric(&self) -> Metric {
+        self.vectors.metric()
+    }
 }
 
 /// Search result with metadata
@@ -585,6 +1259,58 @@ pub struct SearchResult {
     pub context_prev: Option<String>,
     /// Lines of code immediately after this chunk (for context)
     pub context_next: Option<String>,
+    /// Language of the source file (`Language` debug name); None for older indexes
+    pub language: Option<String>,
+}
+
+impl SearchResult {
+    /// Language of the result: the one stored at index time, or detected from
+    /// the path for chunks indexed before languages were stored
+    pub fn language_name(&self) -> String {
+        language_name(self.language.as_deref(), &self.path)
+    }
+}
+
+/// Record `id` under `key` in `index`, keeping its IDs sorted
+fn add_id(index: Option<IdIndex>, wtxn: &mut heed::RwTxn, key: &str, id: u32) -> Result<()> {
+    let Some(index) = index else {
+        return Ok(());
+    };
+    let mut ids = index.get(wtxn, key)?.unwrap_or_default();
+    if let Err(pos) = ids.binary_search(&id) {
+        ids.insert(pos, id);
+        index.put(wtxn, key, &ids)?;
+    }
+    Ok(())
+}
+
+/// Drop `id` from `key` in `index`, returning the IDs left under the key
+fn remove_id(
+    index: Option<IdIndex>,
+    wtxn: &mut heed::RwTxn,
+    key: &str,
+    id: u32,
+) -> Result<Vec<u32>> {
+    let Some(index) = index else {
+        return Ok(vec![]);
+    };
+    let Some(mut ids) = index.get(wtxn, key)? else {
+        return Ok(vec![]);
+    };
+    ids.retain(|&i| i != id);
+    if ids.is_empty() {
+        index.delete(wtxn, key)?;
+    } else {
+        index.put(wtxn, key, &ids)?;
+    }
+    Ok(ids)
+}
+
+/// A stored language, or the one detected from `path` for older chunks
+fn language_name(language: Option<&str>, path: &str) -> String {
+    language
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:?}", Language::from_path(Path::new(path))))
 }
 
 /// Statistics about the vector store
@@ -599,6 +1325,26 @@ pub struct StoreStats {
     pub max_chunk_id: u32,
 }
 
+/// Chunk metadata cache statistics (see `VectorStore::chunk_cache_stats`)
+#[derive(Debug, Clone, Serialize, schemars::JsonSchema)]
+pub struct ChunkCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries currently cached (moka updates this lazily)
+    pub entries: u64,
+    /// Maximum number of cached chunks (0 = cache disabled)
+    pub capacity: u64,
+}
+
+/// Build the chunk metadata cache, sized by `CODESEARCH_CHUNK_CACHE_SIZE` (0 disables it)
+fn chunk_cache_from_env() -> Option<Cache<u32, Arc<ChunkMetadata>>> {
+    let capacity = std::env::var("CODESEARCH_CHUNK_CACHE_SIZE")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(crate::constants::DEFAULT_CHUNK_CACHE_SIZE);
+    (capacity > 0).then(|| Cache::new(capacity))
+}
+
 /// Clean up stale .del files from previous crashed runs
 ///
 /// LMDB creates .del files when deleting items, but if the process crashes
@@ -631,6 +1377,24 @@ fn cleanup_stale_del_files(db_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Classify a failure to open the store, separating damaged files from other errors
+fn open_error(db_path: &Path, err: anyhow::Error) -> CodeSearchError {
+    let err = match err.downcast::<CodeSearchError>() {
+        Ok(err) => return err,
+        Err(err) => err,
+    };
+    if let Some(heed::Error::Mdb(
+        heed::MdbError::Corrupted
+        | heed::MdbError::Invalid
+        | heed::MdbError::VersionMismatch
+        | heed::MdbError::PageNotFound,
+    )) = err.downcast_ref::<heed::Error>()
+    {
+        return CodeSearchError::corrupt(db_path, err.to_string());
+    }
+    CodeSearchError::from(err)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -700,6 +1464,115 @@ mod tests {
         assert!(results[0].score > results[1].score);
     }
 
+    #[test]
+    fn test_search_with_filter_fills_the_limit() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let mut store = VectorStore::new(&db_path, 2).unwrap();
+
+        // 40 chunks close to the query under src/, two far away under docs/
+        let mut chunks: Vec<EmbeddedChunk> = (0..40)
+            .map(|i| {
+                EmbeddedChunk::new(
+                    Chunk::new(
+                        format!("fn near{}() {{}}", i),
+                        0,
+                        1,
+                        ChunkKind::Function,
+                        format!("src/near{}.rs", i),
+                    ),
+                    vec![1.0, i as f32 * 0.001],
+                )
+            })
+            .collect();
+        for (i, embedding) in [vec![0.0, 1.0], vec![-1.0, 0.2]].into_iter().enumerate() {
+            chunks.push(EmbeddedChunk::new(
+                Chunk::new(
+                    format!("fn far{}() {{}}", i),
+                    0,
+                    1,
+                    ChunkKind::Function,
+                    format!("docs/far{}.rs", i),
+                ),
+                embedding,
+            ));
+        }
+        store.insert_chunks(chunks).unwrap();
+        store.build_index().unwrap();
+
+        // Plain search with the same limit keeps none of them
+        let in_docs = |path: &str| path.starts_with("docs/");
+        let unfiltered = store.search(&[1.0, 0.0], 2).unwrap();
+        assert!(!unfiltered.iter().any(|r| in_docs(&r.path)));
+
+        let results = store
+            .search_with_filter(&[1.0, 0.0], 2, None, |meta| in_docs(&meta.path))
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| in_docs(&r.path)));
+        assert!(results[0].score >= results[1].score);
+
+        // A predicate nothing satisfies returns empty rather than looping
+        let none = store
+            .search_with_filter(&[1.0, 0.0], 5, None, |meta| meta.kind == "Class")
+            .unwrap();
+        assert!(none.is_empty());
+
+        // The retrieval cap bounds the widening: the docs/ chunks are beyond it
+        let capped = store
+            .search_with_filter(&[1.0, 0.0], 2, Some(10), |meta| in_docs(&meta.path))
+            .unwrap();
+        assert!(capped.is_empty());
+    }
+
+    #[test]
+    fn test_metric_is_kept_by_the_database() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        {
+            let mut store = VectorStore::new_with_metric(&db_path, 2, Metric::DotProduct).unwrap();
+            assert_eq!(store.metric(), Metric::DotProduct);
+            let chunk = |name: &str, embedding: Vec<f32>| {
+                EmbeddedChunk::new(
+                    Chunk::new(
+                        format!("fn {}() {{}}", name),
+                        0,
+                        1,
+                        ChunkKind::Function,
+                        format!("{}.rs", name),
+                    ),
+                    embedding,
+                )
+            };
+            // Same direction as the query but longer: dot product prefers it, cosine can't tell
+            store
+                .insert_chunks(vec![
+                    chunk("short", vec![1.0, 0.0]),
+                    chunk("long", vec![3.0, 0.0]),
+                ])
+                .unwrap();
+            store.build_index().unwrap();
+            let results = store.search(&[1.0, 0.0], 2).unwrap();
+            assert_eq!(results[0].path, "long.rs");
+            assert!(results[0].score > results[1].score);
+        }
+
+        // Reopening without a metric uses the stored one; asking for another fails
+        assert_eq!(
+            VectorStore::open_readonly(&db_path, 2).unwrap().metric(),
+            Metric::DotProduct
+        );
+        assert_eq!(
+            VectorStore::new(&db_path, 2).unwrap().metric(),
+            Metric::DotProduct
+        );
+        assert!(VectorStore::new_with_metric(&db_path, 2, Metric::Cosine).is_err());
+        // Databases created before the option existed are cosine
+        let old = temp_dir.path().join("old.db");
+        assert_eq!(VectorStore::new(&old, 2).unwrap().metric(), Metric::Cosine);
+    }
+
     #[test]
     fn test_stats() {
         let temp_dir = tempdir().unwrap();
@@ -799,6 +1672,348 @@ mod tests {
         assert_eq!(metadata.path, "test.rs");
     }
 
+    #[test]
+    fn test_get_chunk_cache_hits_and_invalidation() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let mut store = VectorStore::new(&db_path, 4).unwrap();
+        let ids = store
+            .insert_chunks_with_ids(vec![EmbeddedChunk::new(
+                Chunk::new(
+                    "fn cached() {}".to_string(),
+                    0,
+                    1,
+                    ChunkKind::Function,
+                    "cached.rs".to_string(),
+                ),
+                vec![1.0, 0.0, 0.0, 0.0],
+            )])
+            .unwrap();
+
+        assert!(store.get_chunk(ids[0]).unwrap().is_some());
+        assert!(store.get_chunk(ids[0]).unwrap().is_some());
+        let stats = store.chunk_cache_stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+
+        // Deleted chunks must not be served from the cache
+        store.delete_chunks(&ids).unwrap();
+        assert!(store.get_chunk(ids[0]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_chunks_range_skips_gaps() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let mut store = VectorStore::new(&db_path, 4).unwrap();
+        let chunks = (0..5)
+            .map(|i| {
+                EmbeddedChunk::new(
+                    Chunk::new(
+                        format!("fn f{}() {{}}", i),
+                        i,
+                        i + 1,
+                        ChunkKind::Function,
+                        format!("f{}.rs", i),
+                    ),
+                    vec![1.0, 0.0, 0.0, 0.0],
+                )
+            })
+            .collect();
+        let ids = store.insert_chunks_with_ids(chunks).unwrap();
+        store.delete_chunks(&[ids[2]]).unwrap();
+
+        let range = store.get_chunks_range(ids[1], ids[4]).unwrap();
+        let got: Vec<u32> = range.iter().map(|(id, _)| *id).collect();
+        assert_eq!(got, vec![ids[1], ids[3]]);
+        assert_eq!(range[1].1.path, "f3.rs");
+        assert_eq!(store.max_chunk_id().unwrap(), Some(ids[4]));
+    }
+
+    #[test]
+    fn test_path_index_tracks_inserts_and_deletes() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let mut store = VectorStore::new(&db_path, 4).unwrap();
+        let chunk = |path: &str, line: usize| {
+            EmbeddedChunk::new(
+                Chunk::new(
+                    format!("fn f{}() {{}}", line),
+                    line,
+                    line + 1,
+                    ChunkKind::Function,
+                    path.to_string(),
+                ),
+                vec![1.0, 0.0, 0.0, 0.0],
+            )
+        };
+        let ids = store
+            .insert_chunks_with_ids(vec![chunk("a.rs", 0), chunk("b.rs", 0), chunk("a.rs", 5)])
+            .unwrap();
+
+        assert_eq!(store.chunks_for_path("a.rs").unwrap(), vec![ids[0], ids[2]]);
+        assert_eq!(store.indexed_paths().unwrap(), vec!["a.rs", "b.rs"]);
+
+        store.delete_chunks(&[ids[1]]).unwrap();
+        assert!(store.chunks_for_path("b.rs").unwrap().is_empty());
+        assert_eq!(store.indexed_paths().unwrap(), vec!["a.rs"]);
+
+        // The index is persisted alongside the chunks
+        drop(store);
+        let store = VectorStore::open_readonly(&db_path, 4).unwrap();
+        assert_eq!(store.chunks_for_path("a.rs").unwrap(), vec![ids[0], ids[2]]);
+    }
+
+    #[test]
+    fn test_duplicate_chunks_share_one_vector() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let mut store = VectorStore::new(&db_path, 2).unwrap();
+
+        let license = |path: &str| {
+            Chunk::new(
+                "// Licensed under MIT".to_string(),
+                0,
+                1,
+                ChunkKind::Block,
+                path.to_string(),
+            )
+        };
+        assert!(!store.has_content(&license("a.rs").hash).unwrap());
+        let first = store
+            .insert_chunks_with_ids(vec![
+                EmbeddedChunk::new(license("a.rs"), vec![1.0, 0.0]),
+                EmbeddedChunk::new(
+                    Chunk::new(
+                        "fn other() {}".to_string(),
+                        2,
+                        3,
+                        ChunkKind::Function,
+                        "a.rs".to_string(),
+                    ),
+                    vec![0.0, 1.0],
+                ),
+            ])
+            .unwrap();
+        assert!(store.has_content(&license("b.rs").hash).unwrap());
+        let copies = store
+            .insert_duplicate_chunks(vec![license("b.rs"), license("c.rs")])
+            .unwrap();
+        store.build_index().unwrap();
+
+        // One hit on the stored vector returns every copy, with the same score
+        let results = store.search(&[1.0, 0.0], 3).unwrap();
+        let paths: Vec<&str> = results.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.rs", "b.rs", "c.rs"]);
+        assert_eq!(results[0].score, results[2].score);
+        assert_eq!(store.get_vector(copies[0]).unwrap(), Some(vec![1.0, 0.0]));
+
+        // Deleting the copy holding the vector hands it to a remaining one
+        store.delete_chunks(&[first[0], copies[0]]).unwrap();
+        store.build_index().unwrap();
+        let results = store.search(&[1.0, 0.0], 1).unwrap();
+        assert_eq!(results[0].path, "c.rs");
+        assert_eq!(store.get_vector(copies[1]).unwrap(), Some(vec![1.0, 0.0]));
+
+        // Content with no stored copy can't be inserted as a duplicate
+        assert!(store
+            .insert_duplicate_chunks(vec![Chunk::new(
+                "fn new() {}".to_string(),
+                0,
+                1,
+                ChunkKind::Function,
+                "d.rs".to_string(),
+            )])
+            .is_err());
+    }
+
+    #[test]
+    fn test_delete_chunks_by_path_prefix() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let mut store = VectorStore::new(&db_path, 4).unwrap();
+        let chunks = [
+            "a.rs",
+            "legacy/x.rs",
+            "legacy/y/z.rs",
+            "legacy2/w.rs",
+            "legacy/x.rs",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            EmbeddedChunk::new(
+                Chunk::new(
+                    format!("fn f{}() {{}}", i),
+                    i,
+                    i + 1,
+                    ChunkKind::Function,
+                    path.to_string(),
+                ),
+                vec![1.0, 0.0, 0.0, 0.0],
+            )
+        })
+        .collect();
+        let ids = store.insert_chunks_with_ids(chunks).unwrap();
+
+        let mut deleted = store.delete_chunks_by_path_prefix("legacy/").unwrap();
+        deleted.sort();
+        assert_eq!(deleted, vec![ids[1], ids[2], ids[4]]);
+        assert!(store.get_chunk(ids[1]).unwrap().is_none());
+        assert_eq!(store.indexed_paths().unwrap(), vec!["a.rs", "legacy2/w.rs"]);
+        assert_eq!(store.stats().unwrap().total_chunks, 2);
+
+        assert!(store
+            .delete_chunks_by_path_prefix("missing/")
+            .unwrap()
+            .is_empty());
+        assert!(store.delete_chunks_by_path_prefix("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_typed_open_and_search_errors() {
+        let temp_dir = tempdir().unwrap();
+
+        let missing = VectorStore::open_readonly(&temp_dir.path().join("missing.db"), 4);
+        assert!(matches!(
+            missing,
+            Err(CodeSearchError::DatabaseNotFound { .. })
+        ));
+
+        // A data file that isn't LMDB
+        let garbage_path = temp_dir.path().join("garbage.db");
+        fs::create_dir_all(&garbage_path).unwrap();
+        fs::write(garbage_path.join("data.mdb"), vec![0xAB; 8192]).unwrap();
+        assert!(matches!(
+            VectorStore::new(&garbage_path, 4),
+            Err(CodeSearchError::Corrupt { .. })
+        ));
+
+        // Dimensions are checked before anything is read
+        let store = VectorStore::new(&temp_dir.path().join("test.db"), 4).unwrap();
+        assert!(matches!(
+            store.search(&[1.0, 0.0], 1),
+            Err(CodeSearchError::DimensionMismatch {
+                expected: 4,
+                actual: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_chunk_ids_and_vectors() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+
+        let mut store = VectorStore::new(&db_path, 4).unwrap();
+        let chunks = vec![
+            EmbeddedChunk::new(
+                Chunk::new(
+                    "fn a() {}".to_string(),
+                    0,
+                    1,
+                    ChunkKind::Function,
+                    "a.rs".to_string(),
+                ),
+                vec![1.0, 0.0, 0.0, 0.0],
+            ),
+            EmbeddedChunk::new(
+                Chunk::new(
+                    "fn b() {}".to_string(),
+                    0,
+                    1,
+                    ChunkKind::Function,
+                    "b.rs".to_string(),
+                ),
+                vec![0.0, 1.0, 0.0, 0.0],
+            ),
+        ];
+        let ids = store.insert_chunks_with_ids(chunks).unwrap();
+        store.delete_chunks(&ids[..1]).unwrap();
+        store.build_index().unwrap();
+
+        assert_eq!(store.chunk_ids().unwrap(), vec![ids[1]]);
+        let vector = store.get_vector(ids[1]).unwrap().unwrap();
+        assert_eq!(vector.len(), 4);
+        assert!(vector[1] > 0.9);
+    }
+
+    #[test]
+    fn test_chunk_metadata_codec_reads_records_without_language() {
+        let mut chunk = Chunk::new(
+            "fn a() {}".to_string(),
+            0,
+            1,
+            ChunkKind::Function,
+            "a.rs".to_string(),
+        );
+        chunk.language = Some(Language::Rust);
+        let meta = ChunkMetadata::from_embedded_chunk(&EmbeddedChunk::new(chunk, vec![1.0]));
+
+        let bytes = ChunkMetadataCodec::bytes_encode(&meta)
+            .unwrap()
+            .into_owned();
+        let decoded = ChunkMetadataCodec::bytes_decode(&bytes).unwrap();
+        assert_eq!(decoded.language.as_deref(), Some("Rust"));
+        assert_eq!(decoded.to_chunk().language, Some(Language::Rust));
+
+        // Older records are the same bytes without the trailing `language` option
+        let mut legacy = meta.clone();
+        legacy.language = None;
+        let mut bytes = bincode::serialize(&legacy).unwrap();
+        assert_eq!(bytes.pop(), Some(0));
+        let decoded = ChunkMetadataCodec::bytes_decode(&bytes).unwrap();
+        assert_eq!(decoded.language, None);
+        assert_eq!(decoded.content, "fn a() {}");
+        assert_eq!(decoded.searchable_text, meta.searchable_text);
+    }
+
+    #[test]
+    fn test_insert_with_ids_preserves_ids() {
+        let temp_dir = tempdir().unwrap();
+
+        let mut source = VectorStore::new(&temp_dir.path().join("source.db"), 4).unwrap();
+        let mut chunk = Chunk::new(
+            "fn a() {}".to_string(),
+            0,
+            1,
+            ChunkKind::Function,
+            "a.rs".to_string(),
+        );
+        chunk.context = vec!["File: a.rs".to_string(), "Function: a".to_string()];
+        chunk.signature = Some("fn a()".to_string());
+        let ids = source
+            .insert_chunks_with_ids(vec![
+                EmbeddedChunk::new(chunk.clone(), vec![1.0, 0.0, 0.0, 0.0]),
+                EmbeddedChunk::new(chunk, vec![0.0, 1.0, 0.0, 0.0]),
+            ])
+            .unwrap();
+        source.delete_chunks(&ids[..1]).unwrap();
+
+        let rebuilt = source.get_chunk(ids[1]).unwrap().unwrap().to_chunk();
+        assert_eq!(rebuilt.context, vec!["File: a.rs", "Function: a"]);
+        assert_eq!(rebuilt.signature.as_deref(), Some("fn a()"));
+        assert_eq!(rebuilt.language, None);
+
+        let mut target = VectorStore::new(&temp_dir.path().join("target.db"), 2).unwrap();
+        let items = source
+            .all_chunks()
+            .unwrap()
+            .into_iter()
+            .map(|(id, meta)| (id, meta, vec![0.5, 0.5]))
+            .collect();
+        target.insert_with_ids(items).unwrap();
+        target.build_index().unwrap();
+
+        assert_eq!(target.chunk_ids().unwrap(), vec![ids[1]]);
+        assert_eq!(target.get_chunk(ids[1]).unwrap().unwrap().kind, "Function");
+        assert_eq!(target.stats().unwrap().max_chunk_id, ids[1]);
+    }
+
     #[test]
     fn test_persistence() {
         let temp_dir = tempdir().unwrap();
@@ -7,6 +7,63 @@ use std::sync::mpsc::{channel, Receiver};
 use std::time::Duration;
 
 use crate::cache::normalize_path;
+use crate::file::{include_matcher, WatchConfig};
+
+/// Default debounce window of the `serve` / `watch` file watcher
+pub const DEFAULT_WATCH_DEBOUNCE_MS: u64 = 300;
+
+/// Default cap on file events applied in one batch
+pub const DEFAULT_WATCH_MAX_BATCH: usize = 1000;
+
+/// Debounce and batching of the `serve` / `watch` file watcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchSettings {
+    /// Debounce window in milliseconds; a burst also settles after this long without events
+    pub debounce_ms: u64,
+    /// Stop growing a batch once it holds this many events (0 = no limit)
+    pub max_batch: usize,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            debounce_ms: DEFAULT_WATCH_DEBOUNCE_MS,
+            max_batch: DEFAULT_WATCH_MAX_BATCH,
+        }
+    }
+}
+
+impl WatchSettings {
+    /// Resolve from `.codesearch.toml` `[watch]`, then `CODESEARCH_WATCH_DEBOUNCE_MS`
+    /// and `CODESEARCH_WATCH_MAX_BATCH`, then the defaults
+    pub fn resolve(config: Option<&WatchConfig>) -> Self {
+        let env = |name: &str| std::env::var(name).ok().and_then(|s| s.parse().ok());
+        Self::merge(
+            config.copied().unwrap_or_default(),
+            env("CODESEARCH_WATCH_DEBOUNCE_MS"),
+            env("CODESEARCH_WATCH_MAX_BATCH").map(|n: u64| n as usize),
+        )
+    }
+
+    fn merge(config: WatchConfig, env_debounce: Option<u64>, env_max_batch: Option<usize>) -> Self {
+        let defaults = Self::default();
+        Self {
+            debounce_ms: config
+                .debounce_ms
+                .or(env_debounce)
+                .unwrap_or(defaults.debounce_ms),
+            max_batch: config
+                .max_batch
+                .or(env_max_batch)
+                .unwrap_or(defaults.max_batch),
+        }
+    }
+
+    /// How long a burst must stay quiet before its batch is applied
+    pub fn settle(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms)
+    }
+}
 
 /// Normalize a path from notify events to a consistent format.
 /// Strips UNC prefix (`\\?\`) and converts backslashes to forward slashes
@@ -139,6 +196,23 @@ pub enum FileEvent {
     Renamed(PathBuf, PathBuf),
 }
 
+impl FileEvent {
+    /// The path the event leaves behind (the destination of a rename)
+    pub fn path(&self) -> &Path {
+        match self {
+            FileEvent::Modified(path) | FileEvent::Deleted(path) => path,
+            FileEvent::Renamed(_, to) => to,
+        }
+    }
+}
+
+/// Append `more` to `events`, dropping earlier events for the same paths
+fn merge_events(events: &mut Vec<FileEvent>, more: Vec<FileEvent>) {
+    let paths: HashSet<PathBuf> = more.iter().map(|e| e.path().to_path_buf()).collect();
+    events.retain(|e| !paths.contains(e.path()));
+    events.extend(more);
+}
+
 /// File watcher for incremental indexing
 ///
 /// Uses notify-debouncer-full for efficient debounced file watching.
@@ -148,6 +222,8 @@ pub enum FileEvent {
 /// 3. Batched events for efficient processing
 pub struct FileWatcher {
     root: PathBuf,
+    /// `--include` globs the index was built with
+    include: Option<ignore::overrides::Override>,
     debouncer: Option<Debouncer<RecommendedWatcher, FileIdMap>>,
     receiver: Option<Receiver<DebounceEventResult>>,
 }
@@ -157,11 +233,19 @@ impl FileWatcher {
     pub fn new(root: PathBuf) -> Self {
         Self {
             root,
+            include: None,
             debouncer: None,
             receiver: None,
         }
     }
 
+    /// Only report changes to files matching one of `globs`, as `--include` does
+    /// when indexing (deletions are still reported)
+    pub fn with_include_globs(mut self, globs: &[String]) -> Result<Self> {
+        self.include = include_matcher(&self.root, globs)?;
+        Ok(self)
+    }
+
     /// Start watching for file changes
     pub fn start(&mut self, debounce_ms: u64) -> Result<()> {
         let (tx, rx) = channel();
@@ -225,6 +309,11 @@ impl FileWatcher {
         if self.is_in_ignored_dir(path) {
             return false;
         }
+        if let Some(include) = &self.include {
+            if !include.matched(path, false).is_whitelist() {
+                return false;
+            }
+        }
 
         // Must be a file with an indexable extension
         if let Some(ext) = path.extension() {
@@ -325,6 +414,30 @@ impl FileWatcher {
         events
     }
 
+    /// Wait for a burst of changes to settle and return it as one batch
+    ///
+    /// After the first events arrive (within `timeout`), keeps collecting until
+    /// nothing new arrives for `settle` or the batch holds `max_events` events
+    /// (0 = no limit). A branch switch touching thousands of files is then applied,
+    /// and the index rebuilt, once rather than per debounce window. When a path
+    /// changes again during the burst, only its latest event is kept.
+    pub fn wait_for_burst(
+        &self,
+        timeout: Duration,
+        settle: Duration,
+        max_events: usize,
+    ) -> Vec<FileEvent> {
+        let mut events = self.wait_for_events(timeout);
+        while !events.is_empty() && (max_events == 0 || events.len() < max_events) {
+            let more = self.wait_for_events(settle);
+            if more.is_empty() {
+                break;
+            }
+            merge_events(&mut events, more);
+        }
+        events
+    }
+
     fn process_debounce_result(
         &self,
         result: DebounceEventResult,
@@ -417,6 +530,69 @@ mod tests {
         assert!(watcher.is_watchable(Path::new("/tmp/Makefile")));
     }
 
+    #[test]
+    fn test_is_watchable_with_include_globs() {
+        let watcher = FileWatcher::new(PathBuf::from("/tmp"))
+            .with_include_globs(&["src/**".to_string()])
+            .unwrap();
+        assert!(watcher.is_watchable(Path::new("/tmp/src/main.rs")));
+        assert!(!watcher.is_watchable(Path::new("/tmp/tests/cli.rs")));
+        assert!(!watcher.is_watchable(Path::new("/tmp/src/image.png")));
+    }
+
+    #[test]
+    fn test_merge_events_keeps_latest_per_path() {
+        let a = PathBuf::from("/p/a.rs");
+        let b = PathBuf::from("/p/b.rs");
+        let mut events = vec![
+            FileEvent::Modified(a.clone()),
+            FileEvent::Deleted(b.clone()),
+        ];
+        merge_events(
+            &mut events,
+            vec![
+                FileEvent::Deleted(a.clone()),
+                FileEvent::Renamed(PathBuf::from("/p/c.rs"), b.clone()),
+            ],
+        );
+        assert_eq!(
+            events,
+            vec![
+                FileEvent::Deleted(a),
+                FileEvent::Renamed(PathBuf::from("/p/c.rs"), b)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch_settings_precedence() {
+        let none = WatchConfig::default();
+        assert_eq!(
+            WatchSettings::merge(none, None, None),
+            WatchSettings::default()
+        );
+        assert_eq!(
+            WatchSettings::merge(none, Some(1000), Some(0)),
+            WatchSettings {
+                debounce_ms: 1000,
+                max_batch: 0
+            }
+        );
+
+        // The project config wins over the environment
+        let config = WatchConfig {
+            debounce_ms: Some(50),
+            max_batch: None,
+        };
+        assert_eq!(
+            WatchSettings::merge(config, Some(1000), Some(10)),
+            WatchSettings {
+                debounce_ms: 50,
+                max_batch: 10
+            }
+        );
+    }
+
     #[test]
     #[ignore] // Requires actual filesystem events
     fn test_file_watcher() {